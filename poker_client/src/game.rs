@@ -1,14 +1,15 @@
 use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::AtomicBool;
 
 use parking_lot::Mutex;
 
 pub use poker_protocol::{
-    ActionRequiredUpdate, ChatMessage, GameStateUpdate, PlayerConnectedUpdate, PlayerUpdate,
-    ShowdownUpdate,
+    ActionRequiredUpdate, BettingStructure, CashedOutUpdate, ChatMessage, ForcedBet,
+    GameStateUpdate, GameVariant, HandsRevealedUpdate, PlayerConnectedUpdate, PlayerUpdate,
+    SeatOfferedUpdate, SeatRequestUpdate, SettlementReport, ShowdownUpdate, TableCreatedUpdate,
+    WaitlistUpdate,
 };
 
-pub const HOLE_CARDS_COUNT: usize = 2;
-
 /// Client-side player representation for UI display.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Player {
@@ -30,6 +31,12 @@ pub struct Player {
     pub is_sitting_out: bool,
     /// The player's hole cards (as display strings)
     pub hole_cards: Vec<String>,
+    /// Cards dealt face up to the player in stud games
+    pub up_cards: Vec<String>,
+    /// Seat number at the table
+    pub seat: usize,
+    /// Whether the player owes blinds and is waiting for the big blind
+    pub waiting_for_big_blind: bool,
 }
 
 impl Player {
@@ -50,6 +57,9 @@ impl Player {
             is_folded: false,
             is_sitting_out: false,
             hole_cards: vec![],
+            up_cards: vec![],
+            seat: 0,
+            waiting_for_big_blind: false,
         }
     }
 }
@@ -62,12 +72,29 @@ pub struct PokerGameState {
     pub current_street: String,
     pub hand_number: i32,
     pub dealer_position: usize,
+    pub variant: GameVariant,
+    pub betting_structure: BettingStructure,
+    pub forced_bets: Vec<ForcedBet>,
     pub action_required: Option<ActionRequiredUpdate>,
     pub showdown: Option<ShowdownUpdate>,
     pub chat_messages: VecDeque<ChatMessage>,
     pub errors: VecDeque<String>,
     pub my_id: String,
     pub pending_chat: Mutex<String>,
+    /// Players waiting for a seat, first in line first
+    pub waitlist: Vec<String>,
+    /// Seat being held for this player from the waiting list
+    pub seat_offer: Option<SeatOfferedUpdate>,
+    /// Whether this player is watching the table without a seat
+    pub spectating: AtomicBool,
+    /// Private table this player created and hosts
+    pub hosted_table: Option<TableCreatedUpdate>,
+    /// Players waiting for this host's approval to sit down
+    pub seat_requests: Mutex<Vec<SeatRequestUpdate>>,
+    /// The host has paused the table
+    pub paused: bool,
+    /// How the last private table session this player was in settles up
+    pub settlement: Option<SettlementReport>,
 }
 
 impl PokerGameState {
@@ -80,12 +107,22 @@ impl PokerGameState {
             current_street: String::new(),
             hand_number: 0,
             dealer_position: 0,
+            variant: GameVariant::default(),
+            betting_structure: BettingStructure::default(),
+            forced_bets: Vec::new(),
             action_required: None,
             showdown: None,
             chat_messages: VecDeque::new(),
             errors: VecDeque::new(),
             my_id: String::new(),
             pending_chat: Mutex::new(String::new()),
+            waitlist: Vec::new(),
+            seat_offer: None,
+            spectating: AtomicBool::new(false),
+            hosted_table: None,
+            seat_requests: Mutex::new(Vec::new()),
+            paused: false,
+            settlement: None,
         }
     }
 
@@ -96,6 +133,26 @@ impl PokerGameState {
         self.community_cards = update.community_cards;
         self.current_street = update.current_street;
         self.dealer_position = update.dealer_position;
+        self.variant = update.variant;
+        self.betting_structure = update.betting_structure;
+        self.forced_bets = update.forced_bets;
+        self.paused = update.paused;
+    }
+
+    /// Clears the table once its host has ended the session.
+    pub fn end_session(&mut self, game_id: &str) {
+        if self
+            .hosted_table
+            .as_ref()
+            .is_some_and(|table| table.game_id == game_id)
+        {
+            self.hosted_table = None;
+            self.seat_requests.lock().clear();
+        }
+        self.players.clear();
+        self.community_cards.clear();
+        self.action_required = None;
+        self.paused = false;
     }
 
     pub fn set_action_required(&mut self, action: ActionRequiredUpdate) {
@@ -115,11 +172,51 @@ impl PokerGameState {
 
         for (player_id, hole_cards, _, _) in hands {
             if let Some(player) = self.players.get_mut(&player_id) {
-                player.hole_cards = hole_cards;
+                // Stud hands are shown with their up-cards, which are drawn separately.
+                player.hole_cards = hole_cards
+                    .into_iter()
+                    .filter(|c| !player.up_cards.contains(c))
+                    .collect();
+            }
+        }
+    }
+
+    /// Turns up the hands of players all-in once the board is being run out.
+    pub fn reveal_hands(&mut self, update: HandsRevealedUpdate) {
+        for (player_id, cards) in update.hands {
+            if let Some(player) = self.players.get_mut(&player_id) {
+                player.hole_cards = cards
+                    .into_iter()
+                    .filter(|c| !player.up_cards.contains(c))
+                    .collect();
             }
         }
     }
 
+    /// Replaces the waiting list, dropping this player's seat offer once
+    /// they are no longer on it.
+    pub fn set_waitlist(&mut self, update: WaitlistUpdate) {
+        self.waitlist = update.player_ids;
+        if !self.waitlist.contains(&self.my_id) {
+            self.seat_offer = None;
+        }
+    }
+
+    /// Records a seat offer if it is for this player.
+    pub fn offer_seat(&mut self, offer: SeatOfferedUpdate) {
+        if offer.player_id == self.my_id {
+            self.seat_offer = Some(offer);
+        }
+    }
+
+    /// Records a request to sit at the table this player hosts; the player
+    /// making the request is sent a copy, which is ignored.
+    pub fn add_seat_request(&mut self, request: SeatRequestUpdate) {
+        if request.player_id != self.my_id {
+            self.seat_requests.lock().push(request);
+        }
+    }
+
     pub fn add_chat_message(&mut self, msg: ChatMessage) {
         self.chat_messages.push_back(msg);
         if self.chat_messages.len() > 50 {
@@ -152,6 +249,9 @@ impl PokerGameState {
                     .filter(|c| !c.is_empty())
                     .cloned()
                     .collect();
+                player.up_cards = update.up_cards.clone();
+                player.seat = update.seat;
+                player.waiting_for_big_blind = update.waiting_for_big_blind;
             })
             .or_insert_with(|| Player {
                 id: update.player_id.clone(),
@@ -168,6 +268,9 @@ impl PokerGameState {
                     .filter(|c| !c.is_empty())
                     .cloned()
                     .collect(),
+                up_cards: update.up_cards.clone(),
+                seat: update.seat,
+                waiting_for_big_blind: update.waiting_for_big_blind,
             });
     }
 }
@@ -1,4 +1,6 @@
 use std::env;
+use std::path::Path;
+use std::sync::atomic::Ordering;
 use std::sync::mpsc;
 use std::sync::Arc;
 
@@ -8,12 +10,19 @@ use futures::SinkExt;
 use futures::StreamExt;
 use log::{debug, error, info, warn};
 use parking_lot::Mutex;
+use poker_protocol::Hello;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::CertificateDer;
+use rustls::{ClientConfig, RootCertStore};
 use tokio::runtime::Handle;
 use tokio::time::{timeout, Duration};
 use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::Connector;
 
 pub const DEFAULT_SERVER_ADDR: &str = "ws://127.0.0.1:8080";
 pub const ENV_SERVER_ADDR: &str = "POKER_SERVER_URL";
+/// PEM file of CA certificates to trust for a `wss://` server
+pub const ENV_CA_CERT: &str = "POKER_CA_CERT";
 /// Timeout in seconds for establishing initial WebSocket connection
 pub const CONNECTION_TIMEOUT_SECS: u64 = 10;
 /// Maximum number of reconnection attempts before giving up
@@ -26,7 +35,7 @@ pub const MAX_RECONNECT_DELAY_MS: u64 = 30000;
 pub const PING_INTERVAL_SECS: u64 = 30;
 /// Maximum WebSocket message size in bytes (4KB)
 pub const MAX_MESSAGE_SIZE: usize = 4096;
-/// Number of hole cards dealt to each player
+/// Number of hole cards dealt to each player in Texas Hold'em
 pub const HOLE_CARDS_COUNT: usize = 2;
 
 mod game;
@@ -36,6 +45,7 @@ use game::PokerGameState;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum ClientNetworkMessage {
+    Hello(Hello),
     PlayerIdConfirmed(String),
     GameStateUpdate(crate::game::GameStateUpdate),
     PlayerUpdates(Vec<crate::game::PlayerUpdate>),
@@ -43,6 +53,14 @@ enum ClientNetworkMessage {
     PlayerConnected(crate::game::PlayerConnectedUpdate),
     PlayerDisconnected(String),
     Showdown(crate::game::ShowdownUpdate),
+    HandsRevealed(crate::game::HandsRevealedUpdate),
+    CashedOut(crate::game::CashedOutUpdate),
+    SeatOffered(crate::game::SeatOfferedUpdate),
+    WaitlistUpdated(crate::game::WaitlistUpdate),
+    TableCreated(crate::game::TableCreatedUpdate),
+    SeatRequested(crate::game::SeatRequestUpdate),
+    SessionEnded(String),
+    Settlement(crate::game::SettlementReport),
     Chat(crate::game::ChatMessage),
     Error(String),
     Disconnected,
@@ -83,6 +101,16 @@ struct AppState {
     game_state: PokerGameState,
     connected: bool,
     raise_amount: Mutex<String>,
+    /// Hole cards selected for discarding in draw games
+    discards: Mutex<Vec<String>>,
+    /// Whether to straddle when in a straddle position
+    straddle: Mutex<bool>,
+    /// Whether to agree to run the board out twice when all-in
+    run_it_twice: Mutex<bool>,
+    /// Chips to add with a rebuy or top-up
+    chip_amount: Mutex<String>,
+    /// Blinds a host is typing in, as "small/big"
+    blinds_input: Mutex<String>,
 }
 
 impl Default for AppState {
@@ -91,6 +119,11 @@ impl Default for AppState {
             game_state: PokerGameState::new(),
             connected: false,
             raise_amount: Mutex::new(String::new()),
+            discards: Mutex::new(Vec::new()),
+            straddle: Mutex::new(false),
+            run_it_twice: Mutex::new(false),
+            chip_amount: Mutex::new(String::new()),
+            blinds_input: Mutex::new(String::new()),
         }
     }
 }
@@ -155,6 +188,43 @@ fn get_server_address() -> String {
     })
 }
 
+/// Opens a session: a `Hello` advertising the protocol versions and
+/// capabilities the client supports, then `Connect`.
+fn handshake_messages() -> Vec<Message> {
+    // Serializing a Hello cannot fail, and the server would turn away an empty one.
+    let hello = Hello::new(false).to_unified_json().unwrap_or_default();
+    let connect = serde_json::json!({ "type": "Connect" }).to_string();
+    [hello, connect]
+        .into_iter()
+        .map(|msg| Message::Text(msg.into()))
+        .collect()
+}
+
+/// Builds the TLS setup for `wss://` servers: the CA certificates in
+/// `POKER_CA_CERT` if it is set, such as one signing a self-hosted server's
+/// certificate, or the bundled web roots otherwise.
+///
+/// # Returns
+/// `None` to use the bundled web roots, or an error naming the unreadable file
+fn tls_connector() -> Result<Option<Connector>, String> {
+    let Some(path) = env::var_os(ENV_CA_CERT) else {
+        return Ok(None);
+    };
+    let invalid = |e: &dyn std::fmt::Display| format!("{}: {}", Path::new(&path).display(), e);
+    let mut roots = RootCertStore::empty();
+    for certificate in CertificateDer::pem_file_iter(&path).map_err(|e| invalid(&e))? {
+        let certificate = certificate.map_err(|e| invalid(&e))?;
+        roots.add(certificate).map_err(|e| invalid(&e))?;
+    }
+    if roots.is_empty() {
+        return Err(invalid(&"no certificates found"));
+    }
+    let config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Some(Connector::Rustls(Arc::new(config))))
+}
+
 async fn connect_with_retry(
     server_addr: &str,
     tx_for_network: &mpsc::Sender<ClientNetworkMessage>,
@@ -168,6 +238,17 @@ async fn connect_with_retry(
     ),
     String,
 > {
+    let connector = match tls_connector() {
+        Ok(connector) => connector,
+        Err(e) => {
+            let message = format!("Cannot load CA certificates: {}", e);
+            if let Err(err) = tx_for_network.send(ClientNetworkMessage::Error(message.clone())) {
+                warn!("Failed to send CA certificate error: {}", err);
+            }
+            return Err(message);
+        }
+    };
+
     loop {
         let delay = {
             let mut state = reconnect_state.lock();
@@ -193,7 +274,12 @@ async fn connect_with_retry(
 
         let connection_result = timeout(
             Duration::from_secs(CONNECTION_TIMEOUT_SECS),
-            tokio_tungstenite::connect_async(server_addr),
+            tokio_tungstenite::connect_async_tls_with_config(
+                server_addr,
+                None,
+                false,
+                connector.clone(),
+            ),
         )
         .await;
 
@@ -296,12 +382,10 @@ fn setup_network(mut commands: Commands, server_config: Res<ServerConfig>) {
 
         let (mut write, read) = ws_stream.split();
 
-        let connect_msg = serde_json::json!({
-            "type": "connect"
-        });
-        info!("Sending connect message: {}", connect_msg);
+        let handshake = handshake_messages();
+        info!("Sending hello and connect messages: {:?}", handshake);
         if let Err(e) = write
-            .send(Message::Text(connect_msg.to_string().into()))
+            .send_all(&mut futures::stream::iter(handshake).map(Ok))
             .await
         {
             error!("Failed to send connect message: {}", e);
@@ -449,6 +533,12 @@ fn handle_network_messages(
                 drop(rx);
                 info!("Got message: {:?}", message);
                 match message {
+                    ClientNetworkMessage::Hello(hello) => {
+                        info!(
+                            "Server speaks protocol version {} with {:?}",
+                            hello.version, hello.features
+                        );
+                    }
                     ClientNetworkMessage::PlayerIdConfirmed(id) => {
                         app_state.connected = true;
                         app_state.game_state.my_id = id.clone();
@@ -512,9 +602,47 @@ fn handle_network_messages(
                         app_state.game_state.set_action_required(update);
                     }
                     ClientNetworkMessage::Showdown(update) => {
-                        info!("Showdown! Winners: {:?}", update.winners);
+                        info!(
+                            "Showdown! Winners: {:?} Low winners: {:?}",
+                            update.winners, update.low_winners
+                        );
                         app_state.game_state.show_showdown(update);
                     }
+                    ClientNetworkMessage::HandsRevealed(update) => {
+                        info!("Hands revealed, running it {} time(s)", update.runs);
+                        app_state.game_state.reveal_hands(update);
+                    }
+                    ClientNetworkMessage::CashedOut(update) => {
+                        info!("Player {} cashed out ${}", update.player_id, update.chips);
+                        app_state.game_state.players.remove(&update.player_id);
+                    }
+                    ClientNetworkMessage::SeatOffered(offer) => {
+                        info!("Seat {} offered to {}", offer.seat, offer.player_id);
+                        app_state.game_state.offer_seat(offer);
+                    }
+                    ClientNetworkMessage::WaitlistUpdated(update) => {
+                        info!("Waiting list: {:?}", update.player_ids);
+                        app_state.game_state.set_waitlist(update);
+                    }
+                    ClientNetworkMessage::TableCreated(update) => {
+                        info!("Hosting private table {}", update.game_id);
+                        app_state.game_state.hosted_table = Some(update);
+                    }
+                    ClientNetworkMessage::SeatRequested(request) => {
+                        info!(
+                            "{} asked to sit at {}",
+                            request.player_name, request.game_id
+                        );
+                        app_state.game_state.add_seat_request(request);
+                    }
+                    ClientNetworkMessage::SessionEnded(game_id) => {
+                        info!("Session ended at {}", game_id);
+                        app_state.game_state.end_session(&game_id);
+                    }
+                    ClientNetworkMessage::Settlement(report) => {
+                        info!("Settlement for {}", report.game_id);
+                        app_state.game_state.settlement = Some(report);
+                    }
                     ClientNetworkMessage::Chat(msg) => {
                         info!("Chat from {}: {}", msg.player_name, msg.text);
                         app_state.game_state.add_chat_message(msg);
@@ -578,11 +706,9 @@ fn trigger_reconnection(network_res: &Res<NetworkResources>, _commands: &mut Com
 
         let (mut write, read) = ws_stream.split();
 
-        let connect_msg = serde_json::json!({
-            "type": "connect"
-        });
+        let handshake = handshake_messages();
         if let Err(e) = write
-            .send(Message::Text(connect_msg.to_string().into()))
+            .send_all(&mut futures::stream::iter(handshake).map(Ok))
             .await
         {
             error!("Failed to send connect message during reconnection: {}", e);
@@ -597,7 +723,7 @@ fn trigger_reconnection(network_res: &Res<NetworkResources>, _commands: &mut Com
             return;
         }
 
-        let (write_tx, mut write_rx) = tokio::sync::mpsc::channel::<String>(100);
+        let (_write_tx, mut write_rx) = tokio::sync::mpsc::channel::<String>(100);
         let write_task = tokio::spawn(async move {
             while let Some(msg) = write_rx.recv().await {
                 if let Err(e) = write.send(Message::Text(msg.into())).await {
@@ -607,7 +733,9 @@ fn trigger_reconnection(network_res: &Res<NetworkResources>, _commands: &mut Com
         });
 
         let ui_tx_clone = ui_tx.clone();
+        let tx_for_read = tx_for_reconnect.clone();
         let read_task = tokio::spawn(async move {
+            let tx_for_reconnect = tx_for_read;
             let mut read = read;
             while let Some(result) = read.next().await {
                 match result {
@@ -705,12 +833,278 @@ fn update_ui(
                 .color(status_color),
             );
             ui.add_space(20.0);
+            let game_state = &app_state.game_state;
             ui.label(format!(
-                "Hand #{} | {}",
-                app_state.game_state.hand_number, app_state.game_state.current_street
+                "Hand #{} | {} {} | {}",
+                game_state.hand_number,
+                game_state.betting_structure,
+                game_state.variant,
+                game_state.current_street
             ));
+            ui.add_space(20.0);
+            if let Some(mut straddle) = app_state.straddle.try_lock() {
+                if ui.checkbox(&mut straddle, "Straddle").changed() {
+                    if let Ok(msg) = serde_json::to_string(&serde_json::json!({
+                        "type": "Straddle",
+                        "enabled": *straddle
+                    })) {
+                        let _ = network_res.ui_tx.send(msg);
+                        info!("Sent Straddle: {}", *straddle);
+                    }
+                }
+            }
+            if let Some(mut twice) = app_state.run_it_twice.try_lock() {
+                if ui.checkbox(&mut twice, "Run it twice").changed() {
+                    let times = if *twice { 2 } else { 1 };
+                    if let Ok(msg) = serde_json::to_string(&serde_json::json!({
+                        "type": "RunIt",
+                        "times": times
+                    })) {
+                        let _ = network_res.ui_tx.send(msg);
+                        info!("Sent RunIt: {}", times);
+                    }
+                }
+            }
+            let waiting_for_big_blind = app_state
+                .game_state
+                .players
+                .get(&app_state.game_state.my_id)
+                .is_some_and(|p| p.waiting_for_big_blind);
+            if waiting_for_big_blind {
+                ui.label("Waiting for the big blind");
+                if ui.button("Post Blind").clicked() {
+                    if let Ok(msg) = serde_json::to_string(&serde_json::json!({
+                        "type": "PostBlind"
+                    })) {
+                        let _ = network_res.ui_tx.send(msg);
+                        info!("Sent PostBlind");
+                    }
+                }
+            }
+            let my_chips = app_state
+                .game_state
+                .players
+                .get(&app_state.game_state.my_id)
+                .map(|p| p.chips);
+            if let Some(chips) = my_chips {
+                if let Some(mut amount) = app_state.chip_amount.try_lock() {
+                    ui.add(egui::TextEdit::singleline(&mut *amount).desired_width(60.0));
+                    let (add_type, label) = if chips == 0 {
+                        ("Rebuy", "Rebuy")
+                    } else {
+                        ("TopUp", "Top Up")
+                    };
+                    let add_amount = amount.parse::<i32>().ok().filter(|&a| a > 0);
+                    if ui
+                        .add_enabled(add_amount.is_some(), egui::Button::new(label))
+                        .clicked()
+                    {
+                        if let Ok(msg) = serde_json::to_string(&serde_json::json!({
+                            "type": add_type,
+                            "amount": add_amount
+                        })) {
+                            let _ = network_res.ui_tx.send(msg);
+                            info!("Sent {}: {:?}", add_type, add_amount);
+                            amount.clear();
+                        }
+                    }
+                }
+                if ui.button("Cash Out").clicked() {
+                    if let Ok(msg) = serde_json::to_string(&serde_json::json!({
+                        "type": "CashOut"
+                    })) {
+                        let _ = network_res.ui_tx.send(msg);
+                        info!("Sent CashOut");
+                    }
+                }
+            } else {
+                let game_state = &app_state.game_state;
+                let place = game_state
+                    .waitlist
+                    .iter()
+                    .position(|id| *id == game_state.my_id);
+                let (msg_type, label) = match (&game_state.seat_offer, place) {
+                    (Some(offer), _) => ("AcceptSeat", format!("Take seat {}", offer.seat + 1)),
+                    (None, Some(place)) => {
+                        ui.label(format!("Waiting list #{}", place + 1));
+                        ("LeaveWaitlist", "Leave Waiting List".to_string())
+                    }
+                    (None, None) => ("JoinWaitlist", "Join Waiting List".to_string()),
+                };
+                if ui.button(label).clicked() {
+                    if let Ok(msg) = serde_json::to_string(&serde_json::json!({
+                        "type": msg_type
+                    })) {
+                        let _ = network_res.ui_tx.send(msg);
+                        info!("Sent {}", msg_type);
+                    }
+                }
+                let spectating = game_state.spectating.load(Ordering::Relaxed);
+                let (msg_type, label) = if spectating {
+                    ("StopSpectating", "Stop Watching")
+                } else {
+                    ("Spectate", "Watch Table")
+                };
+                if ui.button(label).clicked() {
+                    if let Ok(msg) = serde_json::to_string(&serde_json::json!({
+                        "type": msg_type
+                    })) {
+                        let _ = network_res.ui_tx.send(msg);
+                        info!("Sent {}", msg_type);
+                        game_state.spectating.store(!spectating, Ordering::Relaxed);
+                    }
+                }
+            }
         });
 
+        if app_state.game_state.paused {
+            ui.label("Paused by the host");
+        }
+        if let Some(table) = &app_state.game_state.hosted_table {
+            ui.label(format!("Hosting {}", table.game_id));
+            if !table.invite_codes.is_empty() {
+                ui.label(format!("Invite codes: {}", table.invite_codes.join(" ")));
+            }
+            let send_host_command = |command: serde_json::Value| {
+                if let Ok(msg) = serde_json::to_string(&command) {
+                    let _ = network_res.ui_tx.send(msg);
+                    info!("Sent host command {}", command["type"]);
+                }
+            };
+            ui.horizontal(|ui| {
+                let (msg_type, label) = if app_state.game_state.paused {
+                    ("ResumeTable", "Resume")
+                } else {
+                    ("PauseTable", "Pause")
+                };
+                if ui.button(label).clicked() {
+                    send_host_command(serde_json::json!({ "type": msg_type }));
+                }
+                if let Some(mut blinds) = app_state.blinds_input.try_lock() {
+                    ui.add(egui::TextEdit::singleline(&mut *blinds).desired_width(60.0));
+                    if ui.button("Set Blinds").clicked() {
+                        let parsed = blinds.split_once('/').and_then(|(sb, bb)| {
+                            Some((
+                                sb.trim().parse::<i32>().ok()?,
+                                bb.trim().parse::<i32>().ok()?,
+                            ))
+                        });
+                        if let Some((small_blind, big_blind)) = parsed {
+                            send_host_command(serde_json::json!({
+                                "type": "SetBlinds",
+                                "small_blind": small_blind,
+                                "big_blind": big_blind
+                            }));
+                            blinds.clear();
+                        }
+                    }
+                }
+                if ui.button("End Session").clicked() {
+                    send_host_command(serde_json::json!({ "type": "EndSession" }));
+                }
+            });
+            for player in app_state.game_state.players.values() {
+                if player.id == app_state.game_state.my_id {
+                    continue;
+                }
+                ui.horizontal(|ui| {
+                    ui.label(&player.name);
+                    for (msg_type, label) in [("SitOutPlayer", "Sit Out"), ("KickPlayer", "Kick")] {
+                        if ui.button(label).clicked() {
+                            send_host_command(serde_json::json!({
+                                "type": msg_type,
+                                "player_id": player.id
+                            }));
+                        }
+                    }
+                });
+            }
+        }
+        if let Some(report) = &app_state.game_state.settlement {
+            ui.label(format!("Settle up for {}", report.game_id));
+            for transfer in &report.transfers {
+                ui.label(format!(
+                    "{} pays {} ${}",
+                    report.name_of(&transfer.from),
+                    report.name_of(&transfer.to),
+                    transfer.amount
+                ));
+            }
+            if report.rake > 0 {
+                ui.label(format!("Rake: ${}", report.rake));
+            }
+            ui.horizontal(|ui| {
+                for (extension, label) in [("csv", "Export CSV"), ("json", "Export JSON")] {
+                    if ui.button(label).clicked() {
+                        let contents = if extension == "csv" {
+                            Ok(report.to_csv())
+                        } else {
+                            report.to_json().map_err(|e| e.to_string())
+                        };
+                        let path = format!("settlement-{}.{}", report.game_id, extension);
+                        match contents
+                            .and_then(|c| std::fs::write(&path, c).map_err(|e| e.to_string()))
+                        {
+                            Ok(()) => info!("Exported settlement to {}", path),
+                            Err(e) => error!("Failed to export settlement: {}", e),
+                        }
+                    }
+                }
+            });
+        }
+        if let Some(mut requests) = app_state.game_state.seat_requests.try_lock() {
+            requests.retain(|request| {
+                let mut answered = false;
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} wants a seat", request.player_name));
+                    for (msg_type, label) in [("ApproveSeat", "Approve"), ("RejectSeat", "Reject")]
+                    {
+                        if ui.button(label).clicked() {
+                            if let Ok(msg) = serde_json::to_string(&serde_json::json!({
+                                "type": msg_type,
+                                "player_id": request.player_id
+                            })) {
+                                let _ = network_res.ui_tx.send(msg);
+                                info!("Sent {} for {}", msg_type, request.player_id);
+                                answered = true;
+                            }
+                        }
+                    }
+                });
+                !answered
+            });
+        }
+
+        let forced_bets: Vec<String> = app_state
+            .game_state
+            .forced_bets
+            .iter()
+            .map(|bet| {
+                let name = app_state
+                    .game_state
+                    .players
+                    .get(&bet.player_id)
+                    .map(|p| p.name.as_str())
+                    .unwrap_or(bet.player_id.as_str());
+                format!("{} {} ${}", name, bet.kind, bet.amount)
+            })
+            .collect();
+        if !forced_bets.is_empty() {
+            ui.label(format!("Posted: {}", forced_bets.join(", ")));
+        }
+        if let Some(showdown) = &app_state.game_state.showdown {
+            if showdown.boards.len() > 1 {
+                for (run, board) in showdown.boards.iter().enumerate() {
+                    ui.label(format!(
+                        "Run {}: {} won by {}",
+                        run + 1,
+                        board.community_cards.join(" "),
+                        board.winners.join(", ")
+                    ));
+                }
+            }
+        }
+
         ui.add_space(10.0);
         ui.separator();
         ui.add_space(10.0);
@@ -794,29 +1188,34 @@ fn update_ui(
                     || villain.is_folded
                     || villain.is_all_in)
             {
-                let card1_rect = egui::Rect::from_center_size(
-                    egui::pos2(villain_pos.x - 30.0, villain_pos.y + 35.0),
+                let rects = hole_card_rects(
+                    egui::pos2(villain_pos.x, villain_pos.y + 35.0),
+                    villain.hole_cards.len(),
                     egui::Vec2::new(35.0, 49.0),
                 );
-                draw_card(ui.painter(), card1_rect, &villain.hole_cards[0]);
-                if villain.hole_cards.len() >= 2 {
-                    let card2_rect = egui::Rect::from_center_size(
-                        egui::pos2(villain_pos.x + 30.0, villain_pos.y + 35.0),
-                        egui::Vec2::new(35.0, 49.0),
-                    );
-                    draw_card(ui.painter(), card2_rect, &villain.hole_cards[1]);
+                for (rect, card) in rects.into_iter().zip(&villain.hole_cards) {
+                    draw_card(ui.painter(), rect, card);
                 }
             } else if villain.hole_cards.is_empty() {
-                let card1_rect = egui::Rect::from_center_size(
-                    egui::pos2(villain_pos.x - 30.0, villain_pos.y + 35.0),
+                let rects = hole_card_rects(
+                    egui::pos2(villain_pos.x, villain_pos.y + 35.0),
+                    app_state.game_state.variant.hole_card_count(),
                     egui::Vec2::new(35.0, 49.0),
                 );
-                let card2_rect = egui::Rect::from_center_size(
-                    egui::pos2(villain_pos.x + 30.0, villain_pos.y + 35.0),
+                for rect in rects {
+                    draw_back_of_card(ui.painter(), rect);
+                }
+            }
+
+            if !villain.up_cards.is_empty() {
+                let rects = hole_card_rects(
+                    egui::pos2(villain_pos.x, villain_pos.y + 90.0),
+                    villain.up_cards.len(),
                     egui::Vec2::new(35.0, 49.0),
                 );
-                draw_back_of_card(ui.painter(), card1_rect);
-                draw_back_of_card(ui.painter(), card2_rect);
+                for (rect, card) in rects.into_iter().zip(&villain.up_cards) {
+                    draw_card(ui.painter(), rect, card);
+                }
             }
         }
 
@@ -849,17 +1248,50 @@ fn update_ui(
             );
 
             if !hero.hole_cards.is_empty() {
-                let card1_rect = egui::Rect::from_center_size(
-                    egui::pos2(hero_pos.x - 35.0, hero_pos.y + 35.0),
+                let rects = hole_card_rects(
+                    egui::pos2(hero_pos.x, hero_pos.y + 35.0),
+                    hero.hole_cards.len(),
                     egui::Vec2::new(40.0, 56.0),
                 );
-                draw_card(ui.painter(), card1_rect, &hero.hole_cards[0]);
-                if hero.hole_cards.len() >= 2 {
-                    let card2_rect = egui::Rect::from_center_size(
-                        egui::pos2(hero_pos.x + 35.0, hero_pos.y + 35.0),
-                        egui::Vec2::new(40.0, 56.0),
-                    );
-                    draw_card(ui.painter(), card2_rect, &hero.hole_cards[1]);
+                let is_drawing = app_state
+                    .game_state
+                    .action_required
+                    .as_ref()
+                    .is_some_and(|a| a.is_draw && a.player_id == app_state.game_state.my_id);
+                for (i, (rect, card)) in rects.into_iter().zip(&hero.hole_cards).enumerate() {
+                    draw_card(ui.painter(), rect, card);
+                    if !is_drawing {
+                        continue;
+                    }
+                    let response =
+                        ui.interact(rect, egui::Id::new(("hole_card", i)), egui::Sense::click());
+                    if let Some(mut discards) = app_state.discards.try_lock() {
+                        if response.clicked() {
+                            if let Some(pos) = discards.iter().position(|c| c == card) {
+                                discards.remove(pos);
+                            } else {
+                                discards.push(card.clone());
+                            }
+                        }
+                        if discards.contains(card) {
+                            ui.painter().rect_stroke(
+                                rect,
+                                4.0,
+                                egui::Stroke::new(3.0, egui::Color32::YELLOW),
+                            );
+                        }
+                    }
+                }
+            }
+
+            if !hero.up_cards.is_empty() {
+                let rects = hole_card_rects(
+                    egui::pos2(hero_pos.x, hero_pos.y - 85.0),
+                    hero.up_cards.len(),
+                    egui::Vec2::new(40.0, 56.0),
+                );
+                for (rect, card) in rects.into_iter().zip(&hero.up_cards) {
+                    draw_card(ui.painter(), rect, card);
                 }
             }
         }
@@ -873,12 +1305,51 @@ fn update_ui(
             let action_min_raise = action.min_raise;
             let action_current_bet = action.current_bet;
             let action_player_chips = action.player_chips;
+            let action_max_raise = if action.max_raise > 0 {
+                action.max_raise
+            } else {
+                action_player_chips
+            };
+
+            if is_my_turn && action.is_draw {
+                ui.colored_label(egui::Color32::GREEN, "YOUR DRAW!");
+                let selected = app_state
+                    .discards
+                    .try_lock()
+                    .map(|g| g.clone())
+                    .unwrap_or_default();
+                ui.label("Click cards to select them for discarding.");
 
-            if is_my_turn {
+                ui.add_space(15.0);
+                let label = if selected.is_empty() {
+                    "Stand Pat".to_string()
+                } else {
+                    format!("Draw {}", selected.len())
+                };
+                let draw_btn = egui::Button::new(label)
+                    .fill(egui::Color32::from_rgb(0, 100, 200))
+                    .min_size(egui::Vec2::new(120.0, 40.0));
+                if ui.add(draw_btn).clicked() {
+                    let cards: Vec<poker_protocol::Card> = selected
+                        .iter()
+                        .filter_map(|c| poker_protocol::Card::parse(c))
+                        .collect();
+                    if let Ok(msg) = serde_json::to_string(&serde_json::json!({
+                        "type": "Action",
+                        "action": { "Draw": cards }
+                    })) {
+                        let _ = network_res.ui_tx.send(msg);
+                        info!("Sent Draw action: {} cards", cards.len());
+                        if let Some(mut guard) = app_state.discards.try_lock() {
+                            guard.clear();
+                        }
+                    }
+                }
+            } else if is_my_turn {
                 ui.colored_label(egui::Color32::GREEN, "YOUR TURN!");
                 ui.label(format!(
-                    "Min raise: ${} | Your chips: ${}",
-                    action_min_raise, action_player_chips
+                    "Min raise: ${} | Max raise: ${} | Your chips: ${}",
+                    action_min_raise, action_max_raise, action_player_chips
                 ));
 
                 ui.add_space(15.0);
@@ -888,7 +1359,7 @@ fn update_ui(
                         .min_size(egui::Vec2::new(100.0, 40.0));
                     if ui.add(fold_btn).clicked() {
                         if let Ok(msg) = serde_json::to_string(&serde_json::json!({
-                            "type": "action",
+                            "type": "Action",
                             "action": "Fold"
                         })) {
                             let _ = network_res.ui_tx.send(msg);
@@ -905,7 +1376,7 @@ fn update_ui(
                             .min_size(egui::Vec2::new(100.0, 40.0));
                         if ui.add(check_btn).clicked() {
                             if let Ok(msg) = serde_json::to_string(&serde_json::json!({
-                                "type": "action",
+                                "type": "Action",
                                 "action": "Check"
                             })) {
                                 let _ = network_res.ui_tx.send(msg);
@@ -918,7 +1389,7 @@ fn update_ui(
                             .min_size(egui::Vec2::new(120.0, 40.0));
                         if ui.add(call_btn).clicked() {
                             if let Ok(msg) = serde_json::to_string(&serde_json::json!({
-                                "type": "action",
+                                "type": "Action",
                                 "action": "Call"
                             })) {
                                 let _ = network_res.ui_tx.send(msg);
@@ -952,10 +1423,11 @@ fn update_ui(
                         .min_size(egui::Vec2::new(100.0, 40.0));
                     let can_raise = is_valid_raise
                         && raise_amount_clamped >= action_min_raise
-                        && raise_amount_clamped <= action_player_chips;
+                        && raise_amount_clamped <= action_player_chips
+                        && raise_amount_clamped <= action_max_raise;
                     if ui.add_enabled(can_raise, raise_btn).clicked() {
                         if let Ok(msg) = serde_json::to_string(&serde_json::json!({
-                            "type": "action",
+                            "type": "Action",
                             "action": "Raise",
                              "amount": raise_amount_clamped
                         })) {
@@ -974,7 +1446,7 @@ fn update_ui(
                         .min_size(egui::Vec2::new(100.0, 40.0));
                     if ui.add(allin_btn).clicked() {
                         if let Ok(msg) = serde_json::to_string(&serde_json::json!({
-                            "type": "action",
+                            "type": "Action",
                             "action": "AllIn"
                         })) {
                             let _ = network_res.ui_tx.send(msg);
@@ -1021,7 +1493,7 @@ fn update_ui(
                     && !pending_chat_text.is_empty()
                 {
                     if let Ok(msg) = serde_json::to_string(&serde_json::json!({
-                        "type": "chat",
+                        "type": "Chat",
                         "text": pending_chat_text
                     })) {
                         let _ = network_res.ui_tx.send(msg);
@@ -1041,6 +1513,27 @@ fn update_ui(
     });
 }
 
+/// Lays out `count` hole cards side by side, centred on `center`.
+///
+/// Two-card hands are spread apart as before; larger hands (such as Omaha)
+/// are packed more tightly so they fit inside the seat.
+fn hole_card_rects(center: egui::Pos2, count: usize, card_size: egui::Vec2) -> Vec<egui::Rect> {
+    let spacing = if count > HOLE_CARDS_COUNT {
+        card_size.x + 4.0
+    } else {
+        card_size.x * 1.75
+    };
+    let offset = count.saturating_sub(1) as f32 * spacing / 2.0;
+    (0..count)
+        .map(|i| {
+            egui::Rect::from_center_size(
+                egui::pos2(center.x - offset + i as f32 * spacing, center.y),
+                card_size,
+            )
+        })
+        .collect()
+}
+
 fn draw_card(painter: &egui::Painter, rect: egui::Rect, card: &str) {
     painter.rect_filled(rect, 4.0, egui::Color32::WHITE);
     painter.rect_stroke(rect, 4.0, egui::Stroke::new(1.0, egui::Color32::BLACK));
@@ -1083,6 +1576,7 @@ fn draw_back_of_card(painter: &egui::Painter, rect: egui::Rect) {
 
 fn convert_message(msg: crate::network::NetworkMessage) -> ClientNetworkMessage {
     match msg {
+        crate::network::NetworkMessage::Hello(hello) => ClientNetworkMessage::Hello(hello),
         crate::network::NetworkMessage::PlayerIdConfirmed(id) => {
             ClientNetworkMessage::PlayerIdConfirmed(id)
         }
@@ -1102,6 +1596,30 @@ fn convert_message(msg: crate::network::NetworkMessage) -> ClientNetworkMessage
             ClientNetworkMessage::PlayerDisconnected(id)
         }
         crate::network::NetworkMessage::Showdown(update) => ClientNetworkMessage::Showdown(update),
+        crate::network::NetworkMessage::HandsRevealed(update) => {
+            ClientNetworkMessage::HandsRevealed(update)
+        }
+        crate::network::NetworkMessage::CashedOut(update) => {
+            ClientNetworkMessage::CashedOut(update)
+        }
+        crate::network::NetworkMessage::SeatOffered(offer) => {
+            ClientNetworkMessage::SeatOffered(offer)
+        }
+        crate::network::NetworkMessage::WaitlistUpdated(update) => {
+            ClientNetworkMessage::WaitlistUpdated(update)
+        }
+        crate::network::NetworkMessage::TableCreated(update) => {
+            ClientNetworkMessage::TableCreated(update)
+        }
+        crate::network::NetworkMessage::SeatRequested(request) => {
+            ClientNetworkMessage::SeatRequested(request)
+        }
+        crate::network::NetworkMessage::SessionEnded(game_id) => {
+            ClientNetworkMessage::SessionEnded(game_id)
+        }
+        crate::network::NetworkMessage::Settlement(report) => {
+            ClientNetworkMessage::Settlement(report)
+        }
         crate::network::NetworkMessage::Chat(msg) => ClientNetworkMessage::Chat(msg),
         crate::network::NetworkMessage::Error(msg) => ClientNetworkMessage::Error(msg),
         crate::network::NetworkMessage::Ping(_) => {
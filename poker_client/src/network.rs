@@ -1,10 +1,13 @@
 use crate::game::{
-    ActionRequiredUpdate, ChatMessage, GameStateUpdate, PlayerConnectedUpdate, PlayerUpdate,
-    ShowdownUpdate,
+    ActionRequiredUpdate, CashedOutUpdate, ChatMessage, GameStateUpdate, HandsRevealedUpdate,
+    PlayerConnectedUpdate, PlayerUpdate, SeatOfferedUpdate, SeatRequestUpdate, SettlementReport,
+    ShowdownUpdate, TableCreatedUpdate, WaitlistUpdate,
 };
+use poker_protocol::Hello;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NetworkMessage {
+    Hello(Hello),
     PlayerIdConfirmed(String),
     GameState(GameStateUpdate),
     PlayerUpdates(Vec<PlayerUpdate>),
@@ -12,6 +15,14 @@ pub enum NetworkMessage {
     PlayerConnected(PlayerConnectedUpdate),
     PlayerDisconnected(String),
     Showdown(ShowdownUpdate),
+    HandsRevealed(HandsRevealedUpdate),
+    CashedOut(CashedOutUpdate),
+    SeatOffered(SeatOfferedUpdate),
+    WaitlistUpdated(WaitlistUpdate),
+    TableCreated(TableCreatedUpdate),
+    SeatRequested(SeatRequestUpdate),
+    SessionEnded(String),
+    Settlement(SettlementReport),
     Chat(ChatMessage),
     Error(String),
     Ping(u64),
@@ -32,6 +43,7 @@ fn parse_message_by_type(
     value: &serde_json::Value,
 ) -> Result<NetworkMessage, serde_json::Error> {
     match type_str {
+        "Hello" => serde_json::from_value::<Hello>(value.clone()).map(NetworkMessage::Hello),
         "GameStateUpdate" => {
             serde_json::from_value::<GameStateUpdate>(value.clone()).map(NetworkMessage::GameState)
         }
@@ -52,6 +64,24 @@ fn parse_message_by_type(
         "Showdown" => {
             serde_json::from_value::<ShowdownUpdate>(value.clone()).map(NetworkMessage::Showdown)
         }
+        "HandsRevealed" => serde_json::from_value::<HandsRevealedUpdate>(value.clone())
+            .map(NetworkMessage::HandsRevealed),
+        "CashedOut" => {
+            serde_json::from_value::<CashedOutUpdate>(value.clone()).map(NetworkMessage::CashedOut)
+        }
+        "SeatOffered" => serde_json::from_value::<SeatOfferedUpdate>(value.clone())
+            .map(NetworkMessage::SeatOffered),
+        "WaitlistUpdated" => serde_json::from_value::<WaitlistUpdate>(value.clone())
+            .map(NetworkMessage::WaitlistUpdated),
+        "TableCreated" => serde_json::from_value::<TableCreatedUpdate>(value.clone())
+            .map(NetworkMessage::TableCreated),
+        "SeatRequested" => serde_json::from_value::<SeatRequestUpdate>(value.clone())
+            .map(NetworkMessage::SeatRequested),
+        "SessionEnded" => Ok(NetworkMessage::SessionEnded(
+            value["game_id"].as_str().unwrap_or_default().to_string(),
+        )),
+        "Settlement" => serde_json::from_value::<SettlementReport>(value.clone())
+            .map(NetworkMessage::Settlement),
         "Chat" => serde_json::from_value::<ChatMessage>(value.clone()).map(NetworkMessage::Chat),
         "Error" => {
             let error_msg = value["message"]
@@ -108,6 +138,31 @@ fn parse_message_by_type(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_hello() {
+        let json = r#"{"type": "Hello", "version": 1, "min_version": 1, "codecs": ["json"], "signing": false, "features": ["waitlist"]}"#;
+        match parse_message(json).unwrap() {
+            NetworkMessage::Hello(hello) => {
+                assert_eq!(hello.version, 1);
+                assert_eq!(hello.codecs, vec!["json".to_string()]);
+                assert_eq!(hello.features, vec!["waitlist".to_string()]);
+            }
+            other => panic!("Expected Hello, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_settlement() {
+        let json = r#"{"type": "Settlement", "game_id": "private-1", "results": [{"player_id": "p1", "player_name": "Ann", "bought_in": 200, "cashed_out": 120, "net": -80}, {"player_id": "p2", "player_name": "Bo", "bought_in": 200, "cashed_out": 280, "net": 80}], "transfers": [{"from": "p1", "to": "p2", "amount": 80}], "rake": 0}"#;
+        match parse_message(json).unwrap() {
+            NetworkMessage::Settlement(report) => {
+                assert_eq!(report.transfers.len(), 1);
+                assert_eq!(report.name_of("p1"), "Ann");
+            }
+            other => panic!("Expected Settlement, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_parse_game_state_update() {
         let json = r#"{"type": "GameStateUpdate", "game_id": "test", "hand_number": 1, "pot": 100, "side_pots": [], "community_cards": ["A♥", "K♠"], "current_street": "Flop", "dealer_position": 0}"#;
@@ -235,6 +290,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_cashed_out() {
+        let json = r#"{"type": "CashedOut", "player_id": "p1", "chips": 1250}"#;
+        match parse_message(json) {
+            Ok(NetworkMessage::CashedOut(update)) => {
+                assert_eq!(update.player_id, "p1");
+                assert_eq!(update.chips, 1250);
+            }
+            other => panic!("Expected CashedOut message, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_seat_offered() {
+        let json =
+            r#"{"type": "SeatOffered", "player_id": "p1", "seat": 3, "expires_in_secs": 30}"#;
+        match parse_message(json) {
+            Ok(NetworkMessage::SeatOffered(offer)) => {
+                assert_eq!(offer.seat, 3);
+                assert_eq!(offer.expires_in_secs, 30);
+            }
+            other => panic!("Expected SeatOffered message, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_seat_requested() {
+        let json = r#"{"type": "SeatRequested", "game_id": "private-1", "player_id": "p2", "player_name": "Player2"}"#;
+        match parse_message(json) {
+            Ok(NetworkMessage::SeatRequested(request)) => {
+                assert_eq!(request.game_id, "private-1");
+                assert_eq!(request.player_name, "Player2");
+            }
+            other => panic!("Expected SeatRequested message, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_hands_revealed() {
+        let json = r#"{"type": "HandsRevealed", "hands": [["p1", ["A♥", "K♠"]]], "runs": 2}"#;
+        match parse_message(json) {
+            Ok(NetworkMessage::HandsRevealed(update)) => {
+                assert_eq!(update.runs, 2);
+                assert_eq!(update.hands[0].1.len(), 2);
+            }
+            other => panic!("Expected HandsRevealed message, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_parse_ping() {
         let json = r#"{"type": "Ping", "timestamp": 1234567890}"#;
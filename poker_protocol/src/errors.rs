@@ -31,6 +31,12 @@ pub enum ProtocolError {
 
     #[error("Failed to generate HMAC key")]
     HmacKeyGeneration,
+
+    #[error("Invalid ICM input: {0}")]
+    InvalidIcmInput(String),
+
+    #[error("Incompatible client: {0}")]
+    Incompatible(String),
 }
 
 impl From<serde_json::Error> for ProtocolError {
@@ -101,6 +107,87 @@ pub enum ServerError {
     #[error("Game is full")]
     GameFull,
 
+    #[error("Invalid draw: {0}")]
+    InvalidDraw(String),
+
+    #[error("Straddles are not allowed at this table")]
+    StraddleNotAllowed,
+
+    #[error("No blinds owed")]
+    NoBlindsOwed,
+
+    #[error("This table allows running it at most {0} times")]
+    TooManyRuns(u32),
+
+    #[error("Buy-in must be between {0} and {1}")]
+    BuyInOutOfRange(i32, i32),
+
+    #[error("Amount exceeds your balance ({0})")]
+    InsufficientBalance(i32),
+
+    #[error("Already seated at this table")]
+    AlreadySeated,
+
+    #[error("Rebuys are only for players with no chips; top up instead")]
+    RebuyNotBusted,
+
+    #[error("You can add at most {0} more chips")]
+    TopUpExceedsMax(i32),
+
+    #[error("Cannot cash out while in a hand")]
+    CashOutInHand,
+
+    #[error("Seat {0} does not exist")]
+    InvalidSeat(usize),
+
+    #[error("Seat {0} is taken")]
+    SeatTaken(usize),
+
+    #[error("Not on the waiting list")]
+    NotOnWaitlist,
+
+    #[error("No seat has been offered to you")]
+    NoSeatOffer,
+
+    #[error("Not watching this table")]
+    NotSpectating,
+
+    #[error("Spectators cannot chat at this table")]
+    SpectatorChatDisabled,
+
+    #[error("Wrong password or invite code")]
+    InvalidTableCredential,
+
+    #[error("Invalid table settings: {0}")]
+    InvalidTableSettings(String),
+
+    #[error("Only the table's host can do that")]
+    NotTableHost,
+
+    #[error("No seat request from player {0}")]
+    NoSeatRequest(String),
+
+    #[error("The host turned down your seat request")]
+    SeatRequestRejected,
+
+    #[error("The table is not paused")]
+    NotPaused,
+
+    #[error("Wait for the hand to finish")]
+    HandInProgress,
+
+    #[error("Player {0} is in a hand; they are sitting out and can be removed once it ends")]
+    KickInHand(String),
+
+    #[error("The host removed you from the table")]
+    RemovedByHost,
+
+    #[error("An administrator removed you from the server")]
+    RemovedByAdmin,
+
+    #[error("Hello may only be sent once, as the first message")]
+    UnexpectedHello,
+
     #[error("Mutex lock failed")]
     LockFailed,
 
@@ -0,0 +1,396 @@
+use std::collections::HashSet;
+
+use ring::rand::SecureRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ProtocolError;
+
+/// Largest field evaluated with the exact recursion; bigger fields use Monte Carlo.
+pub const EXACT_ICM_MAX_PLAYERS: usize = 12;
+/// Number of simulated finishing orders used for large fields.
+pub const DEFAULT_ICM_ITERATIONS: usize = 200_000;
+
+fn validate_icm_input(stacks: &[i32], payouts: &[i32]) -> Result<(), ProtocolError> {
+    if stacks.is_empty() {
+        return Err(ProtocolError::InvalidIcmInput(
+            "at least one stack is required".to_string(),
+        ));
+    }
+    if stacks.iter().any(|&s| s <= 0) {
+        return Err(ProtocolError::InvalidIcmInput(
+            "stacks must be positive".to_string(),
+        ));
+    }
+    if payouts.iter().any(|&p| p < 0) {
+        return Err(ProtocolError::InvalidIcmInput(
+            "payouts must not be negative".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Calculates each player's prize equity with the Independent Chip Model.
+///
+/// Fields of up to `EXACT_ICM_MAX_PLAYERS` players are solved exactly; larger
+/// fields are estimated with `DEFAULT_ICM_ITERATIONS` simulated finishing orders.
+///
+/// # Arguments
+/// * `stacks` - Chip stack of every remaining player
+/// * `payouts` - Prize for each finishing place, first place first
+///
+/// # Returns
+/// `Ok(Vec<f64>)` with one equity per stack, `Err(ProtocolError::InvalidIcmInput)` otherwise
+pub fn icm_equities(stacks: &[i32], payouts: &[i32]) -> Result<Vec<f64>, ProtocolError> {
+    if stacks.len() <= EXACT_ICM_MAX_PLAYERS {
+        return icm_equities_exact(stacks, payouts);
+    }
+    let mut seed = [0u8; 8];
+    ring::rand::SystemRandom::new()
+        .fill(&mut seed)
+        .map_err(|_| ProtocolError::InvalidIcmInput("random source unavailable".to_string()))?;
+    icm_equities_monte_carlo(
+        stacks,
+        payouts,
+        DEFAULT_ICM_ITERATIONS,
+        u64::from_le_bytes(seed),
+    )
+}
+
+/// Calculates ICM equities exactly using the Malmuth-Harville recursion.
+///
+/// The recursion walks every set of players that can occupy the paid places,
+/// so its cost grows with `2^players`. Fields larger than
+/// `EXACT_ICM_MAX_PLAYERS` are rejected; use `icm_equities_monte_carlo` for them.
+///
+/// # Arguments
+/// * `stacks` - Chip stack of every remaining player
+/// * `payouts` - Prize for each finishing place, first place first
+///
+/// # Returns
+/// `Ok(Vec<f64>)` with one equity per stack, `Err(ProtocolError::InvalidIcmInput)` otherwise
+pub fn icm_equities_exact(stacks: &[i32], payouts: &[i32]) -> Result<Vec<f64>, ProtocolError> {
+    validate_icm_input(stacks, payouts)?;
+    if stacks.len() > EXACT_ICM_MAX_PLAYERS {
+        return Err(ProtocolError::InvalidIcmInput(format!(
+            "too many players for exact ICM: {} (at most {})",
+            stacks.len(),
+            EXACT_ICM_MAX_PLAYERS
+        )));
+    }
+
+    let player_count = stacks.len();
+    let paid_places = payouts.len().min(player_count);
+    let total_chips: f64 = stacks.iter().map(|&s| s as f64).sum();
+    let mut equities = vec![0.0; player_count];
+
+    // probability[mask] is the chance that exactly the players in `mask`
+    // finished in the top `mask.count_ones()` places.
+    let mut probability = vec![0.0f64; 1 << player_count];
+    probability[0] = 1.0;
+
+    for mask in 0..probability.len() {
+        let place = mask.count_ones() as usize;
+        let reach = probability[mask];
+        if place >= paid_places || reach == 0.0 {
+            continue;
+        }
+
+        let taken_chips: f64 = (0..player_count)
+            .filter(|i| mask & (1 << i) != 0)
+            .map(|i| stacks[i] as f64)
+            .sum();
+        let remaining_chips = total_chips - taken_chips;
+
+        for (i, &stack) in stacks.iter().enumerate() {
+            if mask & (1 << i) != 0 {
+                continue;
+            }
+            let finish = reach * stack as f64 / remaining_chips;
+            equities[i] += finish * payouts[place] as f64;
+            probability[mask | (1 << i)] += finish;
+        }
+    }
+
+    Ok(equities)
+}
+
+/// Estimates ICM equities by sampling finishing orders.
+///
+/// # Arguments
+/// * `stacks` - Chip stack of every remaining player
+/// * `payouts` - Prize for each finishing place, first place first
+/// * `iterations` - Number of finishing orders to simulate
+/// * `seed` - Seed for the sampler, so results are reproducible
+///
+/// # Returns
+/// `Ok(Vec<f64>)` with one equity per stack, `Err(ProtocolError::InvalidIcmInput)` otherwise
+pub fn icm_equities_monte_carlo(
+    stacks: &[i32],
+    payouts: &[i32],
+    iterations: usize,
+    seed: u64,
+) -> Result<Vec<f64>, ProtocolError> {
+    validate_icm_input(stacks, payouts)?;
+    if iterations == 0 {
+        return Err(ProtocolError::InvalidIcmInput(
+            "iterations must be positive".to_string(),
+        ));
+    }
+
+    let player_count = stacks.len();
+    let paid_places = payouts.len().min(player_count);
+    let total_chips: i64 = stacks.iter().map(|&s| s as i64).sum();
+    let mut rng = SplitMix64(seed);
+    let mut totals = vec![0.0f64; player_count];
+    let mut finished = vec![false; player_count];
+
+    for _ in 0..iterations {
+        finished.iter_mut().for_each(|f| *f = false);
+        let mut remaining_chips = total_chips;
+
+        for &payout in payouts.iter().take(paid_places) {
+            let mut target = rng.next_below(remaining_chips as u64) as i64;
+            let mut chosen = 0;
+            for (i, &stack) in stacks.iter().enumerate() {
+                if finished[i] {
+                    continue;
+                }
+                chosen = i;
+                if target < stack as i64 {
+                    break;
+                }
+                target -= stack as i64;
+            }
+            finished[chosen] = true;
+            remaining_chips -= stacks[chosen] as i64;
+            totals[chosen] += payout as f64;
+        }
+    }
+
+    Ok(totals
+        .into_iter()
+        .map(|total| total / iterations as f64)
+        .collect())
+}
+
+/// Small deterministic generator used for Monte Carlo sampling.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn next_below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+/// An ICM deal offered to every remaining player at a final table.
+///
+/// The deal only takes effect once every player has accepted it; a single
+/// rejection cancels it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IcmChopProposal {
+    /// Players in the deal, in the order they were given
+    pub player_ids: Vec<String>,
+    /// Chip stacks the deal was calculated from
+    pub stacks: Vec<i32>,
+    /// Prize each player receives if the deal is accepted
+    pub amounts: Vec<i32>,
+    accepted: HashSet<String>,
+    rejected: bool,
+}
+
+impl IcmChopProposal {
+    /// Creates a chop proposal from the current stacks and the remaining payouts.
+    ///
+    /// Equities are rounded down to whole chips and the leftover chips go to the
+    /// largest fractional remainders, so the amounts always add up to the prize pool.
+    ///
+    /// # Arguments
+    /// * `players` - `(player_id, stack)` for every remaining player
+    /// * `payouts` - Prize for each remaining place, first place first
+    ///
+    /// # Returns
+    /// `Ok(Self)` on success, `Err(ProtocolError::InvalidIcmInput)` otherwise
+    pub fn new(players: &[(String, i32)], payouts: &[i32]) -> Result<Self, ProtocolError> {
+        let stacks: Vec<i32> = players.iter().map(|(_, stack)| *stack).collect();
+        let equities = icm_equities(&stacks, payouts)?;
+
+        let prize_pool: i64 = payouts.iter().take(players.len()).map(|&p| p as i64).sum();
+        let mut amounts: Vec<i32> = equities.iter().map(|e| e.floor() as i32).collect();
+        let mut leftover = prize_pool - amounts.iter().map(|&a| a as i64).sum::<i64>();
+
+        let mut by_remainder: Vec<usize> = (0..players.len()).collect();
+        by_remainder.sort_by(|&a, &b| {
+            let ra = equities[a] - equities[a].floor();
+            let rb = equities[b] - equities[b].floor();
+            rb.total_cmp(&ra).then_with(|| stacks[b].cmp(&stacks[a]))
+        });
+        for idx in by_remainder.into_iter().cycle().take(players.len() * 2) {
+            if leftover <= 0 {
+                break;
+            }
+            amounts[idx] += 1;
+            leftover -= 1;
+        }
+
+        Ok(Self {
+            player_ids: players.iter().map(|(id, _)| id.clone()).collect(),
+            stacks,
+            amounts,
+            accepted: HashSet::new(),
+            rejected: false,
+        })
+    }
+
+    /// Returns the prize offered to a player, if they are part of the deal.
+    pub fn amount_for(&self, player_id: &str) -> Option<i32> {
+        self.player_ids
+            .iter()
+            .position(|id| id == player_id)
+            .map(|idx| self.amounts[idx])
+    }
+
+    /// Records a player's acceptance.
+    ///
+    /// # Returns
+    /// `Ok(true)` once every player has accepted, `Ok(false)` while acceptances
+    /// are outstanding, `Err(ProtocolError::InvalidIcmInput)` for unknown players
+    /// or a deal that was already rejected
+    pub fn accept(&mut self, player_id: &str) -> Result<bool, ProtocolError> {
+        if self.rejected {
+            return Err(ProtocolError::InvalidIcmInput(
+                "deal was rejected".to_string(),
+            ));
+        }
+        if self.amount_for(player_id).is_none() {
+            return Err(ProtocolError::InvalidIcmInput(format!(
+                "player {} is not part of the deal",
+                player_id
+            )));
+        }
+        self.accepted.insert(player_id.to_string());
+        Ok(self.is_accepted())
+    }
+
+    /// Records a player's rejection, cancelling the deal for everyone.
+    pub fn reject(&mut self, player_id: &str) {
+        if self.amount_for(player_id).is_some() {
+            self.rejected = true;
+        }
+    }
+
+    /// Returns `true` when every player in the deal has accepted it.
+    pub fn is_accepted(&self) -> bool {
+        !self.rejected && self.accepted.len() == self.player_ids.len()
+    }
+
+    /// Returns `true` when any player has rejected the deal.
+    pub fn is_rejected(&self) -> bool {
+        self.rejected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {} to be within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_icm_equal_stacks_split_evenly() {
+        let equities = icm_equities_exact(&[1000, 1000, 1000], &[50, 30, 20]).unwrap();
+        for equity in equities {
+            assert_close(equity, 100.0 / 3.0, 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_icm_known_three_player_result() {
+        // 5000/3000/2000 with a 50/30/20 payout is a standard textbook example.
+        let equities = icm_equities_exact(&[5000, 3000, 2000], &[50, 30, 20]).unwrap();
+        assert_close(equities[0], 38.393, 1e-3);
+        assert_close(equities[1], 32.750, 1e-3);
+        assert_close(equities[2], 28.857, 1e-3);
+        assert_close(equities.iter().sum(), 100.0, 1e-9);
+    }
+
+    #[test]
+    fn test_icm_monte_carlo_matches_exact() {
+        let stacks = [4000, 2500, 1500, 1000, 1000];
+        let payouts = [50, 30, 20];
+        let exact = icm_equities_exact(&stacks, &payouts).unwrap();
+        let estimate = icm_equities_monte_carlo(&stacks, &payouts, 200_000, 42).unwrap();
+        for (e, m) in exact.iter().zip(estimate.iter()) {
+            assert_close(*m, *e, 0.5);
+        }
+    }
+
+    #[test]
+    fn test_icm_large_field_uses_monte_carlo() {
+        let stacks = vec![1000; EXACT_ICM_MAX_PLAYERS + 3];
+        let equities = icm_equities(&stacks, &[100, 60, 40]).unwrap();
+        assert_eq!(equities.len(), stacks.len());
+        assert_close(equities.iter().sum(), 200.0, 1e-6);
+    }
+
+    #[test]
+    fn test_icm_rejects_invalid_input() {
+        assert!(icm_equities(&[], &[100]).is_err());
+        assert!(icm_equities(&[100, 0], &[100]).is_err());
+        assert!(icm_equities(&[100, 200], &[-1]).is_err());
+    }
+
+    #[test]
+    fn test_icm_exact_rejects_large_fields() {
+        let stacks = vec![1000; EXACT_ICM_MAX_PLAYERS + 1];
+        assert!(matches!(
+            icm_equities_exact(&stacks, &[100, 60, 40]),
+            Err(ProtocolError::InvalidIcmInput(_))
+        ));
+        assert!(icm_equities_exact(&vec![1000; 64], &[100]).is_err());
+    }
+
+    #[test]
+    fn test_chop_proposal_amounts_sum_to_prize_pool() {
+        let players = vec![
+            ("a".to_string(), 5000),
+            ("b".to_string(), 3000),
+            ("c".to_string(), 2000),
+        ];
+        let proposal = IcmChopProposal::new(&players, &[500, 300, 200]).unwrap();
+        assert_eq!(proposal.amounts.iter().sum::<i32>(), 1000);
+        assert_eq!(proposal.amount_for("a"), Some(384));
+    }
+
+    #[test]
+    fn test_chop_proposal_requires_everyone() {
+        let players = vec![("a".to_string(), 3000), ("b".to_string(), 1000)];
+        let mut proposal = IcmChopProposal::new(&players, &[700, 300]).unwrap();
+        assert!(!proposal.accept("a").unwrap());
+        assert!(proposal.accept("x").is_err());
+        assert!(proposal.accept("b").unwrap());
+        assert!(proposal.is_accepted());
+
+        let mut rejected = IcmChopProposal::new(&players, &[700, 300]).unwrap();
+        rejected.accept("a").unwrap();
+        rejected.reject("b");
+        assert!(rejected.is_rejected());
+        assert!(!rejected.is_accepted());
+        assert!(rejected.accept("b").is_err());
+    }
+}
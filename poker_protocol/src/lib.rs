@@ -8,14 +8,40 @@ use ring::rand::SecureRandom;
 use serde::{Deserialize, Serialize};
 
 mod errors;
+mod icm;
 mod types;
 
 pub use errors::{ConnectionError, ProtocolError, ServerError};
-pub use types::{Card, GameStage, HandEvaluation, HandRank, PlayerState, Rank, Street, Suit};
+pub use icm::{
+    icm_equities, icm_equities_exact, icm_equities_monte_carlo, IcmChopProposal,
+    DEFAULT_ICM_ITERATIONS, EXACT_ICM_MAX_PLAYERS,
+};
+pub use types::{
+    AnteStructure, BettingStructure, Card, ForcedBet, ForcedBetKind, GameStage, GameVariant,
+    HandEvaluation, HandRank, PlayerState, Rank, StraddleRule, Street, Suit,
+    DEFAULT_FIXED_LIMIT_RAISE_CAP,
+};
 
 pub type ServerResult<T> = std::result::Result<T, ServerError>;
 
 pub const HMAC_SECRET_LEN: usize = 32;
+/// Stands in for a face-down card the recipient is not allowed to see
+pub const HIDDEN_CARD: &str = "[hidden]";
+/// Newest protocol version this build speaks
+pub const PROTOCOL_VERSION: u32 = 1;
+/// Oldest protocol version this build still speaks
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+/// JSON objects tagged with a "type" field, as produced by `to_unified_json`
+pub const CODEC_JSON: &str = "json";
+/// Optional features this build supports, advertised in `Hello`
+pub const FEATURES: &[&str] = &[
+    "draw_games",
+    "private_tables",
+    "run_it_multiple",
+    "spectating",
+    "straddle",
+    "waitlist",
+];
 const MESSAGE_TIMESTAMP_MAX_DIFF_MS: u64 = 30000;
 const NONCE_CACHE_SIZE: usize = 1000;
 const NONCE_EXPIRY_MS: u64 = 60000;
@@ -211,12 +237,150 @@ mod tests {
         assert!(key.is_some());
     }
 
+    #[test]
+    fn test_parse_draw_action() {
+        let value = serde_json::json!({"Draw": [{"suit": "Hearts", "rank": "King"}]});
+        assert_eq!(
+            PlayerAction::from_value(&value, None),
+            Some(PlayerAction::Draw(vec![Card::new(
+                Suit::Hearts,
+                Rank::King
+            )]))
+        );
+
+        let stand_pat = serde_json::json!({"Draw": []});
+        assert_eq!(
+            PlayerAction::from_value(&stand_pat, None),
+            Some(PlayerAction::Draw(vec![]))
+        );
+    }
+
+    #[test]
+    fn test_redacted_hides_hole_cards_only() {
+        let update = PlayerUpdate {
+            player_id: "p1".to_string(),
+            player_name: "Player1".to_string(),
+            chips: 990,
+            current_bet: 10,
+            has_acted: false,
+            is_all_in: false,
+            is_folded: false,
+            is_sitting_out: false,
+            hole_cards: vec!["A♥".to_string(), "K♠".to_string()],
+            up_cards: vec!["Q♦".to_string()],
+            seat: 2,
+            waiting_for_big_blind: false,
+        };
+        let ServerMessage::PlayerUpdates(redacted) =
+            ServerMessage::PlayerUpdates(vec![update]).redacted()
+        else {
+            panic!("Expected PlayerUpdates");
+        };
+        assert_eq!(redacted[0].hole_cards, vec![HIDDEN_CARD, HIDDEN_CARD]);
+        assert_eq!(redacted[0].up_cards, vec!["Q♦"]);
+        assert_eq!(redacted[0].chips, 990);
+
+        let chat = ServerMessage::Error("oops".to_string());
+        assert_eq!(chat.redacted(), chat);
+    }
+
+    #[test]
+    fn test_settlement_csv_names_players_and_quotes_fields() {
+        let report = SettlementReport {
+            game_id: "private-1".to_string(),
+            results: vec![
+                PlayerResult {
+                    player_id: "p1".to_string(),
+                    player_name: "Smith, Jo".to_string(),
+                    bought_in: 1000,
+                    cashed_out: 400,
+                    net: -600,
+                },
+                PlayerResult {
+                    player_id: "p2".to_string(),
+                    player_name: "Ann".to_string(),
+                    bought_in: 1000,
+                    cashed_out: 1600,
+                    net: 600,
+                },
+            ],
+            transfers: vec![Transfer {
+                from: "p1".to_string(),
+                to: "p2".to_string(),
+                amount: 600,
+            }],
+            rake: 0,
+        };
+        assert_eq!(report.to_csv(), "from,to,amount\n\"Smith, Jo\",Ann,600\n");
+
+        let json = ServerMessage::Settlement(report.clone())
+            .to_unified_json()
+            .unwrap();
+        assert!(json.contains("\"type\":\"Settlement\""));
+        let parsed: SettlementReport = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, report);
+    }
+
     #[test]
     fn test_hmac_key_from_bytes_too_short() {
         let bytes = vec![1u8; 10];
         let key = HmacKey::from_bytes(&bytes);
         assert!(key.is_none());
     }
+
+    #[test]
+    fn test_hello_negotiates_shared_version_codec_and_features() {
+        let server = Hello {
+            version: 3,
+            min_version: 1,
+            codecs: vec!["cbor".to_string(), CODEC_JSON.to_string()],
+            signing: false,
+            features: vec!["waitlist".to_string(), "straddle".to_string()],
+        };
+        let client = Hello {
+            version: 2,
+            min_version: 2,
+            codecs: vec!["msgpack".to_string(), CODEC_JSON.to_string()],
+            signing: true,
+            features: vec!["straddle".to_string(), "teleport".to_string()],
+        };
+
+        let agreed = server.negotiate(&client).unwrap();
+        assert_eq!(agreed.version, 2);
+        assert_eq!(agreed.codecs, vec![CODEC_JSON.to_string()]);
+        assert!(!agreed.signing);
+        assert_eq!(agreed.features, vec!["straddle".to_string()]);
+
+        let json = ServerMessage::Hello(agreed.clone())
+            .to_unified_json()
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["type"], "Hello");
+        assert_eq!(serde_json::from_value::<Hello>(value).unwrap(), agreed);
+    }
+
+    #[test]
+    fn test_hello_rejects_incompatible_clients() {
+        let server = Hello::new(true);
+
+        let too_new = Hello {
+            version: PROTOCOL_VERSION + 2,
+            min_version: PROTOCOL_VERSION + 1,
+            ..Hello::new(true)
+        };
+        let no_codec = Hello {
+            codecs: vec!["cbor".to_string()],
+            ..Hello::new(true)
+        };
+        let unsigned = Hello::new(false);
+        for client in [too_new, no_codec, unsigned] {
+            assert!(matches!(
+                server.negotiate(&client),
+                Err(ProtocolError::Incompatible(_))
+            ));
+        }
+        assert!(server.negotiate(&Hello::new(true)).is_ok());
+    }
 }
 
 /// # Poker Protocol
@@ -226,6 +390,14 @@ mod tests {
 /// ## Client Messages (unified format with "type" field)
 ///
 /// ```json
+/// {"type": "Hello", "version": 1, "min_version": 1, "codecs": ["json"], "signing": false, "features": ["waitlist"]}
+/// ```
+/// Must be the first message: the protocol versions, codecs in order of
+/// preference, and optional features the client supports, and whether it
+/// can sign messages. The server answers with its own `Hello`, or with an
+/// `Error` before closing the connection if the two cannot talk.
+///
+/// ```json
 /// {"type": "Connect"}
 /// ```
 /// Connect to the server and join the game.
@@ -237,8 +409,9 @@ mod tests {
 /// {"type": "Action", "action": "AllIn"}
 /// {"type": "Action", "action": "Bet", "amount": 100}
 /// {"type": "Action", "action": "Raise", "amount": 50}
+/// {"type": "Action", "action": {"Draw": [{"suit": "Hearts", "rank": "King"}]}}
 /// ```
-/// Perform a poker action.
+/// Perform a poker action. `Draw` discards the listed cards in draw games.
 ///
 /// ```json
 /// {"type": "Chat", "text": "Hello everyone!"}
@@ -256,6 +429,105 @@ mod tests {
 /// Return to the game after sitting out.
 ///
 /// ```json
+/// {"type": "Straddle", "enabled": true}
+/// ```
+/// Opt in to or out of straddling at tables that allow straddles.
+///
+/// ```json
+/// {"type": "PostBlind"}
+/// ```
+/// Post owed blinds to be dealt in next hand rather than waiting for the big blind.
+///
+/// ```json
+/// {"type": "RunIt", "times": 2}
+/// ```
+/// Agree to run the board out this many times when all-in; every player in
+/// the hand must agree for the board to be run more than once.
+///
+/// ```json
+/// {"type": "BuyIn", "amount": 500, "game_id": "..."}
+/// ```
+/// Take a seat with this many chips from your balance, within the table's
+/// buy-in limits. This and the seating, waiting list and spectating messages
+/// below take an optional `game_id`; without one they act on the table you
+/// are seated at or watching, or else the main table.
+///
+/// ```json
+/// {"type": "Rebuy", "amount": 500}
+/// {"type": "TopUp", "amount": 200}
+/// ```
+/// Add chips to your stack once you have busted (`Rebuy`), or while you
+/// still have chips, up to the table's maximum buy-in (`TopUp`). Chips added
+/// during a hand are put in play before the next hand.
+///
+/// ```json
+/// {"type": "CashOut"}
+/// ```
+/// Leave the table and return your stack to your balance. Not allowed while
+/// you are in a hand.
+///
+/// ```json
+/// {"type": "TakeSeat", "seat": 3, "game_id": "..."}
+/// ```
+/// Sit down in a particular empty seat with the largest buy-in your balance allows.
+///
+/// ```json
+/// {"type": "JoinWaitlist"}
+/// {"type": "LeaveWaitlist"}
+/// ```
+/// Join or leave the waiting list for a seat at a full table.
+///
+/// ```json
+/// {"type": "AcceptSeat"}
+/// ```
+/// Take the seat you were offered from the waiting list before the offer runs out.
+///
+/// ```json
+/// {"type": "Spectate"}
+/// {"type": "StopSpectating"}
+/// ```
+/// Start or stop watching the table without sitting down. Spectators see
+/// public table state with every face-down card hidden, and may chat only
+/// at tables that allow it.
+///
+/// ```json
+/// {"type": "ListTables"}
+/// ```
+/// List the public tables. Private tables are never listed.
+///
+/// ```json
+/// {"type": "CreateTable", "small_blind": 1, "big_blind": 2, "password": "s3cret"}
+/// {"type": "CreateTable", "small_blind": 1, "big_blind": 2, "invite_codes": 6}
+/// ```
+/// Create a private table you host, joined with either a password or
+/// one-time invite codes.
+///
+/// ```json
+/// {"type": "JoinTable", "game_id": "...", "credential": "s3cret", "seat": 2}
+/// ```
+/// Ask to sit down at a table. Private tables need the password or an
+/// unused invite code, and the host must approve the request; `seat` is optional.
+///
+/// ```json
+/// {"type": "ApproveSeat", "player_id": "..."}
+/// {"type": "RejectSeat", "player_id": "..."}
+/// ```
+/// As a private table's host, seat or turn away a player who asked to join.
+///
+/// ```json
+/// {"type": "PauseTable"}
+/// {"type": "ResumeTable"}
+/// {"type": "SetBlinds", "small_blind": 2, "big_blind": 4}
+/// {"type": "SitOutPlayer", "player_id": "..."}
+/// {"type": "KickPlayer", "player_id": "..."}
+/// {"type": "EndSession"}
+/// ```
+/// Host controls for a private table. A pause takes effect once the hand in
+/// play ends, and new blinds apply from the next hand. A player can only be
+/// removed between hands; kicking one mid-hand sits them out until it ends.
+/// Ending the session cashes everyone out and closes the table.
+///
+/// ```json
 /// {"type": "Ping", "timestamp": 1234567890}
 /// ```
 /// Send a ping for keep-alive.
@@ -263,6 +535,12 @@ mod tests {
 /// ## Server Messages
 ///
 /// ```json
+/// {"type": "Hello", "version": 1, "min_version": 1, "codecs": ["json"], "signing": false, "features": ["waitlist"]}
+/// ```
+/// What the connection will use: the protocol version and codec picked,
+/// whether messages must be signed, and the features both sides support.
+///
+/// ```json
 /// {"type": "Connected", "player_id": "player_id_here"}
 /// ```
 /// Confirmation of connection with player ID.
@@ -295,7 +573,54 @@ mod tests {
 /// ```json
 /// {"type": "Showdown", "community_cards": ["A♥", "K♠", "Q♦"], "hands": [["player_id", ["A♥", "K♠"], "Pair", "Pair of Aces"]], "winners": ["player_id"]}
 /// ```
-/// Showdown results after the final betting round.
+/// Showdown results after the final betting round. `boards` lists every
+/// board dealt when the hand was run out more than once, with its winners.
+///
+/// ```json
+/// {"type": "HandsRevealed", "hands": [["player_id", ["A♥", "K♠"]]], "runs": 2}
+/// ```
+/// Hands turned face up once the action is closed and the board is run out.
+///
+/// ```json
+/// {"type": "CashedOut", "player_id": "...", "chips": 1250}
+/// ```
+/// Notification of a player cashing out and leaving their seat.
+///
+/// ```json
+/// {"type": "SeatOffered", "player_id": "...", "seat": 3, "expires_in_secs": 30}
+/// ```
+/// A seat has opened and is held for the player first in line on the waiting list.
+///
+/// ```json
+/// {"type": "WaitlistUpdated", "player_ids": ["...", "..."]}
+/// ```
+/// The waiting list, first in line first.
+///
+/// ```json
+/// {"type": "TableList", "tables": [{"game_id": "main_table", "players": 4, "small_blind": 5, "big_blind": 10, "variant": "TexasHoldem"}]}
+/// ```
+/// The public tables.
+///
+/// ```json
+/// {"type": "TableCreated", "game_id": "...", "invite_codes": ["K7QP2M9X"]}
+/// ```
+/// Your private table was created, with any invite codes to hand out.
+///
+/// ```json
+/// {"type": "SeatRequested", "game_id": "...", "player_id": "...", "player_name": "Player1"}
+/// ```
+/// A player asked to sit at a private table; sent to the host and the player.
+///
+/// ```json
+/// {"type": "SessionEnded", "game_id": "..."}
+/// ```
+/// The host ended the session and the table has closed.
+///
+/// ```json
+/// {"type": "Settlement", "game_id": "...", "results": [{"player_id": "...", "player_name": "Player1", "bought_in": 1000, "cashed_out": 1500, "net": 500}], "transfers": [{"from": "...", "to": "...", "amount": 500}], "rake": 0}
+/// ```
+/// Who won and lost what over a private table's session, and who pays whom
+/// to settle up; sent to everyone who played when the session ends.
 ///
 /// ```json
 /// {"type": "Chat", "player_id": "...", "player_name": "Player1", "text": "Hello!", "timestamp": 1234567890}
@@ -316,6 +641,8 @@ pub enum PlayerAction {
     Bet(i32),
     Raise(i32),
     AllIn,
+    /// Discards the given cards and replaces them from the deck; an empty list stands pat
+    Draw(Vec<Card>),
 }
 
 impl PlayerAction {
@@ -363,6 +690,10 @@ impl PlayerAction {
             Some(action)
         } else if let Some((_, action)) = Self::parse_amount_action(value, "Raise", max_chips) {
             Some(action)
+        } else if let Some(discards) = value.get("Draw") {
+            serde_json::from_value::<Vec<Card>>(discards.clone())
+                .ok()
+                .map(PlayerAction::Draw)
         } else {
             None
         }
@@ -408,6 +739,10 @@ impl fmt::Display for PlayerAction {
             PlayerAction::Bet(amount) => write!(f, "Bet({})", amount),
             PlayerAction::Raise(amount) => write!(f, "Raise({})", amount),
             PlayerAction::AllIn => write!(f, "AllIn"),
+            PlayerAction::Draw(cards) => {
+                let cards: Vec<String> = cards.iter().map(|c| c.to_string()).collect();
+                write!(f, "Draw([{}])", cards.join(", "))
+            }
         }
     }
 }
@@ -508,29 +843,252 @@ impl SignedMessage {
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClientMessage {
+    /// Opens the connection, advertising what the client supports
+    Hello(Hello),
     Connect,
     Reconnect(String),
     Action(PlayerAction),
     Chat(String),
     SitOut,
     Return,
+    /// Opts in to (true) or out of (false) straddling when in a straddle position
+    Straddle(bool),
+    /// Posts owed blinds to be dealt in next hand instead of waiting for the big blind
+    PostBlind,
+    /// Sets how many times the player agrees to run the board out when all-in
+    RunIt(u32),
+    /// Takes a seat with this many chips from the player's balance, at the
+    /// named table or else the player's own
+    BuyIn(i32, Option<String>),
+    /// Adds chips to a busted player's stack
+    Rebuy(i32),
+    /// Adds chips to a stack that has not busted, up to the maximum buy-in
+    TopUp(i32),
+    /// Leaves the table, returning the player's stack to their balance
+    CashOut,
+    /// Sits down in a particular empty seat at the named table or else the player's own
+    TakeSeat(usize, Option<String>),
+    /// Joins the waiting list for a seat at the named table or else the player's own
+    JoinWaitlist(Option<String>),
+    /// Leaves the waiting list, giving up any seat held for the player
+    LeaveWaitlist(Option<String>),
+    /// Takes the seat offered to the player from the waiting list
+    AcceptSeat(Option<String>),
+    /// Watches the named table, or else the player's own, without sitting down
+    Spectate(Option<String>),
+    /// Stops watching the table
+    StopSpectating,
+    /// Lists the public tables
+    ListTables,
+    /// Creates a private table hosted by the player
+    CreateTable(CreateTableRequest),
+    /// Asks to sit down at a table, with its credential if it is private
+    JoinTable(JoinTableRequest),
+    /// Seats a player who asked to join the host's private table
+    ApproveSeat(String),
+    /// Turns away a player who asked to join the host's private table
+    RejectSeat(String),
+    /// Pauses the host's table once the current hand ends
+    PauseTable,
+    /// Resumes the host's paused table
+    ResumeTable,
+    /// Sets the small and big blinds from the next hand at the host's table
+    SetBlinds(i32, i32),
+    /// Sits out a player at the host's table
+    SitOutPlayer(String),
+    /// Removes a player from the host's table, returning their stack to their balance
+    KickPlayer(String),
+    /// Cashes everyone out and closes the host's table
+    EndSession,
+}
+
+/// Settings for a new private table. Exactly one of a password or invite
+/// codes must be given.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreateTableRequest {
+    pub small_blind: i32,
+    pub big_blind: i32,
+    /// Password every player must give to join
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Number of one-time invite codes to issue instead of a password
+    #[serde(default)]
+    pub invite_codes: u32,
+}
+
+/// A request to sit down at a particular table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoinTableRequest {
+    pub game_id: String,
+    /// The table's password or an invite code, for private tables
+    #[serde(default)]
+    pub credential: Option<String>,
+    /// Seat to sit in, or `None` for any free seat
+    #[serde(default)]
+    pub seat: Option<usize>,
+}
+
+/// The protocol versions and capabilities one side of a connection supports,
+/// exchanged before anything else so either side can evolve without breaking
+/// the other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hello {
+    /// Newest protocol version spoken
+    pub version: u32,
+    /// Oldest protocol version spoken
+    pub min_version: u32,
+    /// Message encodings spoken, in order of preference
+    pub codecs: Vec<String>,
+    /// Whether messages are signed with HMAC
+    #[serde(default)]
+    pub signing: bool,
+    /// Optional features supported, from `FEATURES`
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+impl Hello {
+    /// Returns what this build supports.
+    ///
+    /// # Arguments
+    /// * `signing` - Whether messages are signed with HMAC
+    pub fn new(signing: bool) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            min_version: MIN_PROTOCOL_VERSION,
+            codecs: vec![CODEC_JSON.to_string()],
+            signing,
+            features: FEATURES.iter().map(|feature| feature.to_string()).collect(),
+        }
+    }
+
+    /// Agrees how to talk with a client, as the server.
+    ///
+    /// # Arguments
+    /// * `client` - What the client supports
+    ///
+    /// # Returns
+    /// `Ok(Hello)` with the single version and codec to use, whether
+    /// messages must be signed and the features both sides support, or
+    /// `Err(ProtocolError::Incompatible)` saying why the two cannot talk
+    pub fn negotiate(&self, client: &Hello) -> Result<Hello, ProtocolError> {
+        let version = self.version.min(client.version);
+        if version < self.min_version.max(client.min_version) {
+            return Err(ProtocolError::Incompatible(format!(
+                "protocol versions {}-{} are not supported, this server speaks {}-{}",
+                client.min_version, client.version, self.min_version, self.version
+            )));
+        }
+        let codec = client
+            .codecs
+            .iter()
+            .find(|codec| self.codecs.contains(codec))
+            .ok_or_else(|| {
+                ProtocolError::Incompatible(format!(
+                    "none of the codecs {:?} are supported, this server speaks {:?}",
+                    client.codecs, self.codecs
+                ))
+            })?;
+        if self.signing && !client.signing {
+            return Err(ProtocolError::Incompatible(
+                "this server requires signed messages".to_string(),
+            ));
+        }
+        Ok(Hello {
+            version,
+            min_version: version,
+            codecs: vec![codec.clone()],
+            signing: self.signing,
+            features: client
+                .features
+                .iter()
+                .filter(|feature| self.features.contains(feature))
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// Converts the hello to the unified JSON format, which both sides send.
+    ///
+    /// # Returns
+    /// `Ok(JSON string)` on success, `Err(ProtocolError::JsonSerialize)` on failure
+    pub fn to_unified_json(&self) -> Result<String, ProtocolError> {
+        let mut value = serde_json::to_value(self).map_err(|_| ProtocolError::JsonSerialize)?;
+        let object = value.as_object_mut().ok_or(ProtocolError::JsonSerialize)?;
+        object.insert(
+            "type".to_string(),
+            serde_json::Value::String("Hello".to_string()),
+        );
+        serde_json::to_string(&value).map_err(|_| ProtocolError::JsonSerialize)
+    }
 }
 
 impl fmt::Display for ClientMessage {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            ClientMessage::Hello(hello) => write!(f, "Hello(v{})", hello.version),
             ClientMessage::Connect => write!(f, "Connect"),
             ClientMessage::Reconnect(id) => write!(f, "Reconnect({})", id),
             ClientMessage::Action(a) => write!(f, "Action({})", a),
             ClientMessage::Chat(t) => write!(f, "Chat({})", t),
             ClientMessage::SitOut => write!(f, "SitOut"),
             ClientMessage::Return => write!(f, "Return"),
+            ClientMessage::Straddle(enabled) => write!(f, "Straddle({})", enabled),
+            ClientMessage::PostBlind => write!(f, "PostBlind"),
+            ClientMessage::RunIt(times) => write!(f, "RunIt({})", times),
+            ClientMessage::BuyIn(amount, game_id) => {
+                write!(f, "BuyIn({}{})", amount, table_suffix(game_id))
+            }
+            ClientMessage::Rebuy(amount) => write!(f, "Rebuy({})", amount),
+            ClientMessage::TopUp(amount) => write!(f, "TopUp({})", amount),
+            ClientMessage::CashOut => write!(f, "CashOut"),
+            ClientMessage::TakeSeat(seat, game_id) => {
+                write!(f, "TakeSeat({}{})", seat, table_suffix(game_id))
+            }
+            ClientMessage::JoinWaitlist(game_id) => {
+                write!(f, "JoinWaitlist({})", table_suffix(game_id))
+            }
+            ClientMessage::LeaveWaitlist(game_id) => {
+                write!(f, "LeaveWaitlist({})", table_suffix(game_id))
+            }
+            ClientMessage::AcceptSeat(game_id) => {
+                write!(f, "AcceptSeat({})", table_suffix(game_id))
+            }
+            ClientMessage::Spectate(game_id) => write!(f, "Spectate({})", table_suffix(game_id)),
+            ClientMessage::StopSpectating => write!(f, "StopSpectating"),
+            ClientMessage::ListTables => write!(f, "ListTables"),
+            // Credentials are left out so they never end up in logs.
+            ClientMessage::CreateTable(request) => write!(
+                f,
+                "CreateTable({}/{})",
+                request.small_blind, request.big_blind
+            ),
+            ClientMessage::JoinTable(request) => write!(f, "JoinTable({})", request.game_id),
+            ClientMessage::ApproveSeat(player_id) => write!(f, "ApproveSeat({})", player_id),
+            ClientMessage::RejectSeat(player_id) => write!(f, "RejectSeat({})", player_id),
+            ClientMessage::PauseTable => write!(f, "PauseTable"),
+            ClientMessage::ResumeTable => write!(f, "ResumeTable"),
+            ClientMessage::SetBlinds(small, big) => write!(f, "SetBlinds({}/{})", small, big),
+            ClientMessage::SitOutPlayer(player_id) => write!(f, "SitOutPlayer({})", player_id),
+            ClientMessage::KickPlayer(player_id) => write!(f, "KickPlayer({})", player_id),
+            ClientMessage::EndSession => write!(f, "EndSession"),
         }
     }
 }
 
+/// Formats the table a message names for `ClientMessage`'s `Display`, or
+/// nothing when it is left to the player's own table.
+fn table_suffix(game_id: &Option<String>) -> String {
+    match game_id {
+        Some(game_id) => format!(" at {}", game_id),
+        None => String::new(),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ServerMessage {
+    /// What the connection will use, in answer to the client's `Hello`
+    Hello(Hello),
     Connected(String),
     Ping(u64),
     Pong(u64),
@@ -540,6 +1098,16 @@ pub enum ServerMessage {
     PlayerConnected(PlayerConnectedUpdate),
     PlayerDisconnected(PlayerDisconnectedUpdate),
     Showdown(ShowdownUpdate),
+    HandsRevealed(HandsRevealedUpdate),
+    CashedOut(CashedOutUpdate),
+    SeatOffered(SeatOfferedUpdate),
+    WaitlistUpdated(WaitlistUpdate),
+    TableList(Vec<TableSummary>),
+    TableCreated(TableCreatedUpdate),
+    SeatRequested(SeatRequestUpdate),
+    /// The host ended the session at this table, which has closed
+    SessionEnded(String),
+    Settlement(SettlementReport),
     Chat(ChatMessage),
     Error(String),
 }
@@ -553,6 +1121,18 @@ pub struct GameStateUpdate {
     pub community_cards: Vec<String>,
     pub current_street: String,
     pub dealer_position: usize,
+    /// Game variant being dealt this hand; changes between hands at mixed-game tables
+    #[serde(default)]
+    pub variant: GameVariant,
+    /// Betting structure the current game is played with
+    #[serde(default)]
+    pub betting_structure: BettingStructure,
+    /// Antes, blinds, straddles and bring-ins posted this hand, in the order posted
+    #[serde(default)]
+    pub forced_bets: Vec<ForcedBet>,
+    /// The host has paused the table; no new hand starts until it is resumed
+    #[serde(default)]
+    pub paused: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -566,6 +1146,13 @@ pub struct PlayerUpdate {
     pub is_folded: bool,
     pub is_sitting_out: bool,
     pub hole_cards: Vec<String>,
+    #[serde(default)]
+    pub up_cards: Vec<String>,
+    #[serde(default)]
+    pub seat: usize,
+    /// The player owes blinds and is sitting out until the big blind reaches them
+    #[serde(default)]
+    pub waiting_for_big_blind: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -575,6 +1162,12 @@ pub struct ActionRequiredUpdate {
     pub min_raise: i32,
     pub current_bet: i32,
     pub player_chips: i32,
+    /// Largest total the player may raise to under the table's betting structure
+    #[serde(default)]
+    pub max_raise: i32,
+    /// True when the player must draw (discard and replace cards) rather than bet
+    #[serde(default)]
+    pub is_draw: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -593,7 +1186,157 @@ pub struct PlayerDisconnectedUpdate {
 pub struct ShowdownUpdate {
     pub community_cards: Vec<String>,
     pub hands: Vec<(String, Vec<String>, String, String)>,
+    /// Players awarded the high half (or all) of at least one pot
+    pub winners: Vec<String>,
+    /// Players awarded the low half of at least one pot in split-pot games
+    #[serde(default)]
+    pub low_winners: Vec<String>,
+    /// Qualifying low hands shown down, as (player_id, description)
+    #[serde(default)]
+    pub low_hands: Vec<(String, String)>,
+    /// Every board the hand was run out on, in the order dealt
+    #[serde(default)]
+    pub boards: Vec<BoardResult>,
+}
+
+/// One board of a hand and the players who won its share of the pots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardResult {
+    pub community_cards: Vec<String>,
     pub winners: Vec<String>,
+    #[serde(default)]
+    pub low_winners: Vec<String>,
+}
+
+/// Hands turned face up when the action is closed with an all-in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandsRevealedUpdate {
+    /// Each live player's cards, as (player_id, cards)
+    pub hands: Vec<(String, Vec<String>)>,
+    /// Number of times the board will be run out
+    pub runs: u32,
+}
+
+/// A player leaving the table with their stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CashedOutUpdate {
+    pub player_id: String,
+    /// Chips returned to the player's balance
+    pub chips: i32,
+}
+
+/// A seat held for a player on the waiting list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeatOfferedUpdate {
+    pub player_id: String,
+    pub seat: usize,
+    /// Seconds the player has to take the seat before it is offered to the next in line
+    pub expires_in_secs: u64,
+}
+
+/// Players waiting for a seat, first in line first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaitlistUpdate {
+    pub player_ids: Vec<String>,
+}
+
+/// A public table as listed in the lobby.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSummary {
+    pub game_id: String,
+    /// Players seated at the table
+    pub players: usize,
+    pub small_blind: i32,
+    pub big_blind: i32,
+    pub variant: GameVariant,
+}
+
+/// A private table created for its host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableCreatedUpdate {
+    pub game_id: String,
+    /// One-time invite codes to hand out; empty for password tables
+    pub invite_codes: Vec<String>,
+}
+
+/// A player asking to sit at a private table, awaiting the host's approval.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeatRequestUpdate {
+    pub game_id: String,
+    pub player_id: String,
+    pub player_name: String,
+}
+
+/// A player's chips in and out over a table session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerResult {
+    pub player_id: String,
+    pub player_name: String,
+    /// Chips brought to the table: the buy-in plus any rebuys and top-ups
+    pub bought_in: i32,
+    /// Chips taken away from the table
+    pub cashed_out: i32,
+    /// Winnings, negative for a loss
+    pub net: i32,
+}
+
+/// One payment settling up a session, from a loser to a winner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    /// The paying player's id
+    pub from: String,
+    /// The receiving player's id
+    pub to: String,
+    pub amount: i32,
+}
+
+/// How a table session settles up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettlementReport {
+    pub game_id: String,
+    pub results: Vec<PlayerResult>,
+    pub transfers: Vec<Transfer>,
+    /// Chips taken as rake, which losers owe the house rather than a player
+    pub rake: i32,
+}
+
+impl SettlementReport {
+    /// Returns a player's display name, or their id if they are not in the report.
+    pub fn name_of<'a>(&'a self, player_id: &'a str) -> &'a str {
+        self.results
+            .iter()
+            .find(|result| result.player_id == player_id)
+            .map_or(player_id, |result| result.player_name.as_str())
+    }
+
+    /// Exports the transfers as CSV, one `from,to,amount` row per payment,
+    /// with players named as they were at the table.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("from,to,amount\n");
+        for transfer in &self.transfers {
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                csv_field(self.name_of(&transfer.from)),
+                csv_field(self.name_of(&transfer.to)),
+                transfer.amount
+            ));
+        }
+        csv
+    }
+
+    /// Exports the whole report as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, ProtocolError> {
+        serde_json::to_string_pretty(self).map_err(|_| ProtocolError::JsonSerialize)
+    }
+}
+
+/// Quotes a CSV field if it holds a comma, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -605,12 +1348,30 @@ pub struct ChatMessage {
 }
 
 impl ServerMessage {
+    /// Returns the message as seen by someone who is not seated at the table,
+    /// with every player's face-down cards hidden.
+    pub fn redacted(&self) -> ServerMessage {
+        match self {
+            ServerMessage::PlayerUpdates(updates) => ServerMessage::PlayerUpdates(
+                updates
+                    .iter()
+                    .map(|update| PlayerUpdate {
+                        hole_cards: vec![HIDDEN_CARD.to_string(); update.hole_cards.len().max(1)],
+                        ..update.clone()
+                    })
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
     /// Converts the message to a unified JSON format with a "type" field.
     ///
     /// # Returns
     /// `Ok(JSON string)` on success, `Err(ProtocolError::JsonSerialize)` on failure
     pub fn to_unified_json(&self) -> Result<String, ProtocolError> {
         let value = match self {
+            ServerMessage::Hello(hello) => return hello.to_unified_json(),
             ServerMessage::Connected(player_id) => {
                 serde_json::json!({ "type": "Connected", "player_id": player_id })
             }
@@ -697,6 +1458,67 @@ impl ServerMessage {
                         Ok(serde_json::Value::Object(result))
                     },
                 )?,
+            ServerMessage::HandsRevealed(update) => {
+                serde_json::json!({
+                    "type": "HandsRevealed",
+                    "hands": update.hands,
+                    "runs": update.runs
+                })
+            }
+            ServerMessage::CashedOut(update) => {
+                serde_json::json!({
+                    "type": "CashedOut",
+                    "player_id": update.player_id,
+                    "chips": update.chips
+                })
+            }
+            ServerMessage::SeatOffered(update) => {
+                serde_json::json!({
+                    "type": "SeatOffered",
+                    "player_id": update.player_id,
+                    "seat": update.seat,
+                    "expires_in_secs": update.expires_in_secs
+                })
+            }
+            ServerMessage::WaitlistUpdated(update) => {
+                serde_json::json!({
+                    "type": "WaitlistUpdated",
+                    "player_ids": update.player_ids
+                })
+            }
+            ServerMessage::TableList(tables) => {
+                serde_json::json!({
+                    "type": "TableList",
+                    "tables": tables
+                })
+            }
+            ServerMessage::TableCreated(update) => {
+                serde_json::json!({
+                    "type": "TableCreated",
+                    "game_id": update.game_id,
+                    "invite_codes": update.invite_codes
+                })
+            }
+            ServerMessage::SessionEnded(game_id) => {
+                serde_json::json!({ "type": "SessionEnded", "game_id": game_id })
+            }
+            ServerMessage::Settlement(report) => {
+                serde_json::json!({
+                    "type": "Settlement",
+                    "game_id": report.game_id,
+                    "results": report.results,
+                    "transfers": report.transfers,
+                    "rake": report.rake
+                })
+            }
+            ServerMessage::SeatRequested(update) => {
+                serde_json::json!({
+                    "type": "SeatRequested",
+                    "game_id": update.game_id,
+                    "player_id": update.player_id,
+                    "player_name": update.player_name
+                })
+            }
             ServerMessage::Chat(msg) => {
                 serde_json::json!({
                     "type": "Chat",
@@ -105,6 +105,30 @@ impl Card {
     pub fn new(suit: Suit, rank: Rank) -> Self {
         Self { suit, rank }
     }
+
+    /// Parses a card from its display form, such as `A♥` or `10♠`.
+    ///
+    /// # Examples
+    /// ```
+    /// use poker_protocol::{Card, Suit, Rank};
+    /// assert_eq!(Card::parse("10♠"), Some(Card::new(Suit::Spades, Rank::Ten)));
+    /// assert_eq!(Card::parse("[hidden]"), None);
+    /// ```
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut chars = s.trim().chars();
+        let suit = match chars.next_back()? {
+            '♣' => Suit::Clubs,
+            '♦' => Suit::Diamonds,
+            '♥' => Suit::Hearts,
+            '♠' => Suit::Spades,
+            _ => return None,
+        };
+        let rank_str = chars.as_str();
+        (2..=14)
+            .filter_map(Rank::from_u8)
+            .find(|rank| rank.to_string() == rank_str)
+            .map(|rank| Self::new(suit, rank))
+    }
 }
 
 impl fmt::Display for Card {
@@ -120,6 +144,45 @@ pub enum Street {
     Turn,
     River,
     Showdown,
+    /// Stud: two down cards and one up card
+    ThirdStreet,
+    /// Stud: second up card
+    FourthStreet,
+    /// Stud: third up card
+    FifthStreet,
+    /// Stud: fourth up card
+    SixthStreet,
+    /// Stud: final down card
+    SeventhStreet,
+    /// Draw: betting on the original five cards
+    PreDraw,
+    /// Draw: betting after the first draw
+    FirstDraw,
+    /// Draw: betting after the second draw
+    SecondDraw,
+    /// Draw: betting after the third draw
+    ThirdDraw,
+}
+
+impl Street {
+    /// Returns the street that follows this one, ending at the showdown.
+    pub fn next(&self) -> Street {
+        match self {
+            Street::Preflop => Street::Flop,
+            Street::Flop => Street::Turn,
+            Street::Turn => Street::River,
+            Street::ThirdStreet => Street::FourthStreet,
+            Street::FourthStreet => Street::FifthStreet,
+            Street::FifthStreet => Street::SixthStreet,
+            Street::SixthStreet => Street::SeventhStreet,
+            Street::PreDraw => Street::FirstDraw,
+            Street::FirstDraw => Street::SecondDraw,
+            Street::SecondDraw => Street::ThirdDraw,
+            Street::River | Street::SeventhStreet | Street::ThirdDraw | Street::Showdown => {
+                Street::Showdown
+            }
+        }
+    }
 }
 
 impl fmt::Display for Street {
@@ -130,6 +193,260 @@ impl fmt::Display for Street {
             Street::Turn => write!(f, "Turn"),
             Street::River => write!(f, "River"),
             Street::Showdown => write!(f, "Showdown"),
+            Street::ThirdStreet => write!(f, "3rd Street"),
+            Street::FourthStreet => write!(f, "4th Street"),
+            Street::FifthStreet => write!(f, "5th Street"),
+            Street::SixthStreet => write!(f, "6th Street"),
+            Street::SeventhStreet => write!(f, "7th Street"),
+            Street::PreDraw => write!(f, "Pre-Draw"),
+            Street::FirstDraw => write!(f, "First Draw"),
+            Street::SecondDraw => write!(f, "Second Draw"),
+            Street::ThirdDraw => write!(f, "Third Draw"),
+        }
+    }
+}
+
+/// Default number of bets and raises allowed per street in fixed-limit games.
+pub const DEFAULT_FIXED_LIMIT_RAISE_CAP: u32 = 4;
+
+/// Betting limits a table plays with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BettingStructure {
+    /// Any bet or raise up to the player's whole stack.
+    #[default]
+    NoLimit,
+    /// Bets and raises are capped at the size of the pot after calling.
+    PotLimit,
+    /// Fixed bet sizes per street with a cap on the number of raises.
+    FixedLimit {
+        /// Bet and raise size for the early streets (pre-flop and flop, 3rd and 4th
+        /// street, or before and after the first draw)
+        small_bet: i32,
+        /// Bet and raise size for the later streets (turn and river, 5th through 7th
+        /// street, or after the second and third draws)
+        big_bet: i32,
+        /// Maximum number of bets and raises per street, including the opening bet
+        raise_cap: u32,
+    },
+}
+
+impl BettingStructure {
+    /// Creates a fixed-limit structure with a big bet of twice the small bet
+    /// and the default raise cap.
+    pub fn fixed_limit(small_bet: i32) -> Self {
+        BettingStructure::FixedLimit {
+            small_bet,
+            big_bet: small_bet.saturating_mul(2),
+            raise_cap: DEFAULT_FIXED_LIMIT_RAISE_CAP,
+        }
+    }
+}
+
+impl fmt::Display for BettingStructure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BettingStructure::NoLimit => write!(f, "No-Limit"),
+            BettingStructure::PotLimit => write!(f, "Pot-Limit"),
+            BettingStructure::FixedLimit {
+                small_bet, big_bet, ..
+            } => write!(f, "Fixed-Limit {}/{}", small_bet, big_bet),
+        }
+    }
+}
+
+/// Antes collected before the deal, in addition to any blinds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnteStructure {
+    /// No antes
+    #[default]
+    None,
+    /// The button posts a single ante of this amount on behalf of the table
+    ButtonAnte(i32),
+    /// Every player dealt in posts an ante of this amount
+    PerPlayer(i32),
+    /// The big blind posts a single ante of this amount on behalf of the table
+    BigBlindAnte(i32),
+}
+
+/// Which players may post a voluntary straddle before the cards are dealt.
+///
+/// A straddle is a blind raise to twice the big blind: the straddler acts last
+/// preflop, and the minimum raise is measured from the straddle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StraddleRule {
+    /// No straddles
+    #[default]
+    None,
+    /// The player under the gun may straddle
+    UnderTheGun,
+    /// The button may straddle, in which case action starts with the small blind;
+    /// otherwise the player under the gun may
+    Mississippi,
+}
+
+/// Kind of forced or blind bet posted before the first voluntary action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ForcedBetKind {
+    Ante,
+    BigBlindAnte,
+    SmallBlind,
+    BigBlind,
+    /// A small blind owed for missing it, which does not count toward the player's bet
+    DeadSmallBlind,
+    Straddle,
+    BringIn,
+}
+
+impl fmt::Display for ForcedBetKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ForcedBetKind::Ante => write!(f, "ante"),
+            ForcedBetKind::BigBlindAnte => write!(f, "big blind ante"),
+            ForcedBetKind::SmallBlind => write!(f, "small blind"),
+            ForcedBetKind::BigBlind => write!(f, "big blind"),
+            ForcedBetKind::DeadSmallBlind => write!(f, "dead small blind"),
+            ForcedBetKind::Straddle => write!(f, "straddle"),
+            ForcedBetKind::BringIn => write!(f, "bring-in"),
+        }
+    }
+}
+
+/// A forced bet posted by one player at the start of a hand.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForcedBet {
+    pub player_id: String,
+    pub kind: ForcedBetKind,
+    /// Chips actually posted, which is less than the full amount for a short stack
+    pub amount: i32,
+}
+
+/// Poker game played at a table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameVariant {
+    /// Two hole cards, any combination with the board
+    #[default]
+    TexasHoldem,
+    /// Four hole cards, exactly two of which play with three board cards
+    Omaha,
+    /// Omaha with the pot split between the best high and the best eight-or-better low
+    OmahaHiLo,
+    /// Hold'em with the twos through fives removed; a flush beats a full house
+    ShortDeck,
+    /// Seven card stud: no board, each player gets their own down and up cards
+    SevenCardStud,
+    /// Seven card stud with the pot split between the best high and the best eight-or-better low
+    SevenCardStudHiLo,
+    /// Seven card stud played for the best ace-to-five low
+    Razz,
+    /// Five-card deuce-to-seven lowball with three draws
+    DeuceToSevenTripleDraw,
+    /// Five-card deuce-to-seven lowball with a single draw
+    DeuceToSevenSingleDraw,
+}
+
+impl GameVariant {
+    /// Returns the number of face-down cards dealt to each player at the start of a hand.
+    pub fn hole_card_count(&self) -> usize {
+        match self {
+            GameVariant::TexasHoldem
+            | GameVariant::ShortDeck
+            | GameVariant::SevenCardStud
+            | GameVariant::SevenCardStudHiLo
+            | GameVariant::Razz => 2,
+            GameVariant::Omaha | GameVariant::OmahaHiLo => 4,
+            GameVariant::DeuceToSevenTripleDraw | GameVariant::DeuceToSevenSingleDraw => 5,
+        }
+    }
+
+    /// Returns the lowest rank included in the deck.
+    pub fn lowest_rank(&self) -> Rank {
+        match self {
+            GameVariant::ShortDeck => Rank::Six,
+            _ => Rank::Two,
+        }
+    }
+
+    /// Returns true if pots are split between a high hand and a qualifying low.
+    pub fn is_split_pot(&self) -> bool {
+        matches!(
+            self,
+            GameVariant::OmahaHiLo | GameVariant::SevenCardStudHiLo
+        )
+    }
+
+    /// Returns true for stud games, which deal up cards instead of a shared board.
+    pub fn is_stud(&self) -> bool {
+        matches!(
+            self,
+            GameVariant::SevenCardStud | GameVariant::SevenCardStudHiLo | GameVariant::Razz
+        )
+    }
+
+    /// Returns the number of draws in a hand; zero for board and stud games.
+    pub fn draw_count(&self) -> usize {
+        match self {
+            GameVariant::DeuceToSevenTripleDraw => 3,
+            GameVariant::DeuceToSevenSingleDraw => 1,
+            _ => 0,
+        }
+    }
+
+    /// Returns the street a hand's first betting round is played on.
+    pub fn first_street(&self) -> Street {
+        if self.is_stud() {
+            Street::ThirdStreet
+        } else if self.draw_count() > 0 {
+            Street::PreDraw
+        } else {
+            Street::Preflop
+        }
+    }
+
+    /// Returns the street that follows `street` in this variant, ending at the
+    /// showdown once the variant's draws have been played.
+    pub fn next_street(&self, street: Street) -> Street {
+        let draws_played = match street {
+            Street::FirstDraw => 1,
+            Street::SecondDraw => 2,
+            Street::ThirdDraw => 3,
+            _ => return street.next(),
+        };
+        if draws_played >= self.draw_count() {
+            Street::Showdown
+        } else {
+            street.next()
+        }
+    }
+
+    /// Returns the betting structure the variant is usually played with.
+    ///
+    /// # Arguments
+    /// * `big_blind` - The table's big blind, used as the small bet in fixed-limit games
+    pub fn default_betting_structure(&self, big_blind: i32) -> BettingStructure {
+        match self {
+            GameVariant::TexasHoldem | GameVariant::ShortDeck => BettingStructure::NoLimit,
+            GameVariant::Omaha | GameVariant::OmahaHiLo => BettingStructure::PotLimit,
+            GameVariant::SevenCardStud
+            | GameVariant::SevenCardStudHiLo
+            | GameVariant::Razz
+            | GameVariant::DeuceToSevenTripleDraw => BettingStructure::fixed_limit(big_blind),
+            GameVariant::DeuceToSevenSingleDraw => BettingStructure::NoLimit,
+        }
+    }
+}
+
+impl fmt::Display for GameVariant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameVariant::TexasHoldem => write!(f, "Texas Hold'em"),
+            GameVariant::Omaha => write!(f, "Omaha"),
+            GameVariant::OmahaHiLo => write!(f, "Omaha Hi/Lo"),
+            GameVariant::ShortDeck => write!(f, "Short Deck Hold'em"),
+            GameVariant::SevenCardStud => write!(f, "Seven Card Stud"),
+            GameVariant::SevenCardStudHiLo => write!(f, "Seven Card Stud Hi/Lo"),
+            GameVariant::Razz => write!(f, "Razz"),
+            GameVariant::DeuceToSevenTripleDraw => write!(f, "2-7 Triple Draw"),
+            GameVariant::DeuceToSevenSingleDraw => write!(f, "2-7 Single Draw"),
         }
     }
 }
@@ -142,6 +459,8 @@ pub enum GameStage {
     BettingRound(Street),
     Showdown,
     HandComplete,
+    /// Held between hands by the table's host; no hand starts until resumed
+    Paused,
 }
 
 /// Represents the complete state of a player at the poker table.
@@ -158,8 +477,14 @@ pub struct PlayerState {
     pub chips: i32,
     /// Current bet amount in the active betting round
     pub current_bet: i32,
+    /// Chips committed on earlier streets of the current hand
+    #[serde(default)]
+    pub total_bet: i32,
     /// The player's hole cards (hidden until showdown)
     pub hole_cards: Vec<Card>,
+    /// Cards dealt face up to this player in stud games
+    #[serde(default)]
+    pub up_cards: Vec<Card>,
     /// Whether the player has acted in the current betting round
     pub has_acted: bool,
     /// Whether the player is all-in (cannot act further)
@@ -177,13 +502,20 @@ impl PlayerState {
             name,
             chips,
             current_bet: 0,
+            total_bet: 0,
             hole_cards: Vec::new(),
+            up_cards: Vec::new(),
             has_acted: false,
             is_all_in: false,
             is_folded: false,
             is_sitting_out: false,
         }
     }
+
+    /// Returns the total chips this player has committed to the current hand.
+    pub fn hand_contribution(&self) -> i32 {
+        self.total_bet.saturating_add(self.current_bet)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -0,0 +1,426 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::Utc;
+use futures::stream::StreamExt;
+use futures::SinkExt;
+use parking_lot::Mutex;
+use poker_protocol::{GameVariant, ServerError, ServerResult};
+use serde::{Deserialize, Serialize};
+use tokio_tungstenite::accept_hdr_async;
+use tokio_tungstenite::tungstenite::handshake::server::{
+    Callback, ErrorResponse, Request, Response,
+};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+
+use crate::server::{watch_table, PokerServer};
+use crate::tls::Transport;
+
+/// Audit entries kept in memory for the `AuditTrail` command
+const AUDIT_RECENT_CAPACITY: usize = 1000;
+
+/// An operator command sent to the admin endpoint as JSON, such as
+/// `{"command": "Kick", "player_id": "..."}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command")]
+pub enum AdminCommand {
+    ListConnections,
+    ListTables,
+    ListPlayers,
+    /// Removes a player from the server, cashing them out of their table
+    Kick {
+        player_id: String,
+    },
+    /// Bans the address a player connected from and removes them
+    Ban {
+        player_id: String,
+    },
+    Unban {
+        ip: String,
+    },
+    PauseTable {
+        game_id: String,
+    },
+    ResumeTable {
+        game_id: String,
+    },
+    /// Sets the blinds used from a table's next hand on
+    SetBlinds {
+        game_id: String,
+        small_blind: i32,
+        big_blind: i32,
+    },
+    /// Adds chips to a player's balance, or takes them away if negative
+    AdjustChips {
+        player_id: String,
+        amount: i32,
+    },
+    /// Returns the most recent entries of the audit trail
+    AuditTrail,
+}
+
+/// Open connections, as listed by `ListConnections`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionsInfo {
+    pub total: usize,
+    /// Open connections by address
+    pub by_ip: BTreeMap<String, usize>,
+    pub banned: Vec<String>,
+}
+
+/// A player, as listed by `ListPlayers`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlayerInfo {
+    pub player_id: String,
+    pub name: String,
+    /// Chips held away from the table
+    pub balance: i32,
+    pub connected: bool,
+    pub ip: Option<String>,
+    /// The table the player is seated at
+    pub table: Option<String>,
+    /// The table the player is watching
+    pub spectating: Option<String>,
+}
+
+/// A table, as listed by `ListTables`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableInfo {
+    pub game_id: String,
+    pub players: usize,
+    pub small_blind: i32,
+    pub big_blind: i32,
+    pub variant: GameVariant,
+    pub paused: bool,
+    pub hand_in_progress: bool,
+    /// The host of a private table
+    pub host: Option<String>,
+    pub spectators: usize,
+    /// Chips raked from the table's pots since it opened
+    pub rake: i64,
+    /// Hands the rake was taken from
+    pub raked_hands: usize,
+}
+
+/// One command run through the admin endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditEntry {
+    /// When the command ran, in RFC 3339
+    pub timestamp: String,
+    /// The address the command came from
+    pub admin: String,
+    pub command: AdminCommand,
+    /// `ok`, or the error the command failed with
+    pub outcome: String,
+}
+
+/// Every command run through the admin endpoint, appended to a file as
+/// JSON lines and kept in memory for recent lookups.
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    recent: VecDeque<AuditEntry>,
+}
+
+impl AuditLog {
+    /// Creates an audit log.
+    ///
+    /// # Arguments
+    /// * `path` - The file entries are appended to, or `None` to keep them
+    ///   in memory only
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Records a command and how it turned out.
+    ///
+    /// # Arguments
+    /// * `admin` - The address the command came from
+    /// * `command` - The command run
+    /// * `outcome` - `Ok` or the error the command failed with
+    pub fn record<T>(&mut self, admin: &str, command: &AdminCommand, outcome: &ServerResult<T>) {
+        let entry = AuditEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            admin: admin.to_string(),
+            command: command.clone(),
+            outcome: match outcome {
+                Ok(_) => "ok".to_string(),
+                Err(e) => e.to_string(),
+            },
+        };
+        info!(
+            "Admin {} ran {:?}: {}",
+            entry.admin, entry.command, entry.outcome
+        );
+
+        if let Some(path) = &self.path {
+            let written = serde_json::to_string(&entry)
+                .map_err(|e| e.to_string())
+                .and_then(|line| {
+                    OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(path)
+                        .and_then(|mut file| writeln!(file, "{}", line))
+                        .map_err(|e| e.to_string())
+                });
+            if let Err(e) = written {
+                warn!("Failed to write audit entry to {}: {}", path.display(), e);
+            }
+        }
+
+        if self.recent.len() == AUDIT_RECENT_CAPACITY {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(entry);
+    }
+
+    /// Returns the most recent entries, oldest first.
+    pub fn recent(&self) -> Vec<AuditEntry> {
+        self.recent.iter().cloned().collect()
+    }
+}
+
+/// Runs an admin command against the server.
+///
+/// # Returns
+/// The command's result as JSON, `null` for commands that return nothing
+pub fn execute(
+    server: &mut PokerServer,
+    command: &AdminCommand,
+) -> ServerResult<serde_json::Value> {
+    let game = |server: &PokerServer, game_id: &str| {
+        server
+            .get_game(game_id)
+            .ok_or_else(|| ServerError::GameNotFound(game_id.to_string()))
+    };
+    match command {
+        AdminCommand::ListConnections => to_json(&server.connections_info()),
+        AdminCommand::ListTables => to_json(&server.tables_info()),
+        AdminCommand::ListPlayers => to_json(&server.players_info()),
+        AdminCommand::Kick { player_id } => server
+            .remove_player(player_id)
+            .map(|()| serde_json::Value::Null),
+        AdminCommand::Ban { player_id } => server.ban_player(player_id).map(Into::into),
+        AdminCommand::Unban { ip } => Ok(server.unban(ip).into()),
+        AdminCommand::PauseTable { game_id } => {
+            game(server, game_id)?.lock().pause();
+            Ok(serde_json::Value::Null)
+        }
+        AdminCommand::ResumeTable { game_id } => {
+            let game = game(server, game_id)?;
+            game.lock().resume()?;
+            watch_table(&game);
+            Ok(serde_json::Value::Null)
+        }
+        AdminCommand::SetBlinds {
+            game_id,
+            small_blind,
+            big_blind,
+        } => game(server, game_id)?
+            .lock()
+            .set_next_blinds(*small_blind, *big_blind)
+            .map(|()| serde_json::Value::Null),
+        AdminCommand::AdjustChips { player_id, amount } => {
+            server.adjust_chips(player_id, *amount).map(Into::into)
+        }
+        // Answered by the connection, which holds the audit log.
+        AdminCommand::AuditTrail => Ok(serde_json::Value::Null),
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> ServerResult<serde_json::Value> {
+    serde_json::to_value(value).map_err(|e| ServerError::GameState(e.to_string()))
+}
+
+/// Compares tokens in time that does not depend on where they differ.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Lets the admin handshake through only with the admin token, sent as
+/// `Authorization: Bearer <token>`.
+struct AdminRequest<'a> {
+    token: &'a str,
+}
+
+impl Callback for AdminRequest<'_> {
+    fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+        let authorized = request
+            .headers()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|given| tokens_match(given, self.token));
+        if authorized {
+            Ok(response)
+        } else {
+            let mut error = ErrorResponse::new(Some("Unauthorized".to_string()));
+            *error.status_mut() = StatusCode::UNAUTHORIZED;
+            Err(error)
+        }
+    }
+}
+
+/// Serves an operator on the admin endpoint: each text message is a
+/// command, answered with `{"ok": true, "result": ...}` or
+/// `{"ok": false, "error": "..."}`, and recorded in the audit log.
+///
+/// # Arguments
+/// * `stream` - The operator's connection, after any TLS handshake
+/// * `addr` - The operator's address, recorded with each command
+/// * `server` - The poker server to control
+/// * `token` - The token operators must present
+/// * `audit` - The log every command is recorded in
+pub async fn handle_admin_connection(
+    stream: Box<dyn Transport>,
+    addr: SocketAddr,
+    server: Arc<Mutex<PokerServer>>,
+    token: Arc<String>,
+    audit: Arc<Mutex<AuditLog>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let ws_stream = match accept_hdr_async(stream, AdminRequest { token: &token }).await {
+        Ok(ws_stream) => ws_stream,
+        Err(e) => {
+            warn!("Admin connection from {} refused: {}", addr, e);
+            return Ok(());
+        }
+    };
+    info!("Admin connected from {}", addr);
+    let admin = addr.to_string();
+
+    let (mut write, mut read) = ws_stream.split();
+    while let Some(message) = read.next().await {
+        let text = match message? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let response = match serde_json::from_str::<AdminCommand>(&text) {
+            Ok(AdminCommand::AuditTrail) => {
+                let result = to_json(&audit.lock().recent());
+                audit
+                    .lock()
+                    .record(&admin, &AdminCommand::AuditTrail, &result);
+                result
+            }
+            Ok(command) => {
+                let result = execute(&mut server.lock(), &command);
+                audit.lock().record(&admin, &command, &result);
+                result
+            }
+            Err(e) => Err(ServerError::GameState(format!(
+                "Invalid admin command: {}",
+                e
+            ))),
+        };
+        let reply = match response {
+            Ok(result) => serde_json::json!({ "ok": true, "result": result }),
+            Err(e) => serde_json::json!({ "ok": false, "error": e.to_string() }),
+        };
+        write.send(Message::Text(reply.to_string().into())).await?;
+    }
+    info!("Admin {} disconnected", addr);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_commands_control_players_and_tables() {
+        let mut server = PokerServer::new();
+        server.create_game("main_table".to_string(), 5, 10);
+        server.register_player("p1".to_string(), "Player1".to_string(), 1000);
+        let (tx, _rx) = tokio::sync::mpsc::channel(16);
+        server.connect_player("p1", tx);
+        server.set_player_ip("p1", "10.0.0.7");
+
+        let balance = execute(
+            &mut server,
+            &AdminCommand::AdjustChips {
+                player_id: "p1".to_string(),
+                amount: -400,
+            },
+        )
+        .unwrap();
+        assert_eq!(balance, 600);
+        assert!(matches!(
+            execute(
+                &mut server,
+                &AdminCommand::AdjustChips {
+                    player_id: "p1".to_string(),
+                    amount: -601,
+                },
+            ),
+            Err(ServerError::InsufficientBalance(600))
+        ));
+
+        execute(
+            &mut server,
+            &AdminCommand::PauseTable {
+                game_id: "main_table".to_string(),
+            },
+        )
+        .unwrap();
+        let tables = execute(&mut server, &AdminCommand::ListTables).unwrap();
+        assert_eq!(tables[0]["paused"], true);
+        assert_eq!(tables[0]["rake"], 0);
+
+        let banned = execute(
+            &mut server,
+            &AdminCommand::Ban {
+                player_id: "p1".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(banned, "10.0.0.7");
+        assert!(server.is_banned("10.0.0.7"));
+        let players = execute(&mut server, &AdminCommand::ListPlayers).unwrap();
+        assert_eq!(players[0]["connected"], false);
+    }
+
+    #[test]
+    fn test_audit_log_appends_every_command() {
+        let path = std::env::temp_dir().join(format!("audit-{}.log", uuid::Uuid::new_v4()));
+        let mut audit = AuditLog::new(Some(path.clone()));
+        audit.record("127.0.0.1:5000", &AdminCommand::ListTables, &Ok(()));
+        audit.record::<()>(
+            "127.0.0.1:5000",
+            &AdminCommand::Kick {
+                player_id: "ghost".to_string(),
+            },
+            &Err(ServerError::PlayerNotFound("ghost".to_string())),
+        );
+
+        let lines = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let entries: Vec<serde_json::Value> = lines
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["outcome"], "ok");
+        assert_eq!(entries[1]["command"]["command"], "Kick");
+        assert_eq!(audit.recent().len(), 2);
+
+        assert!(tokens_match("s3cret", "s3cret"));
+        assert!(!tokens_match("s3creT", "s3cret"));
+        assert!(!tokens_match("s3cre", "s3cret"));
+    }
+}
@@ -0,0 +1,243 @@
+use poker_protocol::{BettingStructure, Street};
+
+#[cfg(test)]
+use poker_protocol::DEFAULT_FIXED_LIMIT_RAISE_CAP;
+
+/// Snapshot of a betting round from the point of view of the player to act.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BettingContext {
+    /// Street currently being bet
+    pub street: Street,
+    /// Total chips in the pot, including bets made on this street
+    pub pot: i32,
+    /// Highest bet made on this street
+    pub current_bet: i32,
+    /// Amount the acting player has already put in on this street
+    pub player_bet: i32,
+    /// Chips the acting player has behind
+    pub player_chips: i32,
+    /// Smallest total that counts as a full bet or raise on this street
+    pub min_raise_to: i32,
+    /// The table's big blind
+    pub big_blind: i32,
+    /// Number of bets and raises already made on this street
+    pub raises_this_street: u32,
+}
+
+/// Legal bet and raise sizes for the player to act.
+///
+/// All amounts are "raise to" totals for the current street, so they can be
+/// compared directly against `PlayerState::current_bet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BettingLimits {
+    /// Smallest legal bet or raise total (a shorter all-in is always allowed)
+    pub min_raise_to: i32,
+    /// Largest legal bet or raise total
+    pub max_raise_to: i32,
+    /// Whether the player may bet or raise at all
+    pub raise_allowed: bool,
+}
+
+/// Returns the fixed-limit bet size for a street.
+///
+/// # Arguments
+/// * `small_bet` - Bet size for pre-flop and the flop, third and fourth street, or
+///   the first two rounds of a draw game
+/// * `big_bet` - Bet size for the turn and river, fifth street onwards, or the
+///   last two rounds of a draw game
+/// * `street` - The street being bet
+pub fn fixed_limit_bet_size(small_bet: i32, big_bet: i32, street: Street) -> i32 {
+    match street {
+        Street::Preflop
+        | Street::Flop
+        | Street::ThirdStreet
+        | Street::FourthStreet
+        | Street::PreDraw
+        | Street::FirstDraw => small_bet,
+        Street::Turn
+        | Street::River
+        | Street::FifthStreet
+        | Street::SixthStreet
+        | Street::SeventhStreet
+        | Street::SecondDraw
+        | Street::ThirdDraw
+        | Street::Showdown => big_bet,
+    }
+}
+
+/// Calculates the legal bet and raise sizes under a betting structure.
+///
+/// # Arguments
+/// * `structure` - The table's betting structure
+/// * `ctx` - The current state of the betting round
+///
+/// # Returns
+/// The minimum and maximum "raise to" totals, capped at the player's stack
+pub fn betting_limits(structure: BettingStructure, ctx: &BettingContext) -> BettingLimits {
+    let all_in_to = ctx.player_bet.saturating_add(ctx.player_chips);
+    let to_call = ctx.current_bet.saturating_sub(ctx.player_bet).max(0);
+    let min_full_raise_to = ctx
+        .min_raise_to
+        .max(ctx.current_bet.saturating_add(ctx.big_blind.max(1)));
+
+    let (min_raise_to, max_raise_to, under_cap) = match structure {
+        BettingStructure::NoLimit => (min_full_raise_to, all_in_to, true),
+        BettingStructure::PotLimit => {
+            // A pot-sized raise is the call plus the pot as it stands after calling.
+            let pot_after_call = ctx.pot.saturating_add(to_call);
+            (
+                min_full_raise_to,
+                ctx.current_bet.saturating_add(pot_after_call),
+                true,
+            )
+        }
+        BettingStructure::FixedLimit {
+            small_bet,
+            big_bet,
+            raise_cap,
+        } => {
+            let bet_size = fixed_limit_bet_size(small_bet, big_bet, ctx.street);
+            // A forced bet smaller than a full bet (such as the stud bring-in)
+            // is completed to the bet size rather than raised on top of.
+            let bet_to = if ctx.current_bet < bet_size {
+                bet_size
+            } else {
+                ctx.current_bet.saturating_add(bet_size)
+            };
+            // The big blind is the opening bet of a blinded first round, so it
+            // counts towards the cap just as a completed bring-in does.
+            let blind_bets = u32::from(matches!(ctx.street, Street::Preflop | Street::PreDraw));
+            let bets_made = ctx.raises_this_street.saturating_add(blind_bets);
+            (bet_to, bet_to, bets_made < raise_cap)
+        }
+    };
+
+    BettingLimits {
+        min_raise_to,
+        max_raise_to: max_raise_to.min(all_in_to).max(min_raise_to.min(all_in_to)),
+        raise_allowed: under_cap && all_in_to > ctx.current_bet,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(street: Street, pot: i32, current_bet: i32, player_bet: i32) -> BettingContext {
+        BettingContext {
+            street,
+            pot,
+            current_bet,
+            player_bet,
+            player_chips: 1000,
+            min_raise_to: current_bet + 10,
+            big_blind: 10,
+            raises_this_street: 0,
+        }
+    }
+
+    #[test]
+    fn test_no_limit_allows_full_stack() {
+        let limits = betting_limits(
+            BettingStructure::NoLimit,
+            &context(Street::Preflop, 15, 10, 5),
+        );
+        assert_eq!(limits.min_raise_to, 20);
+        assert_eq!(limits.max_raise_to, 1005);
+        assert!(limits.raise_allowed);
+    }
+
+    #[test]
+    fn test_pot_limit_small_blind_raise() {
+        // Blinds 5/10: the small blind calls 5 making the pot 20, then raises 20 more.
+        let limits = betting_limits(
+            BettingStructure::PotLimit,
+            &context(Street::Preflop, 15, 10, 5),
+        );
+        assert_eq!(limits.min_raise_to, 20);
+        assert_eq!(limits.max_raise_to, 30);
+    }
+
+    #[test]
+    fn test_pot_limit_facing_bet() {
+        // Pot of 100, opponent bets 50: call 50 (pot 200), raise 200 more to 250.
+        let limits = betting_limits(
+            BettingStructure::PotLimit,
+            &context(Street::Flop, 150, 50, 0),
+        );
+        assert_eq!(limits.max_raise_to, 250);
+    }
+
+    #[test]
+    fn test_pot_limit_capped_by_stack() {
+        let mut ctx = context(Street::Flop, 500, 0, 0);
+        ctx.player_chips = 200;
+        let limits = betting_limits(BettingStructure::PotLimit, &ctx);
+        assert_eq!(limits.max_raise_to, 200);
+    }
+
+    #[test]
+    fn test_fixed_limit_sizes_by_street() {
+        let structure = BettingStructure::FixedLimit {
+            small_bet: 10,
+            big_bet: 20,
+            raise_cap: DEFAULT_FIXED_LIMIT_RAISE_CAP,
+        };
+
+        let flop = betting_limits(structure, &context(Street::Flop, 30, 0, 0));
+        assert_eq!((flop.min_raise_to, flop.max_raise_to), (10, 10));
+
+        let turn = betting_limits(structure, &context(Street::Turn, 50, 20, 0));
+        assert_eq!((turn.min_raise_to, turn.max_raise_to), (40, 40));
+    }
+
+    #[test]
+    fn test_fixed_limit_raise_cap() {
+        let structure = BettingStructure::FixedLimit {
+            small_bet: 10,
+            big_bet: 20,
+            raise_cap: 4,
+        };
+        let mut ctx = context(Street::Flop, 100, 40, 30);
+        ctx.raises_this_street = 4;
+        assert!(!betting_limits(structure, &ctx).raise_allowed);
+
+        ctx.raises_this_street = 3;
+        assert!(betting_limits(structure, &ctx).raise_allowed);
+    }
+
+    #[test]
+    fn test_fixed_limit_raise_cap_counts_big_blind() {
+        let structure = BettingStructure::FixedLimit {
+            small_bet: 10,
+            big_bet: 20,
+            raise_cap: 4,
+        };
+        let mut ctx = context(Street::Preflop, 115, 40, 10);
+        ctx.raises_this_street = 3;
+        assert!(!betting_limits(structure, &ctx).raise_allowed);
+
+        ctx.raises_this_street = 2;
+        assert!(betting_limits(structure, &ctx).raise_allowed);
+    }
+
+    #[test]
+    fn test_fixed_limit_completes_bring_in() {
+        let structure = BettingStructure::fixed_limit(10);
+
+        let third = betting_limits(structure, &context(Street::ThirdStreet, 25, 5, 0));
+        assert_eq!((third.min_raise_to, third.max_raise_to), (10, 10));
+
+        let fifth = betting_limits(structure, &context(Street::FifthStreet, 60, 0, 0));
+        assert_eq!((fifth.min_raise_to, fifth.max_raise_to), (20, 20));
+    }
+
+    #[test]
+    fn test_short_stack_cannot_raise() {
+        let mut ctx = context(Street::Flop, 100, 50, 0);
+        ctx.player_chips = 40;
+        let limits = betting_limits(BettingStructure::NoLimit, &ctx);
+        assert!(!limits.raise_allowed);
+        assert_eq!(limits.max_raise_to, 40);
+    }
+}
@@ -0,0 +1,43 @@
+/// Smallest buy-in by default, in big blinds
+const DEFAULT_MIN_BUY_IN_BIG_BLINDS: i32 = 20;
+/// Largest buy-in by default, in big blinds
+const DEFAULT_MAX_BUY_IN_BIG_BLINDS: i32 = 100;
+
+/// Limits on the chips a player may sit down with or add to their stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuyInRules {
+    /// Fewest chips a player may sit down or rebuy with
+    pub min: i32,
+    /// Most chips a player may sit down with, or top up to
+    pub max: i32,
+}
+
+impl Default for BuyInRules {
+    fn default() -> Self {
+        Self::for_big_blind(10)
+    }
+}
+
+impl BuyInRules {
+    /// Returns the usual 20 to 100 big blind buy-in for a table.
+    ///
+    /// # Arguments
+    /// * `big_blind` - The table's big blind
+    pub fn for_big_blind(big_blind: i32) -> Self {
+        Self {
+            min: big_blind.saturating_mul(DEFAULT_MIN_BUY_IN_BIG_BLINDS),
+            max: big_blind.saturating_mul(DEFAULT_MAX_BUY_IN_BIG_BLINDS),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buy_in_range_scales_with_big_blind() {
+        let rules = BuyInRules::for_big_blind(2);
+        assert_eq!((rules.min, rules.max), (40, 200));
+    }
+}
@@ -0,0 +1,635 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Parser};
+use poker_protocol::AnteStructure;
+use serde::Deserialize;
+
+use crate::buy_in::BuyInRules;
+use crate::game::TableConfig;
+use crate::rake::RakeStructure;
+use crate::rotation::RotationSchedule;
+use crate::{
+    parse_betting_structure, parse_game_rotation, parse_game_variant, parse_rake_caps,
+    parse_straddle_rule, ServerConfig, INACTIVITY_TIMEOUT_MS, MAX_CONNECTIONS,
+    MAX_CONNECTIONS_PER_IP, MAX_MESSAGE_SIZE, SESSION_TOKEN_EXPIRY_HOURS, STARTING_CHIPS,
+};
+
+/// Environment variable naming the configuration file
+pub const ENV_CONFIG: &str = "POKER_CONFIG";
+/// Table opened when the configuration does not list any
+pub const DEFAULT_TABLE_ID: &str = "main_table";
+
+/// Command-line flags, which override the configuration file.
+#[derive(Debug, Clone, Default, Parser)]
+#[command(version, about = "Poker server")]
+pub struct Cli {
+    /// TOML configuration file; re-read when the server receives SIGHUP
+    #[arg(short, long, env = ENV_CONFIG)]
+    pub config: Option<PathBuf>,
+    #[command(flatten)]
+    pub server: ServerSection,
+}
+
+/// Server-wide settings, from the `[server]` section of the configuration
+/// file or the matching command-line flags. Unset settings keep the value
+/// from the environment or the built-in default.
+#[derive(Debug, Clone, Default, Deserialize, Args)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSection {
+    /// Address players connect to
+    #[arg(long)]
+    pub addr: Option<String>,
+    /// Address of the delayed observer feed
+    #[arg(long)]
+    pub tv_addr: Option<String>,
+    /// Address of the Prometheus metrics endpoint
+    #[arg(long)]
+    pub metrics_addr: Option<String>,
+    /// Address of the admin endpoint, when it is enabled
+    #[arg(long)]
+    pub admin_addr: Option<String>,
+    /// Most connections the server accepts at once
+    #[arg(long)]
+    pub max_connections: Option<usize>,
+    /// Most connections accepted from one address
+    #[arg(long)]
+    pub max_connections_per_ip: Option<usize>,
+    /// Largest message accepted from a player, in bytes
+    #[arg(long)]
+    pub max_message_size: Option<usize>,
+    /// Milliseconds a player may stay silent before being disconnected
+    #[arg(long)]
+    pub inactivity_timeout_ms: Option<u64>,
+    /// Balance new players start with
+    #[arg(long)]
+    pub starting_chips: Option<i32>,
+    /// Hours a session token stays valid
+    #[arg(long)]
+    pub session_token_expiry_hours: Option<u64>,
+    /// Seconds the observer feed runs behind the tables
+    #[arg(long)]
+    pub tv_delay_secs: Option<u64>,
+    /// Whether client messages must be signed
+    #[arg(long)]
+    pub enable_hmac: Option<bool>,
+    /// PEM certificate chain; players and admins connect over TLS when set
+    #[arg(long)]
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key for `tls_cert`
+    #[arg(long)]
+    pub tls_key: Option<PathBuf>,
+    /// PEM CA certificates; the admin endpoint then requires a client
+    /// certificate signed by one of them
+    #[arg(long)]
+    pub admin_client_ca: Option<PathBuf>,
+}
+
+impl ServerSection {
+    /// Overrides the settings this section sets.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration to update
+    pub fn apply(&self, config: &mut ServerConfig) {
+        override_with(&mut config.addr, &self.addr);
+        override_with(&mut config.tv_addr, &self.tv_addr);
+        override_with(&mut config.metrics_addr, &self.metrics_addr);
+        override_with(&mut config.admin_addr, &self.admin_addr);
+        override_with(&mut config.max_connections, &self.max_connections);
+        override_with(
+            &mut config.max_connections_per_ip,
+            &self.max_connections_per_ip,
+        );
+        override_with(&mut config.max_message_size, &self.max_message_size);
+        override_with(
+            &mut config.inactivity_timeout_ms,
+            &self.inactivity_timeout_ms,
+        );
+        override_with(&mut config.starting_chips, &self.starting_chips);
+        override_with(
+            &mut config.session_token_expiry_hours,
+            &self.session_token_expiry_hours,
+        );
+        override_with(&mut config.tv_delay_secs, &self.tv_delay_secs);
+        override_with(&mut config.enable_hmac_verification, &self.enable_hmac);
+        if self.tls_cert.is_some() {
+            config.tls_cert = self.tls_cert.clone();
+        }
+        if self.tls_key.is_some() {
+            config.tls_key = self.tls_key.clone();
+        }
+        if self.admin_client_ca.is_some() {
+            config.admin_client_ca = self.admin_client_ca.clone();
+        }
+    }
+}
+
+fn override_with<T: Clone>(field: &mut T, value: &Option<T>) {
+    if let Some(value) = value {
+        *field = value.clone();
+    }
+}
+
+/// One table opened at startup, from a `[[tables]]` entry. Names and
+/// formats match the `POKER_*` environment variables; unset settings use
+/// the variant's usual structure and buy-ins sized to the big blind.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TableSection {
+    pub id: String,
+    pub variant: Option<String>,
+    pub betting: Option<String>,
+    pub small_blind: Option<i32>,
+    pub big_blind: Option<i32>,
+    pub ante: Option<i32>,
+    pub button_ante: Option<i32>,
+    pub big_blind_ante: Option<i32>,
+    pub straddle: Option<String>,
+    pub max_runs: Option<u32>,
+    pub max_bet_per_hand: Option<i32>,
+    pub rake_percent: Option<f64>,
+    pub rake_cap: Option<String>,
+    pub no_flop_no_drop: Option<bool>,
+    pub min_buy_in: Option<i32>,
+    pub max_buy_in: Option<i32>,
+    pub seat_offer_secs: Option<u64>,
+    pub action_secs: Option<u64>,
+    pub spectator_chat: Option<bool>,
+    pub rotation: Option<String>,
+    pub rotation_hands: Option<u32>,
+}
+
+impl TableSection {
+    /// Builds the table's configuration, noting every invalid setting.
+    ///
+    /// # Arguments
+    /// * `defaults` - Settings used for the blinds and bet limit when unset
+    /// * `problems` - Where invalid settings are reported
+    pub fn table_config(&self, defaults: &ServerConfig, problems: &mut Vec<String>) -> TableConfig {
+        let table = format!("tables.{}", self.id);
+        let mut problem = |message: String| problems.push(format!("{}: {}", table, message));
+
+        let (small_blind, big_blind) = match (self.small_blind, self.big_blind) {
+            (None, None) => (defaults.small_blind, defaults.big_blind),
+            (small, big) => {
+                let big = big.unwrap_or_else(|| small.unwrap_or(0).saturating_mul(2));
+                (small.unwrap_or(big / 2), big)
+            }
+        };
+        if small_blind <= 0 || big_blind < small_blind {
+            problem(format!(
+                "blinds {}/{} must be positive, with the big blind at least the small blind",
+                small_blind, big_blind
+            ));
+        }
+
+        let variant = match &self.variant {
+            Some(name) => parse_game_variant(name).unwrap_or_else(|| {
+                problem(format!("unknown variant `{}`", name));
+                Default::default()
+            }),
+            None => Default::default(),
+        };
+        let mut config = TableConfig::for_variant(variant, small_blind, big_blind);
+        config.max_bet_per_hand = defaults.max_bet_per_hand;
+        config.buy_in = BuyInRules::for_big_blind(big_blind);
+
+        if let Some(name) = &self.betting {
+            match parse_betting_structure(name, big_blind) {
+                Some(structure) => config.betting_structure = structure,
+                None => problem(format!("unknown betting structure `{}`", name)),
+            }
+        }
+
+        let amounts = [self.ante, self.button_ante, self.big_blind_ante];
+        if amounts.into_iter().flatten().any(|amount| amount <= 0) {
+            problem("antes must be positive".to_string());
+        }
+        let antes: Vec<AnteStructure> = [
+            self.ante.map(AnteStructure::PerPlayer),
+            self.button_ante.map(AnteStructure::ButtonAnte),
+            self.big_blind_ante.map(AnteStructure::BigBlindAnte),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        match antes[..] {
+            [] => {}
+            [ante] => config.ante = ante,
+            _ => problem("set only one of ante, button_ante and big_blind_ante".to_string()),
+        }
+
+        if let Some(name) = &self.straddle {
+            match parse_straddle_rule(name) {
+                Some(rule) => config.straddle = rule,
+                None => problem(format!("unknown straddle rule `{}`", name)),
+            }
+        }
+        if let Some(runs) = self.max_runs {
+            if runs == 0 {
+                problem("max_runs must be at least 1".to_string());
+            }
+            config.max_runs = runs.max(1);
+        }
+        if let Some(max_bet) = self.max_bet_per_hand {
+            if max_bet <= 0 {
+                problem("max_bet_per_hand must be positive".to_string());
+            }
+            config.max_bet_per_hand = max_bet;
+        }
+
+        let mut rake = RakeStructure::default();
+        if let Some(percent) = self.rake_percent {
+            if (0.0..=100.0).contains(&percent) {
+                rake.rate_bps = (percent * 100.0).round() as u32;
+            } else {
+                problem(format!(
+                    "rake_percent {} must be between 0 and 100",
+                    percent
+                ));
+            }
+        }
+        if let Some(caps) = &self.rake_cap {
+            match parse_rake_caps(caps) {
+                Some(caps) => rake.caps = caps,
+                None => problem(format!("malformed rake_cap `{}`", caps)),
+            }
+        }
+        if let Some(no_flop_no_drop) = self.no_flop_no_drop {
+            rake.no_flop_no_drop = no_flop_no_drop;
+        }
+        config.rake = rake;
+
+        if let Some(min) = self.min_buy_in {
+            config.buy_in.min = min;
+        }
+        if let Some(max) = self.max_buy_in {
+            config.buy_in.max = max;
+        }
+        if config.buy_in.min <= 0 || config.buy_in.max < config.buy_in.min {
+            problem(format!(
+                "buy-in range {}-{} must be positive, with the maximum at least the minimum",
+                config.buy_in.min, config.buy_in.max
+            ));
+        }
+        if let Some(secs) = self.seat_offer_secs {
+            config.seat_offer_secs = secs;
+        }
+        if let Some(secs) = self.action_secs {
+            config.action_secs = secs;
+        }
+        if let Some(spectator_chat) = self.spectator_chat {
+            config.spectator_chat = spectator_chat;
+        }
+
+        if let Some(name) = &self.rotation {
+            let schedule = match self.rotation_hands {
+                Some(0) => {
+                    problem("rotation_hands must be at least 1".to_string());
+                    RotationSchedule::EachOrbit
+                }
+                Some(hands) => RotationSchedule::EveryHands(hands),
+                None => RotationSchedule::EachOrbit,
+            };
+            match parse_game_rotation(name, big_blind, schedule) {
+                Some(rotation) => config.rotation = Some(rotation),
+                None => problem(format!("unknown rotation `{}`", name)),
+            }
+        }
+        config
+    }
+}
+
+/// The contents of a configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub server: ServerSection,
+    pub tables: Vec<TableSection>,
+}
+
+impl ConfigFile {
+    /// Reads and parses a configuration file.
+    ///
+    /// # Arguments
+    /// * `path` - The file to read
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+        toml::from_str(&contents).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+}
+
+/// Why a configuration could not be used.
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    /// Every setting that failed validation
+    Invalid(Vec<String>),
+    /// A certificate or key that could not be loaded
+    Tls(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "cannot parse {}: {}", path.display(), e),
+            ConfigError::Tls(message) => write!(f, "cannot set up TLS: {}", message),
+            ConfigError::Invalid(problems) => {
+                write!(f, "invalid configuration:")?;
+                for problem in problems {
+                    write!(f, "\n  {}", problem)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Limits the server enforces on every connection, which can change while
+/// it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerLimits {
+    pub max_connections: usize,
+    pub max_connections_per_ip: usize,
+    pub max_message_size: usize,
+    pub inactivity_timeout_ms: u64,
+    pub starting_chips: i32,
+    pub session_token_expiry_hours: u64,
+}
+
+impl Default for ServerLimits {
+    fn default() -> Self {
+        Self {
+            max_connections: MAX_CONNECTIONS,
+            max_connections_per_ip: MAX_CONNECTIONS_PER_IP,
+            max_message_size: MAX_MESSAGE_SIZE,
+            inactivity_timeout_ms: INACTIVITY_TIMEOUT_MS,
+            starting_chips: STARTING_CHIPS,
+            session_token_expiry_hours: SESSION_TOKEN_EXPIRY_HOURS,
+        }
+    }
+}
+
+/// Everything the server runs with: built-in defaults, overridden by the
+/// environment, then the configuration file, then command-line flags.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub server: ServerConfig,
+    /// Tables to open, by game id
+    pub tables: Vec<(String, TableConfig)>,
+}
+
+impl Settings {
+    /// Loads and validates the settings, reading the configuration file if
+    /// one is given.
+    ///
+    /// # Arguments
+    /// * `cli` - The command-line flags
+    ///
+    /// # Returns
+    /// The settings, or every problem found with them
+    pub fn load(cli: &Cli) -> Result<Self, ConfigError> {
+        let file = match &cli.config {
+            Some(path) => ConfigFile::load(path)?,
+            None => ConfigFile::default(),
+        };
+        Self::resolve(ServerConfig::from_env(), &file, &cli.server)
+    }
+
+    /// Applies a configuration file and flag overrides to a base configuration.
+    ///
+    /// # Arguments
+    /// * `server` - The configuration from the environment
+    /// * `file` - The configuration file's contents
+    /// * `overrides` - Settings given on the command line
+    pub fn resolve(
+        mut server: ServerConfig,
+        file: &ConfigFile,
+        overrides: &ServerSection,
+    ) -> Result<Self, ConfigError> {
+        file.server.apply(&mut server);
+        overrides.apply(&mut server);
+
+        let mut problems = server_problems(&server);
+        let mut ids = HashSet::new();
+        let mut tables = Vec::new();
+        for table in &file.tables {
+            if table.id.trim().is_empty() {
+                problems.push("tables: every table needs an id".to_string());
+            } else if !ids.insert(table.id.as_str()) {
+                problems.push(format!("tables.{}: id is used more than once", table.id));
+            }
+            tables.push((table.id.clone(), table.table_config(&server, &mut problems)));
+        }
+        if tables.is_empty() {
+            tables.push((DEFAULT_TABLE_ID.to_string(), server.table_config()));
+        }
+
+        if problems.is_empty() {
+            Ok(Self { server, tables })
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+}
+
+/// Checks the server-wide settings, whichever source they came from.
+fn server_problems(config: &ServerConfig) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (name, addr) in [
+        ("addr", &config.addr),
+        ("tv_addr", &config.tv_addr),
+        ("metrics_addr", &config.metrics_addr),
+        ("admin_addr", &config.admin_addr),
+    ] {
+        if addr.parse::<SocketAddr>().is_err() {
+            problems.push(format!(
+                "server.{}: `{}` is not an address and port",
+                name, addr
+            ));
+        }
+    }
+    for (name, value) in [
+        ("max_connections", config.max_connections as u64),
+        (
+            "max_connections_per_ip",
+            config.max_connections_per_ip as u64,
+        ),
+        ("max_message_size", config.max_message_size as u64),
+        ("inactivity_timeout_ms", config.inactivity_timeout_ms),
+        (
+            "session_token_expiry_hours",
+            config.session_token_expiry_hours,
+        ),
+    ] {
+        if value == 0 {
+            problems.push(format!("server.{}: must be at least 1", name));
+        }
+    }
+    if config.tls_cert.is_some() != config.tls_key.is_some() {
+        problems.push("server: set both tls_cert and tls_key, or neither".to_string());
+    }
+    if config.admin_client_ca.is_some() && config.tls_cert.is_none() {
+        problems.push("server.admin_client_ca: needs tls_cert and tls_key".to_string());
+    }
+    if !(0..=config.max_player_chips).contains(&config.starting_chips) {
+        problems.push(format!(
+            "server.starting_chips: must be between 0 and {}",
+            config.max_player_chips
+        ));
+    }
+    problems
+}
+
+/// Names the server-wide settings that changed but only take effect on
+/// restart.
+///
+/// # Arguments
+/// * `old` - The settings the server is running with
+/// * `new` - The reloaded settings
+pub fn restart_only_server_changes(old: &ServerConfig, new: &ServerConfig) -> Vec<&'static str> {
+    [
+        ("addr", old.addr == new.addr),
+        ("tv_addr", old.tv_addr == new.tv_addr),
+        ("metrics_addr", old.metrics_addr == new.metrics_addr),
+        ("admin_addr", old.admin_addr == new.admin_addr),
+        ("tv_delay_secs", old.tv_delay_secs == new.tv_delay_secs),
+        (
+            "enable_hmac",
+            old.enable_hmac_verification == new.enable_hmac_verification,
+        ),
+        ("tls_cert", old.tls_cert == new.tls_cert),
+        ("tls_key", old.tls_key == new.tls_key),
+        (
+            "admin_client_ca",
+            old.admin_client_ca == new.admin_client_ca,
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, unchanged)| !unchanged)
+    .map(|(name, _)| name)
+    .collect()
+}
+
+/// Names the table settings that changed but only take effect when the
+/// table is opened again.
+///
+/// # Arguments
+/// * `old` - The table's current configuration
+/// * `new` - The reloaded configuration
+pub fn restart_only_table_changes(old: &TableConfig, new: &TableConfig) -> Vec<&'static str> {
+    [
+        ("variant", old.variant == new.variant),
+        ("betting", old.betting_structure == new.betting_structure),
+        ("ante", old.ante == new.ante),
+        ("straddle", old.straddle == new.straddle),
+        ("max_runs", old.max_runs == new.max_runs),
+        ("rake", old.rake == new.rake),
+        ("rotation", old.rotation == new.rotation),
+    ]
+    .into_iter()
+    .filter(|(_, unchanged)| !unchanged)
+    .map(|(name, _)| name)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poker_protocol::{BettingStructure, GameVariant};
+
+    fn parse(contents: &str) -> Result<Settings, ConfigError> {
+        let file: ConfigFile = toml::from_str(contents).unwrap();
+        Settings::resolve(ServerConfig::default(), &file, &ServerSection::default())
+    }
+
+    #[test]
+    fn test_config_file_defines_limits_and_tables() {
+        let settings = parse(
+            r#"
+            [server]
+            max_connections = 250
+            starting_chips = 5000
+
+            [[tables]]
+            id = "holdem-1-2"
+            small_blind = 1
+            big_blind = 2
+
+            [[tables]]
+            id = "plo-5-10"
+            variant = "omaha"
+            big_blind = 10
+            rake_percent = 5
+            rake_cap = "2:1,4:3"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.server.max_connections, 250);
+        assert_eq!(settings.server.limits().starting_chips, 5000);
+        assert_eq!(settings.server.max_message_size, MAX_MESSAGE_SIZE);
+        let (id, plo) = &settings.tables[1];
+        assert_eq!(id, "plo-5-10");
+        assert_eq!(plo.variant, GameVariant::Omaha);
+        assert_eq!(plo.betting_structure, BettingStructure::PotLimit);
+        assert_eq!((plo.small_blind, plo.big_blind), (5, 10));
+        assert_eq!(plo.buy_in, BuyInRules::for_big_blind(10));
+        assert_eq!(plo.rake.rate_bps, 500);
+        assert_eq!(plo.rake.caps.len(), 2);
+    }
+
+    #[test]
+    fn test_every_invalid_setting_is_reported() {
+        let Err(ConfigError::Invalid(problems)) = parse(
+            r#"
+            [server]
+            addr = "localhost"
+            max_connections = 0
+
+            [[tables]]
+            id = "a"
+            small_blind = 10
+            big_blind = 5
+            variant = "canasta"
+
+            [[tables]]
+            id = "a"
+            ante = 1
+            button_ante = 1
+            "#,
+        ) else {
+            panic!("the configuration should be rejected");
+        };
+        assert_eq!(problems.len(), 6, "{:?}", problems);
+        assert!(problems[0].starts_with("server.addr"));
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("unknown variant `canasta`")));
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("id is used more than once")));
+
+        let unknown = toml::from_str::<ConfigFile>("[server]\nmax_conections = 5\n");
+        assert!(unknown.unwrap_err().to_string().contains("max_conections"));
+    }
+
+    #[test]
+    fn test_flags_override_the_file() {
+        let file: ConfigFile = toml::from_str("[server]\nmax_connections = 250\n").unwrap();
+        let flags = ServerSection {
+            max_connections: Some(20),
+            ..Default::default()
+        };
+        let settings = Settings::resolve(ServerConfig::default(), &file, &flags).unwrap();
+        assert_eq!(settings.server.max_connections, 20);
+        assert_eq!(settings.tables[0].0, DEFAULT_TABLE_ID);
+    }
+}
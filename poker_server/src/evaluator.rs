@@ -0,0 +1,389 @@
+use std::cmp::Ordering;
+
+use poker_protocol::{Card, GameVariant, HandEvaluation, Rank};
+
+use crate::lowball::low_value;
+
+/// Number of cards that make up a showdown hand.
+const HAND_SIZE: usize = 5;
+
+/// Hand category names, indexed by the category values used for lowball hands.
+const CATEGORY_NAMES: [&str; 9] = [
+    "Low",
+    "Pair",
+    "TwoPair",
+    "ThreeOfAKind",
+    "Straight",
+    "Flush",
+    "FullHouse",
+    "FourOfAKind",
+    "StraightFlush",
+];
+
+/// How a variant ranks hands at showdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandRanking {
+    /// Standard high hands
+    High,
+    /// Ace-to-five lowball: aces play low, straights and flushes do not count,
+    /// but pairs do
+    AceToFiveLow,
+    /// Deuce-to-seven lowball: aces play high, and straights, flushes and
+    /// pairs all count against the hand
+    DeuceToSevenLow,
+}
+
+impl HandRanking {
+    /// Returns the ranking used by a variant's showdown.
+    pub fn for_variant(variant: GameVariant) -> Self {
+        match variant {
+            GameVariant::Razz => HandRanking::AceToFiveLow,
+            GameVariant::DeuceToSevenTripleDraw | GameVariant::DeuceToSevenSingleDraw => {
+                HandRanking::DeuceToSevenLow
+            }
+            GameVariant::TexasHoldem
+            | GameVariant::Omaha
+            | GameVariant::OmahaHiLo
+            | GameVariant::ShortDeck
+            | GameVariant::SevenCardStud
+            | GameVariant::SevenCardStudHiLo => HandRanking::High,
+        }
+    }
+
+    /// Returns true if the lowest hand wins.
+    pub fn is_low(&self) -> bool {
+        !matches!(self, HandRanking::High)
+    }
+}
+
+/// A hand ranked for showdown, comparable regardless of the ranking that
+/// produced it: a greater `RankedHand` is the better hand.
+#[derive(Debug, Clone)]
+pub struct RankedHand {
+    strength: Vec<i32>,
+    category: String,
+    description: String,
+}
+
+impl RankedHand {
+    /// Wraps a high-hand evaluation.
+    ///
+    /// # Arguments
+    /// * `eval` - The evaluated high hand
+    /// * `category_strength` - Strength of the hand's category in the table's variant
+    pub fn high(eval: &HandEvaluation, category_strength: u8) -> Self {
+        let mut strength = vec![category_strength as i32, eval.primary_rank];
+        strength.extend(eval.tiebreakers.iter().copied());
+        Self {
+            strength,
+            category: format!("{:?}", eval.rank),
+            description: eval.description.clone(),
+        }
+    }
+
+    /// Returns the hand category, such as `Flush` or `Low`.
+    pub fn category(&self) -> &str {
+        &self.category
+    }
+
+    /// Returns a human-readable description of the hand.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+impl PartialEq for RankedHand {
+    fn eq(&self, other: &Self) -> bool {
+        self.strength == other.strength
+    }
+}
+
+impl Eq for RankedHand {}
+
+impl PartialOrd for RankedHand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RankedHand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.strength.cmp(&other.strength)
+    }
+}
+
+/// Ranks the best ace-to-five low (Razz) that can be made from `cards`.
+///
+/// Every hand has a low; pairs make it worse. With fewer than five cards the
+/// cards are ranked as they are, which orders partial stud boards.
+pub fn ace_to_five_low(cards: &[Card]) -> RankedHand {
+    best_low(cards, |hand| {
+        let values: Vec<i32> = hand.iter().map(|c| low_value(c) as i32).collect();
+        lowball_key(&values, false, false)
+    })
+}
+
+/// Ranks the best deuce-to-seven low that can be made from `cards`.
+///
+/// Aces are always high, so A-2-3-4-5 is not a straight, and the best hand is
+/// 7-5-4-3-2 in mixed suits.
+pub fn deuce_to_seven_low(cards: &[Card]) -> RankedHand {
+    best_low(cards, |hand| {
+        let values: Vec<i32> = hand.iter().map(|c| c.rank as i32).collect();
+        let suited = hand.len() == HAND_SIZE && hand.iter().all(|c| c.suit == hand[0].suit);
+        lowball_key(&values, true, suited)
+    })
+}
+
+/// Picks the lowest hand from every five-card combination of `cards`.
+///
+/// # Arguments
+/// * `cards` - The cards available to the player
+/// * `key` - Returns a hand's category, grouped card values and description;
+///   smaller keys are better lows
+fn best_low(cards: &[Card], key: impl Fn(&[Card]) -> (Vec<i32>, i32, Vec<i32>)) -> RankedHand {
+    let hand_size = cards.len().min(HAND_SIZE);
+    card_combinations(cards, hand_size)
+        .iter()
+        .map(|hand| {
+            let (strength_key, category, values) = key(hand);
+            RankedHand {
+                // Negate so that the lowest hand compares greatest.
+                strength: strength_key.iter().map(|v| -v).collect(),
+                category: CATEGORY_NAMES[category as usize].to_string(),
+                description: describe_low(category, &values),
+            }
+        })
+        .max()
+        .unwrap_or(RankedHand {
+            strength: Vec::new(),
+            category: CATEGORY_NAMES[0].to_string(),
+            description: "No cards".to_string(),
+        })
+}
+
+/// Builds the comparison key for a lowball hand.
+///
+/// # Arguments
+/// * `values` - Card values, with the ace as 1 (ace-to-five) or 14 (deuce-to-seven)
+/// * `straights_count` - Whether straights and flushes count against the hand
+/// * `suited` - Whether all five cards share a suit
+///
+/// # Returns
+/// The key (smaller is better), the hand category and the card values in
+/// comparison order
+fn lowball_key(values: &[i32], straights_count: bool, suited: bool) -> (Vec<i32>, i32, Vec<i32>) {
+    let mut groups: Vec<(usize, i32)> = Vec::new();
+    for &value in values {
+        match groups.iter_mut().find(|(_, v)| *v == value) {
+            Some(group) => group.0 += 1,
+            None => groups.push((1, value)),
+        }
+    }
+    groups.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+
+    let counts: Vec<usize> = groups.iter().map(|(count, _)| *count).collect();
+    let ordered: Vec<i32> = groups
+        .iter()
+        .flat_map(|&(count, value)| std::iter::repeat_n(value, count))
+        .collect();
+
+    let is_straight = straights_count
+        && counts.len() == HAND_SIZE
+        && ordered[0] - ordered[HAND_SIZE - 1] == (HAND_SIZE as i32 - 1);
+    let is_flush = straights_count && suited;
+
+    let category = match counts.as_slice() {
+        _ if is_flush && is_straight => 8,
+        [4, ..] => 7,
+        [3, 2, ..] => 6,
+        _ if is_flush => 5,
+        _ if is_straight => 4,
+        [3, ..] => 3,
+        [2, 2, ..] => 2,
+        [2, ..] => 1,
+        _ => 0,
+    };
+
+    let mut key = vec![category];
+    key.extend(ordered.iter().copied());
+    (key, category, ordered)
+}
+
+/// Describes a lowball hand, e.g. `7-5-4-3-2 low` or `Pair: K-K-5-3-A`.
+fn describe_low(category: i32, values: &[i32]) -> String {
+    let cards: Vec<String> = values
+        .iter()
+        .map(|&value| match value {
+            1 => "A".to_string(),
+            v => Rank::from_u8(v as u8)
+                .map(|r| r.to_string())
+                .unwrap_or_else(|| "?".to_string()),
+        })
+        .collect();
+    if category == 0 {
+        format!("{} low", cards.join("-"))
+    } else {
+        format!("{}: {}", CATEGORY_NAMES[category as usize], cards.join("-"))
+    }
+}
+
+/// Returns every `k`-card combination of `cards`, preserving order.
+pub fn card_combinations(cards: &[Card], k: usize) -> Vec<Vec<Card>> {
+    if k == 0 {
+        return vec![Vec::new()];
+    }
+    if cards.len() < k {
+        return Vec::new();
+    }
+
+    let mut result = Vec::new();
+    for (i, &first) in cards.iter().enumerate() {
+        for mut rest in card_combinations(&cards[i + 1..], k - 1) {
+            rest.insert(0, first);
+            result.push(rest);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poker_protocol::Suit;
+
+    fn offsuit(ranks: &[Rank]) -> Vec<Card> {
+        ranks
+            .iter()
+            .enumerate()
+            .map(|(i, &rank)| {
+                let suit = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades][i % 4];
+                Card::new(suit, rank)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ace_to_five_wheel_is_best() {
+        let wheel = ace_to_five_low(&offsuit(&[
+            Rank::Five,
+            Rank::Four,
+            Rank::Three,
+            Rank::Two,
+            Rank::Ace,
+        ]));
+        let six_low = ace_to_five_low(&offsuit(&[
+            Rank::Six,
+            Rank::Four,
+            Rank::Three,
+            Rank::Two,
+            Rank::Ace,
+        ]));
+        assert!(wheel > six_low);
+        assert_eq!(wheel.description(), "5-4-3-2-A low");
+    }
+
+    #[test]
+    fn test_ace_to_five_pair_loses_to_king_low() {
+        let king_low = ace_to_five_low(&offsuit(&[
+            Rank::King,
+            Rank::Queen,
+            Rank::Jack,
+            Rank::Nine,
+            Rank::Eight,
+        ]));
+        let paired = ace_to_five_low(&offsuit(&[
+            Rank::Two,
+            Rank::Two,
+            Rank::Three,
+            Rank::Four,
+            Rank::Five,
+        ]));
+        assert!(king_low > paired);
+        assert_eq!(paired.category(), "Pair");
+    }
+
+    #[test]
+    fn test_ace_to_five_picks_best_five_of_seven() {
+        let hand = ace_to_five_low(&offsuit(&[
+            Rank::King,
+            Rank::Seven,
+            Rank::Seven,
+            Rank::Five,
+            Rank::Three,
+            Rank::Two,
+            Rank::Ace,
+        ]));
+        assert_eq!(hand.description(), "7-5-3-2-A low");
+    }
+
+    #[test]
+    fn test_deuce_to_seven_best_hand() {
+        let number_one = deuce_to_seven_low(&offsuit(&[
+            Rank::Seven,
+            Rank::Five,
+            Rank::Four,
+            Rank::Three,
+            Rank::Two,
+        ]));
+        let seven_six = deuce_to_seven_low(&offsuit(&[
+            Rank::Seven,
+            Rank::Six,
+            Rank::Four,
+            Rank::Three,
+            Rank::Two,
+        ]));
+        assert!(number_one > seven_six);
+        assert_eq!(number_one.description(), "7-5-4-3-2 low");
+    }
+
+    #[test]
+    fn test_deuce_to_seven_counts_straights_and_flushes() {
+        let straight = deuce_to_seven_low(&offsuit(&[
+            Rank::Six,
+            Rank::Five,
+            Rank::Four,
+            Rank::Three,
+            Rank::Two,
+        ]));
+        assert_eq!(straight.category(), "Straight");
+
+        let flush: Vec<Card> = [Rank::Eight, Rank::Six, Rank::Four, Rank::Three, Rank::Two]
+            .iter()
+            .map(|&rank| Card::new(Suit::Hearts, rank))
+            .collect();
+        let flush = deuce_to_seven_low(&flush);
+        assert_eq!(flush.category(), "Flush");
+
+        let king_low = deuce_to_seven_low(&offsuit(&[
+            Rank::King,
+            Rank::Queen,
+            Rank::Jack,
+            Rank::Nine,
+            Rank::Eight,
+        ]));
+        assert!(king_low > straight);
+        assert!(king_low > flush);
+    }
+
+    #[test]
+    fn test_deuce_to_seven_ace_plays_high() {
+        let wheel = deuce_to_seven_low(&offsuit(&[
+            Rank::Ace,
+            Rank::Five,
+            Rank::Four,
+            Rank::Three,
+            Rank::Two,
+        ]));
+        assert_eq!(wheel.category(), "Low");
+
+        let king_low = deuce_to_seven_low(&offsuit(&[
+            Rank::King,
+            Rank::Five,
+            Rank::Four,
+            Rank::Three,
+            Rank::Two,
+        ]));
+        assert!(king_low > wheel);
+    }
+}
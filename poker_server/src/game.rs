@@ -1,21 +1,166 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
-use log::{debug, error, warn};
 use poker_protocol::{
-    ActionRequiredUpdate, Card, GameStage, GameStateUpdate, HandEvaluation, HandRank, PlayerAction,
-    PlayerConnectedUpdate, PlayerState, PlayerUpdate, Rank, ServerError, ServerMessage,
-    ServerResult, ShowdownUpdate, Street, Suit,
+    ActionRequiredUpdate, AnteStructure, BettingStructure, BoardResult, Card, CashedOutUpdate,
+    ForcedBet, ForcedBetKind, GameStage, GameStateUpdate, GameVariant, HandEvaluation, HandRank,
+    HandsRevealedUpdate, PlayerAction, PlayerConnectedUpdate, PlayerState, PlayerUpdate, Rank,
+    SeatOfferedUpdate, ServerError, ServerMessage, ServerResult, SettlementReport, ShowdownUpdate,
+    StraddleRule, Street, Suit, WaitlistUpdate, HIDDEN_CARD,
 };
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use tokio::sync::broadcast;
+use tracing::{debug, error, info, info_span, warn, Span};
 
-use crate::MAX_BET_MULTIPLIER;
+use crate::betting::{betting_limits, fixed_limit_bet_size, BettingContext, BettingLimits};
+use crate::buy_in::BuyInRules;
+use crate::evaluator::{
+    ace_to_five_low, card_combinations, deuce_to_seven_low, HandRanking, RankedHand,
+};
+use crate::history::{HandEvent, HandHistory};
+use crate::lowball::{best_qualifying_low, low_value, LowHand, EIGHT_OR_BETTER};
+use crate::rake::{RakeLedger, RakeStructure};
+use crate::rotation::GameRotation;
+use crate::seating::{BlindDebt, Seats, Waitlist};
+use crate::settlement::Ledger;
 
 /// Maximum pot size to prevent integer overflow (half of i32::MAX)
 const MAX_POT: i32 = i32::MAX / 2;
 /// Maximum number of players allowed at a table (standard Texas Hold'em)
 pub const MAX_PLAYERS: usize = 10;
+/// Maximum number of players at a stud table, so the deck lasts to seventh street
+pub const MAX_STUD_PLAYERS: usize = 8;
+/// Number of community cards on a complete board
+const BOARD_SIZE: usize = 5;
+/// Seconds a waiting player has to take an offered seat by default
+pub const DEFAULT_SEAT_OFFER_SECS: u64 = 30;
+/// Seconds a player has to act on their turn by default
+pub const DEFAULT_ACTION_SECS: u64 = 30;
+
+/// Table-level settings a game is created with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableConfig {
+    /// Small blind amount (the bring-in in stud games)
+    pub small_blind: i32,
+    /// Big blind amount
+    pub big_blind: i32,
+    /// Betting limits used for validation and legal-action calculation
+    pub betting_structure: BettingStructure,
+    /// Largest total a player may bet on a single street
+    pub max_bet_per_hand: i32,
+    /// Game variant dealt at the table
+    pub variant: GameVariant,
+    /// Antes posted before each hand
+    pub ante: AnteStructure,
+    /// Which players may straddle in no-limit and pot-limit games
+    pub straddle: StraddleRule,
+    /// Most times an all-in board may be run out when every player agrees;
+    /// 1 always runs it once
+    pub max_runs: u32,
+    /// Rake taken from each pot before it is awarded
+    pub rake: RakeStructure,
+    /// Buy-in limits
+    pub buy_in: BuyInRules,
+    /// Seconds a player on the waiting list has to take a seat held for them
+    pub seat_offer_secs: u64,
+    /// Seconds a player has to act before they are checked or folded; 0 for
+    /// no turn clock
+    pub action_secs: u64,
+    /// Whether spectators watching the table may chat
+    pub spectator_chat: bool,
+    /// Games to cycle through at a mixed-game table; overrides `variant`,
+    /// `betting_structure` and `ante` with the current game's settings
+    pub rotation: Option<GameRotation>,
+}
+
+impl Default for TableConfig {
+    fn default() -> Self {
+        Self {
+            small_blind: 5,
+            big_blind: 10,
+            betting_structure: BettingStructure::NoLimit,
+            max_bet_per_hand: crate::MAX_BET_PER_HAND,
+            variant: GameVariant::TexasHoldem,
+            ante: AnteStructure::None,
+            straddle: StraddleRule::None,
+            max_runs: 1,
+            rake: RakeStructure::default(),
+            buy_in: BuyInRules::default(),
+            seat_offer_secs: DEFAULT_SEAT_OFFER_SECS,
+            action_secs: DEFAULT_ACTION_SECS,
+            spectator_chat: false,
+            rotation: None,
+        }
+    }
+}
+
+impl TableConfig {
+    /// Creates a no-limit table configuration with the given blinds.
+    ///
+    /// # Arguments
+    /// * `small_blind` - Small blind amount
+    /// * `big_blind` - Big blind amount
+    pub fn new(small_blind: i32, big_blind: i32) -> Self {
+        Self {
+            small_blind,
+            big_blind,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a configuration for a variant using its usual betting structure.
+    ///
+    /// Stud tables post no blinds: the small blind is used as the bring-in, the
+    /// big blind as the small bet, and every player antes a fifth of the big blind.
+    ///
+    /// # Arguments
+    /// * `variant` - The game variant to deal
+    /// * `small_blind` - Small blind amount
+    /// * `big_blind` - Big blind amount
+    pub fn for_variant(variant: GameVariant, small_blind: i32, big_blind: i32) -> Self {
+        let ante = if variant.is_stud() {
+            AnteStructure::PerPlayer((big_blind / 5).max(1))
+        } else {
+            AnteStructure::None
+        };
+        Self {
+            betting_structure: variant.default_betting_structure(big_blind),
+            variant,
+            ante,
+            ..Self::new(small_blind, big_blind)
+        }
+    }
+}
+
+/// Who is dealt in to a hand and who posts the blinds.
+#[derive(Debug, Clone, Default)]
+struct HandSeating {
+    /// Players dealt in, clockwise from the first seat after the button
+    dealt_in: Vec<String>,
+    /// Player on the button; `None` if the button is dead
+    button: Option<String>,
+    /// Player posting the small blind; `None` if the small blind is dead
+    small_blind: Option<String>,
+    big_blind: Option<String>,
+    /// Players posting owed blinds to be dealt in before the big blind reaches them
+    posting: Vec<(String, BlindDebt)>,
+}
+
+/// The remaining streets of a hand whose action has closed, dealt without
+/// any further betting.
+#[derive(Debug, Clone)]
+struct RunOut {
+    /// Number of boards to deal
+    runs: u32,
+    /// Street the action closed on; each run deals the streets after it
+    closed_on: Street,
+    /// Community cards dealt before the action closed, shared by every run
+    shared_board: Vec<Card>,
+    /// Completed boards, in the order they were run
+    boards: Vec<Vec<Card>>,
+}
 
 #[derive(Debug)]
 pub struct PokerGame {
@@ -28,13 +173,81 @@ pub struct PokerGame {
     pot: i32,
     side_pots: Vec<(i32, Vec<String>)>,
     current_street: Street,
+    /// Seat holding the dealer button, which may be empty under dead-button rules
     dealer_position: usize,
+    /// Seat that posted, or would have posted, the small blind last hand
+    small_blind_seat: Option<usize>,
+    /// Seat that posted the big blind last hand; `None` before the first hand
+    big_blind_seat: Option<usize>,
+    seats: Seats,
+    /// Blinds owed by seated players before they are dealt in again
+    blind_debts: HashMap<String, BlindDebt>,
     current_player_id: Option<String>,
     min_raise: i32,
+    raises_this_street: u32,
+    betting_structure: BettingStructure,
+    variant: GameVariant,
+    ante: AnteStructure,
+    /// Antes collected this hand; dead money that belongs to the main pot
+    antes_collected: i32,
+    straddle_rule: StraddleRule,
+    /// Players who have opted in to straddling when they are in a straddle position
+    straddlers: HashSet<String>,
+    max_runs: u32,
+    /// Times each player agrees to run the board out when all-in
+    run_preferences: HashMap<String, u32>,
+    /// Set once the action has closed and the remaining streets are being dealt
+    run_out: Option<RunOut>,
+    /// Set while a task is dealing the streets of closed hands
+    run_out_task_running: bool,
+    rake: RakeStructure,
+    /// Rake taken from every hand at the table
+    rake_ledger: RakeLedger,
+    /// Set once a street after the first is dealt with two or more players
+    /// in the hand, making the hand eligible for rake under no-flop-no-drop
+    flop_seen: bool,
+    buy_in: BuyInRules,
+    /// Rebuys and top-ups made during a hand, added to stacks before the next
+    pending_chips: HashMap<String, i32>,
+    /// Players removed during a hand, cashed out when it ends
+    leaving: HashSet<String>,
+    /// Chips every player has brought to and taken from the table
+    ledger: Ledger,
+    /// Players waiting for a seat and the seats held for them
+    waitlist: Waitlist,
+    seat_offer_timeout: Duration,
+    /// Set while a task is watching for seat offers to run out
+    offer_timer_running: bool,
+    action_timeout: Duration,
+    /// When the player to act runs out of time
+    action_deadline: Option<Instant>,
+    /// Time the player to act had left when the table was paused
+    frozen_action_time: Option<Duration>,
+    /// Set while a task is watching the turn clock
+    action_clock_running: bool,
+    spectator_chat: bool,
+    /// Set by the host to hold the table once the current hand ends
+    paused: bool,
+    /// Small and big blinds the host has set for the next hand
+    next_blinds: Option<(i32, i32)>,
+    /// Everything that has happened in the current hand
+    hand_history: HandHistory,
+    /// Mixed-game rotation, if the table plays more than one game
+    rotation: Option<GameRotation>,
+    /// Hands dealt since the current game in the rotation started
+    hands_in_game: u32,
+    /// Players still to draw this round, in draw order
+    pending_draws: Vec<String>,
+    /// Cards discarded in draw games, reshuffled if the deck runs out
+    discards: Vec<Card>,
     pub tx: broadcast::Sender<ServerMessage>,
     pub game_stage: GameStage,
     hand_number: i32,
     max_bet_per_hand: i32,
+    /// Span covering everything that happens at the table
+    table_span: Span,
+    /// Span covering the hand being played, within the table's span
+    hand_span: Span,
 }
 
 impl PokerGame {
@@ -70,16 +283,40 @@ impl PokerGame {
     /// * `small_blind` - Small blind amount
     /// * `big_blind` - Big blind amount
     /// * `tx` - Broadcast channel sender for game messages
+    #[allow(dead_code)]
     pub fn new(
         game_id: String,
         small_blind: i32,
         big_blind: i32,
         tx: broadcast::Sender<ServerMessage>,
     ) -> Self {
+        Self::with_config(game_id, TableConfig::new(small_blind, big_blind), tx)
+    }
+
+    /// Creates a new poker game instance from a table configuration.
+    ///
+    /// # Arguments
+    /// * `game_id` - Unique identifier for this game table
+    /// * `config` - Blinds, betting structure and limits for the table
+    /// * `tx` - Broadcast channel sender for game messages
+    pub fn with_config(
+        game_id: String,
+        config: TableConfig,
+        tx: broadcast::Sender<ServerMessage>,
+    ) -> Self {
+        let (variant, betting_structure, ante) = match &config.rotation {
+            Some(rotation) => {
+                let game = rotation.current();
+                (game.variant, game.betting_structure, game.ante)
+            }
+            None => (config.variant, config.betting_structure, config.ante),
+        };
         Self {
+            table_span: info_span!("table", game_id = %game_id),
+            hand_span: Span::none(),
             game_id,
-            small_blind,
-            big_blind,
+            small_blind: config.small_blind,
+            big_blind: config.big_blind,
             players: HashMap::new(),
             community_cards: Vec::new(),
             deck: Vec::new(),
@@ -87,15 +324,64 @@ impl PokerGame {
             side_pots: Vec::new(),
             current_street: Street::Preflop,
             dealer_position: 0,
+            small_blind_seat: None,
+            big_blind_seat: None,
+            seats: Seats::new(seat_count(variant, config.rotation.as_ref())),
+            blind_debts: HashMap::new(),
             current_player_id: None,
-            min_raise: big_blind.saturating_mul(2),
+            min_raise: config.big_blind.saturating_mul(2),
+            raises_this_street: 0,
+            betting_structure,
+            variant,
+            ante,
+            antes_collected: 0,
+            straddle_rule: config.straddle,
+            straddlers: HashSet::new(),
+            max_runs: config.max_runs.max(1),
+            run_preferences: HashMap::new(),
+            run_out: None,
+            run_out_task_running: false,
+            rake: config.rake,
+            rake_ledger: RakeLedger::default(),
+            flop_seen: false,
+            buy_in: config.buy_in,
+            pending_chips: HashMap::new(),
+            leaving: HashSet::new(),
+            ledger: Ledger::default(),
+            waitlist: Waitlist::default(),
+            seat_offer_timeout: Duration::from_secs(config.seat_offer_secs),
+            offer_timer_running: false,
+            action_timeout: Duration::from_secs(config.action_secs),
+            action_deadline: None,
+            frozen_action_time: None,
+            action_clock_running: false,
+            spectator_chat: config.spectator_chat,
+            paused: false,
+            next_blinds: None,
+            hand_history: HandHistory::new(0, variant),
+            rotation: config.rotation,
+            hands_in_game: 0,
+            pending_draws: Vec::new(),
+            discards: Vec::new(),
             tx,
             game_stage: GameStage::WaitingForPlayers,
             hand_number: 0,
-            max_bet_per_hand: crate::MAX_BET_PER_HAND,
+            max_bet_per_hand: config.max_bet_per_hand,
         }
     }
 
+    /// Returns the betting structure this table plays with.
+    #[allow(dead_code)]
+    pub fn betting_structure(&self) -> BettingStructure {
+        self.betting_structure
+    }
+
+    /// Returns the game variant dealt at this table.
+    #[allow(dead_code)]
+    pub fn variant(&self) -> GameVariant {
+        self.variant
+    }
+
     /// Safely calculates the new pot value with overflow protection
     /// Returns None if the amount would exceed the maximum pot size
     fn calculate_new_pot(&mut self, amount: i32) -> Option<i32> {
@@ -130,19 +416,40 @@ impl PokerGame {
     /// # Returns
     /// * `Ok(())` if the player was added successfully
     /// * `Err(ServerError::GameFull)` if the table is at maximum capacity
+    #[allow(dead_code)]
     pub fn add_player(&mut self, player_id: String, name: String, chips: i32) -> ServerResult<()> {
-        if self.players.len() >= MAX_PLAYERS {
+        self.add_player_at(player_id, name, chips, None)
+    }
+
+    /// Adds a new player to the game in a particular seat, or in the seat
+    /// held for them or the lowest free seat if `seat` is `None`.
+    fn add_player_at(
+        &mut self,
+        player_id: String,
+        name: String,
+        chips: i32,
+        seat: Option<usize>,
+    ) -> ServerResult<()> {
+        if self.players.len() >= self.max_players() {
             warn!(
                 "Attempted to add player {} to full table ({}/{} players)",
                 player_id,
                 self.players.len(),
-                MAX_PLAYERS
+                self.max_players()
             );
             return Err(ServerError::GameFull);
         }
 
+        let seat = self.seat_for(&player_id, seat)?;
         let player = PlayerState::new(player_id.clone(), name.clone(), chips);
+        self.seats.sit_at(seat, player_id.clone());
         self.players.insert(player_id.clone(), player);
+        self.blind_debts
+            .insert(player_id.clone(), BlindDebt::new_player());
+        if self.waitlist.leave(&player_id) {
+            self.broadcast_waitlist();
+            self.offer_open_seats();
+        }
 
         let update = ServerMessage::PlayerConnected(PlayerConnectedUpdate {
             player_id,
@@ -158,6 +465,141 @@ impl PokerGame {
         Ok(())
     }
 
+    /// Returns the maximum number of players this table seats, limited by any
+    /// stud game in the table's rotation.
+    pub fn max_players(&self) -> usize {
+        self.seats.count()
+    }
+
+    /// Picks the seat a new player sits in.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player sitting down
+    /// * `requested` - The seat the player asked for, if any
+    ///
+    /// # Returns
+    /// The requested seat if it is free, otherwise the seat held for the
+    /// player or the lowest seat that is neither taken nor held for someone else
+    fn seat_for(&self, player_id: &str, requested: Option<usize>) -> ServerResult<usize> {
+        let offered = self.waitlist.offer_for(player_id).map(|offer| offer.seat);
+        match requested {
+            Some(seat) if seat >= self.seats.count() => Err(ServerError::InvalidSeat(seat)),
+            Some(seat) => {
+                let held_for_other = self.waitlist.is_held(seat) && offered != Some(seat);
+                if self.seats.occupant(seat).is_some() || held_for_other {
+                    return Err(ServerError::SeatTaken(seat));
+                }
+                Ok(seat)
+            }
+            None => offered
+                .or_else(|| {
+                    self.seats
+                        .empty_seats()
+                        .find(|&seat| !self.waitlist.is_held(seat))
+                })
+                .ok_or(ServerError::GameFull),
+        }
+    }
+
+    /// Adds a player to the waiting list, offering them a seat straight away
+    /// if one is free.
+    ///
+    /// # Returns
+    /// The player's place in the list, starting from 1
+    pub fn join_waitlist(&mut self, player_id: &str) -> ServerResult<usize> {
+        if self.players.contains_key(player_id) {
+            return Err(ServerError::AlreadySeated);
+        }
+        let place = self.waitlist.join(player_id);
+        self.broadcast_waitlist();
+        self.offer_open_seats();
+        Ok(place)
+    }
+
+    /// Takes a player off the waiting list, passing any seat held for them
+    /// to the next in line.
+    pub fn leave_waitlist(&mut self, player_id: &str) -> ServerResult<()> {
+        if !self.waitlist.leave(player_id) {
+            return Err(ServerError::NotOnWaitlist);
+        }
+        self.broadcast_waitlist();
+        self.offer_open_seats();
+        Ok(())
+    }
+
+    /// Returns the seat held for a player on the waiting list.
+    pub fn seat_offer(&self, player_id: &str) -> Option<usize> {
+        self.waitlist.offer_for(player_id).map(|offer| offer.seat)
+    }
+
+    /// Returns whether spectators watching the table may chat.
+    pub fn allows_spectator_chat(&self) -> bool {
+        self.spectator_chat
+    }
+
+    /// Returns the players on the waiting list, first in line first.
+    pub fn waitlisted_players(&self) -> impl Iterator<Item = &str> {
+        self.waitlist.players()
+    }
+
+    /// Holds each free seat for the next player in line without an offer.
+    fn offer_open_seats(&mut self) {
+        let open: Vec<usize> = self
+            .seats
+            .empty_seats()
+            .filter(|&seat| !self.waitlist.is_held(seat))
+            .collect();
+        for seat in open {
+            let Some(offer) = self.waitlist.offer(seat, self.seat_offer_timeout) else {
+                break;
+            };
+            self.broadcast_message(ServerMessage::SeatOffered(SeatOfferedUpdate {
+                player_id: offer.player_id,
+                seat,
+                expires_in_secs: self.seat_offer_timeout.as_secs(),
+            }));
+        }
+    }
+
+    fn broadcast_waitlist(&self) {
+        self.broadcast_message(ServerMessage::WaitlistUpdated(WaitlistUpdate {
+            player_ids: self.waitlist.players().map(str::to_string).collect(),
+        }));
+    }
+
+    /// Claims the job of expiring seat offers if any are outstanding and no
+    /// task is already watching them.
+    ///
+    /// # Returns
+    /// `true` if the caller should run `expire_seat_offers` until it returns `None`
+    pub fn claim_offer_timer(&mut self) -> bool {
+        if self.offer_timer_running || self.waitlist.next_expiry().is_none() {
+            return false;
+        }
+        self.offer_timer_running = true;
+        true
+    }
+
+    /// Withdraws seat offers that have run out, taking those players off the
+    /// waiting list and offering the seats to the next in line.
+    ///
+    /// # Returns
+    /// How long until the next offer runs out, or `None` once no offers are
+    /// outstanding
+    pub fn expire_seat_offers(&mut self) -> Option<Duration> {
+        if !self.waitlist.expire(Instant::now()).is_empty() {
+            self.broadcast_waitlist();
+            self.offer_open_seats();
+        }
+        match self.waitlist.next_expiry() {
+            Some(at) => Some(at.saturating_duration_since(Instant::now())),
+            None => {
+                self.offer_timer_running = false;
+                None
+            }
+        }
+    }
+
     /// Sets a player to sit out (they won't receive cards or be required to act).
     ///
     /// # Arguments
@@ -178,1744 +620,5179 @@ impl PokerGame {
         }
     }
 
-    fn create_deck(&mut self) {
-        self.deck = Vec::with_capacity(52);
-        for suit in [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades] {
-            for rank in [
-                Rank::Two,
-                Rank::Three,
-                Rank::Four,
-                Rank::Five,
-                Rank::Six,
-                Rank::Seven,
-                Rank::Eight,
-                Rank::Nine,
-                Rank::Ten,
-                Rank::Jack,
-                Rank::Queen,
-                Rank::King,
-                Rank::Ace,
-            ] {
-                self.deck.push(Card::new(suit, rank));
-            }
-        }
-        let mut rng = thread_rng();
-        self.deck.shuffle(&mut rng);
+    /// Returns the smallest and largest buy-in a player may sit down with.
+    pub fn buy_in_range(&self) -> (i32, i32) {
+        (self.buy_in.min, self.buy_in.max)
     }
 
-    fn deal_card(&mut self) -> Option<Card> {
-        self.deck.pop()
+    /// Seats a player with a buy-in inside the table's limits.
+    ///
+    /// # Arguments
+    /// * `player_id` - Unique player identifier
+    /// * `name` - Player's display name
+    /// * `amount` - Chips the player sits down with
+    /// * `seat` - The seat to sit in, or `None` for the seat held for the
+    ///   player or the lowest free seat
+    ///
+    /// # Returns
+    /// * `Ok(())` if the player was seated
+    /// * `Err(ServerError::AlreadySeated)` if the player is already at the table
+    /// * `Err(ServerError::BuyInOutOfRange)` if the amount is outside the limits
+    /// * `Err(ServerError::SeatTaken)` if the seat is taken or held for someone else
+    pub fn buy_in(
+        &mut self,
+        player_id: String,
+        name: String,
+        amount: i32,
+        seat: Option<usize>,
+    ) -> ServerResult<()> {
+        if self.players.contains_key(&player_id) {
+            return Err(ServerError::AlreadySeated);
+        }
+        let (min, max) = self.buy_in_range();
+        if amount < min || amount > max {
+            return Err(ServerError::BuyInOutOfRange(min, max));
+        }
+        self.add_player_at(player_id.clone(), name.clone(), amount, seat)?;
+        self.ledger.record_buy_in(&player_id, &name, amount);
+        Ok(())
     }
 
-    fn post_blinds(&mut self) {
-        let active_player_ids = self.get_active_player_ids();
-        if active_player_ids.len() < 2 {
-            debug!(
-                "Cannot post blinds: only {} active players (need at least 2)",
-                active_player_ids.len()
-            );
-            return;
+    /// Adds chips to the stack of a player who has busted.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player rebuying
+    /// * `amount` - Chips to add, within the table's buy-in limits
+    pub fn rebuy(&mut self, player_id: &str, amount: i32) -> ServerResult<()> {
+        let stack = self.stack_with_pending(player_id)?;
+        if stack > 0 {
+            return Err(ServerError::RebuyNotBusted);
         }
+        if amount < self.buy_in.min || amount > self.buy_in.max {
+            return Err(ServerError::BuyInOutOfRange(
+                self.buy_in.min,
+                self.buy_in.max,
+            ));
+        }
+        self.add_chips(player_id, amount);
+        Ok(())
+    }
 
-        let sb_idx = self.dealer_position % active_player_ids.len();
-        let bb_idx = (sb_idx + 1) % active_player_ids.len();
-
-        let sb_player_id = active_player_ids[sb_idx].clone();
-        let bb_player_id = active_player_ids[bb_idx].clone();
+    /// Adds chips to the stack of a player who has not busted, up to the
+    /// table's maximum buy-in.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player topping up
+    /// * `amount` - Chips to add
+    pub fn top_up(&mut self, player_id: &str, amount: i32) -> ServerResult<()> {
+        let stack = self.stack_with_pending(player_id)?;
+        if stack <= 0 {
+            return Err(ServerError::NoChips);
+        }
+        if amount <= 0 {
+            return Err(ServerError::InvalidAmount);
+        }
+        let room = self.buy_in.max.saturating_sub(stack).max(0);
+        if amount > room {
+            return Err(ServerError::TopUpExceedsMax(room));
+        }
+        self.add_chips(player_id, amount);
+        Ok(())
+    }
 
-        let mut total_pot = 0;
+    /// Returns a player's stack, including chips still to be added after
+    /// the current hand.
+    fn stack_with_pending(&self, player_id: &str) -> ServerResult<i32> {
+        let player = self
+            .players
+            .get(player_id)
+            .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+        let pending = self.pending_chips.get(player_id).copied().unwrap_or(0);
+        Ok(player.chips.saturating_add(pending))
+    }
 
-        if let Some(sb_player) = self.players.get_mut(&sb_player_id) {
-            let sb_amount = self.small_blind.min(sb_player.chips);
-            sb_player.chips -= sb_amount;
-            sb_player.current_bet = sb_amount;
-            total_pot += sb_amount;
+    /// Adds chips to a player's stack, holding them back until the hand is
+    /// over if one is being played.
+    fn add_chips(&mut self, player_id: &str, amount: i32) {
+        if let Some(player) = self.players.get(player_id) {
+            self.ledger.record_buy_in(player_id, &player.name, amount);
         }
-
-        if let Some(bb_player) = self.players.get_mut(&bb_player_id) {
-            let bb_amount = self.big_blind.min(bb_player.chips);
-            bb_player.chips -= bb_amount;
-            bb_player.current_bet = bb_amount;
-            total_pot += bb_amount;
+        *self.pending_chips.entry(player_id.to_string()).or_insert(0) += amount;
+        if self.is_hand_in_progress() {
+            return;
+        }
+        self.apply_pending_chips();
+        self.broadcast_game_state();
+        if self.ready_player_count() >= 2 {
+            self.start_hand();
         }
+    }
 
-        self.pot = total_pot;
-        self.min_raise = self.big_blind.saturating_mul(2);
+    /// Moves chips added during the last hand into players' stacks.
+    fn apply_pending_chips(&mut self) {
+        for (player_id, amount) in self.pending_chips.drain() {
+            if let Some(player) = self.players.get_mut(&player_id) {
+                player.chips = player.chips.saturating_add(amount);
+            }
+        }
     }
 
-    fn deal_hole_cards(&mut self) {
-        for _ in 0..2 {
-            let player_ids: Vec<String> = self
-                .players
-                .iter()
-                .filter(|(_, p)| !p.is_sitting_out && p.chips > 0)
-                .map(|(id, _)| id.clone())
-                .collect();
+    /// Returns true while a hand is being dealt or played.
+    pub fn is_hand_in_progress(&self) -> bool {
+        !matches!(
+            self.game_stage,
+            GameStage::WaitingForPlayers | GameStage::HandComplete | GameStage::Paused
+        )
+    }
 
-            let cards_to_deal: Vec<Option<Card>> =
-                player_ids.iter().map(|_| self.deal_card()).collect();
+    /// Holds the table so no new hand starts. A hand in play is finished
+    /// first, with the turn clock stopped until the table resumes.
+    pub fn pause(&mut self) {
+        self.paused = true;
+        if let Some(deadline) = self.action_deadline.take() {
+            self.frozen_action_time = Some(deadline.saturating_duration_since(Instant::now()));
+        }
+        if !self.is_hand_in_progress() {
+            self.game_stage = GameStage::Paused;
+        }
+        self.broadcast_game_state();
+    }
 
-            for (player_id, card) in player_ids.iter().zip(cards_to_deal.into_iter()) {
-                if let Some(card) = card {
-                    if let Some(player) = self.players.get_mut(player_id) {
-                        player.hole_cards.push(card);
-                    }
-                }
+    /// Resumes a paused table, dealing a hand if enough players are ready.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the table was resumed
+    /// * `Err(ServerError::NotPaused)` if the table was not paused
+    pub fn resume(&mut self) -> ServerResult<()> {
+        if !self.paused {
+            return Err(ServerError::NotPaused);
+        }
+        self.paused = false;
+        if let Some(left) = self.frozen_action_time.take() {
+            self.action_deadline = Some(Instant::now() + left);
+        }
+        if self.game_stage == GameStage::Paused {
+            self.game_stage = GameStage::WaitingForPlayers;
+            self.broadcast_game_state();
+            if self.ready_player_count() >= 2 {
+                self.start_hand();
             }
         }
+        Ok(())
     }
 
-    fn get_active_player_ids(&self) -> Vec<String> {
-        self.players
-            .values()
-            .filter(|p| !p.is_folded && !p.is_sitting_out && p.chips > 0)
-            .map(|p| p.id.clone())
-            .collect()
+    /// Returns whether the host has paused the table.
+    pub fn is_paused(&self) -> bool {
+        self.paused
     }
 
-    fn start_hand(&mut self) {
-        self.hand_number += 1;
-        self.create_deck();
+    /// Returns how long the player to act has left, with the clock stopped
+    /// while the table is paused.
+    #[cfg(test)]
+    fn action_time_left(&self) -> Option<Duration> {
+        self.action_deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+            .or(self.frozen_action_time)
+    }
 
-        for player in self.players.values_mut() {
-            player.current_bet = 0;
-            player.hole_cards.clear();
-            player.has_acted = false;
-            player.is_all_in = false;
-            player.is_folded = false;
+    /// Starts the clock of the player asked to act, stopped if the table is
+    /// paused.
+    fn start_action_clock(&mut self) {
+        if self.action_timeout.is_zero() {
+            return;
         }
+        if self.paused {
+            self.action_deadline = None;
+            self.frozen_action_time = Some(self.action_timeout);
+        } else {
+            self.action_deadline = Some(Instant::now() + self.action_timeout);
+            self.frozen_action_time = None;
+        }
+    }
 
-        self.community_cards.clear();
-        self.side_pots.clear();
-        self.pot = 0;
+    /// Claims the job of watching the turn clock if a player is on the clock
+    /// and no task is already watching it.
+    ///
+    /// # Returns
+    /// `true` if the caller should run `expire_action` until it returns `None`
+    pub fn claim_action_clock(&mut self) -> bool {
+        if self.action_clock_running || self.action_deadline.is_none() {
+            return false;
+        }
+        self.action_clock_running = true;
+        true
+    }
 
-        self.post_blinds();
-        self.deal_hole_cards();
-        self.current_street = Street::Preflop;
-        self.game_stage = GameStage::BettingRound(Street::Preflop);
+    /// Acts for the player to act once their time is up: they stand pat in
+    /// a draw, check if they can, and otherwise fold.
+    ///
+    /// # Returns
+    /// How long until the next player to act runs out of time, or `None`
+    /// once no one is on the clock
+    pub fn expire_action(&mut self) -> Option<Duration> {
+        if let Some(deadline) = self.action_deadline {
+            if Instant::now() < deadline {
+                return Some(deadline.saturating_duration_since(Instant::now()));
+            }
+            self.action_deadline = None;
+            let player_id = self
+                .current_player_id
+                .clone()
+                .filter(|_| self.is_hand_in_progress());
+            if let Some(player_id) = player_id {
+                info!("Player {} ran out of time to act", player_id);
+                let acted = if self.pending_draws.is_empty() {
+                    self.handle_action(&player_id, PlayerAction::Check)
+                        .or_else(|_| self.handle_action(&player_id, PlayerAction::Fold))
+                } else {
+                    self.handle_action(&player_id, PlayerAction::Draw(Vec::new()))
+                };
+                if let Err(e) = acted {
+                    warn!(
+                        "Could not act for {} after their time ran out: {}",
+                        player_id, e
+                    );
+                }
+            }
+        }
+        match self.action_deadline {
+            Some(deadline) => Some(deadline.saturating_duration_since(Instant::now())),
+            None => {
+                self.action_clock_running = false;
+                None
+            }
+        }
+    }
 
-        let active_player_ids = self.get_active_player_ids();
-        // First player to act preflop is the player after the big blind (UTG)
-        // Big blind is at index 1, so first to act is at index 2 (wrapping around)
-        self.current_player_id = if active_player_ids.len() >= 3 {
-            active_player_ids.get(2).cloned()
-        } else if active_player_ids.len() == 2 {
-            // In heads-up, first to act is the small blind (dealer/button)
-            active_player_ids.first().cloned()
+    /// Returns the span the table's events belong in: the current hand's
+    /// while one is being played, otherwise the table's.
+    pub fn span(&self) -> &Span {
+        if self.is_hand_in_progress() {
+            &self.hand_span
         } else {
-            active_player_ids.first().cloned()
-        };
+            &self.table_span
+        }
+    }
 
-        self.broadcast_game_state();
-        self.request_action();
+    /// Returns the number of hands dealt at the table so far.
+    pub fn hand_number(&self) -> i32 {
+        self.hand_number
     }
 
-    fn broadcast_game_state(&self) {
-        let update = GameStateUpdate {
-            game_id: self.game_id.clone(),
-            hand_number: self.hand_number,
-            pot: self.pot,
-            side_pots: self.side_pots.clone(),
-            community_cards: self.community_cards.iter().map(|c| c.to_string()).collect(),
-            current_street: self.current_street.to_string(),
-            dealer_position: self.dealer_position,
-        };
-        self.broadcast_message(ServerMessage::GameStateUpdate(update));
-        let players: Vec<PlayerUpdate> = self
-            .players
-            .values()
-            .map(|p| PlayerUpdate {
-                player_id: p.id.clone(),
-                player_name: p.name.clone(),
-                chips: p.chips,
-                current_bet: p.current_bet,
-                has_acted: p.has_acted,
-                is_all_in: p.is_all_in,
-                is_folded: p.is_folded,
-                is_sitting_out: p.is_sitting_out,
-                hole_cards: if p.hole_cards.is_empty() {
-                    vec!["[hidden]".to_string()]
-                } else {
-                    p.hole_cards.iter().map(|c| c.to_string()).collect()
-                },
-            })
-            .collect();
-        self.broadcast_message(ServerMessage::PlayerUpdates(players));
+    /// Sets the blinds used from the next hand on.
+    ///
+    /// # Arguments
+    /// * `small_blind` - The new small blind
+    /// * `big_blind` - The new big blind, at least the small blind
+    pub fn set_next_blinds(&mut self, small_blind: i32, big_blind: i32) -> ServerResult<()> {
+        if small_blind <= 0 || big_blind < small_blind {
+            return Err(ServerError::InvalidTableSettings(
+                "blinds must be positive, with the big blind at least the small blind".to_string(),
+            ));
+        }
+        self.next_blinds = Some((small_blind, big_blind));
+        Ok(())
     }
 
-    fn request_action(&mut self) {
-        let active_player_ids = self.get_active_player_ids();
-        if active_player_ids.is_empty() {
-            return;
+    /// Applies a reloaded configuration's changes to the settings that can
+    /// change while the table runs. Settings the reload left alone are not
+    /// touched, so changes made by the host or an administrator stand.
+    ///
+    /// New blinds wait for the next hand; buy-in limits, the bet limit, the
+    /// seat offer time, the turn clock and spectator chat apply at once.
+    ///
+    /// # Arguments
+    /// * `old` - The configuration the table is running with
+    /// * `new` - The reloaded configuration
+    pub fn reconfigure(&mut self, old: &TableConfig, new: &TableConfig) -> ServerResult<()> {
+        if (old.small_blind, old.big_blind) != (new.small_blind, new.big_blind) {
+            self.set_next_blinds(new.small_blind, new.big_blind)?;
         }
-
-        let player_id = self
-            .current_player_id
-            .clone()
-            .or_else(|| active_player_ids.first().cloned())
-            .unwrap_or_default();
-
-        let player = self.players.get(&player_id);
-
-        let action_update = ActionRequiredUpdate {
-            player_id: player.map(|p| p.id.clone()).unwrap_or_default(),
-            player_name: player.map(|p| p.name.clone()).unwrap_or_default(),
-            min_raise: self.min_raise,
-            current_bet: self.get_current_bet(),
-            player_chips: player.map(|p| p.chips).unwrap_or(0),
-        };
-
-        self.broadcast_message(ServerMessage::ActionRequired(action_update));
+        if old.buy_in != new.buy_in {
+            self.buy_in = new.buy_in;
+        }
+        if old.max_bet_per_hand != new.max_bet_per_hand {
+            self.max_bet_per_hand = new.max_bet_per_hand;
+        }
+        if old.seat_offer_secs != new.seat_offer_secs {
+            self.seat_offer_timeout = Duration::from_secs(new.seat_offer_secs);
+        }
+        if old.action_secs != new.action_secs {
+            self.action_timeout = Duration::from_secs(new.action_secs);
+        }
+        if old.spectator_chat != new.spectator_chat {
+            self.spectator_chat = new.spectator_chat;
+        }
+        Ok(())
     }
 
-    fn get_current_bet(&self) -> i32 {
+    /// Returns the number of players with chips who are not sitting out.
+    fn ready_player_count(&self) -> usize {
         self.players
             .values()
-            .filter(|p| !p.is_folded)
-            .map(|p| p.current_bet)
-            .max()
-            .unwrap_or(0)
+            .filter(|p| p.chips > 0 && !p.is_sitting_out)
+            .count()
     }
 
-    #[cfg(test)]
-    fn get_player_to_act(&self) -> Option<&PlayerState> {
-        let active_player_ids = self.get_active_player_ids();
-        if active_player_ids.is_empty() {
-            return None;
+    /// Returns true if a player has cards or chips in the hand being played.
+    fn is_in_hand(&self, player_id: &str) -> bool {
+        self.is_hand_in_progress()
+            && self.players.get(player_id).is_some_and(|p| {
+                p.hand_contribution() > 0 || (!p.is_folded && !p.hole_cards.is_empty())
+            })
+    }
+
+    /// Folds a player who is being removed in the middle of a hand and cashes
+    /// them out once the hand ends. A player who is all in, or whose hand is
+    /// being run out, has nothing left to decide and plays the hand out.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player being removed
+    pub fn leave_after_hand(&mut self, player_id: &str) -> ServerResult<()> {
+        if !self.players.contains_key(player_id) {
+            return Err(ServerError::PlayerNotFound(player_id.to_string()));
         }
+        self.leaving.insert(player_id.to_string());
+        if self.pending_draws.first().is_some_and(|id| id == player_id) {
+            self.handle_draw(player_id, Vec::new())?;
+        }
+        self.pending_draws.retain(|id| id != player_id);
 
-        if let Some(ref player_id) = self.current_player_id {
-            if active_player_ids.contains(player_id) {
-                return self.players.get(player_id);
+        let decided = self
+            .players
+            .get(player_id)
+            .is_none_or(|p| p.is_folded || p.is_all_in || p.hole_cards.is_empty());
+        if decided || self.run_out.is_some() {
+            self.sit_out(player_id);
+            return Ok(());
+        }
+        if self.current_player_id.as_deref() == Some(player_id) {
+            self.handle_action(player_id, PlayerAction::Fold)?;
+        } else {
+            if let Some(player) = self.players.get_mut(player_id) {
+                player.is_folded = true;
+                player.has_acted = true;
             }
+            self.hand_history.record(HandEvent::Action {
+                player_id: player_id.to_string(),
+                action: PlayerAction::Fold,
+            });
+            self.broadcast_game_state();
         }
-
-        active_player_ids
-            .first()
-            .and_then(|id| self.players.get(id))
+        self.sit_out(player_id);
+        Ok(())
     }
 
-    /// Validates a bet amount before processing
+    /// Removes a player from the table with their stack, including any chips
+    /// they added that have not yet been put in play.
     ///
     /// # Arguments
-    /// * `player` - The player placing the bet
-    /// * `amount` - The bet amount to validate
-    /// * `current_bet` - The current highest bet in the round
-    /// * `pot` - The current pot size
+    /// * `player_id` - The player cashing out
     ///
     /// # Returns
-    /// `Ok(())` if the bet is valid, or an error otherwise
-    fn validate_bet_amount(
-        &self,
-        player: &PlayerState,
-        amount: i32,
-        current_bet: i32,
-        pot: i32,
-    ) -> ServerResult<()> {
-        if amount <= 0 {
-            return Err(ServerError::InvalidAmount);
+    /// * `Ok(chips)` with the chips the player leaves with
+    /// * `Err(ServerError::CashOutInHand)` if the player is in the current hand
+    pub fn cash_out(&mut self, player_id: &str) -> ServerResult<i32> {
+        let stack = self.stack_with_pending(player_id)?;
+        if self.is_in_hand(player_id) {
+            return Err(ServerError::CashOutInHand);
         }
 
-        if current_bet > 0 && amount < current_bet {
-            return Err(ServerError::CannotBet);
+        self.players.remove(player_id);
+        self.pending_chips.remove(player_id);
+        self.leaving.remove(player_id);
+        self.seats.stand(player_id);
+        self.blind_debts.remove(player_id);
+        self.straddlers.remove(player_id);
+        self.run_preferences.remove(player_id);
+        self.ledger.record_cash_out(player_id, stack);
+
+        self.broadcast_message(ServerMessage::CashedOut(CashedOutUpdate {
+            player_id: player_id.to_string(),
+            chips: stack,
+        }));
+        self.offer_open_seats();
+        Ok(stack)
+    }
+
+    fn create_deck(&mut self) {
+        self.deck = Vec::with_capacity(52);
+        for suit in [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades] {
+            for rank in [
+                Rank::Two,
+                Rank::Three,
+                Rank::Four,
+                Rank::Five,
+                Rank::Six,
+                Rank::Seven,
+                Rank::Eight,
+                Rank::Nine,
+                Rank::Ten,
+                Rank::Jack,
+                Rank::Queen,
+                Rank::King,
+                Rank::Ace,
+            ] {
+                if (rank as u8) < (self.variant.lowest_rank() as u8) {
+                    continue;
+                }
+                self.deck.push(Card::new(suit, rank));
+            }
+        }
+        let mut rng = thread_rng();
+        self.deck.shuffle(&mut rng);
+    }
+
+    fn deal_card(&mut self) -> Option<Card> {
+        self.deck.pop()
+    }
+
+    /// Works out who is dealt in to the next hand and moves the button and
+    /// blinds under dead-button rules: the big blind always moves to the next
+    /// player ready to play, the small blind to the seat that had the big blind
+    /// and the button to the seat that had the small blind, even if those seats
+    /// are now empty or sitting out. Sitting-out players the blinds pass owe
+    /// them before they are dealt in again. Heads-up, the button posts the
+    /// small blind.
+    fn seat_players_for_hand(&mut self) -> HandSeating {
+        if self.variant.is_stud() {
+            // Stud has no blinds, so everyone ready to play is dealt in.
+            let dealt_in = self.ready_player_ids_from(self.dealer_position);
+            for id in &dealt_in {
+                if let Some(debt) = self.blind_debts.get_mut(id) {
+                    debt.new_player = false;
+                }
+            }
+            return HandSeating {
+                dealt_in,
+                ..HandSeating::default()
+            };
         }
 
-        if amount > player.chips {
-            return Err(ServerError::BetExceedsChips(amount, player.chips));
+        let seating = match self.big_blind_seat.and_then(|seat| self.move_blinds(seat)) {
+            Some(seating) if seating.dealt_in.len() >= 2 => seating,
+            _ => self.reset_blinds(),
+        };
+        for id in &seating.dealt_in {
+            self.blind_debts.insert(id.clone(), BlindDebt::default());
         }
+        seating
+    }
 
-        let max_bet = pot.saturating_mul(MAX_BET_MULTIPLIER);
-        if amount > max_bet && player.chips > max_bet {
-            return Err(ServerError::InvalidBet(format!(
-                "Bet exceeds maximum allowed: {} (pot: {})",
-                max_bet, pot
-            )));
+    /// Moves the blinds on from the previous hand's big blind.
+    ///
+    /// # Returns
+    /// The seating for the hand, or `None` if no one is ready for the big blind
+    fn move_blinds(&mut self, previous_big_blind: usize) -> Option<HandSeating> {
+        let order: Vec<(usize, String)> = self
+            .seats
+            .clockwise_from(previous_big_blind)
+            .into_iter()
+            .map(|(seat, id)| (seat, id.to_string()))
+            .collect();
+
+        let mut big_blind = None;
+        for (seat, id) in order {
+            let Some(player) = self.players.get(&id) else {
+                continue;
+            };
+            if !player.is_sitting_out && player.chips > 0 {
+                big_blind = Some((seat, id));
+                break;
+            }
+            if player.is_sitting_out {
+                self.blind_debts.entry(id).or_default().missed_big = true;
+            }
+        }
+        let (big_blind_seat, big_blind_id) = big_blind?;
+
+        let small_blind_seat = previous_big_blind;
+        if let Some(id) = self.seats.occupant(small_blind_seat) {
+            if self.players.get(id).is_some_and(|p| p.is_sitting_out) {
+                self.blind_debts
+                    .entry(id.to_string())
+                    .or_default()
+                    .missed_small = true;
+            }
+        }
+        let mut button_seat = self.small_blind_seat.unwrap_or(small_blind_seat);
+        if button_seat == big_blind_seat {
+            button_seat = small_blind_seat;
         }
 
-        if amount > self.max_bet_per_hand {
-            return Err(ServerError::InvalidBet(format!(
-                "Bet exceeds table maximum: {}",
-                self.max_bet_per_hand
-            )));
+        let dealt_in: Vec<String> = self
+            .ready_player_ids_from(button_seat)
+            .into_iter()
+            .filter(|id| {
+                *id == big_blind_id
+                    || self
+                        .blind_debts
+                        .get(id)
+                        .is_none_or(|debt| !debt.owes() || debt.will_post)
+            })
+            .collect();
+        let posting: Vec<(String, BlindDebt)> = dealt_in
+            .iter()
+            .filter(|id| **id != big_blind_id)
+            .filter_map(|id| {
+                let debt = self.blind_debts.get(id)?;
+                debt.owes().then(|| (id.clone(), *debt))
+            })
+            .collect();
+
+        if dealt_in.len() == 2 {
+            // Heads-up the other player takes the button and the small blind.
+            let other = dealt_in.iter().find(|id| **id != big_blind_id)?.clone();
+            let other_seat = self.seats.seat_of(&other)?;
+            self.dealer_position = other_seat;
+            self.small_blind_seat = Some(other_seat);
+            self.big_blind_seat = Some(big_blind_seat);
+            return Some(HandSeating {
+                dealt_in: self
+                    .ready_player_ids_from(other_seat)
+                    .into_iter()
+                    .filter(|id| dealt_in.contains(id))
+                    .collect(),
+                button: Some(other.clone()),
+                small_blind: Some(other),
+                big_blind: Some(big_blind_id),
+                posting,
+            });
         }
 
-        if amount < self.min_raise && player.chips > self.min_raise {
-            return Err(ServerError::MinBet(self.min_raise));
+        let occupant_dealt_in = |seat: usize| {
+            self.seats
+                .occupant(seat)
+                .filter(|id| dealt_in.iter().any(|d| d == id))
+                .map(str::to_string)
+        };
+        let seating = HandSeating {
+            button: occupant_dealt_in(button_seat),
+            small_blind: occupant_dealt_in(small_blind_seat),
+            big_blind: Some(big_blind_id),
+            dealt_in: dealt_in.clone(),
+            posting,
+        };
+        self.dealer_position = button_seat;
+        self.small_blind_seat = Some(small_blind_seat);
+        self.big_blind_seat = Some(big_blind_seat);
+        Some(seating)
+    }
+
+    /// Starts the blinds afresh with everyone ready to play dealt in, moving
+    /// the button to the next ready player. Used for the first hand and when
+    /// too few players remain to move the blinds on.
+    fn reset_blinds(&mut self) -> HandSeating {
+        let start = if self.big_blind_seat.is_some() {
+            self.dealer_position
+        } else {
+            self.seats.count().saturating_sub(1)
+        };
+        let ready = self.ready_player_ids_from(start);
+        if ready.len() < 2 {
+            return HandSeating {
+                dealt_in: ready,
+                ..HandSeating::default()
+            };
         }
 
-        Ok(())
+        let (small_blind, big_blind) = if ready.len() == 2 { (0, 1) } else { (1, 2) };
+        let seat = |idx: usize| self.seats.seat_of(&ready[idx]);
+        self.dealer_position = seat(0).unwrap_or(0);
+        self.small_blind_seat = seat(small_blind);
+        self.big_blind_seat = seat(big_blind);
+
+        let mut dealt_in = ready.clone();
+        dealt_in.rotate_left(1);
+        HandSeating {
+            dealt_in,
+            button: Some(ready[0].clone()),
+            small_blind: Some(ready[small_blind].clone()),
+            big_blind: Some(ready[big_blind].clone()),
+            posting: Vec::new(),
+        }
     }
 
-    /// Validates a raise amount before processing
+    /// Returns players who are ready to play (not sitting out, with chips),
+    /// clockwise starting with the seat after `seat`.
+    fn ready_player_ids_from(&self, seat: usize) -> Vec<String> {
+        self.seats
+            .clockwise_from(seat)
+            .into_iter()
+            .filter(|(_, id)| {
+                self.players
+                    .get(*id)
+                    .is_some_and(|p| !p.is_sitting_out && p.chips > 0)
+            })
+            .map(|(_, id)| id.to_string())
+            .collect()
+    }
+
+    /// Chooses to post owed blinds and be dealt in to the next hand, rather
+    /// than waiting for the big blind.
     ///
     /// # Arguments
-    /// * `player` - The player raising
-    /// * `total_bet` - The total bet amount after the raise
+    /// * `player_id` - The player posting
     ///
     /// # Returns
-    /// `Ok(())` if the raise is valid, or an error otherwise
-    fn validate_raise_amount(&self, player: &PlayerState, total_bet: i32) -> ServerResult<()> {
-        if total_bet <= player.current_bet {
-            return Err(ServerError::InvalidRaise(
-                "Raise amount must increase the bet".to_string(),
-            ));
+    /// `Err(ServerError::NoBlindsOwed)` if the player can already be dealt in
+    pub fn post_blind(&mut self, player_id: &str) -> ServerResult<()> {
+        let debt = self
+            .blind_debts
+            .get_mut(player_id)
+            .filter(|debt| debt.owes())
+            .ok_or(ServerError::NoBlindsOwed)?;
+        debt.will_post = true;
+        Ok(())
+    }
+
+    /// Returns the seat a player is sitting in.
+    pub fn seat_of(&self, player_id: &str) -> Option<usize> {
+        self.seats.seat_of(player_id)
+    }
+
+    /// Returns true if a player owes blinds and is sitting out hands until the
+    /// big blind reaches them.
+    pub fn is_waiting_for_big_blind(&self, player_id: &str) -> bool {
+        let sitting_out = self.players.get(player_id).is_none_or(|p| p.is_sitting_out);
+        !sitting_out
+            && self
+                .blind_debts
+                .get(player_id)
+                .is_some_and(|debt| debt.owes() && !debt.will_post)
+    }
+
+    /// Takes a forced bet from a player, capped at their stack, and records it
+    /// in the hand history. Antes are dead money; blinds, straddles and
+    /// bring-ins count toward the player's bet on the street.
+    ///
+    /// # Returns
+    /// The number of chips actually posted
+    fn post_forced_bet(&mut self, player_id: &str, kind: ForcedBetKind, amount: i32) -> i32 {
+        let Some(player) = self.players.get_mut(player_id) else {
+            return 0;
+        };
+        let posted = amount.max(0).min(player.chips);
+        player.chips -= posted;
+        if player.chips == 0 {
+            player.is_all_in = true;
         }
+        match kind {
+            ForcedBetKind::Ante | ForcedBetKind::BigBlindAnte | ForcedBetKind::DeadSmallBlind => {
+                self.antes_collected += posted
+            }
+            _ => player.current_bet = player.current_bet.saturating_add(posted),
+        }
+        self.pot = self.pot.saturating_add(posted);
+        self.hand_history.record(HandEvent::ForcedBet(ForcedBet {
+            player_id: player_id.to_string(),
+            kind,
+            amount: posted,
+        }));
+        posted
+    }
 
-        if total_bet < self.min_raise {
-            return Err(ServerError::MinRaise(self.min_raise));
+    /// Collects the table's antes from the players dealt into the hand. A big
+    /// blind ante is posted with the blinds instead, after the big blind itself.
+    /// A button ante falls to the first player after a dead button.
+    ///
+    /// # Arguments
+    /// * `seating` - Players dealt in and the hand's positions
+    fn post_antes(&mut self, seating: &HandSeating) {
+        if seating.dealt_in.len() < 2 {
+            return;
         }
 
-        let required_chips = total_bet.saturating_sub(player.current_bet);
-        if required_chips > player.chips {
-            return Err(ServerError::RaiseInsufficientChips(
-                required_chips,
-                player.chips,
-            ));
+        let (ante_payers, ante): (Vec<String>, i32) = match self.ante {
+            AnteStructure::None | AnteStructure::BigBlindAnte(_) => (Vec::new(), 0),
+            AnteStructure::ButtonAnte(amount) => {
+                let payer = seating
+                    .button
+                    .clone()
+                    .unwrap_or_else(|| seating.dealt_in[0].clone());
+                (vec![payer], amount)
+            }
+            AnteStructure::PerPlayer(amount) => (seating.dealt_in.clone(), amount),
+        };
+
+        for player_id in ante_payers {
+            self.post_forced_bet(&player_id, ForcedBetKind::Ante, ante);
         }
+    }
 
-        if total_bet > self.max_bet_per_hand {
-            return Err(ServerError::InvalidBet(format!(
-                "Raise exceeds table maximum: {}",
-                self.max_bet_per_hand
-            )));
+    /// Posts the small and big blinds, then any blinds owed by players who
+    /// chose to post rather than wait for the big blind: a live big blind for
+    /// new players and those who missed it, and a dead small blind for those
+    /// who missed the small blind.
+    ///
+    /// # Arguments
+    /// * `seating` - Players dealt in and the hand's positions
+    fn post_blinds(&mut self, seating: &HandSeating) {
+        if seating.dealt_in.len() < 2 {
+            debug!(
+                "Cannot post blinds: only {} active players (need at least 2)",
+                seating.dealt_in.len()
+            );
+            return;
         }
 
-        Ok(())
+        if let Some(sb_player_id) = &seating.small_blind {
+            self.post_forced_bet(sb_player_id, ForcedBetKind::SmallBlind, self.small_blind);
+        }
+        if let Some(bb_player_id) = &seating.big_blind {
+            self.post_forced_bet(bb_player_id, ForcedBetKind::BigBlind, self.big_blind);
+            // A short big blind pays the blind before the ante.
+            if let AnteStructure::BigBlindAnte(amount) = self.ante {
+                self.post_forced_bet(bb_player_id, ForcedBetKind::BigBlindAnte, amount);
+            }
+        }
+        for (player_id, debt) in &seating.posting {
+            if debt.owes_big_blind() {
+                self.post_forced_bet(player_id, ForcedBetKind::BigBlind, self.big_blind);
+            }
+            if debt.missed_small {
+                self.post_forced_bet(player_id, ForcedBetKind::DeadSmallBlind, self.small_blind);
+            }
+        }
+
+        self.min_raise = self.big_blind.saturating_mul(2);
     }
 
-    /// Processes a player's action in the game.
+    /// Posts a straddle of twice the big blind for the first opted-in player in
+    /// a straddle position. Straddles need at least three players and are not
+    /// played in fixed-limit games.
     ///
     /// # Arguments
-    /// * `player_id` - The ID of the player taking the action
-    /// * `action` - The action being taken (fold, check, call, bet, raise, all-in)
+    /// * `seating` - Players dealt in and the hand's positions
     ///
     /// # Returns
-    /// * `Ok(())` if the action was processed successfully
-    /// * `Err(ServerError)` if the action is invalid or it's not the player's turn
-    pub fn handle_action(&mut self, player_id: &str, action: PlayerAction) -> ServerResult<()> {
-        let current_bet = self.get_current_bet();
-
-        let active_player_ids = self.get_active_player_ids();
-        let current_player_id = self.current_player_id.as_ref();
+    /// The straddler's index in `seating.dealt_in`, if anyone straddled
+    fn post_straddle(&mut self, seating: &HandSeating) -> Option<usize> {
+        let dealt_in = &seating.dealt_in;
+        if dealt_in.len() < 3
+            || matches!(self.betting_structure, BettingStructure::FixedLimit { .. })
+        {
+            return None;
+        }
 
-        let is_player_turn = match current_player_id {
-            Some(id) => id == player_id,
-            None => active_player_ids
-                .first()
-                .map(|id| id == player_id)
-                .unwrap_or(false),
+        let bb_idx = dealt_in
+            .iter()
+            .position(|id| Some(id) == seating.big_blind.as_ref())?;
+        let utg_idx = (bb_idx + 1) % dealt_in.len();
+        let button_idx = seating
+            .button
+            .as_ref()
+            .filter(|id| **id != dealt_in[bb_idx] && Some(*id) != seating.small_blind.as_ref())
+            .and_then(|id| dealt_in.iter().position(|d| d == id));
+        let positions = match self.straddle_rule {
+            StraddleRule::None => return None,
+            StraddleRule::UnderTheGun => vec![utg_idx],
+            StraddleRule::Mississippi => button_idx.into_iter().chain([utg_idx]).collect(),
         };
 
-        if !is_player_turn {
-            return Err(ServerError::NotYourTurn);
+        let straddle_idx = positions.into_iter().find(|&idx| {
+            let id = &dealt_in[idx];
+            self.straddlers.contains(id) && self.players.get(id).is_some_and(|p| p.chips > 0)
+        })?;
+
+        // A player who has just posted a big blind straddles for the difference.
+        let straddle = self.big_blind.saturating_mul(2);
+        let already_posted = self.players[&dealt_in[straddle_idx]].current_bet;
+        let posted = self.post_forced_bet(
+            &dealt_in[straddle_idx],
+            ForcedBetKind::Straddle,
+            straddle - already_posted,
+        );
+        // The straddle acts as a third blind, so raises are measured from it.
+        if already_posted + posted == straddle {
+            self.min_raise = straddle.saturating_mul(2);
         }
+        Some(straddle_idx)
+    }
 
-        let pot = self.pot;
-
-        match action {
-            PlayerAction::Fold => {
-                if let Some(player) = self.players.get_mut(player_id) {
-                    player.is_folded = true;
-                    player.has_acted = true;
-                }
-            }
-            PlayerAction::Check => {
-                let player_call_amount = current_bet
-                    - self
-                        .players
-                        .get(player_id)
-                        .map(|p| p.current_bet)
-                        .unwrap_or(0);
-                if player_call_amount > 0 {
-                    return Err(ServerError::CannotCheck);
-                }
-                if let Some(player) = self.players.get_mut(player_id) {
-                    player.has_acted = true;
-                }
-            }
-            PlayerAction::Call => {
-                let player_current_bet;
-                let player_chips;
-                {
-                    let player = self
-                        .players
-                        .get(player_id)
-                        .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
-                    player_current_bet = player.current_bet;
-                    player_chips = player.chips;
-                }
+    /// Opts a player in to or out of straddling in future hands.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player changing their preference
+    /// * `enabled` - Whether the player straddles when in a straddle position
+    ///
+    /// # Returns
+    /// `Err(ServerError::StraddleNotAllowed)` if the table does not allow straddles
+    pub fn set_straddle(&mut self, player_id: &str, enabled: bool) -> ServerResult<()> {
+        if self.straddle_rule == StraddleRule::None {
+            return Err(ServerError::StraddleNotAllowed);
+        }
+        if !self.players.contains_key(player_id) {
+            return Err(ServerError::PlayerNotFound(player_id.to_string()));
+        }
+        if enabled {
+            self.straddlers.insert(player_id.to_string());
+        } else {
+            self.straddlers.remove(player_id);
+        }
+        Ok(())
+    }
 
-                let call_amount = current_bet
-                    .saturating_sub(player_current_bet)
-                    .min(player_chips);
+    /// Sets how many times a player agrees to run the board out when all-in.
+    /// The board is only run more than once if every player in the hand agrees.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player changing their preference
+    /// * `times` - Runs the player agrees to; 1 to always run it once
+    ///
+    /// # Returns
+    /// `Err(ServerError::TooManyRuns)` if `times` is above the table's limit
+    pub fn set_run_it(&mut self, player_id: &str, times: u32) -> ServerResult<()> {
+        if times == 0 {
+            return Err(ServerError::InvalidAmount);
+        }
+        if times > self.max_runs {
+            return Err(ServerError::TooManyRuns(self.max_runs));
+        }
+        if !self.players.contains_key(player_id) {
+            return Err(ServerError::PlayerNotFound(player_id.to_string()));
+        }
+        self.run_preferences.insert(player_id.to_string(), times);
+        Ok(())
+    }
 
-                if call_amount > 0 {
-                    let new_pot = self.calculate_new_pot(call_amount).ok_or_else(|| {
-                        ServerError::InvalidBet("Pot size exceeds maximum allowed".to_string())
-                    })?;
-                    self.pot = new_pot;
-                }
+    /// Returns the first player at or after `start` in seat order who can
+    /// still act this hand.
+    fn next_to_act_from(&self, seat_order: &[String], start: usize) -> Option<String> {
+        (0..seat_order.len())
+            .map(|offset| &seat_order[(start + offset) % seat_order.len()])
+            .find(|id| {
+                self.players
+                    .get(*id)
+                    .is_some_and(|p| !p.is_folded && !p.is_all_in && p.chips > 0)
+            })
+            .cloned()
+    }
 
-                let player = self
-                    .players
-                    .get_mut(player_id)
-                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
-                player.chips = player.chips.saturating_sub(call_amount);
-                player.current_bet = player.current_bet.saturating_add(call_amount);
-                player.has_acted = true;
+    /// Posts the stud bring-in for the player showing the lowest up-card.
+    ///
+    /// The bring-in player still gets to act once the others have had a chance
+    /// to complete the bet to the small bet.
+    ///
+    /// # Returns
+    /// The player to the left of the bring-in, who acts first
+    fn post_bring_in(&mut self) -> Option<String> {
+        let active_player_ids = self.get_active_player_ids();
+        let bring_in_id = self.bring_in_player_id()?;
+        self.post_forced_bet(&bring_in_id, ForcedBetKind::BringIn, self.small_blind);
 
-                if player.chips == 0 {
-                    player.is_all_in = true;
+        // Completing the bring-in to the small bet counts as the street's first bet.
+        self.min_raise = match self.betting_structure {
+            BettingStructure::FixedLimit { small_bet, .. } => small_bet,
+            _ => self.big_blind,
+        };
+
+        let idx = active_player_ids.iter().position(|id| *id == bring_in_id)?;
+        Some(active_player_ids[(idx + 1) % active_player_ids.len()].clone())
+    }
+
+    /// Returns the player who must bring in: the lowest up-card by rank, with
+    /// ties broken by suit (clubs lowest, then diamonds, hearts and spades).
+    /// In lowball stud the highest card brings in instead, aces counting low
+    /// and spades breaking ties.
+    fn bring_in_player_id(&self) -> Option<String> {
+        let lowball = HandRanking::for_variant(self.variant).is_low();
+        self.get_active_player_ids()
+            .into_iter()
+            .filter_map(|id| {
+                let card = *self.players.get(&id)?.up_cards.first()?;
+                Some((id, card))
+            })
+            .min_by_key(|(_, card)| {
+                if lowball {
+                    (u8::MAX - low_value(card), u8::MAX - suit_order(card.suit))
+                } else {
+                    (card.rank as u8, suit_order(card.suit))
                 }
+            })
+            .map(|(id, _)| id)
+    }
+
+    /// Returns the player who acts first on fourth street and later: the best
+    /// hand showing, with ties going to the earliest seat.
+    fn best_visible_hand_player_id(&self) -> Option<String> {
+        let mut best: Option<(String, RankedHand)> = None;
+        for id in self.get_active_player_ids() {
+            let Some(player) = self.players.get(&id) else {
+                continue;
+            };
+            let hand = self.rank_up_cards(&player.up_cards);
+            if best.as_ref().is_none_or(|(_, b)| hand > *b) {
+                best = Some((id, hand));
             }
-            PlayerAction::Bet(amount) => {
-                let player = self
-                    .players
-                    .get(player_id)
-                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+        }
+        best.map(|(id, _)| id)
+    }
 
-                self.validate_bet_amount(player, amount, current_bet, pot)?;
+    /// Ranks a stud player's up-cards under the table's hand ranking.
+    fn rank_up_cards(&self, cards: &[Card]) -> RankedHand {
+        match HandRanking::for_variant(self.variant) {
+            HandRanking::High => {
+                let eval = self.evaluate_up_cards(cards);
+                RankedHand::high(&eval, self.hand_rank_strength(eval.rank))
+            }
+            HandRanking::AceToFiveLow => ace_to_five_low(cards),
+            HandRanking::DeuceToSevenLow => deuce_to_seven_low(cards),
+        }
+    }
 
-                if current_bet > 0 && amount < self.min_raise {
-                    return Err(ServerError::MinRaise(self.min_raise));
-                }
+    /// Evaluates a stud player's up-cards for action order. Only pairs, trips
+    /// and quads count; straights and flushes are not considered on board.
+    fn evaluate_up_cards(&self, cards: &[Card]) -> HandEvaluation {
+        self.check_four_of_a_kind(cards)
+            .or_else(|| self.check_three_of_a_kind(cards))
+            .or_else(|| self.check_two_pair(cards))
+            .or_else(|| self.check_pair(cards))
+            .unwrap_or_else(|| HandEvaluation::high_card(cards))
+    }
 
-                let bet_amount = amount;
-                let new_pot = self.calculate_new_pot(bet_amount).ok_or_else(|| {
-                    ServerError::InvalidBet("Pot size exceeds maximum allowed".to_string())
-                })?;
+    /// Returns the ids of players still in the hand who have been dealt cards,
+    /// clockwise from the first seat after the button.
+    fn dealt_in_player_ids(&self) -> Vec<String> {
+        self.seats
+            .clockwise_from(self.dealer_position)
+            .into_iter()
+            .filter_map(|(_, id)| self.players.get(id))
+            .filter(|p| !p.is_folded && !p.hole_cards.is_empty())
+            .map(|p| p.id.clone())
+            .collect()
+    }
 
-                let player = self
-                    .players
-                    .get_mut(player_id)
-                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
-                player.chips = player.chips.saturating_sub(bet_amount);
-                player.current_bet = bet_amount;
-                self.pot = new_pot;
-                self.min_raise = bet_amount.saturating_mul(2);
-                player.has_acted = true;
+    /// Deals one face-up card to each player still in a stud hand.
+    fn deal_up_cards(&mut self) {
+        for player_id in self.dealt_in_player_ids() {
+            if let Some(card) = self.deal_card() {
+                if let Some(player) = self.players.get_mut(&player_id) {
+                    player.up_cards.push(card);
+                }
+            }
+        }
+    }
 
-                if player.chips == 0 {
-                    player.is_all_in = true;
+    /// Deals the final stud card face down. If too few cards remain for every
+    /// player, a single community card is dealt that everyone shares.
+    fn deal_seventh_street(&mut self) {
+        let player_ids = self.dealt_in_player_ids();
+        if self.deck.len() < player_ids.len() {
+            self.deal_community_cards(1);
+            return;
+        }
+        for player_id in player_ids {
+            if let Some(card) = self.deal_card() {
+                if let Some(player) = self.players.get_mut(&player_id) {
+                    player.hole_cards.push(card);
                 }
             }
-            PlayerAction::Raise(amount) => {
-                let total_bet = current_bet.saturating_add(amount);
+        }
+    }
 
-                let player = self
-                    .players
-                    .get(player_id)
-                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+    /// Deals the cards that open a street after the first.
+    fn deal_street(&mut self, street: Street) {
+        if self.dealt_in_player_ids().len() >= 2 {
+            self.flop_seen = true;
+        }
+        match street {
+            Street::Flop => self.deal_community_cards(3),
+            Street::Turn | Street::River => self.deal_community_cards(1),
+            Street::FourthStreet | Street::FifthStreet | Street::SixthStreet => {
+                self.deal_up_cards()
+            }
+            Street::SeventhStreet => self.deal_seventh_street(),
+            Street::FirstDraw | Street::SecondDraw | Street::ThirdDraw => self.begin_draw(),
+            Street::Preflop | Street::ThirdStreet | Street::PreDraw | Street::Showdown => {}
+        }
+    }
 
-                self.validate_raise_amount(player, total_bet)?;
+    /// Opens a draw: every player still in the hand, including those all-in,
+    /// discards and replaces cards in turn before the betting resumes.
+    fn begin_draw(&mut self) {
+        self.pending_draws = self.dealt_in_player_ids();
+        self.current_player_id = self.pending_draws.first().cloned();
+    }
 
-                let actual_raise = total_bet.saturating_sub(player.current_bet);
+    /// Replaces a player's discards with new cards from the deck.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player drawing
+    /// * `discards` - The cards to throw away; empty to stand pat
+    ///
+    /// # Returns
+    /// `Ok(())` if the draw was made, or `ServerError::InvalidDraw` otherwise
+    fn handle_draw(&mut self, player_id: &str, discards: Vec<Card>) -> ServerResult<()> {
+        if !self.pending_draws.iter().any(|id| id == player_id) {
+            return Err(ServerError::InvalidDraw(
+                "There is no draw in progress".to_string(),
+            ));
+        }
 
-                let new_pot = self.calculate_new_pot(actual_raise).ok_or_else(|| {
-                    ServerError::InvalidBet("Pot size exceeds maximum allowed".to_string())
-                })?;
+        let player = self
+            .players
+            .get(player_id)
+            .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+        for (i, card) in discards.iter().enumerate() {
+            if !player.hole_cards.contains(card) {
+                return Err(ServerError::InvalidDraw(format!(
+                    "{} is not in your hand",
+                    card
+                )));
+            }
+            if discards[..i].contains(card) {
+                return Err(ServerError::InvalidDraw(format!(
+                    "{} is discarded more than once",
+                    card
+                )));
+            }
+        }
 
-                let player = self
-                    .players
-                    .get_mut(player_id)
-                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
-                player.chips = player.chips.saturating_sub(actual_raise);
-                player.current_bet = player.current_bet.saturating_add(actual_raise);
-                self.pot = new_pot;
-                self.min_raise = player.current_bet.saturating_mul(2);
-                player.has_acted = true;
+        if self.deck.len() < discards.len() {
+            // Reshuffle earlier discards under the remaining stub.
+            let mut reshuffled = std::mem::take(&mut self.discards);
+            reshuffled.shuffle(&mut thread_rng());
+            reshuffled.append(&mut self.deck);
+            self.deck = reshuffled;
+        }
 
-                if player.chips == 0 {
-                    player.is_all_in = true;
-                }
+        self.hand_history.record(HandEvent::Draw {
+            player_id: player_id.to_string(),
+            discarded: discards.len(),
+        });
+        let replacements: Vec<Card> = discards.iter().filter_map(|_| self.deal_card()).collect();
+        if let Some(player) = self.players.get_mut(player_id) {
+            player.hole_cards.retain(|card| !discards.contains(card));
+            player.hole_cards.extend(replacements);
+        }
+        self.discards.extend(discards);
+
+        self.pending_draws.retain(|id| id != player_id);
+        match self.pending_draws.first() {
+            Some(next) => self.current_player_id = Some(next.clone()),
+            None if self.run_out.is_some() => {
+                // Everyone has drawn; the run-out carries on without betting.
+                self.current_player_id = None;
+                self.broadcast_game_state();
+                return Ok(());
             }
-            PlayerAction::AllIn => {
-                let player = self
-                    .players
-                    .get(player_id)
-                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
-
-                if player.chips == 0 {
-                    return Err(ServerError::NoChips);
+            None => {
+                let active_player_ids = self.get_active_player_ids();
+                if active_player_ids.is_empty() {
+                    self.end_hand();
+                    return Ok(());
                 }
+                self.current_player_id = active_player_ids.first().cloned();
+            }
+        }
 
-                let all_in_amount = player.chips;
-                let new_bet = player.current_bet.saturating_add(all_in_amount);
+        self.broadcast_game_state();
+        self.request_action();
+        Ok(())
+    }
 
-                let new_pot = self.calculate_new_pot(all_in_amount).ok_or_else(|| {
-                    ServerError::InvalidBet("Pot size exceeds maximum allowed".to_string())
-                })?;
+    fn deal_hole_cards(&mut self) {
+        for _ in 0..self.variant.hole_card_count() {
+            let player_ids: Vec<String> = self
+                .players
+                .iter()
+                .filter(|(_, p)| !p.is_sitting_out && !p.is_folded && (p.chips > 0 || p.is_all_in))
+                .map(|(id, _)| id.clone())
+                .collect();
 
-                let player = self
-                    .players
-                    .get_mut(player_id)
-                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
-                player.chips = 0;
-                player.current_bet = new_bet;
-                self.pot = new_pot;
-                player.is_all_in = true;
-                player.has_acted = true;
+            let cards_to_deal: Vec<Option<Card>> =
+                player_ids.iter().map(|_| self.deal_card()).collect();
 
-                let new_total_bet = player.current_bet;
-                if new_total_bet > current_bet {
-                    let potential_min_raise = new_total_bet.saturating_mul(2);
-                    if potential_min_raise > self.min_raise {
-                        self.min_raise = potential_min_raise;
+            for (player_id, card) in player_ids.iter().zip(cards_to_deal) {
+                if let Some(card) = card {
+                    if let Some(player) = self.players.get_mut(player_id) {
+                        player.hole_cards.push(card);
                     }
                 }
             }
         }
+    }
 
-        self.broadcast_game_state();
-        self.advance_action();
-
-        Ok(())
+    /// Returns the players still able to act, clockwise from the first seat
+    /// after the button, so the first id is the first to act after the flop.
+    fn get_active_player_ids(&self) -> Vec<String> {
+        self.seats
+            .clockwise_from(self.dealer_position)
+            .into_iter()
+            .filter_map(|(_, id)| self.players.get(id))
+            .filter(|p| !p.is_folded && !p.is_sitting_out && p.chips > 0)
+            .map(|p| p.id.clone())
+            .collect()
     }
 
-    fn all_players_acted(&self) -> bool {
-        self.players
+    /// Moves a mixed-game table on to the next game in its rotation once the
+    /// current game has been played for its share of hands.
+    fn rotate_game_if_due(&mut self) {
+        let players = self
+            .players
             .values()
-            .filter(|p| !p.is_folded && !p.is_all_in)
-            .all(|p| p.has_acted)
-    }
+            .filter(|p| !p.is_sitting_out && p.chips > 0)
+            .count();
+        let Some(rotation) = self.rotation.as_mut() else {
+            return;
+        };
 
-    fn bets_equalized(&self) -> bool {
-        let active_players: Vec<_> = self.players.values().filter(|p| !p.is_folded).collect();
-        if active_players.is_empty() {
-            return true;
+        if rotation.is_due(self.hands_in_game, players) {
+            let game = rotation.advance().clone();
+            info!(
+                "Table {} switching to {} {}",
+                self.game_id, game.betting_structure, game.variant
+            );
+            self.variant = game.variant;
+            self.betting_structure = game.betting_structure;
+            self.ante = game.ante;
+            self.hands_in_game = 0;
         }
-        let target_bet = active_players
-            .iter()
-            .map(|p| p.current_bet)
-            .max()
-            .unwrap_or(0);
-        active_players
-            .iter()
-            .all(|p| p.current_bet == target_bet || p.is_all_in)
-    }
-
-    fn should_advance_street(&self) -> bool {
-        self.all_players_acted() && self.bets_equalized()
+        self.hands_in_game += 1;
     }
 
-    fn advance_action(&mut self) {
-        let active_player_ids = self.get_active_player_ids();
-        if active_player_ids.is_empty() {
-            self.end_hand();
+    fn start_hand(&mut self) {
+        if self.paused {
+            self.game_stage = GameStage::Paused;
+            self.broadcast_game_state();
             return;
         }
-
-        if let Some(ref current_id) = self.current_player_id {
-            if let Some(current_idx) = active_player_ids.iter().position(|id| id == current_id) {
-                let next_idx = (current_idx + 1) % active_player_ids.len();
-                self.current_player_id = Some(active_player_ids[next_idx].clone());
-            } else {
-                self.current_player_id = active_player_ids.first().cloned();
-            }
-        } else {
-            self.current_player_id = active_player_ids.first().cloned();
+        if let Some((small_blind, big_blind)) = self.next_blinds.take() {
+            self.small_blind = small_blind;
+            self.big_blind = big_blind;
         }
+        self.hand_number += 1;
+        self.hand_span =
+            info_span!(parent: &self.table_span, "hand", hand_number = self.hand_number);
+        let hand_span = self.hand_span.clone();
+        let _hand = hand_span.enter();
+        info!("Dealing hand");
+        self.rotate_game_if_due();
+        self.create_deck();
 
-        if self.current_street != Street::Showdown && self.should_advance_street() {
-            match self.current_street {
-                Street::Preflop => {
-                    self.current_street = Street::Flop;
-                    self.deal_community_cards(3);
-                }
-                Street::Flop => {
-                    self.current_street = Street::Turn;
-                    self.deal_community_cards(1);
-                }
-                Street::Turn => {
-                    self.current_street = Street::River;
-                    self.deal_community_cards(1);
-                }
-                Street::River => {
-                    self.current_street = Street::Showdown;
-                    self.showdown();
-                    return;
-                }
-                Street::Showdown => {
-                    return;
-                }
-            }
-
-            self.broadcast_game_state();
-            self.request_action();
-        } else {
-            self.request_action();
+        for player in self.players.values_mut() {
+            player.current_bet = 0;
+            player.total_bet = 0;
+            player.hole_cards.clear();
+            player.up_cards.clear();
+            player.has_acted = false;
+            player.is_all_in = false;
+            player.is_folded = false;
         }
-    }
 
-    fn deal_community_cards(&mut self, count: usize) {
-        if self.current_street == Street::Showdown {
-            error!("Cannot deal community cards during showdown");
-            return;
+        self.community_cards.clear();
+        self.side_pots.clear();
+        self.pot = 0;
+        self.raises_this_street = 0;
+        self.antes_collected = 0;
+        self.pending_draws.clear();
+        self.discards.clear();
+        self.run_out = None;
+        self.flop_seen = false;
+        self.hand_history = HandHistory::new(self.hand_number, self.variant);
+
+        let seating = self.seat_players_for_hand();
+        // Players waiting for the big blind sit this hand out.
+        for player in self.players.values_mut() {
+            if !seating.dealt_in.contains(&player.id) {
+                player.is_folded = true;
+            }
+        }
+        self.post_antes(&seating);
+        let mut straddle_idx = None;
+        if !self.variant.is_stud() {
+            self.post_blinds(&seating);
+            straddle_idx = self.post_straddle(&seating);
         }
+        self.deal_hole_cards();
+        self.current_street = self.variant.first_street();
+        self.game_stage = GameStage::BettingRound(self.current_street);
 
-        if count == 0 || count > 5 {
-            error!("Invalid community card count: {}", count);
+        if self.variant.is_stud() {
+            self.deal_up_cards();
+            self.current_player_id = self.post_bring_in();
+            self.broadcast_game_state();
+            self.open_action();
             return;
         }
 
-        let max_cards = match self.current_street {
-            Street::Preflop => 5,
-            Street::Flop => 4,
-            Street::Turn => 5,
-            Street::River => 5,
-            Street::Showdown => 0,
-        };
+        // Preflop action starts with the player after the big blind (UTG), or
+        // after the straddler, who then acts last. In heads-up that is the
+        // small blind on the button.
+        let dealt_in = &seating.dealt_in;
+        let bb_idx = dealt_in
+            .iter()
+            .position(|id| Some(id) == seating.big_blind.as_ref())
+            .unwrap_or(0);
+        let first_idx = straddle_idx.unwrap_or(bb_idx) + 1;
+        self.current_player_id = self.next_to_act_from(dealt_in, first_idx);
 
-        if self.community_cards.len() + count > max_cards {
-            error!(
-                "Cannot deal {} cards, would exceed maximum of {}",
-                count, max_cards
-            );
-            return;
+        self.broadcast_game_state();
+        self.open_action();
+    }
+
+    /// Asks the first player to act, or runs the board out straight away
+    /// when the forced bets have left no one able to bet against anyone else.
+    fn open_action(&mut self) {
+        let current_bet = self.get_current_bet();
+        let closed = match self.get_active_player_ids().as_slice() {
+            [] => true,
+            [only] => self
+                .players
+                .get(only)
+                .is_some_and(|p| p.current_bet >= current_bet),
+            _ => false,
+        };
+        if closed && self.dealt_in_player_ids().len() >= 2 {
+            self.begin_run_out();
+        } else {
+            self.request_action();
         }
+    }
 
-        for _ in 0..count {
-            if let Some(card) = self.deal_card() {
-                self.community_cards.push(card);
-            }
+    /// Returns the public state of the table.
+    pub fn game_state_update(&self) -> GameStateUpdate {
+        GameStateUpdate {
+            game_id: self.game_id.clone(),
+            hand_number: self.hand_number,
+            pot: self.pot,
+            side_pots: self.side_pots.clone(),
+            community_cards: self.community_cards.iter().map(|c| c.to_string()).collect(),
+            current_street: self.current_street.to_string(),
+            dealer_position: self.dealer_position,
+            variant: self.variant,
+            betting_structure: self.betting_structure,
+            forced_bets: self.hand_history.forced_bets(),
+            paused: self.paused,
         }
     }
 
-    fn calculate_side_pots(&self) -> Vec<(i32, Vec<String>)> {
-        let mut pots = Vec::new();
-        let players: Vec<_> = self.players.values().filter(|p| !p.is_folded).collect();
+    fn broadcast_game_state(&self) {
+        self.broadcast_message(ServerMessage::GameStateUpdate(self.game_state_update()));
+        let players: Vec<PlayerUpdate> = self
+            .players
+            .values()
+            .map(|p| PlayerUpdate {
+                player_id: p.id.clone(),
+                player_name: p.name.clone(),
+                chips: p.chips,
+                current_bet: p.current_bet,
+                has_acted: p.has_acted,
+                is_all_in: p.is_all_in,
+                is_folded: p.is_folded,
+                is_sitting_out: p.is_sitting_out,
+                hole_cards: if p.hole_cards.is_empty() {
+                    vec![HIDDEN_CARD.to_string()]
+                } else {
+                    p.hole_cards.iter().map(|c| c.to_string()).collect()
+                },
+                up_cards: p.up_cards.iter().map(|c| c.to_string()).collect(),
+                seat: self.seats.seat_of(&p.id).unwrap_or_default(),
+                waiting_for_big_blind: self.is_waiting_for_big_blind(&p.id),
+            })
+            .collect();
+        self.broadcast_message(ServerMessage::PlayerUpdates(players));
+    }
 
-        if players.is_empty() {
-            return pots;
+    fn request_action(&mut self) {
+        let active_player_ids = self.get_active_player_ids();
+        // Players who are all-in still draw.
+        if active_player_ids.is_empty() && self.pending_draws.is_empty() {
+            return;
         }
 
-        let mut players_vec: Vec<_> = players.iter().collect();
-        players_vec.sort_by_key(|&p| p.current_bet);
+        let player_id = self
+            .current_player_id
+            .clone()
+            .or_else(|| active_player_ids.first().cloned())
+            .unwrap_or_default();
+
+        let player = self.players.get(&player_id);
+        let current_bet = self.get_current_bet();
+        let limits = player.map(|p| self.betting_limits_for(p, current_bet, self.pot));
 
-        let min_bet = players_vec.first().map(|p| p.current_bet).unwrap_or(0);
+        let action_update = ActionRequiredUpdate {
+            player_id: player.map(|p| p.id.clone()).unwrap_or_default(),
+            player_name: player.map(|p| p.name.clone()).unwrap_or_default(),
+            min_raise: limits.map(|l| l.min_raise_to).unwrap_or(self.min_raise),
+            current_bet,
+            player_chips: player.map(|p| p.chips).unwrap_or(0),
+            max_raise: limits.map(|l| l.max_raise_to).unwrap_or(0),
+            is_draw: !self.pending_draws.is_empty(),
+        };
 
-        let main_pot_players: Vec<String> = players_vec.iter().map(|p| p.id.clone()).collect();
-        let player_count: i32 = main_pot_players.len().try_into().unwrap_or(i32::MAX);
-        let main_pot_amount = min_bet.saturating_mul(player_count);
-        pots.push((main_pot_amount, main_pot_players));
+        self.broadcast_message(ServerMessage::ActionRequired(action_update));
+        self.start_action_clock();
+    }
 
-        let mut current_level = min_bet;
+    fn get_current_bet(&self) -> i32 {
+        self.players
+            .values()
+            .filter(|p| !p.is_folded)
+            .map(|p| p.current_bet)
+            .max()
+            .unwrap_or(0)
+    }
 
-        for player in players_vec.iter() {
-            let excess = player.current_bet - current_level;
-            if excess > 0 {
-                let eligible_players: Vec<String> = players_vec
-                    .iter()
-                    .filter(|p| p.current_bet >= player.current_bet)
-                    .map(|p| p.id.clone())
-                    .collect();
+    #[cfg(test)]
+    fn get_player_to_act(&self) -> Option<&PlayerState> {
+        let active_player_ids = self.get_active_player_ids();
+        if active_player_ids.is_empty() {
+            return None;
+        }
 
-                let eligible_count: i32 = eligible_players.len().try_into().unwrap_or(i32::MAX);
-                let side_pot_amount = excess.saturating_mul(eligible_count);
-                pots.push((side_pot_amount, eligible_players));
+        if let Some(ref player_id) = self.current_player_id {
+            if active_player_ids.contains(player_id) {
+                return self.players.get(player_id);
             }
-            current_level = player.current_bet;
         }
 
-        pots
+        active_player_ids
+            .first()
+            .and_then(|id| self.players.get(id))
     }
 
-    fn showdown(&mut self) {
-        let active_players: Vec<&PlayerState> =
-            self.players.values().filter(|p| !p.is_folded).collect();
+    /// Calculates the legal bet and raise sizes for a player under the table's
+    /// betting structure.
+    ///
+    /// # Arguments
+    /// * `player` - The player to act
+    /// * `current_bet` - The current highest bet in the round
+    /// * `pot` - The current pot size
+    fn betting_limits_for(
+        &self,
+        player: &PlayerState,
+        current_bet: i32,
+        pot: i32,
+    ) -> BettingLimits {
+        let ctx = BettingContext {
+            street: self.current_street,
+            pot,
+            current_bet,
+            player_bet: player.current_bet,
+            player_chips: player.chips,
+            min_raise_to: self.min_raise,
+            big_blind: self.big_blind,
+            raises_this_street: self.raises_this_street,
+        };
+        betting_limits(self.betting_structure, &ctx)
+    }
 
-        if active_players.is_empty() {
-            self.end_hand();
+    /// Records a bet or raise to `new_total`, updating the minimum raise and the
+    /// number of raises made on this street. Incomplete all-in raises do not
+    /// reopen the betting; in fixed-limit games only a raise of the full bet
+    /// size, or a bring-in completed to it, counts towards the raise cap.
+    fn record_bet(&mut self, previous_bet: i32, new_total: i32) {
+        if new_total <= previous_bet {
             return;
         }
+        let full_raise_to = match self.betting_structure {
+            BettingStructure::FixedLimit {
+                small_bet, big_bet, ..
+            } => {
+                let bet_size = fixed_limit_bet_size(small_bet, big_bet, self.current_street);
+                if previous_bet < bet_size {
+                    bet_size
+                } else {
+                    previous_bet.saturating_add(bet_size)
+                }
+            }
+            _ => self.min_raise,
+        };
+        if new_total >= full_raise_to {
+            let increment = new_total.saturating_sub(previous_bet);
+            self.min_raise = new_total.saturating_add(increment);
+            self.raises_this_street += 1;
+        }
+    }
 
-        let side_pots = self.calculate_side_pots();
-
-        let mut hand_evals: Vec<(&PlayerState, HandEvaluation)> = active_players
-            .iter()
-            .map(|p| (*p, self.evaluate_hand(p)))
-            .collect();
+    /// Validates a bet amount before processing
+    ///
+    /// # Arguments
+    /// * `player` - The player placing the bet
+    /// * `amount` - The bet amount to validate
+    /// * `current_bet` - The current highest bet in the round
+    /// * `pot` - The current pot size
+    ///
+    /// # Returns
+    /// `Ok(())` if the bet is valid, or an error otherwise
+    fn validate_bet_amount(
+        &self,
+        player: &PlayerState,
+        amount: i32,
+        current_bet: i32,
+        pot: i32,
+    ) -> ServerResult<()> {
+        if amount <= 0 {
+            return Err(ServerError::InvalidAmount);
+        }
 
-        if hand_evals.is_empty() {
-            self.end_hand();
-            return;
+        if current_bet > 0 && amount < current_bet {
+            return Err(ServerError::CannotBet);
         }
 
-        hand_evals.sort_by(|a, b| b.1.cmp(&a.1));
+        let required_chips = amount.saturating_sub(player.current_bet);
+        if required_chips > player.chips {
+            return Err(ServerError::BetExceedsChips(amount, player.chips));
+        }
 
-        let best_eval = &hand_evals[0].1;
-        let winners: Vec<&PlayerState> = hand_evals
-            .iter()
-            .filter(|(_, eval)| {
-                eval.rank == best_eval.rank && eval.tiebreakers == best_eval.tiebreakers
-            })
-            .map(|(p, _)| *p)
-            .collect();
+        let limits = self.betting_limits_for(player, current_bet, pot);
+        if !limits.raise_allowed {
+            return Err(ServerError::InvalidBet(format!(
+                "No further bets allowed this street ({})",
+                self.betting_structure
+            )));
+        }
 
-        let winner_ids: Vec<String> = winners.iter().map(|p| p.id.clone()).collect();
+        if amount > limits.max_raise_to {
+            return Err(ServerError::InvalidBet(format!(
+                "Bet exceeds {} maximum: {} (pot: {})",
+                self.betting_structure, limits.max_raise_to, pot
+            )));
+        }
 
-        let showdown_update = ShowdownUpdate {
-            community_cards: self.community_cards.iter().map(|c| c.to_string()).collect(),
-            hands: hand_evals
-                .iter()
-                .map(|(p, eval)| {
-                    (
-                        p.id.clone(),
-                        p.hole_cards.iter().map(|c| c.to_string()).collect(),
-                        format!("{:?}", eval.rank),
-                        eval.description.clone(),
-                    )
-                })
-                .collect(),
-            winners: winner_ids.clone(),
-        };
+        if amount > self.max_bet_per_hand {
+            return Err(ServerError::InvalidBet(format!(
+                "Bet exceeds table maximum: {}",
+                self.max_bet_per_hand
+            )));
+        }
 
-        let winnings_distribution: Vec<(String, i32)> = {
-            let mut distributed = Vec::new();
-            for (pot_amount, eligible_players) in side_pots {
-                let pot_winner_ids: Vec<String> = winners
-                    .iter()
-                    .filter(|w| eligible_players.contains(&w.id))
-                    .map(|w| w.id.clone())
-                    .collect();
+        let is_all_in = required_chips == player.chips;
+        if amount < limits.min_raise_to && !is_all_in {
+            if current_bet > 0 {
+                return Err(ServerError::MinRaise(limits.min_raise_to));
+            }
+            return Err(ServerError::MinBet(limits.min_raise_to));
+        }
 
-                if pot_winner_ids.is_empty() {
-                    continue;
-                }
+        Ok(())
+    }
 
-                let winner_count_in_pot = pot_winner_ids.len() as i32;
-                let pot_per_winner = pot_amount / winner_count_in_pot;
-                let remainder = pot_amount % winner_count_in_pot;
+    /// Validates a raise amount before processing
+    ///
+    /// # Arguments
+    /// * `player` - The player raising
+    /// * `total_bet` - The total bet amount after the raise
+    ///
+    /// # Returns
+    /// `Ok(())` if the raise is valid, or an error otherwise
+    fn validate_raise_amount(&self, player: &PlayerState, total_bet: i32) -> ServerResult<()> {
+        if total_bet <= player.current_bet {
+            return Err(ServerError::InvalidRaise(
+                "Raise amount must increase the bet".to_string(),
+            ));
+        }
 
-                for (i, winner_id) in pot_winner_ids.iter().enumerate() {
-                    let mut winnings = pot_per_winner;
-                    if i < remainder as usize {
-                        winnings += 1;
-                    }
-                    distributed.push((winner_id.clone(), winnings));
-                }
-            }
-            distributed
-        };
+        let limits = self.betting_limits_for(player, self.get_current_bet(), self.pot);
+        if !limits.raise_allowed {
+            return Err(ServerError::InvalidRaise(format!(
+                "No further raises allowed this street ({})",
+                self.betting_structure
+            )));
+        }
 
-        for (winner_id, winnings) in winnings_distribution {
-            if let Some(player) = self.players.get_mut(&winner_id) {
-                player.chips += winnings;
-            }
+        let required_chips = total_bet.saturating_sub(player.current_bet);
+        let is_all_in = required_chips == player.chips;
+        if total_bet < limits.min_raise_to && !is_all_in {
+            return Err(ServerError::MinRaise(limits.min_raise_to));
         }
 
-        self.broadcast_message(ServerMessage::Showdown(showdown_update));
+        if required_chips > player.chips {
+            return Err(ServerError::RaiseInsufficientChips(
+                required_chips,
+                player.chips,
+            ));
+        }
 
-        self.end_hand();
-    }
+        if total_bet > limits.max_raise_to {
+            return Err(ServerError::InvalidRaise(format!(
+                "Raise exceeds {} maximum: {}",
+                self.betting_structure, limits.max_raise_to
+            )));
+        }
 
-    fn evaluate_hand(&self, player: &PlayerState) -> HandEvaluation {
-        let all_cards: Vec<Card> = player
-            .hole_cards
-            .iter()
-            .chain(self.community_cards.iter())
-            .cloned()
-            .collect();
+        if total_bet > self.max_bet_per_hand {
+            return Err(ServerError::InvalidBet(format!(
+                "Raise exceeds table maximum: {}",
+                self.max_bet_per_hand
+            )));
+        }
 
-        // Handle edge cases: empty hand or less than 5 cards
-        match all_cards.len() {
-            0 => {
-                return HandEvaluation {
-                    rank: HandRank::HighCard,
-                    primary_rank: 0,
-                    tiebreakers: vec![],
-                    description: "No cards".to_string(),
-                };
+        Ok(())
+    }
+
+    /// Processes a player's action in the game.
+    ///
+    /// # Arguments
+    /// * `player_id` - The ID of the player taking the action
+    /// * `action` - The action being taken (fold, check, call, bet, raise, all-in)
+    ///
+    /// # Returns
+    /// * `Ok(())` if the action was processed successfully
+    /// * `Err(ServerError)` if the action is invalid or it's not the player's turn
+    pub fn handle_action(&mut self, player_id: &str, action: PlayerAction) -> ServerResult<()> {
+        let current_bet = self.get_current_bet();
+
+        let active_player_ids = self.get_active_player_ids();
+        let current_player_id = self.current_player_id.as_ref();
+
+        let is_player_turn = match current_player_id {
+            Some(id) => id == player_id,
+            None => active_player_ids
+                .first()
+                .map(|id| id == player_id)
+                .unwrap_or(false),
+        };
+
+        if !is_player_turn || (self.run_out.is_some() && !matches!(action, PlayerAction::Draw(_))) {
+            return Err(ServerError::NotYourTurn);
+        }
+
+        if !self.pending_draws.is_empty() && !matches!(action, PlayerAction::Draw(_)) {
+            return Err(ServerError::InvalidDraw(
+                "Players must draw before the betting resumes".to_string(),
+            ));
+        }
+
+        let pot = self.pot;
+        let recorded_action = action.clone();
+
+        match action {
+            PlayerAction::Draw(discards) => return self.handle_draw(player_id, discards),
+            PlayerAction::Fold => {
+                if let Some(player) = self.players.get_mut(player_id) {
+                    player.is_folded = true;
+                    player.has_acted = true;
+                }
             }
-            1..=4 => {
-                let top_rank = all_cards.iter().map(|c| c.rank as i32).max().unwrap_or(0);
-                return HandEvaluation {
-                    rank: HandRank::HighCard,
-                    primary_rank: top_rank,
-                    tiebreakers: all_cards.iter().map(|c| c.rank as i32).collect(),
-                    description: format!("High Card ({} cards)", all_cards.len()),
-                };
+            PlayerAction::Check => {
+                let player_call_amount = current_bet
+                    - self
+                        .players
+                        .get(player_id)
+                        .map(|p| p.current_bet)
+                        .unwrap_or(0);
+                if player_call_amount > 0 {
+                    return Err(ServerError::CannotCheck);
+                }
+                if let Some(player) = self.players.get_mut(player_id) {
+                    player.has_acted = true;
+                }
             }
-            _ => {}
-        }
+            PlayerAction::Call => {
+                let player_current_bet;
+                let player_chips;
+                {
+                    let player = self
+                        .players
+                        .get(player_id)
+                        .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+                    player_current_bet = player.current_bet;
+                    player_chips = player.chips;
+                }
 
-        // Check hands in descending order of rank
-        self.check_straight_flush(&all_cards)
-            .or_else(|| self.check_four_of_a_kind(&all_cards))
-            .or_else(|| self.check_full_house(&all_cards))
-            .or_else(|| self.check_flush(&all_cards))
-            .or_else(|| self.check_straight(&all_cards))
-            .or_else(|| self.check_three_of_a_kind(&all_cards))
-            .or_else(|| self.check_two_pair(&all_cards))
-            .or_else(|| self.check_pair(&all_cards))
-            .unwrap_or_else(|| HandEvaluation::high_card(&all_cards))
-    }
+                let call_amount = current_bet
+                    .saturating_sub(player_current_bet)
+                    .min(player_chips);
 
-    fn check_straight_flush(&self, cards: &[Card]) -> Option<HandEvaluation> {
-        if let Some(flush_cards) = self.get_flush_cards(cards) {
-            let ranks: Vec<u8> = flush_cards.iter().map(|c| c.rank as u8).collect();
+                if call_amount > 0 {
+                    let new_pot = self.calculate_new_pot(call_amount).ok_or_else(|| {
+                        ServerError::InvalidBet("Pot size exceeds maximum allowed".to_string())
+                    })?;
+                    self.pot = new_pot;
+                }
+
+                let player = self
+                    .players
+                    .get_mut(player_id)
+                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+                player.chips = player.chips.saturating_sub(call_amount);
+                player.current_bet = player.current_bet.saturating_add(call_amount);
+                player.has_acted = true;
+
+                if player.chips == 0 {
+                    player.is_all_in = true;
+                }
+            }
+            PlayerAction::Bet(amount) => {
+                let player = self
+                    .players
+                    .get(player_id)
+                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+
+                self.validate_bet_amount(player, amount, current_bet, pot)?;
+
+                let bet_amount = amount.saturating_sub(player.current_bet);
+                let new_pot = self.calculate_new_pot(bet_amount).ok_or_else(|| {
+                    ServerError::InvalidBet("Pot size exceeds maximum allowed".to_string())
+                })?;
+
+                let player = self
+                    .players
+                    .get_mut(player_id)
+                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+                player.chips = player.chips.saturating_sub(bet_amount);
+                player.current_bet = amount;
+                player.has_acted = true;
+
+                if player.chips == 0 {
+                    player.is_all_in = true;
+                }
+                self.pot = new_pot;
+                self.record_bet(current_bet, amount);
+            }
+            PlayerAction::Raise(amount) => {
+                let total_bet = current_bet.saturating_add(amount);
+
+                let player = self
+                    .players
+                    .get(player_id)
+                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+
+                self.validate_raise_amount(player, total_bet)?;
+
+                let actual_raise = total_bet.saturating_sub(player.current_bet);
+
+                let new_pot = self.calculate_new_pot(actual_raise).ok_or_else(|| {
+                    ServerError::InvalidBet("Pot size exceeds maximum allowed".to_string())
+                })?;
+
+                let player = self
+                    .players
+                    .get_mut(player_id)
+                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+                player.chips = player.chips.saturating_sub(actual_raise);
+                player.current_bet = player.current_bet.saturating_add(actual_raise);
+                player.has_acted = true;
+
+                if player.chips == 0 {
+                    player.is_all_in = true;
+                }
+                self.pot = new_pot;
+                self.record_bet(current_bet, total_bet);
+            }
+            PlayerAction::AllIn => {
+                let player = self
+                    .players
+                    .get(player_id)
+                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+
+                if player.chips == 0 {
+                    return Err(ServerError::NoChips);
+                }
+
+                let all_in_amount = player.chips;
+                let new_bet = player.current_bet.saturating_add(all_in_amount);
+
+                if new_bet > current_bet {
+                    let limits = self.betting_limits_for(player, current_bet, pot);
+                    if !limits.raise_allowed || new_bet > limits.max_raise_to {
+                        return Err(ServerError::InvalidBet(format!(
+                            "All-in of {} exceeds the {} maximum of {}",
+                            new_bet, self.betting_structure, limits.max_raise_to
+                        )));
+                    }
+                }
 
-            let has_wheel = ranks.contains(&2)
-                && ranks.contains(&3)
-                && ranks.contains(&4)
-                && ranks.contains(&5)
-                && ranks.contains(&14);
+                let new_pot = self.calculate_new_pot(all_in_amount).ok_or_else(|| {
+                    ServerError::InvalidBet("Pot size exceeds maximum allowed".to_string())
+                })?;
+
+                let player = self
+                    .players
+                    .get_mut(player_id)
+                    .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+                player.chips = 0;
+                player.current_bet = new_bet;
+                self.pot = new_pot;
+                player.is_all_in = true;
+                player.has_acted = true;
 
-            if has_wheel {
-                return Some(HandEvaluation::straight_flush(5));
+                self.record_bet(current_bet, new_bet);
             }
+        }
+
+        self.hand_history.record(HandEvent::Action {
+            player_id: player_id.to_string(),
+            action: recorded_action,
+        });
+        self.broadcast_game_state();
+        self.advance_action();
+
+        Ok(())
+    }
+
+    fn all_players_acted(&self) -> bool {
+        self.players
+            .values()
+            .filter(|p| !p.is_folded && !p.is_all_in)
+            .all(|p| p.has_acted)
+    }
 
-            return self
-                .check_straight_from_cards(&flush_cards)
-                .map(|eval| HandEvaluation::straight_flush(eval.primary_rank as u8));
+    fn bets_equalized(&self) -> bool {
+        let active_players: Vec<_> = self.players.values().filter(|p| !p.is_folded).collect();
+        if active_players.is_empty() {
+            return true;
         }
-        None
+        let target_bet = active_players
+            .iter()
+            .map(|p| p.current_bet)
+            .max()
+            .unwrap_or(0);
+        active_players
+            .iter()
+            .all(|p| p.current_bet == target_bet || p.is_all_in)
     }
 
-    fn check_four_of_a_kind(&self, cards: &[Card]) -> Option<HandEvaluation> {
-        let mut rank_counts = HashMap::<u8, usize>::new();
-        for card in cards {
-            *rank_counts.entry(card.rank as u8).or_insert(0) += 1;
+    fn should_advance_street(&self) -> bool {
+        self.all_players_acted() && self.bets_equalized()
+    }
+
+    fn advance_action(&mut self) {
+        let active_player_ids = self.get_active_player_ids();
+        let contested = self.dealt_in_player_ids().len() >= 2;
+        if active_player_ids.is_empty() {
+            if contested {
+                self.begin_run_out();
+            } else {
+                self.award_uncontested();
+            }
+            return;
         }
 
-        for (rank, &count) in &rank_counts {
-            if count == 4 {
-                return Some(HandEvaluation::four_of_a_kind(cards, *rank));
+        if let Some(ref current_id) = self.current_player_id {
+            if let Some(current_idx) = active_player_ids.iter().position(|id| id == current_id) {
+                let next_idx = (current_idx + 1) % active_player_ids.len();
+                self.current_player_id = Some(active_player_ids[next_idx].clone());
+            } else {
+                self.current_player_id = active_player_ids.first().cloned();
             }
+        } else {
+            self.current_player_id = active_player_ids.first().cloned();
         }
-        None
+
+        if self.current_street != Street::Showdown && self.should_advance_street() {
+            if contested && active_player_ids.len() == 1 {
+                // Everyone else still in is all-in, so there is no one left to bet against.
+                self.begin_run_out();
+                return;
+            }
+
+            let next_street = self.variant.next_street(self.current_street);
+            if next_street == Street::Showdown {
+                self.current_street = Street::Showdown;
+                self.hand_history
+                    .record(HandEvent::Street(Street::Showdown));
+                self.showdown();
+                return;
+            }
+
+            self.start_betting_round();
+            self.current_street = next_street;
+            self.hand_history.record(HandEvent::Street(next_street));
+            // After the first street the first player left of the button acts
+            // first; in stud the best hand showing does.
+            self.current_player_id = self.get_active_player_ids().first().cloned();
+            self.deal_street(next_street);
+            if self.variant.is_stud() {
+                self.current_player_id = self.best_visible_hand_player_id();
+            }
+
+            self.broadcast_game_state();
+            self.request_action();
+        } else {
+            self.request_action();
+        }
+    }
+
+    /// Pays the pots to the last player left in the hand, who is all-in and
+    /// whose cards stay face down now everyone else has folded.
+    fn award_uncontested(&mut self) {
+        self.start_betting_round();
+        for (amount, eligible) in self.take_rake() {
+            for (winner_id, winnings) in split_pot(amount, &eligible) {
+                if let Some(player) = self.players.get_mut(&winner_id) {
+                    player.chips += winnings;
+                }
+            }
+        }
+        self.end_hand();
+    }
+
+    /// Moves this street's bets into each player's hand total and reopens the
+    /// betting for the next street.
+    fn start_betting_round(&mut self) {
+        for player in self.players.values_mut() {
+            player.total_bet = player.total_bet.saturating_add(player.current_bet);
+            player.current_bet = 0;
+            player.has_acted = false;
+        }
+        self.min_raise = self.big_blind;
+        self.raises_this_street = 0;
+    }
+
+    /// Closes the action once no more than one player still in the hand can
+    /// bet. The live hands are turned face up and the remaining streets are
+    /// left for `run_out_next_street` to deal.
+    fn begin_run_out(&mut self) {
+        self.start_betting_round();
+        let runs = self.agreed_runs();
+        self.run_out = Some(RunOut {
+            runs,
+            closed_on: self.current_street,
+            shared_board: self.community_cards.clone(),
+            boards: Vec::new(),
+        });
+        self.current_player_id = None;
+
+        let hands = self
+            .dealt_in_player_ids()
+            .iter()
+            .filter_map(|id| self.players.get(id))
+            .map(|p| {
+                let cards = p.hole_cards.iter().chain(p.up_cards.iter());
+                (p.id.clone(), cards.map(|c| c.to_string()).collect())
+            })
+            .collect();
+        self.broadcast_message(ServerMessage::HandsRevealed(HandsRevealedUpdate {
+            hands,
+            runs,
+        }));
+        self.broadcast_game_state();
+    }
+
+    /// Returns how many times the board is run out: the fewest runs any
+    /// player still in the hand agreed to, limited by the table and by the
+    /// cards left in the deck. Stud and draw hands are always run once.
+    fn agreed_runs(&self) -> u32 {
+        let cards_to_come = BOARD_SIZE.saturating_sub(self.community_cards.len());
+        if self.variant.is_stud() || self.variant.draw_count() > 0 || cards_to_come == 0 {
+            return 1;
+        }
+        let agreed = self
+            .dealt_in_player_ids()
+            .iter()
+            .map(|id| self.run_preferences.get(id).copied().unwrap_or(1))
+            .min()
+            .unwrap_or(1);
+        let deck_allows = u32::try_from(self.deck.len() / cards_to_come).unwrap_or(u32::MAX);
+        agreed.min(self.max_runs).min(deck_allows).max(1)
+    }
+
+    /// Returns true while a closed hand has streets left to run out and no
+    /// player is still to draw.
+    pub fn is_running_out(&self) -> bool {
+        self.run_out.is_some() && self.pending_draws.is_empty()
+    }
+
+    /// Claims the job of dealing out closed hands if one is waiting for its
+    /// streets and no task is already dealing them. However a hand starts,
+    /// forced bets alone can close it, so every caller that may have started
+    /// one checks this.
+    ///
+    /// # Returns
+    /// `true` if the caller should run `run_out_next_street` until it returns `false`
+    pub fn claim_run_out(&mut self) -> bool {
+        if self.run_out_task_running || !self.is_running_out() {
+            return false;
+        }
+        self.run_out_task_running = true;
+        true
+    }
+
+    /// Deals the next street of a run-out. When a board is complete the next
+    /// run starts from the cards dealt before the action closed, and after
+    /// the last run the hand goes to showdown.
+    ///
+    /// # Returns
+    /// `true` if there is more to deal, including a following hand already
+    /// closed by its forced bets, or `false` once the table is waiting on
+    /// players to act or draw
+    pub fn run_out_next_street(&mut self) -> bool {
+        let more = self.deal_run_out_street() || self.is_running_out();
+        if !more {
+            self.run_out_task_running = false;
+        }
+        more
+    }
+
+    /// Deals one street of a run-out, going to showdown after the last run.
+    ///
+    /// # Returns
+    /// `true` if the hand has more streets to deal
+    fn deal_run_out_street(&mut self) -> bool {
+        if !self.is_running_out() {
+            return false;
+        }
+
+        let mut next_street = self.variant.next_street(self.current_street);
+        if next_street == Street::Showdown {
+            let Some(run_out) = self.run_out.as_mut() else {
+                return false;
+            };
+            run_out.boards.push(self.community_cards.clone());
+            if run_out.boards.len() >= run_out.runs as usize {
+                self.current_street = Street::Showdown;
+                self.hand_history
+                    .record(HandEvent::Street(Street::Showdown));
+                self.showdown();
+                return false;
+            }
+            self.community_cards = run_out.shared_board.clone();
+            next_street = self.variant.next_street(run_out.closed_on);
+        }
+
+        self.current_street = next_street;
+        self.hand_history.record(HandEvent::Street(next_street));
+        self.deal_street(next_street);
+        self.broadcast_game_state();
+        if !self.pending_draws.is_empty() {
+            self.request_action();
+            return false;
+        }
+        true
+    }
+
+    fn deal_community_cards(&mut self, count: usize) {
+        if self.current_street == Street::Showdown {
+            error!("Cannot deal community cards during showdown");
+            return;
+        }
+
+        if count == 0 || count > 5 {
+            error!("Invalid community card count: {}", count);
+            return;
+        }
+
+        let max_cards = match self.current_street {
+            Street::Preflop => 5,
+            Street::Flop => 4,
+            Street::Turn => 5,
+            Street::River => 5,
+            // Stud only uses a community card when the deck runs out on seventh street.
+            Street::SeventhStreet => 1,
+            Street::ThirdStreet
+            | Street::FourthStreet
+            | Street::FifthStreet
+            | Street::SixthStreet
+            | Street::PreDraw
+            | Street::FirstDraw
+            | Street::SecondDraw
+            | Street::ThirdDraw
+            | Street::Showdown => 0,
+        };
+
+        if self.community_cards.len() + count > max_cards {
+            error!(
+                "Cannot deal {} cards, would exceed maximum of {}",
+                count, max_cards
+            );
+            return;
+        }
+
+        for _ in 0..count {
+            if let Some(card) = self.deal_card() {
+                self.community_cards.push(card);
+            }
+        }
+    }
+
+    /// Splits the chips committed this hand into a main pot and side pots.
+    ///
+    /// Each pot is paired with the players still eligible to win it. Chips put
+    /// in by players who have since folded stay in the pots as dead money.
+    fn calculate_side_pots(&self) -> Vec<(i32, Vec<String>)> {
+        let mut pots = Vec::new();
+        let mut players: Vec<_> = self.players.values().filter(|p| !p.is_folded).collect();
+
+        if players.is_empty() {
+            return pots;
+        }
+
+        players.sort_by_key(|p| p.hand_contribution());
+
+        let mut levels: Vec<i32> = players.iter().map(|p| p.hand_contribution()).collect();
+        levels.dedup();
+
+        let mut previous_level = 0;
+        for (i, &level) in levels.iter().enumerate() {
+            let is_top_level = i + 1 == levels.len();
+            let pot_amount: i32 = self
+                .players
+                .values()
+                .map(|p| {
+                    let contribution = p.hand_contribution();
+                    // Folded chips above the highest live bet go into the last pot.
+                    let capped = if is_top_level {
+                        contribution
+                    } else {
+                        contribution.min(level)
+                    };
+                    capped.saturating_sub(previous_level).max(0)
+                })
+                .sum();
+
+            let pot_amount = if pots.is_empty() {
+                pot_amount.saturating_add(self.antes_collected)
+            } else {
+                pot_amount
+            };
+
+            if pot_amount > 0 {
+                let eligible_players: Vec<String> = players
+                    .iter()
+                    .filter(|p| p.hand_contribution() >= level)
+                    .map(|p| p.id.clone())
+                    .collect();
+                pots.push((pot_amount, eligible_players));
+            }
+            previous_level = level;
+        }
+
+        pots
+    }
+
+    /// Takes the table's rake from the hand's pots and records it in the
+    /// rake ledger. A pot only one player can win holds their uncalled chips
+    /// and is never raked.
+    ///
+    /// # Returns
+    /// The main pot and side pots less the rake, each with its eligible players
+    fn take_rake(&mut self) -> Vec<(i32, Vec<String>)> {
+        let mut pots = self.calculate_side_pots();
+        let contested: Vec<i32> = pots
+            .iter()
+            .map(|(amount, eligible)| if eligible.len() > 1 { *amount } else { 0 })
+            .collect();
+        let players = self
+            .players
+            .values()
+            .filter(|p| !p.hole_cards.is_empty())
+            .count();
+        let rake = self.rake.rake_pots(&contested, players, self.flop_seen);
+
+        for ((amount, _), taken) in pots.iter_mut().zip(&rake) {
+            *amount -= taken;
+        }
+        let total: i32 = rake.iter().sum();
+        if total > 0 {
+            info!("Raked {} from hand #{}", total, self.hand_number);
+            self.pot = self.pot.saturating_sub(total);
+            self.rake_ledger.record(self.hand_number, rake);
+        }
+        pots
+    }
+
+    /// Settles up the session from every buy-in, rebuy, top-up and cash-out
+    /// at the table.
+    pub fn settlement(&self) -> SettlementReport {
+        self.ledger.settle(&self.game_id)
+    }
+
+    /// Returns the rake taken from every hand at the table.
+    pub fn rake_ledger(&self) -> &RakeLedger {
+        &self.rake_ledger
+    }
+
+    /// Shows down every live hand and pays out the pots after taking the
+    /// rake. A hand run out more than once awards each board an equal share
+    /// of every pot.
+    fn showdown(&mut self) {
+        let boards = match self.run_out.take() {
+            Some(run_out) if !run_out.boards.is_empty() => run_out.boards,
+            _ => vec![self.community_cards.clone()],
+        };
+
+        let pots = self.take_rake();
+        let mut showdown_update: Option<ShowdownUpdate> = None;
+        let mut board_results = Vec::new();
+        let mut payouts = Vec::new();
+        for (run, board) in boards.iter().enumerate() {
+            self.community_cards = board.clone();
+            let Some((run_update, run_payouts)) = self.resolve_showdown(&pots, run, boards.len())
+            else {
+                break;
+            };
+            payouts.extend(run_payouts);
+            board_results.push(BoardResult {
+                community_cards: run_update.community_cards.clone(),
+                winners: run_update.winners.clone(),
+                low_winners: run_update.low_winners.clone(),
+            });
+            match showdown_update.as_mut() {
+                None => showdown_update = Some(run_update),
+                Some(update) => {
+                    for id in run_update.winners {
+                        if !update.winners.contains(&id) {
+                            update.winners.push(id);
+                        }
+                    }
+                    for id in run_update.low_winners {
+                        if !update.low_winners.contains(&id) {
+                            update.low_winners.push(id);
+                        }
+                    }
+                }
+            }
+        }
+        self.community_cards = boards[0].clone();
+
+        let Some(mut showdown_update) = showdown_update else {
+            self.end_hand();
+            return;
+        };
+        showdown_update.boards = board_results;
+
+        for (winner_id, winnings) in payouts {
+            if let Some(player) = self.players.get_mut(&winner_id) {
+                player.chips += winnings;
+            }
+        }
+
+        self.broadcast_message(ServerMessage::Showdown(showdown_update));
+
+        self.end_hand();
+    }
+
+    /// Orders tied winners clockwise from the first seat after the button, so
+    /// the odd chip of a split pot always lands on the same player.
+    ///
+    /// # Arguments
+    /// * `player_ids` - The players sharing a pot
+    ///
+    /// # Returns
+    /// The same players in the order odd chips are handed out
+    fn clockwise_from_button(&self, mut player_ids: Vec<String>) -> Vec<String> {
+        let order = self.seats.clockwise_from(self.dealer_position);
+        player_ids.sort_by_key(|id| {
+            order
+                .iter()
+                .position(|(_, seated)| *seated == id.as_str())
+                .unwrap_or(usize::MAX)
+        });
+        player_ids
+    }
+
+    /// Evaluates every live hand and awards each pot to the best hands among
+    /// the players eligible for it. In split-pot games each pot is halved
+    /// between the best high and the best qualifying low.
+    ///
+    /// # Arguments
+    /// * `pots` - The pots to award, less any rake, each with its eligible players
+    /// * `run` - Index of the board being shown down
+    /// * `runs` - Number of boards the hand was run out on; each awards its share of the pots
+    ///
+    /// # Returns
+    /// The showdown summary and the chips won by each player, or `None` if
+    /// no players remain in the hand
+    fn resolve_showdown(
+        &self,
+        pots: &[(i32, Vec<String>)],
+        run: usize,
+        runs: usize,
+    ) -> Option<(ShowdownUpdate, Vec<(String, i32)>)> {
+        let active_players: Vec<&PlayerState> =
+            self.players.values().filter(|p| !p.is_folded).collect();
+
+        if active_players.is_empty() {
+            return None;
+        }
+
+        let mut hand_evals: Vec<(&PlayerState, RankedHand)> = active_players
+            .iter()
+            .map(|p| (*p, self.rank_hand(p)))
+            .collect();
+        hand_evals.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let low_hands: Vec<(&PlayerState, LowHand)> = if self.variant.is_split_pot() {
+            active_players
+                .iter()
+                .filter_map(|p| self.evaluate_low_hand(p).map(|low| (*p, low)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut winner_ids: Vec<String> = Vec::new();
+        let mut low_winner_ids: Vec<String> = Vec::new();
+        let mut payouts: Vec<(String, i32)> = Vec::new();
+
+        for (pot_amount, eligible_players) in pots {
+            let pot_amount = run_share(*pot_amount, run, runs);
+            let pot_high_winners = self.clockwise_from_button(best_hands_among(
+                &hand_evals,
+                eligible_players,
+                |a, b| a > b,
+            ));
+            if pot_high_winners.is_empty() {
+                continue;
+            }
+            let pot_low_winners = self.clockwise_from_button(best_hands_among(
+                &low_hands,
+                eligible_players,
+                |a, b| a < b,
+            ));
+
+            // The high hand takes the odd chip when the pot is split.
+            let low_share = if pot_low_winners.is_empty() {
+                0
+            } else {
+                pot_amount / 2
+            };
+            let high_share = pot_amount - low_share;
+
+            payouts.extend(split_pot(high_share, &pot_high_winners));
+            payouts.extend(split_pot(low_share, &pot_low_winners));
+
+            for id in pot_high_winners {
+                if !winner_ids.contains(&id) {
+                    winner_ids.push(id);
+                }
+            }
+            for id in pot_low_winners {
+                if !low_winner_ids.contains(&id) {
+                    low_winner_ids.push(id);
+                }
+            }
+        }
+
+        let showdown_update = ShowdownUpdate {
+            community_cards: self.community_cards.iter().map(|c| c.to_string()).collect(),
+            hands: hand_evals
+                .iter()
+                .map(|(p, eval)| {
+                    (
+                        p.id.clone(),
+                        p.hole_cards
+                            .iter()
+                            .chain(p.up_cards.iter())
+                            .map(|c| c.to_string())
+                            .collect(),
+                        eval.category().to_string(),
+                        eval.description().to_string(),
+                    )
+                })
+                .collect(),
+            winners: winner_ids,
+            low_winners: low_winner_ids,
+            low_hands: low_hands
+                .iter()
+                .map(|(p, low)| (p.id.clone(), low.description()))
+                .collect(),
+            boards: Vec::new(),
+        };
+
+        Some((showdown_update, payouts))
+    }
+
+    /// Evaluates a player's eight-or-better low for split-pot games. Omaha
+    /// lows use exactly two hole cards and three community cards; stud lows
+    /// may use any five of the player's cards.
+    ///
+    /// # Returns
+    /// The best qualifying low, or `None` if the player has no low
+    fn evaluate_low_hand(&self, player: &PlayerState) -> Option<LowHand> {
+        if !matches!(self.variant, GameVariant::Omaha | GameVariant::OmahaHiLo) {
+            return best_qualifying_low(&self.player_cards(player), EIGHT_OR_BETTER);
+        }
+
+        let mut best: Option<LowHand> = None;
+        for hole in card_combinations(&player.hole_cards, 2) {
+            for board in card_combinations(&self.community_cards, 3) {
+                let cards: Vec<Card> = hole.iter().chain(board.iter()).cloned().collect();
+                if let Some(low) = best_qualifying_low(&cards, EIGHT_OR_BETTER) {
+                    if best.as_ref().is_none_or(|b| low < *b) {
+                        best = Some(low);
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Returns every card a player can use: down cards, up cards and the board.
+    fn player_cards(&self, player: &PlayerState) -> Vec<Card> {
+        player
+            .hole_cards
+            .iter()
+            .chain(player.up_cards.iter())
+            .chain(self.community_cards.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Ranks a player's hand for showdown under the table's hand ranking.
+    fn rank_hand(&self, player: &PlayerState) -> RankedHand {
+        match HandRanking::for_variant(self.variant) {
+            HandRanking::High => {
+                let eval = self.evaluate_hand(player);
+                RankedHand::high(&eval, self.hand_rank_strength(eval.rank))
+            }
+            HandRanking::AceToFiveLow => ace_to_five_low(&self.player_cards(player)),
+            HandRanking::DeuceToSevenLow => deuce_to_seven_low(&self.player_cards(player)),
+        }
+    }
+
+    fn evaluate_hand(&self, player: &PlayerState) -> HandEvaluation {
+        match self.variant {
+            GameVariant::Omaha | GameVariant::OmahaHiLo => self.evaluate_omaha_hand(player),
+            _ => self.evaluate_cards(&self.player_cards(player)),
+        }
+    }
+
+    /// Returns the strength of a hand category for this table's variant.
+    fn hand_rank_strength(&self, rank: HandRank) -> u8 {
+        match (self.variant, rank) {
+            (GameVariant::ShortDeck, HandRank::Flush) => HandRank::FullHouse as u8,
+            (GameVariant::ShortDeck, HandRank::FullHouse) => HandRank::Flush as u8,
+            (_, rank) => rank as u8,
+        }
+    }
+
+    /// Compares two high hands using this table's hand ranking order.
+    fn compare_hands(&self, a: &HandEvaluation, b: &HandEvaluation) -> Ordering {
+        self.hand_rank_strength(a.rank)
+            .cmp(&self.hand_rank_strength(b.rank))
+            .then_with(|| a.primary_rank.cmp(&b.primary_rank))
+            .then_with(|| a.tiebreakers.cmp(&b.tiebreakers))
+    }
+
+    /// Returns the ranks that make the lowest straight when combined with an
+    /// ace, and the straight's high card: A-2-3-4-5 normally, or A-6-7-8-9 in
+    /// short deck.
+    fn low_straight(&self) -> ([u8; 4], u8) {
+        let lowest = self.variant.lowest_rank() as u8;
+        ([lowest, lowest + 1, lowest + 2, lowest + 3], lowest + 3)
+    }
+
+    /// Returns true if `ranks` contain an ace-low straight for this variant.
+    fn has_low_straight(&self, ranks: &[u8]) -> bool {
+        let (low_ranks, _) = self.low_straight();
+        ranks.contains(&(Rank::Ace as u8)) && low_ranks.iter().all(|r| ranks.contains(r))
+    }
+
+    /// Evaluates an Omaha hand, which must use exactly two hole cards and
+    /// three community cards.
+    fn evaluate_omaha_hand(&self, player: &PlayerState) -> HandEvaluation {
+        let hole_count = player.hole_cards.len().min(2);
+        let board_count = self.community_cards.len().min(3);
+
+        let mut best: Option<HandEvaluation> = None;
+        for hole in card_combinations(&player.hole_cards, hole_count) {
+            for board in card_combinations(&self.community_cards, board_count) {
+                let cards: Vec<Card> = hole.iter().chain(board.iter()).cloned().collect();
+                let eval = self.evaluate_cards(&cards);
+                if best
+                    .as_ref()
+                    .is_none_or(|b| self.compare_hands(&eval, b) == Ordering::Greater)
+                {
+                    best = Some(eval);
+                }
+            }
+        }
+
+        best.unwrap_or_else(|| self.evaluate_cards(&[]))
+    }
+
+    /// Evaluates the best five-card high hand that can be made from `all_cards`.
+    fn evaluate_cards(&self, all_cards: &[Card]) -> HandEvaluation {
+        // Handle edge cases: empty hand or less than 5 cards
+        match all_cards.len() {
+            0 => {
+                return HandEvaluation {
+                    rank: HandRank::HighCard,
+                    primary_rank: 0,
+                    tiebreakers: vec![],
+                    description: "No cards".to_string(),
+                };
+            }
+            1..=4 => {
+                let top_rank = all_cards.iter().map(|c| c.rank as i32).max().unwrap_or(0);
+                return HandEvaluation {
+                    rank: HandRank::HighCard,
+                    primary_rank: top_rank,
+                    tiebreakers: all_cards.iter().map(|c| c.rank as i32).collect(),
+                    description: format!("High Card ({} cards)", all_cards.len()),
+                };
+            }
+            _ => {}
+        }
+
+        // Check hands in descending order of rank; short deck ranks a flush
+        // above a full house because flushes are harder to make.
+        let made_hand = self
+            .check_straight_flush(all_cards)
+            .or_else(|| self.check_four_of_a_kind(all_cards));
+        let made_hand = if self.variant == GameVariant::ShortDeck {
+            made_hand
+                .or_else(|| self.check_flush(all_cards))
+                .or_else(|| self.check_full_house(all_cards))
+        } else {
+            made_hand
+                .or_else(|| self.check_full_house(all_cards))
+                .or_else(|| self.check_flush(all_cards))
+        };
+
+        made_hand
+            .or_else(|| self.check_straight(all_cards))
+            .or_else(|| self.check_three_of_a_kind(all_cards))
+            .or_else(|| self.check_two_pair(all_cards))
+            .or_else(|| self.check_pair(all_cards))
+            .unwrap_or_else(|| HandEvaluation::high_card(all_cards))
+    }
+
+    fn check_straight_flush(&self, cards: &[Card]) -> Option<HandEvaluation> {
+        for suit in [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades] {
+            let suited_cards: Vec<Card> =
+                cards.iter().filter(|c| c.suit == suit).cloned().collect();
+            if suited_cards.len() >= 5 {
+                return self
+                    .check_straight_from_cards(&suited_cards)
+                    .map(|eval| HandEvaluation::straight_flush(eval.primary_rank as u8));
+            }
+        }
+        None
+    }
+
+    fn check_four_of_a_kind(&self, cards: &[Card]) -> Option<HandEvaluation> {
+        let mut rank_counts = HashMap::<u8, usize>::new();
+        for card in cards {
+            *rank_counts.entry(card.rank as u8).or_insert(0) += 1;
+        }
+
+        for (rank, &count) in &rank_counts {
+            if count == 4 {
+                return Some(HandEvaluation::four_of_a_kind(cards, *rank));
+            }
+        }
+        None
+    }
+
+    fn check_full_house(&self, cards: &[Card]) -> Option<HandEvaluation> {
+        let mut rank_counts = HashMap::<u8, usize>::new();
+        for card in cards {
+            *rank_counts.entry(card.rank as u8).or_insert(0) += 1;
+        }
+
+        let mut three_of_kind: Option<u8> = None;
+        let mut pairs: Vec<u8> = Vec::new();
+
+        for (&rank, &count) in &rank_counts {
+            if count >= 3 {
+                if three_of_kind.is_none() {
+                    three_of_kind = Some(rank);
+                } else {
+                    pairs.push(rank);
+                }
+            } else if count >= 2 {
+                pairs.push(rank);
+            }
+        }
+
+        if let Some(three_rank) = three_of_kind {
+            if let Some(pair_rank) = pairs.into_iter().find(|&r| r != three_rank) {
+                return Some(HandEvaluation::full_house(three_rank, pair_rank));
+            }
+        }
+
+        None
+    }
+
+    fn check_flush(&self, cards: &[Card]) -> Option<HandEvaluation> {
+        if let Some(flush_cards) = self.get_flush_cards(cards) {
+            return Some(HandEvaluation::flush(&flush_cards));
+        }
+        None
+    }
+
+    fn get_flush_cards(&self, cards: &[Card]) -> Option<Vec<Card>> {
+        for suit in [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades] {
+            let mut flush_cards: Vec<Card> =
+                cards.iter().filter(|c| c.suit == suit).cloned().collect();
+            if flush_cards.len() >= 5 {
+                flush_cards.sort_by_key(|c| c.rank as u8);
+                flush_cards.reverse();
+                flush_cards.truncate(5);
+                return Some(flush_cards);
+            }
+        }
+        None
+    }
+
+    fn check_straight(&self, cards: &[Card]) -> Option<HandEvaluation> {
+        self.check_straight_from_cards(cards)
+    }
+
+    fn check_straight_from_cards(&self, cards: &[Card]) -> Option<HandEvaluation> {
+        let mut ranks: Vec<_> = cards.iter().map(|c| c.rank as u8).collect();
+        ranks.sort();
+        ranks.dedup();
+
+        if ranks.is_empty() {
+            return None;
+        }
+
+        let mut straight_high = 0;
+        let mut consecutive = 1;
+
+        for i in 1..ranks.len() {
+            if ranks[i] == ranks[i - 1] + 1 {
+                consecutive += 1;
+            } else if ranks[i] != ranks[i - 1] {
+                if consecutive >= 5 && ranks[i - 1] > straight_high {
+                    straight_high = ranks[i - 1];
+                }
+                consecutive = 1;
+            }
+        }
+
+        if consecutive >= 5 && ranks[ranks.len() - 1] > straight_high {
+            straight_high = ranks[ranks.len() - 1];
+        }
+
+        if straight_high > 0 {
+            return Some(HandEvaluation::straight(straight_high));
+        }
+
+        if self.has_low_straight(&ranks) {
+            let (_, low_straight_high) = self.low_straight();
+            if low_straight_high == 5 {
+                return Some(HandEvaluation::straight_with_wheel());
+            }
+            return Some(HandEvaluation::straight(low_straight_high));
+        }
+
+        None
+    }
+
+    fn check_three_of_a_kind(&self, cards: &[Card]) -> Option<HandEvaluation> {
+        let mut rank_counts = HashMap::<u8, usize>::new();
+        for card in cards {
+            *rank_counts.entry(card.rank as u8).or_insert(0) += 1;
+        }
+
+        for (rank, &count) in &rank_counts {
+            if count >= 3 {
+                return Some(HandEvaluation::three_of_a_kind(cards, *rank));
+            }
+        }
+        None
+    }
+
+    fn check_two_pair(&self, cards: &[Card]) -> Option<HandEvaluation> {
+        let mut rank_counts = HashMap::<u8, usize>::new();
+        for card in cards {
+            *rank_counts.entry(card.rank as u8).or_insert(0) += 1;
+        }
+
+        let mut pairs: Vec<u8> = rank_counts
+            .iter()
+            .filter(|(_, &count)| count >= 2)
+            .map(|(&rank, _)| rank)
+            .collect();
+
+        if pairs.len() >= 2 {
+            pairs.sort_by(|a, b| b.cmp(a));
+            return Some(HandEvaluation::two_pair(cards, pairs[0], pairs[1]));
+        }
+        None
+    }
+
+    fn check_pair(&self, cards: &[Card]) -> Option<HandEvaluation> {
+        let mut rank_counts = HashMap::<u8, usize>::new();
+        for card in cards {
+            *rank_counts.entry(card.rank as u8).or_insert(0) += 1;
+        }
+
+        for (rank, &count) in &rank_counts {
+            if count >= 2 {
+                return Some(HandEvaluation::pair(cards, *rank));
+            }
+        }
+        None
+    }
+
+    fn end_hand(&mut self) {
+        self.game_stage = GameStage::HandComplete;
+        self.broadcast_game_state();
+
+        // Rebuys and top-ups made during the hand go in before the next one.
+        if !self.pending_chips.is_empty() {
+            self.apply_pending_chips();
+            self.broadcast_game_state();
+        }
+
+        for player_id in std::mem::take(&mut self.leaving) {
+            if let Err(e) = self.cash_out(&player_id) {
+                warn!("Could not cash out departing player {}: {}", player_id, e);
+            }
+        }
+
+        // The button and blinds move when the next hand is seated.
+        if self.ready_player_count() >= 2 {
+            self.start_hand();
+        } else if self.paused {
+            self.game_stage = GameStage::Paused;
+        } else {
+            self.game_stage = GameStage::WaitingForPlayers;
+        }
+    }
+}
+
+/// Returns the number of seats at a table, limited by any stud game it deals.
+fn seat_count(variant: GameVariant, rotation: Option<&GameRotation>) -> usize {
+    let plays_stud = match rotation {
+        Some(rotation) => rotation.games().iter().any(|g| g.variant.is_stud()),
+        None => variant.is_stud(),
+    };
+    if plays_stud {
+        MAX_STUD_PLAYERS
+    } else {
+        MAX_PLAYERS
+    }
+}
+
+/// Returns a suit's rank for breaking stud bring-in ties, clubs lowest.
+fn suit_order(suit: Suit) -> u8 {
+    match suit {
+        Suit::Clubs => 0,
+        Suit::Diamonds => 1,
+        Suit::Hearts => 2,
+        Suit::Spades => 3,
+    }
+}
+
+/// Returns the ids of the eligible players holding the best hand, including
+/// everyone who ties for it.
+///
+/// # Arguments
+/// * `hands` - Hands shown down, keyed by player
+/// * `eligible` - Players eligible for the pot being awarded
+/// * `beats` - Returns true if the first hand wins against the second
+fn best_hands_among<H>(
+    hands: &[(&PlayerState, H)],
+    eligible: &[String],
+    beats: impl Fn(&H, &H) -> bool,
+) -> Vec<String> {
+    let contenders: Vec<&(&PlayerState, H)> = hands
+        .iter()
+        .filter(|(p, _)| eligible.contains(&p.id))
+        .collect();
+    let best = contenders
+        .iter()
+        .map(|(_, hand)| hand)
+        .reduce(|best, hand| if beats(hand, best) { hand } else { best });
+
+    match best {
+        Some(best) => contenders
+            .iter()
+            .filter(|(_, hand)| !beats(hand, best) && !beats(best, hand))
+            .map(|(p, _)| p.id.clone())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Returns one board's share of a pot run out `runs` times, with odd chips
+/// going to the earliest boards.
+fn run_share(amount: i32, run: usize, runs: usize) -> i32 {
+    let runs = i32::try_from(runs.max(1)).unwrap_or(i32::MAX);
+    let run = i32::try_from(run).unwrap_or(i32::MAX);
+    amount / runs + i32::from(run < amount % runs)
+}
+
+/// Splits `amount` evenly between `winners`, giving odd chips to the first winners.
+fn split_pot(amount: i32, winners: &[String]) -> Vec<(String, i32)> {
+    if amount <= 0 || winners.is_empty() {
+        return Vec::new();
+    }
+    let count: i32 = winners.len().try_into().unwrap_or(i32::MAX);
+    let share = amount / count;
+    let remainder = amount % count;
+    winners
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            let bonus = if (i as i32) < remainder { 1 } else { 0 };
+            (id.clone(), share + bonus)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rotation::{RotationGame, RotationSchedule};
+    use poker_protocol::{Card, HandEvaluation, HandRank, PlayerState, Rank, Suit};
+
+    pub fn card(rank: Rank, suit: Suit) -> Card {
+        Card::new(suit, rank)
+    }
+
+    pub fn create_player_with_cards(cards: Vec<Card>) -> PlayerState {
+        let mut player = PlayerState::new("test".to_string(), "Test".to_string(), 1000);
+        player.hole_cards = cards;
+        player
+    }
+
+    #[test]
+    fn test_royal_flush() {
+        let cards = vec![
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::King, Suit::Hearts),
+            card(Rank::Queen, Suit::Hearts),
+            card(Rank::Jack, Suit::Hearts),
+            card(Rank::Ten, Suit::Hearts),
+        ];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let player = create_player_with_cards(cards);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::StraightFlush);
+    }
+
+    #[test]
+    fn test_straight_flush() {
+        let cards = vec![
+            card(Rank::Nine, Suit::Spades),
+            card(Rank::Ten, Suit::Spades),
+            card(Rank::Jack, Suit::Spades),
+            card(Rank::Queen, Suit::Spades),
+            card(Rank::King, Suit::Spades),
+        ];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let player = create_player_with_cards(cards);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::StraightFlush);
+    }
+
+    #[test]
+    fn test_four_of_a_kind() {
+        let cards = vec![
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::Ace, Suit::Diamonds),
+            card(Rank::Ace, Suit::Clubs),
+            card(Rank::Ace, Suit::Spades),
+            card(Rank::King, Suit::Hearts),
+        ];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let player = create_player_with_cards(cards);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::FourOfAKind);
+        assert_eq!(eval.primary_rank, 14); // Ace
+    }
+
+    #[test]
+    fn test_full_house() {
+        let cards = vec![
+            card(Rank::King, Suit::Hearts),
+            card(Rank::King, Suit::Diamonds),
+            card(Rank::King, Suit::Clubs),
+            card(Rank::Queen, Suit::Hearts),
+            card(Rank::Queen, Suit::Diamonds),
+        ];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let player = create_player_with_cards(cards);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::FullHouse);
+        assert_eq!(eval.primary_rank, 13); // Kings
+    }
+
+    #[test]
+    fn test_flush() {
+        let cards = vec![
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::King, Suit::Hearts),
+            card(Rank::Queen, Suit::Hearts),
+            card(Rank::Jack, Suit::Hearts),
+            card(Rank::Nine, Suit::Hearts),
+        ];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let player = create_player_with_cards(cards);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::Flush);
+    }
+
+    #[test]
+    fn test_straight() {
+        let cards = vec![
+            card(Rank::Ten, Suit::Hearts),
+            card(Rank::Jack, Suit::Diamonds),
+            card(Rank::Queen, Suit::Clubs),
+            card(Rank::King, Suit::Spades),
+            card(Rank::Ace, Suit::Hearts),
+        ];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let player = create_player_with_cards(cards);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::Straight);
+    }
+
+    #[test]
+    fn test_three_of_a_kind() {
+        let cards = vec![
+            card(Rank::Seven, Suit::Hearts),
+            card(Rank::Seven, Suit::Diamonds),
+            card(Rank::Seven, Suit::Clubs),
+            card(Rank::King, Suit::Spades),
+            card(Rank::Two, Suit::Hearts),
+        ];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let player = create_player_with_cards(cards);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::ThreeOfAKind);
+        assert_eq!(eval.primary_rank, 7);
+    }
+
+    #[test]
+    fn test_two_pair() {
+        let cards = vec![
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::Ace, Suit::Diamonds),
+            card(Rank::King, Suit::Clubs),
+            card(Rank::King, Suit::Spades),
+            card(Rank::Queen, Suit::Hearts),
+        ];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let player = create_player_with_cards(cards);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::TwoPair);
+    }
+
+    #[test]
+    fn test_pair() {
+        let cards = vec![
+            card(Rank::Jack, Suit::Hearts),
+            card(Rank::Jack, Suit::Diamonds),
+            card(Rank::Ace, Suit::Clubs),
+            card(Rank::King, Suit::Spades),
+            card(Rank::Queen, Suit::Hearts),
+        ];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let player = create_player_with_cards(cards);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::Pair);
+        assert_eq!(eval.primary_rank, 11); // Jacks
+    }
+
+    #[test]
+    fn test_high_card() {
+        let cards = vec![
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::King, Suit::Diamonds),
+            card(Rank::Queen, Suit::Clubs),
+            card(Rank::Jack, Suit::Spades),
+            card(Rank::Nine, Suit::Hearts),
+        ];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let player = create_player_with_cards(cards);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::HighCard);
+    }
+
+    #[test]
+    fn test_hand_comparison() {
+        let straight_flush = HandEvaluation {
+            rank: HandRank::StraightFlush,
+            primary_rank: 10,
+            tiebreakers: vec![10],
+            description: "Straight Flush".to_string(),
+        };
+
+        let four_of_a_kind = HandEvaluation {
+            rank: HandRank::FourOfAKind,
+            primary_rank: 14,
+            tiebreakers: vec![13],
+            description: "Four of a Kind".to_string(),
+        };
+
+        assert!(straight_flush > four_of_a_kind);
+        assert!(four_of_a_kind < straight_flush);
+    }
+
+    #[test]
+    fn test_card_display() {
+        assert_eq!(format!("{}", Card::new(Suit::Hearts, Rank::Ace)), "A♥");
+        assert_eq!(format!("{}", Card::new(Suit::Spades, Rank::Ten)), "10♠");
+        assert_eq!(format!("{}", Card::new(Suit::Diamonds, Rank::King)), "K♦");
+    }
+
+    #[test]
+    fn test_rank_from_u8() {
+        assert_eq!(Rank::from_u8(2), Some(Rank::Two));
+        assert_eq!(Rank::from_u8(10), Some(Rank::Ten));
+        assert_eq!(Rank::from_u8(14), Some(Rank::Ace));
+        assert_eq!(Rank::from_u8(1), None);
+        assert_eq!(Rank::from_u8(15), None);
+    }
+
+    #[test]
+    fn test_check_straight_empty_cards() {
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let result = poker_game.check_straight_from_cards(&[]);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_check_straight_single_card() {
+        let cards = vec![Card::new(Suit::Hearts, Rank::Ace)];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let result = poker_game.check_straight_from_cards(&cards);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_sit_out_and_return() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+
+        game.sit_out("p1");
+        let p1 = game.players.get("p1").unwrap();
+        assert!(p1.is_sitting_out);
+
+        game.return_to_game("p1");
+        let p1 = game.players.get("p1").unwrap();
+        assert!(!p1.is_sitting_out);
+    }
+
+    #[test]
+    fn test_check_wheel_straight() {
+        let cards = vec![
+            Card::new(Suit::Hearts, Rank::Ace),
+            Card::new(Suit::Diamonds, Rank::Two),
+            Card::new(Suit::Clubs, Rank::Three),
+            Card::new(Suit::Spades, Rank::Four),
+            Card::new(Suit::Hearts, Rank::Five),
+        ];
+        let poker_game = PokerGame::new(
+            "test".to_string(),
+            5,
+            10,
+            tokio::sync::broadcast::channel(100).0,
+        );
+        let result = poker_game.check_straight_from_cards(&cards);
+        assert!(result.is_some());
+        let eval = result.unwrap();
+        assert_eq!(eval.rank, HandRank::Straight);
+        assert_eq!(eval.primary_rank, 5);
+        assert!(eval.description.contains("Wheel"));
+    }
+
+    #[test]
+    fn test_betting_round_check_and_call() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
+
+        let player_to_act = game.get_player_to_act();
+        assert!(player_to_act.is_some());
+
+        let player_id = player_to_act.unwrap().id.clone();
+        let player = game.players.get(&player_id).unwrap();
+        let initial_chips = player.chips;
+        let initial_bet = player.current_bet;
+
+        let result = game.handle_action(&player_id, PlayerAction::Call);
+        assert!(result.is_ok());
+
+        let p_updated = game.players.get(&player_id).unwrap();
+        assert!(p_updated.chips <= initial_chips);
+        assert!(p_updated.current_bet >= initial_bet);
+    }
+
+    #[test]
+    fn test_betting_round_fold() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
+
+        let player_to_act = game.get_player_to_act().unwrap();
+        let player_id = player_to_act.id.clone();
+        let result = game.handle_action(&player_id, PlayerAction::Fold);
+        assert!(result.is_ok());
+
+        let p1 = game.players.get("p1").unwrap();
+        let p2 = game.players.get("p2").unwrap();
+
+        assert!(p1.is_folded || p2.is_folded);
+    }
+
+    #[test]
+    fn test_betting_round_call() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
+
+        let player_to_act = game.get_player_to_act().unwrap();
+        let player_id = player_to_act.id.clone();
+        let player = game.players.get(&player_id).unwrap();
+        let initial_chips = player.chips;
+        let initial_bet = player.current_bet;
+
+        let result = game.handle_action(&player_id, PlayerAction::Call);
+        assert!(result.is_ok());
+
+        let p_updated = game.players.get(&player_id).unwrap();
+        assert!(p_updated.chips <= initial_chips);
+        assert!(p_updated.current_bet >= initial_bet);
+    }
+
+    #[test]
+    fn test_betting_round_raise() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
+
+        let player_to_act = game.get_player_to_act().unwrap();
+        let player_id = player_to_act.id.clone();
+        let player = game.players.get(&player_id).unwrap();
+        let initial_chips = player.chips;
+        let initial_bet = player.current_bet;
+
+        let raise_amount = 20;
+        let current_table_bet = game.get_current_bet();
+        let total_bet = current_table_bet + raise_amount;
+        let actual_deduction = total_bet - initial_bet;
+
+        let result = game.handle_action(&player_id, PlayerAction::Raise(20));
+        assert!(result.is_ok());
+
+        let p_updated = game.players.get(&player_id).unwrap();
+        assert_eq!(p_updated.chips, initial_chips - actual_deduction);
+        assert_eq!(p_updated.current_bet, total_bet);
+    }
+
+    #[test]
+    fn test_betting_round_all_in() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 100)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
+
+        let player_to_act = game.get_player_to_act().unwrap();
+        let player_id = player_to_act.id.clone();
+
+        let result = game.handle_action(&player_id, PlayerAction::AllIn);
+        assert!(result.is_ok());
+
+        let player = game.players.get(&player_id).unwrap();
+        assert_eq!(player.chips, 0);
+        assert!(player.is_all_in);
+    }
+
+    #[test]
+    fn test_fewer_than_five_cards_high_card() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let poker_game = PokerGame::new("test".to_string(), 5, 10, tx);
+        let player = create_player_with_cards(vec![
+            Card::new(Suit::Hearts, Rank::Ace),
+            Card::new(Suit::Diamonds, Rank::King),
+            Card::new(Suit::Clubs, Rank::Queen),
+        ]);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::HighCard);
+        assert_eq!(eval.primary_rank, 14);
+    }
+
+    #[test]
+    fn test_empty_hand() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let poker_game = PokerGame::new("test".to_string(), 5, 10, tx);
+        let player = create_player_with_cards(vec![]);
+        let eval = poker_game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::HighCard);
+        assert_eq!(eval.primary_rank, 0);
+    }
+
+    #[test]
+    fn test_side_pots_calculation() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 100)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 200)
+            .unwrap();
+        game.add_player("p3".to_string(), "Player3".to_string(), 300)
+            .unwrap();
+
+        if let Some(p1) = game.players.get_mut("p1") {
+            p1.current_bet = 50;
+        }
+        if let Some(p2) = game.players.get_mut("p2") {
+            p2.current_bet = 100;
+        }
+        if let Some(p3) = game.players.get_mut("p3") {
+            p3.current_bet = 100;
+        }
+
+        let pots = game.calculate_side_pots();
+        assert!(!pots.is_empty());
+
+        let main_pot = pots
+            .iter()
+            .find(|(amount, players)| players.len() == 3 && *amount == 150);
+        assert!(
+            main_pot.is_some(),
+            "Should have main pot with all 3 players"
+        );
+
+        if let Some(p2) = game.players.get_mut("p2") {
+            p2.is_folded = true;
+        }
+
+        let pots_after_fold = game.calculate_side_pots();
+        assert!(!pots_after_fold.is_empty());
+    }
+
+    #[test]
+    fn test_all_in_pot_distribution() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 100)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 500)
+            .unwrap();
+
+        if let Some(p1) = game.players.get_mut("p1") {
+            p1.current_bet = 100;
+            p1.is_all_in = true;
+        }
+        if let Some(p2) = game.players.get_mut("p2") {
+            p2.current_bet = 200;
+        }
+
+        let pots = game.calculate_side_pots();
+        assert!(!pots.is_empty());
+
+        let side_pot = pots.iter().find(|(_amount, players)| {
+            players.contains(&"p1".to_string()) && players.contains(&"p2".to_string())
+        });
+        assert!(side_pot.is_some());
+    }
+
+    #[test]
+    fn test_validate_bet_amount_positive() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let game = PokerGame::new("test".to_string(), 5, 10, tx);
+        let player = PlayerState::new("p1".to_string(), "Player1".to_string(), 1000);
+
+        let result = game.validate_bet_amount(&player, 100, 0, 100);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_bet_amount_zero() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let game = PokerGame::new("test".to_string(), 5, 10, tx);
+        let player = PlayerState::new("p1".to_string(), "Player1".to_string(), 1000);
+
+        assert!(game.validate_bet_amount(&player, 0, 100, 100).is_err());
+        assert!(game.validate_bet_amount(&player, -100, 100, 100).is_err());
+    }
+
+    #[test]
+    fn test_validate_bet_amount_exceeds_chips() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let game = PokerGame::new("test".to_string(), 5, 10, tx);
+        let player = PlayerState::new("p1".to_string(), "Player1".to_string(), 100);
+
+        assert!(game.validate_bet_amount(&player, 200, 100, 100).is_err());
+    }
+
+    #[test]
+    fn test_validate_raise_amount_success() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+
+        let player = game.players.get("p1").unwrap();
+        let result = game.validate_raise_amount(player, 50);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_raise_amount_below_minimum() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+
+        let player = game.players.get("p1").unwrap();
+        game.min_raise = 100;
+        let result = game.validate_raise_amount(player, 50);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_all_in_partial_call() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 200)
+            .unwrap();
+
+        let player_to_act = game.get_player_to_act().unwrap();
+        let player_id = player_to_act.id.clone();
+
+        game.handle_action(&player_id, PlayerAction::Raise(100))
+            .unwrap();
+
+        let next_player = game.get_player_to_act();
+        assert!(
+            next_player.is_some(),
+            "Should have a player to act after raise"
+        );
+        let next_player = next_player.unwrap();
+        let next_player_id = next_player.id.clone();
+
+        let result = game.handle_action(&next_player_id, PlayerAction::AllIn);
+        assert!(
+            result.is_ok(),
+            "All-in should succeed after raise: {:?}",
+            result
+        );
+
+        let p = game.players.get(&next_player_id).unwrap();
+        assert_eq!(p.chips, 0, "Player should have 0 chips after all-in");
+        assert!(p.is_all_in, "Player should be marked as all-in");
+    }
+
+    #[test]
+    fn test_calculate_side_pots_all_in_scenario() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 50)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 100)
+            .unwrap();
+        game.add_player("p3".to_string(), "Player3".to_string(), 100)
+            .unwrap();
+
+        if let Some(p1) = game.players.get_mut("p1") {
+            p1.current_bet = 50;
+            p1.is_all_in = true;
+        }
+        if let Some(p2) = game.players.get_mut("p2") {
+            p2.current_bet = 100;
+        }
+        if let Some(p3) = game.players.get_mut("p3") {
+            p3.current_bet = 100;
+        }
+
+        let pots = game.calculate_side_pots();
+
+        let main_pot_amount: i32 = pots
+            .iter()
+            .find(|(_, players)| players.len() == 3)
+            .map(|(amount, _)| *amount)
+            .unwrap_or(0);
+        assert_eq!(
+            main_pot_amount, 150,
+            "Main pot should have 50 from each player"
+        );
+
+        let side_pot_amount: i32 = pots
+            .iter()
+            .filter(|(_, players)| players.len() == 2)
+            .map(|(amount, _)| *amount)
+            .sum();
+        assert_eq!(
+            side_pot_amount, 100,
+            "Side pot should have 50 from p2 and p3"
+        );
+    }
+
+    #[test]
+    fn test_calculate_new_pot_overflow() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.pot = MAX_POT - 100;
+
+        let result = game.calculate_new_pot(200);
+        assert!(
+            result.is_none(),
+            "Should fail when pot would exceed MAX_POT"
+        );
     }
 
-    fn check_full_house(&self, cards: &[Card]) -> Option<HandEvaluation> {
-        let mut rank_counts = HashMap::<u8, usize>::new();
-        for card in cards {
-            *rank_counts.entry(card.rank as u8).or_insert(0) += 1;
-        }
+    #[test]
+    fn test_calculate_new_pot_success() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.pot = 100;
 
-        let mut three_of_kind: Option<u8> = None;
-        let mut pairs: Vec<u8> = Vec::new();
+        let new_pot = game.calculate_new_pot(50);
+        assert!(new_pot.is_some());
+        assert_eq!(new_pot.unwrap(), 150);
 
-        for (&rank, &count) in &rank_counts {
-            if count >= 3 {
-                if three_of_kind.is_none() {
-                    three_of_kind = Some(rank);
-                } else {
-                    pairs.push(rank);
-                }
-            } else if count >= 2 {
-                pairs.push(rank);
-            }
-        }
+        game.pot = new_pot.unwrap();
+        assert_eq!(game.pot, 150);
+    }
 
-        if let Some(three_rank) = three_of_kind {
-            if let Some(pair_rank) = pairs.into_iter().find(|&r| r != three_rank) {
-                return Some(HandEvaluation::full_house(three_rank, pair_rank));
-            }
-        }
+    #[test]
+    fn test_calculate_new_pot_negative() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
 
-        None
+        assert!(game.calculate_new_pot(-50).is_none());
     }
 
-    fn check_flush(&self, cards: &[Card]) -> Option<HandEvaluation> {
-        if let Some(flush_cards) = self.get_flush_cards(cards) {
-            return Some(HandEvaluation::flush(&flush_cards));
-        }
-        None
-    }
+    #[test]
+    fn test_bet_validation_negative_amount() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
 
-    fn get_flush_cards(&self, cards: &[Card]) -> Option<Vec<Card>> {
-        for suit in [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades] {
-            let mut flush_cards: Vec<Card> =
-                cards.iter().filter(|c| c.suit == suit).cloned().collect();
-            if flush_cards.len() >= 5 {
-                flush_cards.sort_by_key(|c| c.rank as u8);
-                flush_cards.reverse();
-                flush_cards.truncate(5);
-                return Some(flush_cards);
-            }
-        }
-        None
-    }
+        let player_to_act = game.get_player_to_act().unwrap();
+        let player_id = player_to_act.id.clone();
 
-    fn check_straight(&self, cards: &[Card]) -> Option<HandEvaluation> {
-        self.check_straight_from_cards(cards)
+        let result = game.handle_action(&player_id, PlayerAction::Bet(-100));
+        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("InvalidAmount") || err.contains("positive"));
     }
 
-    fn check_straight_from_cards(&self, cards: &[Card]) -> Option<HandEvaluation> {
-        let mut ranks: Vec<_> = cards.iter().map(|c| c.rank as u8).collect();
-        ranks.sort();
-        ranks.dedup();
+    #[test]
+    fn test_bet_validation_exceeds_chips() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 100)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 100)
+            .unwrap();
 
-        if ranks.is_empty() {
-            return None;
-        }
+        let player_to_act = game.get_player_to_act().unwrap();
+        let player_id = player_to_act.id.clone();
 
-        let has_wheel = ranks.contains(&2)
-            && ranks.contains(&3)
-            && ranks.contains(&4)
-            && ranks.contains(&5)
-            && ranks.contains(&14);
+        let result = game.handle_action(&player_id, PlayerAction::Bet(500));
+        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(
+            err.contains("BetExceedsChips") || err.contains("chips") || err.contains("exceeds")
+        );
+    }
 
-        if has_wheel {
-            return Some(HandEvaluation::straight_with_wheel());
-        }
+    #[test]
+    fn test_bet_validation_exceeds_max_bet() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.set_max_bet_per_hand(100);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
 
-        let mut straight_high = 0;
-        let mut consecutive = 1;
+        let player_to_act = game.get_player_to_act().unwrap();
+        let player_id = player_to_act.id.clone();
 
-        for i in 1..ranks.len() {
-            if ranks[i] == ranks[i - 1] + 1 {
-                consecutive += 1;
-            } else if ranks[i] != ranks[i - 1] {
-                if consecutive >= 5 && ranks[i - 1] > straight_high {
-                    straight_high = ranks[i - 1];
-                }
-                consecutive = 1;
-            }
-        }
+        let result = game.handle_action(&player_id, PlayerAction::Bet(200));
+        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("Invalid") || err.contains("exceeds") || err.contains("maximum"));
+    }
 
-        if consecutive >= 5 && ranks[ranks.len() - 1] > straight_high {
-            straight_high = ranks[ranks.len() - 1];
-        }
+    #[test]
+    fn test_all_in_handling() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 50)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
 
-        if straight_high > 0 {
-            return Some(HandEvaluation::straight(straight_high));
-        }
+        let player_to_act = game.get_player_to_act().unwrap();
+        let player_id = player_to_act.id.clone();
 
-        None
+        let result = game.handle_action(&player_id, PlayerAction::AllIn);
+        assert!(result.is_ok(), "All-in should succeed: {:?}", result);
+
+        let p = game.players.get(&player_id).unwrap();
+        assert_eq!(p.chips, 0);
+        assert!(p.is_all_in);
+        assert!(p.has_acted);
     }
 
-    fn check_three_of_a_kind(&self, cards: &[Card]) -> Option<HandEvaluation> {
-        let mut rank_counts = HashMap::<u8, usize>::new();
-        for card in cards {
-            *rank_counts.entry(card.rank as u8).or_insert(0) += 1;
-        }
+    fn create_heads_up_game(config: TableConfig) -> PokerGame {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::with_config("test".to_string(), config, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
+        game
+    }
 
-        for (rank, &count) in &rank_counts {
-            if count >= 3 {
-                return Some(HandEvaluation::three_of_a_kind(cards, *rank));
-            }
-        }
-        None
+    #[test]
+    fn test_no_limit_allows_large_bet() {
+        let mut game = create_heads_up_game(TableConfig::new(5, 10));
+        let player_id = game.get_player_to_act().unwrap().id.clone();
+
+        let result = game.handle_action(&player_id, PlayerAction::Raise(500));
+        assert!(
+            result.is_ok(),
+            "No-limit raise should succeed: {:?}",
+            result
+        );
+        assert_eq!(game.players[&player_id].current_bet, 510);
     }
 
-    fn check_two_pair(&self, cards: &[Card]) -> Option<HandEvaluation> {
-        let mut rank_counts = HashMap::<u8, usize>::new();
-        for card in cards {
-            *rank_counts.entry(card.rank as u8).or_insert(0) += 1;
-        }
+    #[test]
+    fn test_pot_limit_rejects_raise_over_pot() {
+        let config = TableConfig {
+            betting_structure: BettingStructure::PotLimit,
+            ..TableConfig::new(5, 10)
+        };
+        let mut game = create_heads_up_game(config);
+        let player_id = game.get_player_to_act().unwrap().id.clone();
 
-        let mut pairs: Vec<u8> = rank_counts
-            .iter()
-            .filter(|(_, &count)| count >= 2)
-            .map(|(&rank, _)| rank)
-            .collect();
+        // Pot of 15 with 5 to call: the largest raise is to 30.
+        let result = game.handle_action(&player_id, PlayerAction::Raise(21));
+        assert!(matches!(result, Err(ServerError::InvalidRaise(_))));
 
-        if pairs.len() >= 2 {
-            pairs.sort_by(|a, b| b.cmp(a));
-            return Some(HandEvaluation::two_pair(cards, pairs[0], pairs[1]));
-        }
-        None
+        let result = game.handle_action(&player_id, PlayerAction::Raise(20));
+        assert!(
+            result.is_ok(),
+            "Pot-sized raise should succeed: {:?}",
+            result
+        );
+        assert_eq!(game.players[&player_id].current_bet, 30);
     }
 
-    fn check_pair(&self, cards: &[Card]) -> Option<HandEvaluation> {
-        let mut rank_counts = HashMap::<u8, usize>::new();
-        for card in cards {
-            *rank_counts.entry(card.rank as u8).or_insert(0) += 1;
-        }
+    #[test]
+    fn test_pot_limit_rejects_oversized_all_in() {
+        let config = TableConfig {
+            betting_structure: BettingStructure::PotLimit,
+            ..TableConfig::new(5, 10)
+        };
+        let mut game = create_heads_up_game(config);
+        let player_id = game.get_player_to_act().unwrap().id.clone();
 
-        for (rank, &count) in &rank_counts {
-            if count >= 2 {
-                return Some(HandEvaluation::pair(cards, *rank));
-            }
-        }
-        None
+        let result = game.handle_action(&player_id, PlayerAction::AllIn);
+        assert!(matches!(result, Err(ServerError::InvalidBet(_))));
     }
 
-    fn end_hand(&mut self) {
-        self.game_stage = GameStage::HandComplete;
-        self.broadcast_game_state();
-
-        let active_player_ids: Vec<&str> = self
-            .players
-            .values()
-            .filter(|p| p.chips > 0 && !p.is_sitting_out)
-            .map(|p| p.id.as_str())
-            .collect();
+    #[test]
+    fn test_fixed_limit_enforces_bet_size_and_cap() {
+        let config = TableConfig {
+            betting_structure: BettingStructure::FixedLimit {
+                small_bet: 10,
+                big_bet: 20,
+                raise_cap: 3,
+            },
+            ..TableConfig::new(5, 10)
+        };
+        let mut game = create_heads_up_game(config);
 
-        if !active_player_ids.is_empty() {
-            let current_dealer = active_player_ids
-                .get(self.dealer_position % active_player_ids.len())
-                .or(active_player_ids.first());
+        let first = game.get_player_to_act().unwrap().id.clone();
+        assert!(game.handle_action(&first, PlayerAction::Raise(20)).is_err());
+        game.handle_action(&first, PlayerAction::Raise(10)).unwrap();
 
-            if let Some(current) = current_dealer {
-                if let Some(current_idx) = active_player_ids.iter().position(|id| *id == *current) {
-                    let next_idx = (current_idx + 1) % active_player_ids.len();
-                    self.dealer_position = next_idx;
-                }
-            }
-        }
+        // The big blind and two raises reach the cap of three bets.
+        let second = game.get_player_to_act().unwrap().id.clone();
+        game.handle_action(&second, PlayerAction::Raise(10))
+            .unwrap();
 
-        let active_players: Vec<&PlayerState> = self
-            .players
-            .values()
-            .filter(|p| p.chips > 0 && !p.is_sitting_out)
-            .collect();
+        let result = game.handle_action(&first, PlayerAction::Raise(10));
+        assert!(matches!(result, Err(ServerError::InvalidRaise(_))));
+        game.handle_action(&first, PlayerAction::Call).unwrap();
+        assert_eq!(game.current_street, Street::Flop);
+    }
 
-        if active_players.len() >= 2 {
-            self.start_hand();
-        } else {
-            self.game_stage = GameStage::WaitingForPlayers;
+    #[test]
+    fn test_fixed_limit_cap_on_flop_and_turn() {
+        let config = TableConfig {
+            betting_structure: BettingStructure::FixedLimit {
+                small_bet: 10,
+                big_bet: 20,
+                raise_cap: 3,
+            },
+            ..TableConfig::new(5, 10)
+        };
+        let mut game = create_heads_up_game(config);
+        let first = game.get_player_to_act().unwrap().id.clone();
+        game.handle_action(&first, PlayerAction::Call).unwrap();
+        let second = game.get_player_to_act().unwrap().id.clone();
+        game.handle_action(&second, PlayerAction::Check).unwrap();
+
+        for (street, bet) in [(Street::Flop, 10), (Street::Turn, 20)] {
+            assert_eq!(game.current_street, street);
+            let opener = game.get_player_to_act().unwrap().id.clone();
+            game.handle_action(&opener, PlayerAction::Bet(bet)).unwrap();
+            let raiser = game.get_player_to_act().unwrap().id.clone();
+            game.handle_action(&raiser, PlayerAction::Raise(bet))
+                .unwrap();
+            game.handle_action(&opener, PlayerAction::Raise(bet))
+                .unwrap();
+
+            let result = game.handle_action(&raiser, PlayerAction::Raise(bet));
+            assert!(matches!(result, Err(ServerError::InvalidRaise(_))));
+            game.handle_action(&raiser, PlayerAction::Call).unwrap();
         }
+        assert_eq!(game.current_street, Street::River);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use poker_protocol::{Card, HandEvaluation, HandRank, PlayerState, Rank, Suit};
+    #[test]
+    fn test_fixed_limit_short_all_in_does_not_count_towards_cap() {
+        let config = TableConfig {
+            betting_structure: BettingStructure::FixedLimit {
+                small_bet: 20,
+                big_bet: 40,
+                raise_cap: 4,
+            },
+            ..TableConfig::new(5, 10)
+        };
+        let mut game = create_heads_up_game(config);
+        game.current_street = Street::Flop;
+        game.start_betting_round();
+
+        game.record_bet(0, 15);
+        assert_eq!(game.raises_this_street, 0);
+        game.record_bet(15, 20);
+        assert_eq!(game.raises_this_street, 1);
+        game.record_bet(20, 40);
+        assert_eq!(game.raises_this_street, 2);
+    }
 
-    pub fn card(rank: Rank, suit: Suit) -> Card {
-        Card::new(suit, rank)
+    #[test]
+    fn test_new_street_resets_bets() {
+        let mut game = create_heads_up_game(TableConfig::new(5, 10));
+
+        let first = game.get_player_to_act().unwrap().id.clone();
+        game.handle_action(&first, PlayerAction::Call).unwrap();
+        let second = game.get_player_to_act().unwrap().id.clone();
+        game.handle_action(&second, PlayerAction::Check).unwrap();
+
+        assert_eq!(game.current_street, Street::Flop);
+        assert_eq!(game.get_current_bet(), 0);
+        assert!(game.players.values().all(|p| p.total_bet == 10));
+        assert!(game.players.values().all(|p| !p.has_acted));
+        assert_eq!(game.pot, 20);
     }
 
-    pub fn create_player_with_cards(cards: Vec<Card>) -> PlayerState {
-        let mut player = PlayerState::new("test".to_string(), "Test".to_string(), 1000);
-        player.hole_cards = cards;
-        player
+    fn create_omaha_game() -> PokerGame {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        PokerGame::with_config(
+            "test".to_string(),
+            TableConfig::for_variant(GameVariant::Omaha, 5, 10),
+            tx,
+        )
+    }
+
+    #[test]
+    fn test_omaha_deals_four_hole_cards() {
+        let mut game = create_omaha_game();
+        assert_eq!(game.betting_structure(), BettingStructure::PotLimit);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
+
+        assert!(game.players.values().all(|p| p.hole_cards.len() == 4));
     }
 
     #[test]
-    fn test_royal_flush() {
-        let cards = vec![
-            card(Rank::Ace, Suit::Hearts),
+    fn test_omaha_requires_two_hole_cards() {
+        let mut game = create_omaha_game();
+        game.community_cards = vec![
             card(Rank::King, Suit::Hearts),
             card(Rank::Queen, Suit::Hearts),
             card(Rank::Jack, Suit::Hearts),
             card(Rank::Ten, Suit::Hearts),
+            card(Rank::Nine, Suit::Hearts),
         ];
-        let poker_game = PokerGame::new(
-            "test".to_string(),
-            5,
-            10,
-            tokio::sync::broadcast::channel(100).0,
-        );
-        let player = create_player_with_cards(cards);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::StraightFlush);
+        let player = create_player_with_cards(vec![
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::Two, Suit::Clubs),
+            card(Rank::Three, Suit::Diamonds),
+            card(Rank::Four, Suit::Spades),
+        ]);
+
+        // The board alone is a straight flush, but Omaha must play two hole cards.
+        let eval = game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::HighCard);
+        assert_eq!(eval.primary_rank, 14);
     }
 
     #[test]
-    fn test_straight_flush() {
-        let cards = vec![
-            card(Rank::Nine, Suit::Spades),
-            card(Rank::Ten, Suit::Spades),
-            card(Rank::Jack, Suit::Spades),
-            card(Rank::Queen, Suit::Spades),
-            card(Rank::King, Suit::Spades),
+    fn test_omaha_picks_best_two_hole_cards() {
+        let mut game = create_omaha_game();
+        game.community_cards = vec![
+            card(Rank::Ace, Suit::Spades),
+            card(Rank::Seven, Suit::Hearts),
+            card(Rank::Seven, Suit::Clubs),
+            card(Rank::Two, Suit::Diamonds),
+            card(Rank::Nine, Suit::Hearts),
         ];
-        let poker_game = PokerGame::new(
-            "test".to_string(),
-            5,
-            10,
-            tokio::sync::broadcast::channel(100).0,
-        );
-        let player = create_player_with_cards(cards);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::StraightFlush);
+        let player = create_player_with_cards(vec![
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::Ace, Suit::Diamonds),
+            card(Rank::Seven, Suit::Spades),
+            card(Rank::King, Suit::Clubs),
+        ]);
+
+        let eval = game.evaluate_hand(&player);
+        assert_eq!(eval.rank, HandRank::FullHouse);
+        assert_eq!(eval.primary_rank, 14);
     }
 
     #[test]
-    fn test_four_of_a_kind() {
-        let cards = vec![
-            card(Rank::Ace, Suit::Hearts),
-            card(Rank::Ace, Suit::Diamonds),
-            card(Rank::Ace, Suit::Clubs),
-            card(Rank::Ace, Suit::Spades),
-            card(Rank::King, Suit::Hearts),
-        ];
-        let poker_game = PokerGame::new(
+    fn test_card_combinations_count() {
+        let cards: Vec<Card> = [Rank::Two, Rank::Three, Rank::Four, Rank::Five, Rank::Six]
+            .into_iter()
+            .map(|rank| card(rank, Suit::Clubs))
+            .collect();
+        assert_eq!(card_combinations(&cards, 3).len(), 10);
+        assert_eq!(card_combinations(&cards[..4], 2).len(), 6);
+        assert_eq!(card_combinations(&cards, 0).len(), 1);
+        assert!(card_combinations(&cards[..2], 3).is_empty());
+    }
+
+    fn seat_for_showdown(game: &mut PokerGame, id: &str, hole_cards: Vec<Card>, contribution: i32) {
+        let mut player = PlayerState::new(id.to_string(), id.to_string(), 1000);
+        player.hole_cards = hole_cards;
+        player.total_bet = contribution;
+        game.players.insert(id.to_string(), player);
+    }
+
+    fn payout_for(payouts: &[(String, i32)], player_id: &str) -> i32 {
+        payouts
+            .iter()
+            .filter(|(id, _)| id == player_id)
+            .map(|(_, amount)| amount)
+            .sum()
+    }
+
+    fn create_hi_lo_game(board: Vec<Card>) -> PokerGame {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::with_config(
             "test".to_string(),
-            5,
-            10,
-            tokio::sync::broadcast::channel(100).0,
+            TableConfig::for_variant(GameVariant::OmahaHiLo, 5, 10),
+            tx,
         );
-        let player = create_player_with_cards(cards);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::FourOfAKind);
-        assert_eq!(eval.primary_rank, 14); // Ace
+        game.community_cards = board;
+        game
     }
 
-    #[test]
-    fn test_full_house() {
-        let cards = vec![
+    fn low_board() -> Vec<Card> {
+        vec![
+            card(Rank::Ace, Suit::Spades),
+            card(Rank::Two, Suit::Diamonds),
+            card(Rank::Seven, Suit::Clubs),
             card(Rank::King, Suit::Hearts),
-            card(Rank::King, Suit::Diamonds),
-            card(Rank::King, Suit::Clubs),
-            card(Rank::Queen, Suit::Hearts),
-            card(Rank::Queen, Suit::Diamonds),
-        ];
-        let poker_game = PokerGame::new(
-            "test".to_string(),
-            5,
-            10,
-            tokio::sync::broadcast::channel(100).0,
-        );
-        let player = create_player_with_cards(cards);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::FullHouse);
-        assert_eq!(eval.primary_rank, 13); // Kings
+            card(Rank::Queen, Suit::Clubs),
+        ]
     }
 
     #[test]
-    fn test_flush() {
-        let cards = vec![
-            card(Rank::Ace, Suit::Hearts),
-            card(Rank::King, Suit::Hearts),
-            card(Rank::Queen, Suit::Hearts),
-            card(Rank::Jack, Suit::Hearts),
+    fn test_hi_lo_splits_pot_with_odd_chip_to_high() {
+        let mut game = create_hi_lo_game(low_board());
+        let high_hand = vec![
+            card(Rank::King, Suit::Spades),
+            card(Rank::King, Suit::Diamonds),
             card(Rank::Nine, Suit::Hearts),
+            card(Rank::Nine, Suit::Clubs),
         ];
-        let poker_game = PokerGame::new(
-            "test".to_string(),
-            5,
-            10,
-            tokio::sync::broadcast::channel(100).0,
-        );
-        let player = create_player_with_cards(cards);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::Flush);
+        let low_hand = vec![
+            card(Rank::Three, Suit::Hearts),
+            card(Rank::Four, Suit::Hearts),
+            card(Rank::Jack, Suit::Diamonds),
+            card(Rank::Jack, Suit::Spades),
+        ];
+        seat_for_showdown(&mut game, "p1", high_hand, 100);
+        seat_for_showdown(&mut game, "p2", low_hand, 100);
+        seat_for_showdown(&mut game, "p3", Vec::new(), 1);
+        game.players.get_mut("p3").unwrap().is_folded = true;
+
+        let (update, payouts) = game
+            .resolve_showdown(&game.calculate_side_pots(), 0, 1)
+            .unwrap();
+        assert_eq!(update.winners, vec!["p1".to_string()]);
+        assert_eq!(update.low_winners, vec!["p2".to_string()]);
+        assert_eq!(payout_for(&payouts, "p1"), 101);
+        assert_eq!(payout_for(&payouts, "p2"), 100);
+        assert_eq!(payout_for(&payouts, "p3"), 0);
     }
 
     #[test]
-    fn test_straight() {
-        let cards = vec![
-            card(Rank::Ten, Suit::Hearts),
-            card(Rank::Jack, Suit::Diamonds),
+    fn test_split_pot_odd_chip_goes_clockwise_from_button() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::with_config("test".to_string(), TableConfig::new(5, 10), tx);
+        game.community_cards = vec![
+            card(Rank::Ace, Suit::Spades),
+            card(Rank::King, Suit::Diamonds),
             card(Rank::Queen, Suit::Clubs),
-            card(Rank::King, Suit::Spades),
-            card(Rank::Ace, Suit::Hearts),
+            card(Rank::Jack, Suit::Hearts),
+            card(Rank::Ten, Suit::Spades),
         ];
-        let poker_game = PokerGame::new(
-            "test".to_string(),
-            5,
-            10,
-            tokio::sync::broadcast::channel(100).0,
+        seat_for_showdown(
+            &mut game,
+            "p1",
+            vec![
+                card(Rank::Two, Suit::Hearts),
+                card(Rank::Three, Suit::Clubs),
+            ],
+            50,
         );
-        let player = create_player_with_cards(cards);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::Straight);
+        seat_for_showdown(
+            &mut game,
+            "p2",
+            vec![
+                card(Rank::Two, Suit::Clubs),
+                card(Rank::Three, Suit::Hearts),
+            ],
+            50,
+        );
+        seat_for_showdown(&mut game, "p3", Vec::new(), 1);
+        game.players.get_mut("p3").unwrap().is_folded = true;
+        for (seat, id) in ["p1", "p2", "p3"].iter().enumerate() {
+            assert!(game.seats.sit_at(seat, id.to_string()));
+        }
+
+        game.dealer_position = 0;
+        let (_, payouts) = game
+            .resolve_showdown(&game.calculate_side_pots(), 0, 1)
+            .unwrap();
+        assert_eq!(payout_for(&payouts, "p2"), 51);
+        assert_eq!(payout_for(&payouts, "p1"), 50);
+
+        game.dealer_position = 1;
+        let (_, payouts) = game
+            .resolve_showdown(&game.calculate_side_pots(), 0, 1)
+            .unwrap();
+        assert_eq!(payout_for(&payouts, "p1"), 51);
+        assert_eq!(payout_for(&payouts, "p2"), 50);
     }
 
     #[test]
-    fn test_three_of_a_kind() {
-        let cards = vec![
-            card(Rank::Seven, Suit::Hearts),
-            card(Rank::Seven, Suit::Diamonds),
-            card(Rank::Seven, Suit::Clubs),
+    fn test_hi_lo_quartering() {
+        let mut game = create_hi_lo_game(low_board());
+        let high_and_low = vec![
+            card(Rank::Three, Suit::Hearts),
+            card(Rank::Four, Suit::Hearts),
             card(Rank::King, Suit::Spades),
-            card(Rank::Two, Suit::Hearts),
+            card(Rank::King, Suit::Diamonds),
         ];
-        let poker_game = PokerGame::new(
-            "test".to_string(),
-            5,
-            10,
-            tokio::sync::broadcast::channel(100).0,
-        );
-        let player = create_player_with_cards(cards);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::ThreeOfAKind);
-        assert_eq!(eval.primary_rank, 7);
+        let low_only = vec![
+            card(Rank::Three, Suit::Clubs),
+            card(Rank::Four, Suit::Clubs),
+            card(Rank::Jack, Suit::Diamonds),
+            card(Rank::Jack, Suit::Spades),
+        ];
+        seat_for_showdown(&mut game, "p1", high_and_low, 100);
+        seat_for_showdown(&mut game, "p2", low_only, 100);
+
+        let (update, payouts) = game
+            .resolve_showdown(&game.calculate_side_pots(), 0, 1)
+            .unwrap();
+        assert_eq!(update.low_winners.len(), 2);
+        assert_eq!(payout_for(&payouts, "p1"), 150);
+        assert_eq!(payout_for(&payouts, "p2"), 50);
     }
 
     #[test]
-    fn test_two_pair() {
-        let cards = vec![
-            card(Rank::Ace, Suit::Hearts),
-            card(Rank::Ace, Suit::Diamonds),
-            card(Rank::King, Suit::Clubs),
+    fn test_hi_lo_high_scoops_without_qualifying_low() {
+        let mut game = create_hi_lo_game(vec![
+            card(Rank::King, Suit::Hearts),
+            card(Rank::Queen, Suit::Clubs),
+            card(Rank::Nine, Suit::Diamonds),
+            card(Rank::Two, Suit::Spades),
+            card(Rank::Three, Suit::Spades),
+        ]);
+        let kings = vec![
             card(Rank::King, Suit::Spades),
-            card(Rank::Queen, Suit::Hearts),
+            card(Rank::King, Suit::Diamonds),
+            card(Rank::Ten, Suit::Hearts),
+            card(Rank::Ten, Suit::Clubs),
         ];
-        let poker_game = PokerGame::new(
-            "test".to_string(),
-            5,
-            10,
-            tokio::sync::broadcast::channel(100).0,
-        );
-        let player = create_player_with_cards(cards);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::TwoPair);
+        let wheel_draw = vec![
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::Four, Suit::Hearts),
+            card(Rank::Five, Suit::Diamonds),
+            card(Rank::Jack, Suit::Spades),
+        ];
+        seat_for_showdown(&mut game, "p1", kings, 100);
+        seat_for_showdown(&mut game, "p2", wheel_draw, 100);
+
+        let (update, payouts) = game
+            .resolve_showdown(&game.calculate_side_pots(), 0, 1)
+            .unwrap();
+        assert!(update.low_winners.is_empty());
+        assert_eq!(payout_for(&payouts, "p1"), 200);
     }
 
     #[test]
-    fn test_pair() {
-        let cards = vec![
-            card(Rank::Jack, Suit::Hearts),
-            card(Rank::Jack, Suit::Diamonds),
-            card(Rank::Ace, Suit::Clubs),
+    fn test_side_pot_goes_to_best_eligible_hand() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        game.community_cards = vec![
+            card(Rank::Two, Suit::Clubs),
+            card(Rank::Seven, Suit::Diamonds),
+            card(Rank::Nine, Suit::Hearts),
+            card(Rank::Jack, Suit::Spades),
+            card(Rank::Four, Suit::Clubs),
+        ];
+        let aces = vec![card(Rank::Ace, Suit::Hearts), card(Rank::Ace, Suit::Spades)];
+        let kings = vec![
+            card(Rank::King, Suit::Hearts),
             card(Rank::King, Suit::Spades),
+        ];
+        let queens = vec![
             card(Rank::Queen, Suit::Hearts),
+            card(Rank::Queen, Suit::Spades),
         ];
-        let poker_game = PokerGame::new(
-            "test".to_string(),
-            5,
-            10,
-            tokio::sync::broadcast::channel(100).0,
-        );
-        let player = create_player_with_cards(cards);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::Pair);
-        assert_eq!(eval.primary_rank, 11); // Jacks
-    }
+        seat_for_showdown(&mut game, "p1", aces, 50);
+        seat_for_showdown(&mut game, "p2", kings, 100);
+        seat_for_showdown(&mut game, "p3", queens, 100);
 
-    #[test]
-    fn test_high_card() {
-        let cards = vec![
-            card(Rank::Ace, Suit::Hearts),
-            card(Rank::King, Suit::Diamonds),
-            card(Rank::Queen, Suit::Clubs),
-            card(Rank::Jack, Suit::Spades),
-            card(Rank::Nine, Suit::Hearts),
-        ];
-        let poker_game = PokerGame::new(
-            "test".to_string(),
-            5,
-            10,
-            tokio::sync::broadcast::channel(100).0,
-        );
-        let player = create_player_with_cards(cards);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::HighCard);
+        let (_, payouts) = game
+            .resolve_showdown(&game.calculate_side_pots(), 0, 1)
+            .unwrap();
+        assert_eq!(payout_for(&payouts, "p1"), 150);
+        assert_eq!(payout_for(&payouts, "p2"), 100);
+        assert_eq!(payout_for(&payouts, "p3"), 0);
     }
 
     #[test]
-    fn test_hand_comparison() {
-        let straight_flush = HandEvaluation {
-            rank: HandRank::StraightFlush,
-            primary_rank: 10,
-            tiebreakers: vec![10],
-            description: "Straight Flush".to_string(),
-        };
+    fn test_side_pots_include_folded_chips() {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        seat_for_showdown(&mut game, "p1", Vec::new(), 40);
+        seat_for_showdown(&mut game, "p2", Vec::new(), 100);
+        seat_for_showdown(&mut game, "p3", Vec::new(), 100);
+        seat_for_showdown(&mut game, "p4", Vec::new(), 60);
+        game.players.get_mut("p4").unwrap().is_folded = true;
 
-        let four_of_a_kind = HandEvaluation {
-            rank: HandRank::FourOfAKind,
-            primary_rank: 14,
-            tiebreakers: vec![13],
-            description: "Four of a Kind".to_string(),
-        };
+        let pots = game.calculate_side_pots();
+        let total: i32 = pots.iter().map(|(amount, _)| amount).sum();
+        assert_eq!(total, 300);
+        assert_eq!(pots[0].0, 160);
+        assert_eq!(pots[0].1.len(), 3);
+        assert_eq!(pots[1].0, 140);
+        assert!(!pots[1].1.contains(&"p1".to_string()));
+    }
 
-        assert!(straight_flush > four_of_a_kind);
-        assert!(four_of_a_kind < straight_flush);
+    fn create_short_deck_game() -> PokerGame {
+        let tx = tokio::sync::broadcast::channel(100).0;
+        PokerGame::with_config(
+            "test".to_string(),
+            TableConfig::for_variant(GameVariant::ShortDeck, 5, 10),
+            tx,
+        )
     }
 
     #[test]
-    fn test_card_display() {
-        assert_eq!(format!("{}", Card::new(Suit::Hearts, Rank::Ace)), "A♥");
-        assert_eq!(format!("{}", Card::new(Suit::Spades, Rank::Ten)), "10♠");
-        assert_eq!(format!("{}", Card::new(Suit::Diamonds, Rank::King)), "K♦");
+    fn test_short_deck_removes_low_cards() {
+        let mut game = create_short_deck_game();
+        game.create_deck();
+        assert_eq!(game.deck.len(), 36);
+        assert!(game.deck.iter().all(|c| c.rank as u8 >= 6));
     }
 
     #[test]
-    fn test_rank_from_u8() {
-        assert_eq!(Rank::from_u8(2), Some(Rank::Two));
-        assert_eq!(Rank::from_u8(10), Some(Rank::Ten));
-        assert_eq!(Rank::from_u8(14), Some(Rank::Ace));
-        assert_eq!(Rank::from_u8(1), None);
-        assert_eq!(Rank::from_u8(15), None);
-    }
+    fn test_short_deck_ace_six_straight() {
+        let game = create_short_deck_game();
+        let cards = vec![
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::Six, Suit::Diamonds),
+            card(Rank::Seven, Suit::Clubs),
+            card(Rank::Eight, Suit::Spades),
+            card(Rank::Nine, Suit::Hearts),
+        ];
+        let eval = game.check_straight_from_cards(&cards).unwrap();
+        assert_eq!(eval.rank, HandRank::Straight);
+        assert_eq!(eval.primary_rank, 9);
 
-    #[test]
-    fn test_check_straight_empty_cards() {
-        let poker_game = PokerGame::new(
+        let holdem = PokerGame::new(
             "test".to_string(),
             5,
             10,
             tokio::sync::broadcast::channel(100).0,
         );
-        let result = poker_game.check_straight_from_cards(&[]);
-        assert!(result.is_none());
+        assert!(holdem.check_straight_from_cards(&cards).is_none());
     }
 
     #[test]
-    fn test_check_straight_single_card() {
-        let cards = vec![Card::new(Suit::Hearts, Rank::Ace)];
-        let poker_game = PokerGame::new(
+    fn test_short_deck_flush_beats_full_house() {
+        let game = create_short_deck_game();
+        let cards = vec![
+            card(Rank::King, Suit::Hearts),
+            card(Rank::King, Suit::Spades),
+            card(Rank::King, Suit::Diamonds),
+            card(Rank::Nine, Suit::Hearts),
+            card(Rank::Nine, Suit::Clubs),
+            card(Rank::Seven, Suit::Hearts),
+            card(Rank::Six, Suit::Hearts),
+        ];
+        let mut player = create_player_with_cards(cards);
+        player.hole_cards.push(card(Rank::Ten, Suit::Hearts));
+
+        let flush = game.evaluate_hand(&player);
+        assert_eq!(flush.rank, HandRank::Flush);
+
+        let full_house = HandEvaluation::full_house(14, 13);
+        assert_eq!(game.compare_hands(&flush, &full_house), Ordering::Greater);
+
+        let holdem = PokerGame::new(
             "test".to_string(),
             5,
             10,
             tokio::sync::broadcast::channel(100).0,
         );
-        let result = poker_game.check_straight_from_cards(&cards);
-        assert!(result.is_none());
-    }
-
-    #[test]
-    fn test_sit_out_and_return() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
-            .unwrap();
-
-        game.sit_out("p1");
-        let p1 = game.players.get("p1").unwrap();
-        assert!(p1.is_sitting_out);
-
-        game.return_to_game("p1");
-        let p1 = game.players.get("p1").unwrap();
-        assert!(!p1.is_sitting_out);
+        assert_eq!(holdem.evaluate_hand(&player).rank, HandRank::FullHouse);
+        assert_eq!(holdem.compare_hands(&flush, &full_house), Ordering::Less);
     }
 
     #[test]
-    fn test_check_wheel_straight() {
+    fn test_six_high_straight_beats_wheel() {
         let cards = vec![
-            Card::new(Suit::Hearts, Rank::Ace),
-            Card::new(Suit::Diamonds, Rank::Two),
-            Card::new(Suit::Clubs, Rank::Three),
-            Card::new(Suit::Spades, Rank::Four),
-            Card::new(Suit::Hearts, Rank::Five),
+            card(Rank::Ace, Suit::Hearts),
+            card(Rank::Two, Suit::Diamonds),
+            card(Rank::Three, Suit::Clubs),
+            card(Rank::Four, Suit::Spades),
+            card(Rank::Five, Suit::Hearts),
+            card(Rank::Six, Suit::Clubs),
         ];
-        let poker_game = PokerGame::new(
+        let game = PokerGame::new(
             "test".to_string(),
             5,
             10,
             tokio::sync::broadcast::channel(100).0,
         );
-        let result = poker_game.check_straight_from_cards(&cards);
-        assert!(result.is_some());
-        let eval = result.unwrap();
-        assert_eq!(eval.rank, HandRank::Straight);
-        assert_eq!(eval.primary_rank, 5);
-        assert!(eval.description.contains("Wheel"));
+        let eval = game.check_straight_from_cards(&cards).unwrap();
+        assert_eq!(eval.primary_rank, 6);
     }
 
     #[test]
-    fn test_betting_round_check_and_call() {
+    fn test_button_ante_goes_to_main_pot() {
         let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        let config = TableConfig {
+            ante: AnteStructure::ButtonAnte(30),
+            ..TableConfig::for_variant(GameVariant::ShortDeck, 5, 10)
+        };
+        let mut game = PokerGame::with_config("test".to_string(), config, tx);
         game.add_player("p1".to_string(), "Player1".to_string(), 1000)
             .unwrap();
         game.add_player("p2".to_string(), "Player2".to_string(), 1000)
             .unwrap();
 
-        let player_to_act = game.get_player_to_act();
-        assert!(player_to_act.is_some());
-
-        let player_id = player_to_act.unwrap().id.clone();
-        let player = game.players.get(&player_id).unwrap();
-        let initial_chips = player.chips;
-        let initial_bet = player.current_bet;
-
-        let result = game.handle_action(&player_id, PlayerAction::Call);
-        assert!(result.is_ok());
+        assert_eq!(game.pot, 45);
+        assert_eq!(game.antes_collected, 30);
+        let chips: i32 = game.players.values().map(|p| p.chips).sum();
+        assert_eq!(chips, 2000 - 45);
 
-        let p_updated = game.players.get(&player_id).unwrap();
-        assert!(p_updated.chips <= initial_chips);
-        assert!(p_updated.current_bet >= initial_bet);
+        let pots = game.calculate_side_pots();
+        assert_eq!(pots.iter().map(|(amount, _)| amount).sum::<i32>(), 45);
+        assert_eq!(pots[0].1.len(), 2);
     }
 
-    #[test]
-    fn test_betting_round_fold() {
+    /// Seats four players and deals until all four have been dealt in with no
+    /// blinds owed: p2 on the button, p3 in the small blind and p4 in the big blind.
+    fn create_four_handed_game(config: TableConfig) -> PokerGame {
         let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
-            .unwrap();
-        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
-            .unwrap();
+        let mut game = PokerGame::with_config("test".to_string(), config, tx);
+        for i in 1..=4 {
+            game.add_player(format!("p{}", i), format!("Player{}", i), 1000)
+                .unwrap();
+        }
+        game.post_blind("p3").unwrap();
+        game.post_blind("p4").unwrap();
+        game.start_hand();
+        game.start_hand();
+        game
+    }
 
-        let player_to_act = game.get_player_to_act().unwrap();
-        let player_id = player_to_act.id.clone();
-        let result = game.handle_action(&player_id, PlayerAction::Fold);
-        assert!(result.is_ok());
+    fn create_straddle_game(straddle: StraddleRule) -> PokerGame {
+        let mut game = create_four_handed_game(TableConfig {
+            straddle,
+            ..TableConfig::new(5, 10)
+        });
+        for i in 1..=4 {
+            game.set_straddle(&format!("p{}", i), true).unwrap();
+        }
+        game.start_hand();
+        game
+    }
 
-        let p1 = game.players.get("p1").unwrap();
-        let p2 = game.players.get("p2").unwrap();
+    fn forced_bet_kinds(game: &PokerGame) -> Vec<ForcedBetKind> {
+        game.hand_history
+            .forced_bets()
+            .iter()
+            .map(|bet| bet.kind)
+            .collect()
+    }
 
-        assert!(p1.is_folded || p2.is_folded);
+    fn forced_bets_by(game: &PokerGame) -> Vec<(String, ForcedBetKind)> {
+        game.hand_history
+            .forced_bets()
+            .into_iter()
+            .map(|bet| (bet.player_id, bet.kind))
+            .collect()
     }
 
     #[test]
-    fn test_betting_round_call() {
+    fn test_big_blind_ante_posted_after_big_blind() {
         let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+        let config = TableConfig {
+            ante: AnteStructure::BigBlindAnte(10),
+            ..TableConfig::new(5, 10)
+        };
+        let mut game = PokerGame::with_config("test".to_string(), config, tx);
         game.add_player("p1".to_string(), "Player1".to_string(), 1000)
             .unwrap();
         game.add_player("p2".to_string(), "Player2".to_string(), 1000)
             .unwrap();
 
-        let player_to_act = game.get_player_to_act().unwrap();
-        let player_id = player_to_act.id.clone();
-        let player = game.players.get(&player_id).unwrap();
-        let initial_chips = player.chips;
-        let initial_bet = player.current_bet;
-
-        let result = game.handle_action(&player_id, PlayerAction::Call);
-        assert!(result.is_ok());
-
-        let p_updated = game.players.get(&player_id).unwrap();
-        assert!(p_updated.chips <= initial_chips);
-        assert!(p_updated.current_bet >= initial_bet);
+        assert_eq!(
+            forced_bet_kinds(&game),
+            vec![
+                ForcedBetKind::SmallBlind,
+                ForcedBetKind::BigBlind,
+                ForcedBetKind::BigBlindAnte
+            ]
+        );
+        let bets = game.hand_history.forced_bets();
+        assert_eq!(bets[1].player_id, bets[2].player_id);
+        assert_eq!(game.antes_collected, 10);
+        assert_eq!(game.pot, 25);
+        assert_eq!(game.players[&bets[2].player_id].current_bet, 10);
     }
 
     #[test]
-    fn test_betting_round_raise() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 1000);
-        game.add_player("p2".to_string(), "Player2".to_string(), 1000);
+    fn test_utg_straddle_acts_last_and_raises_min_raise() {
+        // p3 is on the button, so p4 and p1 post the blinds and p2 is under the gun.
+        let mut game = create_straddle_game(StraddleRule::UnderTheGun);
 
-        let player_to_act = game.get_player_to_act().unwrap();
-        let player_id = player_to_act.id.clone();
-        let player = game.players.get(&player_id).unwrap();
-        let initial_chips = player.chips;
-        let initial_bet = player.current_bet;
+        assert_eq!(
+            forced_bets_by(&game),
+            vec![
+                ("p4".to_string(), ForcedBetKind::SmallBlind),
+                ("p1".to_string(), ForcedBetKind::BigBlind),
+                ("p2".to_string(), ForcedBetKind::Straddle),
+            ]
+        );
+        assert_eq!(game.players["p2"].current_bet, 20);
+        assert_eq!(game.min_raise, 40);
+        assert_eq!(game.current_player_id.as_deref(), Some("p3"));
 
-        let raise_amount = 20;
-        let current_table_bet = game.get_current_bet();
-        let total_bet = current_table_bet + raise_amount;
-        let actual_deduction = total_bet - initial_bet;
+        for player_id in ["p3", "p4", "p1"] {
+            game.handle_action(player_id, PlayerAction::Call).unwrap();
+        }
+        // Everyone has called the straddle, but the straddler still has the option.
+        assert_eq!(game.current_street, Street::Preflop);
+        assert_eq!(game.current_player_id.as_deref(), Some("p2"));
+
+        game.handle_action("p2", PlayerAction::Check).unwrap();
+        assert_eq!(game.current_street, Street::Flop);
+        assert!(game
+            .hand_history
+            .events()
+            .contains(&HandEvent::Street(Street::Flop)));
+        // After the flop the small blind, first left of the button, acts first.
+        assert_eq!(game.current_player_id.as_deref(), Some("p4"));
+    }
 
-        let result = game.handle_action(&player_id, PlayerAction::Raise(20));
-        assert!(result.is_ok());
+    #[test]
+    fn test_mississippi_straddle_from_button() {
+        let game = create_straddle_game(StraddleRule::Mississippi);
+
+        let straddle = game.hand_history.forced_bets().pop().unwrap();
+        assert_eq!(straddle.kind, ForcedBetKind::Straddle);
+        assert_eq!(straddle.player_id, "p3");
+        // With the button straddling, the small blind is first to act.
+        assert_eq!(game.current_player_id.as_deref(), Some("p4"));
+    }
 
-        let p_updated = game.players.get(&player_id).unwrap();
-        assert_eq!(p_updated.chips, initial_chips - actual_deduction);
-        assert_eq!(p_updated.current_bet, total_bet);
+    #[test]
+    fn test_straddle_requires_table_rule() {
+        let mut game = create_heads_up_game(TableConfig::new(5, 10));
+        assert!(matches!(
+            game.set_straddle("p1", true),
+            Err(ServerError::StraddleNotAllowed)
+        ));
+
+        let mut game = create_straddle_game(StraddleRule::UnderTheGun);
+        for i in 1..=4 {
+            game.set_straddle(&format!("p{}", i), false).unwrap();
+        }
+        game.start_hand();
+        assert!(!forced_bet_kinds(&game).contains(&ForcedBetKind::Straddle));
+        assert_eq!(game.min_raise, 20);
     }
 
     #[test]
-    fn test_betting_round_all_in() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 100);
-        game.add_player("p2".to_string(), "Player2".to_string(), 1000);
+    fn test_heads_up_button_posts_small_blind_and_acts_first() {
+        let mut game = create_heads_up_game(TableConfig::new(5, 10));
+        let button = game
+            .seats
+            .occupant(game.dealer_position)
+            .unwrap()
+            .to_string();
+        let other = if button == "p1" { "p2" } else { "p1" };
 
-        let player_to_act = game.get_player_to_act().unwrap();
-        let player_id = player_to_act.id.clone();
+        assert_eq!(
+            forced_bets_by(&game),
+            vec![
+                (button.clone(), ForcedBetKind::SmallBlind),
+                (other.to_string(), ForcedBetKind::BigBlind),
+            ]
+        );
+        assert_eq!(game.current_player_id.as_deref(), Some(button.as_str()));
 
-        let result = game.handle_action(&player_id, PlayerAction::AllIn);
-        assert!(result.is_ok());
+        game.handle_action(&button, PlayerAction::Call).unwrap();
+        game.handle_action(other, PlayerAction::Check).unwrap();
+        assert_eq!(game.current_street, Street::Flop);
+        assert_eq!(game.current_player_id.as_deref(), Some(other));
 
-        let player = game.players.get(&player_id).unwrap();
-        assert_eq!(player.chips, 0);
-        assert!(player.is_all_in);
+        // The blinds swap each hand, so nobody posts the big blind twice in a row.
+        game.start_hand();
+        assert_eq!(
+            forced_bets_by(&game),
+            vec![
+                (other.to_string(), ForcedBetKind::SmallBlind),
+                (button.clone(), ForcedBetKind::BigBlind),
+            ]
+        );
     }
 
     #[test]
-    fn test_fewer_than_five_cards_high_card() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let poker_game = PokerGame::new("test".to_string(), 5, 10, tx);
-        let player = create_player_with_cards(vec![
-            Card::new(Suit::Hearts, Rank::Ace),
-            Card::new(Suit::Diamonds, Rank::King),
-            Card::new(Suit::Clubs, Rank::Queen),
-        ]);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::HighCard);
-        assert_eq!(eval.primary_rank, 14);
+    fn test_new_player_waits_for_big_blind() {
+        let mut game = create_heads_up_game(TableConfig::new(5, 10));
+        game.add_player("p3".to_string(), "Player3".to_string(), 1000)
+            .unwrap();
+        game.add_player("p4".to_string(), "Player4".to_string(), 1000)
+            .unwrap();
+        assert!(game.is_waiting_for_big_blind("p3"));
+
+        // The big blind moves from p2 to p3, who is dealt in; p4 keeps waiting.
+        game.start_hand();
+        assert!(forced_bets_by(&game).contains(&("p3".to_string(), ForcedBetKind::BigBlind)));
+        assert!(!game.is_waiting_for_big_blind("p3"));
+        assert!(game.is_waiting_for_big_blind("p4"));
+        assert!(game.players["p4"].is_folded);
+        assert!(game.players["p4"].hole_cards.is_empty());
+        assert_eq!(game.get_active_player_ids().len(), 3);
+
+        game.start_hand();
+        assert!(forced_bets_by(&game).contains(&("p4".to_string(), ForcedBetKind::BigBlind)));
+        assert_eq!(game.get_active_player_ids().len(), 4);
     }
 
     #[test]
-    fn test_empty_hand() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let poker_game = PokerGame::new("test".to_string(), 5, 10, tx);
-        let player = create_player_with_cards(vec![]);
-        let eval = poker_game.evaluate_hand(&player);
-        assert_eq!(eval.rank, HandRank::HighCard);
-        assert_eq!(eval.primary_rank, 0);
+    fn test_new_player_can_post_to_play_immediately() {
+        let mut game = create_heads_up_game(TableConfig::new(5, 10));
+        game.add_player("p3".to_string(), "Player3".to_string(), 1000)
+            .unwrap();
+        game.add_player("p4".to_string(), "Player4".to_string(), 1000)
+            .unwrap();
+        game.post_blind("p4").unwrap();
+        assert!(!game.is_waiting_for_big_blind("p4"));
+        assert!(matches!(
+            game.post_blind("p1"),
+            Err(ServerError::NoBlindsOwed)
+        ));
+
+        game.start_hand();
+        let bets = forced_bets_by(&game);
+        assert!(bets.contains(&("p3".to_string(), ForcedBetKind::BigBlind)));
+        assert!(bets.contains(&("p4".to_string(), ForcedBetKind::BigBlind)));
+        assert_eq!(game.players["p4"].current_bet, 10);
+        assert_eq!(game.players["p4"].hole_cards.len(), 2);
     }
 
     #[test]
-    fn test_side_pots_calculation() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 100);
-        game.add_player("p2".to_string(), "Player2".to_string(), 200);
-        game.add_player("p3".to_string(), "Player3".to_string(), 300);
+    fn test_dead_button_and_missed_blinds() {
+        // p2 has the button, p3 the small blind and p4 the big blind.
+        let mut game = create_four_handed_game(TableConfig::new(5, 10));
+        game.sit_out("p3");
+
+        // The big blind moves on to p1 and p4 posts the small blind; the button
+        // stays with p3's seat, even though p3 is sitting out.
+        game.start_hand();
+        assert_eq!(game.dealer_position, game.seat_of("p3").unwrap());
+        assert_eq!(
+            forced_bets_by(&game),
+            vec![
+                ("p4".to_string(), ForcedBetKind::SmallBlind),
+                ("p1".to_string(), ForcedBetKind::BigBlind),
+            ]
+        );
+        assert_eq!(game.current_player_id.as_deref(), Some("p2"));
+
+        // Next the big blind passes p3, who then owes it.
+        game.start_hand();
+        game.start_hand();
+        assert!(forced_bets_by(&game).contains(&("p4".to_string(), ForcedBetKind::BigBlind)));
+        assert!(game.blind_debts["p3"].missed_big);
+        assert!(!game.is_waiting_for_big_blind("p3"));
+
+        game.return_to_game("p3");
+        assert!(game.is_waiting_for_big_blind("p3"));
+        game.post_blind("p3").unwrap();
+        game.start_hand();
+        assert!(forced_bets_by(&game).contains(&("p3".to_string(), ForcedBetKind::BigBlind)));
+        assert!(!game.blind_debts["p3"].owes());
+    }
 
-        if let Some(p1) = game.players.get_mut("p1") {
-            p1.current_bet = 50;
-        }
-        if let Some(p2) = game.players.get_mut("p2") {
-            p2.current_bet = 100;
-        }
-        if let Some(p3) = game.players.get_mut("p3") {
-            p3.current_bet = 100;
-        }
+    #[test]
+    fn test_missed_small_blind_is_posted_dead() {
+        // p2 has the button, p3 the small blind and p4 the big blind.
+        let mut game = create_four_handed_game(TableConfig::new(5, 10));
+        game.sit_out("p4");
+
+        // p4 would have posted the small blind.
+        game.start_hand();
+        assert!(game.blind_debts["p4"].missed_small);
+        assert!(!forced_bet_kinds(&game).contains(&ForcedBetKind::SmallBlind));
+
+        game.return_to_game("p4");
+        game.post_blind("p4").unwrap();
+        game.start_hand();
+        let p4_bets: Vec<_> = game
+            .hand_history
+            .forced_bets()
+            .into_iter()
+            .filter(|bet| bet.player_id == "p4")
+            .map(|bet| (bet.kind, bet.amount))
+            .collect();
+        // Only the small blind was missed, so nothing live is owed.
+        assert_eq!(p4_bets, vec![(ForcedBetKind::DeadSmallBlind, 5)]);
+        assert_eq!(game.players["p4"].current_bet, 0);
+        assert_eq!(game.antes_collected, 5);
+    }
 
-        let pots = game.calculate_side_pots();
-        assert!(!pots.is_empty());
+    #[test]
+    fn test_heads_up_transition_keeps_big_blind_moving() {
+        // Three-handed: p1 on the button, p2 in the small blind, p3 in the big blind.
+        let mut game = create_heads_up_game(TableConfig::new(5, 10));
+        game.add_player("p3".to_string(), "Player3".to_string(), 1000)
+            .unwrap();
+        game.start_hand();
+        assert!(forced_bets_by(&game).contains(&("p3".to_string(), ForcedBetKind::BigBlind)));
 
-        let main_pot = pots
-            .iter()
-            .find(|(amount, players)| players.len() == 3 && *amount == 150);
-        assert!(
-            main_pot.is_some(),
-            "Should have main pot with all 3 players"
+        game.sit_out("p1");
+        game.start_hand();
+        assert_eq!(
+            forced_bets_by(&game),
+            vec![
+                ("p3".to_string(), ForcedBetKind::SmallBlind),
+                ("p2".to_string(), ForcedBetKind::BigBlind),
+            ]
         );
+        assert_eq!(game.dealer_position, game.seat_of("p3").unwrap());
+        assert_eq!(game.current_player_id.as_deref(), Some("p3"));
+    }
 
-        if let Some(p2) = game.players.get_mut("p2") {
-            p2.is_folded = true;
+    fn create_stud_game() -> PokerGame {
+        create_heads_up_game(TableConfig::for_variant(GameVariant::SevenCardStud, 5, 10))
+    }
+
+    #[test]
+    fn test_stud_deals_up_cards_and_posts_bring_in() {
+        let game = create_stud_game();
+        assert_eq!(game.max_players(), MAX_STUD_PLAYERS);
+        assert_eq!(game.current_street, Street::ThirdStreet);
+        assert!(game.community_cards.is_empty());
+        for player in game.players.values() {
+            assert_eq!(player.hole_cards.len(), 2);
+            assert_eq!(player.up_cards.len(), 1);
         }
 
-        let pots_after_fold = game.calculate_side_pots();
-        assert!(!pots_after_fold.is_empty());
+        // Both players ante 2 and the lowest up-card brings in for 5.
+        assert_eq!(game.antes_collected, 4);
+        assert_eq!(game.pot, 9);
+        let bring_in_id = game.bring_in_player_id().unwrap();
+        assert_eq!(game.players[&bring_in_id].current_bet, 5);
+        assert_ne!(
+            game.current_player_id.as_deref(),
+            Some(bring_in_id.as_str())
+        );
     }
 
     #[test]
-    fn test_all_in_pot_distribution() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 100);
-        game.add_player("p2".to_string(), "Player2".to_string(), 500);
+    fn test_stud_bring_in_is_lowest_up_card() {
+        let mut game = create_stud_game();
+        game.add_player("p3".to_string(), "Player3".to_string(), 1000)
+            .unwrap();
+        game.players.get_mut("p1").unwrap().up_cards = vec![card(Rank::Two, Suit::Spades)];
+        game.players.get_mut("p2").unwrap().up_cards = vec![card(Rank::Two, Suit::Clubs)];
+        game.players.get_mut("p3").unwrap().up_cards = vec![card(Rank::King, Suit::Hearts)];
 
-        if let Some(p1) = game.players.get_mut("p1") {
-            p1.current_bet = 100;
-            p1.is_all_in = true;
+        assert_eq!(game.bring_in_player_id().as_deref(), Some("p2"));
+    }
+
+    #[test]
+    fn test_stud_best_visible_hand_acts_first() {
+        let mut game = create_stud_game();
+        game.add_player("p3".to_string(), "Player3".to_string(), 1000)
+            .unwrap();
+        game.players.get_mut("p1").unwrap().up_cards = vec![
+            card(Rank::Ace, Suit::Spades),
+            card(Rank::King, Suit::Spades),
+        ];
+        game.players.get_mut("p2").unwrap().up_cards = vec![
+            card(Rank::Five, Suit::Clubs),
+            card(Rank::Five, Suit::Hearts),
+        ];
+        game.players.get_mut("p3").unwrap().up_cards = vec![
+            card(Rank::Queen, Suit::Hearts),
+            card(Rank::Jack, Suit::Hearts),
+        ];
+
+        assert_eq!(game.best_visible_hand_player_id().as_deref(), Some("p2"));
+    }
+
+    #[test]
+    fn test_stud_plays_through_seventh_street() {
+        let mut game = create_stud_game();
+        let bring_in_id = game.bring_in_player_id().unwrap();
+        let opener = game.current_player_id.clone().unwrap();
+
+        // Completing the bring-in is a fixed-limit bet to the small bet.
+        assert!(game
+            .handle_action(&opener, PlayerAction::Raise(10))
+            .is_err());
+        game.handle_action(&opener, PlayerAction::Raise(5)).unwrap();
+        assert_eq!(game.players[&opener].current_bet, 10);
+        game.handle_action(&bring_in_id, PlayerAction::Call)
+            .unwrap();
+
+        for (street, up_cards) in [
+            (Street::FourthStreet, 2),
+            (Street::FifthStreet, 3),
+            (Street::SixthStreet, 4),
+        ] {
+            assert_eq!(game.current_street, street);
+            assert_eq!(game.current_player_id, game.best_visible_hand_player_id());
+            for player in game.players.values() {
+                assert_eq!(player.up_cards.len(), up_cards);
+            }
+            let first = game.current_player_id.clone().unwrap();
+            game.handle_action(&first, PlayerAction::Check).unwrap();
+            let second = game.current_player_id.clone().unwrap();
+            game.handle_action(&second, PlayerAction::Check).unwrap();
         }
-        if let Some(p2) = game.players.get_mut("p2") {
-            p2.current_bet = 200;
+
+        assert_eq!(game.current_street, Street::SeventhStreet);
+        for player in game.players.values() {
+            assert_eq!(player.hole_cards.len(), 3);
+            assert_eq!(player.up_cards.len(), 4);
+            assert_eq!(player.total_bet, 10);
         }
+    }
+
+    #[test]
+    fn test_stud_shares_community_card_when_deck_runs_out() {
+        let mut game = create_stud_game();
+        game.current_street = Street::SeventhStreet;
+        game.deck.truncate(1);
 
-        let pots = game.calculate_side_pots();
-        assert!(!pots.is_empty());
+        game.deal_seventh_street();
 
-        let side_pot = pots.iter().find(|(_amount, players)| {
-            players.contains(&"p1".to_string()) && players.contains(&"p2".to_string())
-        });
-        assert!(side_pot.is_some());
+        assert_eq!(game.community_cards.len(), 1);
+        for player in game.players.values() {
+            assert_eq!(player.hole_cards.len(), 2);
+        }
     }
 
     #[test]
-    fn test_validate_bet_amount_positive() {
+    fn test_razz_best_low_wins() {
         let tx = tokio::sync::broadcast::channel(100).0;
-        let game = PokerGame::new("test".to_string(), 5, 10, tx);
-        let player = PlayerState::new("p1".to_string(), "Player1".to_string(), 1000);
+        let mut game = PokerGame::with_config(
+            "test".to_string(),
+            TableConfig::for_variant(GameVariant::Razz, 5, 10),
+            tx,
+        );
+        seat_for_showdown(
+            &mut game,
+            "p1",
+            vec![
+                card(Rank::King, Suit::Clubs),
+                card(Rank::King, Suit::Hearts),
+            ],
+            100,
+        );
+        game.players.get_mut("p1").unwrap().up_cards = vec![
+            card(Rank::Ace, Suit::Clubs),
+            card(Rank::Two, Suit::Clubs),
+            card(Rank::Three, Suit::Clubs),
+            card(Rank::Four, Suit::Clubs),
+            card(Rank::Six, Suit::Clubs),
+        ];
+        seat_for_showdown(
+            &mut game,
+            "p2",
+            vec![
+                card(Rank::Nine, Suit::Spades),
+                card(Rank::Nine, Suit::Hearts),
+            ],
+            100,
+        );
+        game.players.get_mut("p2").unwrap().up_cards = vec![
+            card(Rank::Two, Suit::Diamonds),
+            card(Rank::Three, Suit::Diamonds),
+            card(Rank::Four, Suit::Diamonds),
+            card(Rank::Five, Suit::Diamonds),
+            card(Rank::Seven, Suit::Diamonds),
+        ];
 
-        let result = game.validate_bet_amount(&player, 100, 0, 100);
-        assert!(result.is_ok());
+        let (update, payouts) = game
+            .resolve_showdown(&game.calculate_side_pots(), 0, 1)
+            .unwrap();
+        assert_eq!(update.winners, vec!["p1".to_string()]);
+        assert_eq!(payout_for(&payouts, "p1"), 200);
+        let (_, _, category, description) = &update.hands[0];
+        assert_eq!(category, "Low");
+        assert_eq!(description, "6-4-3-2-A low");
     }
 
     #[test]
-    fn test_validate_bet_amount_zero() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let game = PokerGame::new("test".to_string(), 5, 10, tx);
-        let player = PlayerState::new("p1".to_string(), "Player1".to_string(), 1000);
+    fn test_razz_bring_in_is_highest_card() {
+        let mut game = create_heads_up_game(TableConfig::for_variant(GameVariant::Razz, 5, 10));
+        game.add_player("p3".to_string(), "Player3".to_string(), 1000)
+            .unwrap();
+        game.players.get_mut("p1").unwrap().up_cards = vec![card(Rank::King, Suit::Clubs)];
+        game.players.get_mut("p2").unwrap().up_cards = vec![card(Rank::King, Suit::Spades)];
+        game.players.get_mut("p3").unwrap().up_cards = vec![card(Rank::Ace, Suit::Hearts)];
 
-        assert!(game.validate_bet_amount(&player, 0, 100, 100).is_err());
-        assert!(game.validate_bet_amount(&player, -100, 100, 100).is_err());
+        assert_eq!(game.bring_in_player_id().as_deref(), Some("p2"));
     }
 
     #[test]
-    fn test_validate_bet_amount_exceeds_chips() {
+    fn test_stud_hi_lo_splits_high_and_low() {
         let tx = tokio::sync::broadcast::channel(100).0;
-        let game = PokerGame::new("test".to_string(), 5, 10, tx);
-        let player = PlayerState::new("p1".to_string(), "Player1".to_string(), 100);
+        let mut game = PokerGame::with_config(
+            "test".to_string(),
+            TableConfig::for_variant(GameVariant::SevenCardStudHiLo, 5, 10),
+            tx,
+        );
+        seat_for_showdown(
+            &mut game,
+            "p1",
+            vec![
+                card(Rank::King, Suit::Clubs),
+                card(Rank::King, Suit::Hearts),
+            ],
+            100,
+        );
+        game.players.get_mut("p1").unwrap().up_cards = vec![
+            card(Rank::King, Suit::Spades),
+            card(Rank::Queen, Suit::Clubs),
+            card(Rank::Jack, Suit::Diamonds),
+            card(Rank::Nine, Suit::Hearts),
+            card(Rank::Ten, Suit::Hearts),
+        ];
+        seat_for_showdown(
+            &mut game,
+            "p2",
+            vec![card(Rank::Ace, Suit::Spades), card(Rank::Two, Suit::Hearts)],
+            100,
+        );
+        game.players.get_mut("p2").unwrap().up_cards = vec![
+            card(Rank::Three, Suit::Diamonds),
+            card(Rank::Four, Suit::Diamonds),
+            card(Rank::Seven, Suit::Clubs),
+            card(Rank::Queen, Suit::Diamonds),
+            card(Rank::Queen, Suit::Spades),
+        ];
 
-        assert!(game.validate_bet_amount(&player, 200, 100, 100).is_err());
+        let (_, payouts) = game
+            .resolve_showdown(&game.calculate_side_pots(), 0, 1)
+            .unwrap();
+        assert_eq!(payout_for(&payouts, "p1"), 100);
+        assert_eq!(payout_for(&payouts, "p2"), 100);
     }
 
     #[test]
-    fn test_validate_raise_amount_success() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 1000);
+    fn test_rotation_switches_game_between_hands() {
+        let games = vec![
+            RotationGame::new(GameVariant::TexasHoldem, 10),
+            RotationGame::new(GameVariant::Razz, 10),
+        ];
+        let config = TableConfig {
+            rotation: GameRotation::new(games, RotationSchedule::EveryHands(1)),
+            ..TableConfig::new(5, 10)
+        };
+        let (tx, mut rx) = tokio::sync::broadcast::channel(100);
+        let mut game = PokerGame::with_config("test".to_string(), config, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
+        assert_eq!(game.variant(), GameVariant::TexasHoldem);
+        assert_eq!(game.betting_structure(), BettingStructure::NoLimit);
+
+        game.end_hand();
+        assert_eq!(game.variant(), GameVariant::Razz);
+        assert_eq!(game.betting_structure(), BettingStructure::fixed_limit(10));
+        assert_eq!(game.current_street, Street::ThirdStreet);
+
+        let mut last_update = None;
+        while let Ok(message) = rx.try_recv() {
+            if let ServerMessage::GameStateUpdate(update) = message {
+                last_update = Some(update);
+            }
+        }
+        let update = last_update.unwrap();
+        assert_eq!(update.variant, GameVariant::Razz);
+        assert_eq!(update.betting_structure, BettingStructure::fixed_limit(10));
+    }
 
-        let player = game.players.get("p1").unwrap();
-        let result = game.validate_raise_amount(player, 50);
-        assert!(result.is_ok());
+    fn create_triple_draw_game() -> PokerGame {
+        create_heads_up_game(TableConfig::for_variant(
+            GameVariant::DeuceToSevenTripleDraw,
+            5,
+            10,
+        ))
     }
 
     #[test]
-    fn test_validate_raise_amount_below_minimum() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 1000);
+    fn test_triple_draw_betting_and_draw_rounds() {
+        let mut game = create_triple_draw_game();
+        assert_eq!(game.current_street, Street::PreDraw);
+        for player in game.players.values() {
+            assert_eq!(player.hole_cards.len(), 5);
+        }
 
-        let player = game.players.get("p1").unwrap();
-        game.min_raise = 100;
-        let result = game.validate_raise_amount(player, 50);
-        assert!(result.is_err());
+        let small_blind = game.current_player_id.clone().unwrap();
+        game.handle_action(&small_blind, PlayerAction::Call)
+            .unwrap();
+        let big_blind = game.current_player_id.clone().unwrap();
+        game.handle_action(&big_blind, PlayerAction::Check).unwrap();
+
+        assert_eq!(game.current_street, Street::FirstDraw);
+        assert_eq!(game.pending_draws.len(), 2);
+
+        let drawer = game.current_player_id.clone().unwrap();
+        assert!(matches!(
+            game.handle_action(&drawer, PlayerAction::Check),
+            Err(ServerError::InvalidDraw(_))
+        ));
+
+        let hand = game.players[&drawer].hole_cards.clone();
+        let not_held = game.deck[0];
+        assert!(matches!(
+            game.handle_action(&drawer, PlayerAction::Draw(vec![not_held])),
+            Err(ServerError::InvalidDraw(_))
+        ));
+
+        let discards = vec![hand[0], hand[1]];
+        game.handle_action(&drawer, PlayerAction::Draw(discards.clone()))
+            .unwrap();
+        let new_hand = &game.players[&drawer].hole_cards;
+        assert_eq!(new_hand.len(), 5);
+        assert!(discards.iter().all(|card| !new_hand.contains(card)));
+
+        let pat = game.current_player_id.clone().unwrap();
+        assert_ne!(pat, drawer);
+        let pat_hand = game.players[&pat].hole_cards.clone();
+        game.handle_action(&pat, PlayerAction::Draw(vec![]))
+            .unwrap();
+        assert_eq!(game.players[&pat].hole_cards, pat_hand);
+        assert!(game.pending_draws.is_empty());
+
+        let first = game.current_player_id.clone().unwrap();
+        game.handle_action(&first, PlayerAction::Check).unwrap();
+        let second = game.current_player_id.clone().unwrap();
+        game.handle_action(&second, PlayerAction::Check).unwrap();
+        assert_eq!(game.current_street, Street::SecondDraw);
+        assert_eq!(game.pending_draws.len(), 2);
     }
 
     #[test]
-    fn test_all_in_partial_call() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 1000);
-        game.add_player("p2".to_string(), "Player2".to_string(), 200);
+    fn test_draw_reshuffles_discards_when_deck_runs_out() {
+        let mut game = create_triple_draw_game();
+        game.begin_draw();
+        game.deck.clear();
+        game.discards = vec![
+            card(Rank::Ace, Suit::Spades),
+            card(Rank::King, Suit::Spades),
+        ];
 
-        let player_to_act = game.get_player_to_act().unwrap();
-        let player_id = player_to_act.id.clone();
+        let drawer = game.current_player_id.clone().unwrap();
+        let discard = game.players[&drawer].hole_cards[0];
+        game.handle_action(&drawer, PlayerAction::Draw(vec![discard]))
+            .unwrap();
 
-        game.handle_action(&player_id, PlayerAction::Raise(100))
+        assert_eq!(game.players[&drawer].hole_cards.len(), 5);
+        assert_eq!(game.deck.len(), 1);
+        assert_eq!(game.discards, vec![discard]);
+    }
+
+    #[test]
+    fn test_single_draw_has_one_draw() {
+        let single = GameVariant::DeuceToSevenSingleDraw;
+        assert_eq!(single.next_street(Street::PreDraw), Street::FirstDraw);
+        assert_eq!(single.next_street(Street::FirstDraw), Street::Showdown);
+
+        let triple = GameVariant::DeuceToSevenTripleDraw;
+        assert_eq!(triple.next_street(Street::FirstDraw), Street::SecondDraw);
+        assert_eq!(triple.next_street(Street::ThirdDraw), Street::Showdown);
+    }
+
+    fn create_run_out_game(
+        config: TableConfig,
+        chips: [i32; 2],
+    ) -> (PokerGame, broadcast::Receiver<ServerMessage>) {
+        let tx = tokio::sync::broadcast::channel(1000).0;
+        let rx = tx.subscribe();
+        let mut game = PokerGame::with_config("test".to_string(), config, tx);
+        game.add_player("p1".to_string(), "Player1".to_string(), chips[0])
             .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), chips[1])
+            .unwrap();
+        (game, rx)
+    }
 
-        let next_player = game.get_player_to_act();
-        assert!(
-            next_player.is_some(),
-            "Should have a player to act after raise"
-        );
-        let next_player = next_player.unwrap();
-        let next_player_id = next_player.id.clone();
+    fn all_in_and_call(game: &mut PokerGame) {
+        let first = game.current_player_id.clone().unwrap();
+        game.handle_action(&first, PlayerAction::AllIn).unwrap();
+        let second = game.current_player_id.clone().unwrap();
+        game.handle_action(&second, PlayerAction::Call).unwrap();
+    }
 
-        let result = game.handle_action(&next_player_id, PlayerAction::AllIn);
-        assert!(
-            result.is_ok(),
-            "All-in should succeed after raise: {:?}",
-            result
-        );
+    fn received_showdown(rx: &mut broadcast::Receiver<ServerMessage>) -> Option<ShowdownUpdate> {
+        std::iter::from_fn(|| rx.try_recv().ok()).find_map(|message| match message {
+            ServerMessage::Showdown(update) => Some(update),
+            _ => None,
+        })
+    }
 
-        let p = game.players.get(&next_player_id).unwrap();
-        assert_eq!(p.chips, 0, "Player should have 0 chips after all-in");
-        assert!(p.is_all_in, "Player should be marked as all-in");
+    fn chips_at_table(game: &PokerGame) -> i32 {
+        game.players.values().map(|p| p.chips + p.current_bet).sum()
     }
 
     #[test]
-    fn test_calculate_side_pots_all_in_scenario() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 50);
-        game.add_player("p2".to_string(), "Player2".to_string(), 100);
-        game.add_player("p3".to_string(), "Player3".to_string(), 100);
+    fn test_all_in_hand_runs_out_without_betting() {
+        let (mut game, mut rx) = create_run_out_game(TableConfig::new(5, 10), [1000, 1000]);
+        all_in_and_call(&mut game);
+
+        assert!(game.is_running_out());
+        assert!(game.community_cards.is_empty());
+        assert!(matches!(
+            game.handle_action("p1", PlayerAction::Check),
+            Err(ServerError::NotYourTurn)
+        ));
+
+        let revealed = std::iter::from_fn(|| rx.try_recv().ok())
+            .find_map(|message| match message {
+                ServerMessage::HandsRevealed(update) => Some(update),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(revealed.hands.len(), 2);
+        assert!(revealed.hands.iter().all(|(_, cards)| cards.len() == 2));
+        assert_eq!(revealed.runs, 1);
 
-        if let Some(p1) = game.players.get_mut("p1") {
-            p1.current_bet = 50;
-            p1.is_all_in = true;
-        }
-        if let Some(p2) = game.players.get_mut("p2") {
-            p2.current_bet = 100;
-        }
-        if let Some(p3) = game.players.get_mut("p3") {
-            p3.current_bet = 100;
+        let mut streets = Vec::new();
+        while game.run_out_next_street() {
+            streets.push(game.current_street);
         }
+        assert_eq!(streets, vec![Street::Flop, Street::Turn, Street::River]);
 
-        let pots = game.calculate_side_pots();
+        let showdown = received_showdown(&mut rx).unwrap();
+        assert_eq!(showdown.boards.len(), 1);
+        assert_eq!(showdown.boards[0].community_cards.len(), 5);
+        assert_eq!(chips_at_table(&game), 2000);
+    }
 
-        let main_pot_amount: i32 = pots
-            .iter()
-            .find(|(_, players)| players.len() == 3)
-            .map(|(amount, _)| *amount)
-            .unwrap_or(0);
-        assert_eq!(
-            main_pot_amount, 150,
-            "Main pot should have 50 from each player"
-        );
+    #[test]
+    fn test_blinds_that_put_everyone_all_in_run_the_board_out() {
+        let (mut game, mut rx) = create_run_out_game(TableConfig::new(5, 10), [5, 10]);
+
+        assert!(game.is_running_out());
+        while game.run_out_next_street() {}
+        let showdown = received_showdown(&mut rx).unwrap();
+        assert_eq!(showdown.boards[0].community_cards.len(), 5);
+        // Both players may still have chips, in which case the next hand is under way.
+        let in_play = match game.game_stage {
+            GameStage::WaitingForPlayers => 0,
+            _ => game.pot,
+        };
+        let chips: i32 = game.players.values().map(|p| p.chips).sum();
+        assert_eq!(chips + in_play, 15);
+    }
 
-        let side_pot_amount: i32 = pots
-            .iter()
-            .filter(|(_, players)| players.len() == 2)
-            .map(|(amount, _)| *amount)
-            .sum();
-        assert_eq!(
-            side_pot_amount, 100,
-            "Side pot should have 50 from p2 and p3"
-        );
+    #[test]
+    fn test_last_player_all_in_wins_the_pot_when_the_rest_fold() {
+        let (mut game, mut rx) = create_run_out_game(TableConfig::new(5, 10), [1000, 1000]);
+        let hand_number = game.hand_number;
+
+        // Heads-up the small blind acts first, so the big blind folds to the shove.
+        let shover = game.current_player_id.clone().unwrap();
+        game.handle_action(&shover, PlayerAction::AllIn).unwrap();
+        let folder = game.current_player_id.clone().unwrap();
+        game.handle_action(&folder, PlayerAction::Fold).unwrap();
+
+        assert_eq!(game.hand_number, hand_number + 1);
+        assert!(received_showdown(&mut rx).is_none());
+        // The next hand has started, with its blinds posted.
+        let stack = |id: &str| game.players[id].chips + game.players[id].current_bet;
+        assert_eq!(stack(&shover), 1010);
+        assert_eq!(stack(&folder), 990);
     }
 
     #[test]
-    fn test_calculate_new_pot_overflow() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.pot = MAX_POT - 100;
+    fn test_run_it_twice_deals_two_boards() {
+        let config = TableConfig {
+            max_runs: 2,
+            ..TableConfig::new(5, 10)
+        };
+        let (mut game, mut rx) = create_run_out_game(config, [1000, 400]);
+        game.set_run_it("p1", 2).unwrap();
+        game.set_run_it("p2", 2).unwrap();
+        all_in_and_call(&mut game);
+        assert_eq!(game.run_out.as_ref().map(|r| r.runs), Some(2));
+
+        let mut streets = 0;
+        while game.run_out_next_street() {
+            streets += 1;
+        }
+        assert_eq!(streets, 6);
+
+        let showdown = received_showdown(&mut rx).unwrap();
+        assert_eq!(showdown.boards.len(), 2);
+        let first = &showdown.boards[0].community_cards;
+        let second = &showdown.boards[1].community_cards;
+        assert_eq!(first.len(), 5);
+        assert_eq!(second.len(), 5);
+        assert!(first.iter().all(|card| !second.contains(card)));
+        assert_eq!(showdown.community_cards, *first);
+        assert_eq!(chips_at_table(&game), 1400);
+    }
 
-        let result = game.calculate_new_pot(200);
-        assert!(
-            result.is_none(),
-            "Should fail when pot would exceed MAX_POT"
-        );
+    #[test]
+    fn test_run_it_needs_every_player_to_agree() {
+        let config = TableConfig {
+            max_runs: 2,
+            ..TableConfig::new(5, 10)
+        };
+        let (mut game, _rx) = create_run_out_game(config, [1000, 1000]);
+        assert!(matches!(
+            game.set_run_it("p1", 3),
+            Err(ServerError::TooManyRuns(2))
+        ));
+        game.set_run_it("p1", 2).unwrap();
+        all_in_and_call(&mut game);
+        assert_eq!(game.run_out.as_ref().map(|r| r.runs), Some(1));
+
+        let mut single = create_heads_up_game(TableConfig::new(5, 10));
+        assert!(matches!(
+            single.set_run_it("p1", 2),
+            Err(ServerError::TooManyRuns(1))
+        ));
     }
 
     #[test]
-    fn test_calculate_new_pot_success() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.pot = 100;
+    fn test_all_in_draw_hand_still_draws() {
+        let config = TableConfig {
+            betting_structure: BettingStructure::NoLimit,
+            ..TableConfig::for_variant(GameVariant::DeuceToSevenSingleDraw, 5, 10)
+        };
+        let (mut game, mut rx) = create_run_out_game(config, [1000, 1000]);
+        all_in_and_call(&mut game);
 
-        let new_pot = game.calculate_new_pot(50);
-        assert!(new_pot.is_some());
-        assert_eq!(new_pot.unwrap(), 150);
+        assert!(!game.run_out_next_street());
+        assert_eq!(game.current_street, Street::FirstDraw);
+        assert_eq!(game.pending_draws.len(), 2);
+        for _ in 0..2 {
+            let drawer = game.current_player_id.clone().unwrap();
+            game.handle_action(&drawer, PlayerAction::Draw(vec![]))
+                .unwrap();
+        }
 
-        game.pot = new_pot.unwrap();
-        assert_eq!(game.pot, 150);
+        assert!(game.is_running_out());
+        assert!(!game.run_out_next_street());
+        assert!(received_showdown(&mut rx).is_some());
     }
 
     #[test]
-    fn test_calculate_new_pot_negative() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
+    fn test_run_share_gives_odd_chips_to_first_board() {
+        assert_eq!(run_share(15, 0, 2), 8);
+        assert_eq!(run_share(15, 1, 2), 7);
+        assert_eq!(run_share(15, 0, 1), 15);
+    }
 
-        assert!(game.calculate_new_pot(-50).is_none());
+    fn five_percent_rake() -> TableConfig {
+        TableConfig {
+            rake: RakeStructure {
+                rate_bps: 500,
+                caps: Vec::new(),
+                no_flop_no_drop: true,
+            },
+            ..TableConfig::new(5, 10)
+        }
     }
 
     #[test]
-    fn test_bet_validation_negative_amount() {
+    fn test_rake_skips_uncalled_chips_in_side_pots() {
         let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 1000);
-        game.add_player("p2".to_string(), "Player2".to_string(), 1000);
+        let mut game = PokerGame::with_config("test".to_string(), five_percent_rake(), tx);
+        let hand = vec![card(Rank::Two, Suit::Clubs), card(Rank::Three, Suit::Clubs)];
+        seat_for_showdown(&mut game, "p1", hand.clone(), 100);
+        seat_for_showdown(&mut game, "p2", hand.clone(), 300);
+        seat_for_showdown(&mut game, "p3", hand, 500);
+
+        let unraked: Vec<i32> = game.take_rake().iter().map(|(amount, _)| *amount).collect();
+        assert_eq!(unraked, vec![300, 400, 200]);
+        assert!(game.rake_ledger().entries().is_empty());
+
+        game.flop_seen = true;
+        let raked: Vec<i32> = game.take_rake().iter().map(|(amount, _)| *amount).collect();
+        // 5% of the 700 contested chips; p3's 200 uncalled chips are not raked.
+        assert_eq!(raked, vec![285, 380, 200]);
+        assert_eq!(game.rake_ledger().entries()[0].pots, vec![15, 20, 0]);
+        assert_eq!(game.rake_ledger().total(), 35);
+    }
 
-        let player_to_act = game.get_player_to_act().unwrap();
-        let player_id = player_to_act.id.clone();
+    #[test]
+    fn test_rake_taken_before_showdown_pays_out() {
+        let (mut game, mut rx) = create_run_out_game(five_percent_rake(), [1000, 1000]);
+        all_in_and_call(&mut game);
+        while game.run_out_next_street() {}
+
+        assert!(received_showdown(&mut rx).is_some());
+        assert_eq!(game.rake_ledger().total(), 100);
+        assert_eq!(chips_at_table(&game), 1900);
+    }
 
-        let result = game.handle_action(&player_id, PlayerAction::Bet(-100));
-        assert!(result.is_err());
-        let err = result.unwrap_err().to_string();
-        assert!(err.contains("InvalidAmount") || err.contains("positive"));
+    #[test]
+    fn test_rebuy_and_top_up_wait_for_the_hand_to_end() {
+        let config = TableConfig {
+            buy_in: BuyInRules {
+                max: 2000,
+                ..BuyInRules::default()
+            },
+            ..TableConfig::new(5, 10)
+        };
+        let (mut game, _rx) = create_run_out_game(config, [1000, 500]);
+        let p2_chips = game.players["p2"].chips;
+
+        game.top_up("p2", 600).unwrap();
+        assert_eq!(game.players["p2"].chips, p2_chips);
+        assert!(matches!(
+            game.top_up("p2", 1000),
+            Err(ServerError::TopUpExceedsMax(room)) if room == 1400 - p2_chips
+        ));
+        assert!(matches!(
+            game.rebuy("p1", 500),
+            Err(ServerError::RebuyNotBusted)
+        ));
+
+        all_in_and_call(&mut game);
+        while game.run_out_next_street() {}
+
+        // Both players have chips for the next hand once the top-up goes in.
+        assert_eq!(chips_at_table(&game), 2100);
+        assert!(matches!(game.game_stage, GameStage::BettingRound(_)));
     }
 
     #[test]
-    fn test_bet_validation_exceeds_chips() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 100);
-        game.add_player("p2".to_string(), "Player2".to_string(), 1000);
+    fn test_cash_out_between_hands() {
+        let (mut game, _rx) = create_run_out_game(TableConfig::new(5, 10), [1000, 1000]);
+        assert!(matches!(
+            game.cash_out("p1"),
+            Err(ServerError::CashOutInHand)
+        ));
+
+        assert!(matches!(
+            game.buy_in("p3".to_string(), "Player3".to_string(), 100, None),
+            Err(ServerError::BuyInOutOfRange(200, 1000))
+        ));
+        game.buy_in("p3".to_string(), "Player3".to_string(), 800, None)
+            .unwrap();
+        // Not dealt in until the big blind reaches them, so free to leave.
+        assert_eq!(game.cash_out("p3").unwrap(), 800);
+        assert!(!game.players.contains_key("p3"));
+        assert_eq!(game.seat_of("p3"), None);
 
-        let player_to_act = game.get_player_to_act().unwrap();
-        let player_id = player_to_act.id.clone();
+        assert_eq!(game.buy_in_range(), (200, 1000));
+        game.buy_in("p3".to_string(), "Player3".to_string(), 500, None)
+            .unwrap();
+    }
 
-        let result = game.handle_action(&player_id, PlayerAction::Bet(500));
-        assert!(result.is_err());
-        let err = result.unwrap_err().to_string();
-        assert!(
-            err.contains("BetExceedsChips") || err.contains("chips") || err.contains("exceeds")
-        );
+    fn create_full_table(seat_offer_secs: u64) -> PokerGame {
+        let config = TableConfig {
+            seat_offer_secs,
+            ..TableConfig::new(5, 10)
+        };
+        let tx = tokio::sync::broadcast::channel(1000).0;
+        let mut game = PokerGame::with_config("test".to_string(), config, tx);
+        for i in 0..MAX_PLAYERS {
+            game.add_player(format!("p{}", i), format!("Player{}", i), 1000)
+                .unwrap();
+        }
+        game
     }
 
     #[test]
-    fn test_bet_validation_exceeds_max_bet() {
-        let tx = tokio::sync::broadcast::channel(100).0;
-        let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.set_max_bet_per_hand(100);
-        game.add_player("p1".to_string(), "Player1".to_string(), 1000);
-        game.add_player("p2".to_string(), "Player2".to_string(), 1000);
-
-        let player_to_act = game.get_player_to_act().unwrap();
-        let player_id = player_to_act.id.clone();
+    fn test_waitlist_holds_open_seat_for_next_player() {
+        let mut game = create_full_table(DEFAULT_SEAT_OFFER_SECS);
+        assert_eq!(game.join_waitlist("w1").unwrap(), 1);
+        assert_eq!(game.join_waitlist("w2").unwrap(), 2);
+        assert!(matches!(
+            game.join_waitlist("p0"),
+            Err(ServerError::AlreadySeated)
+        ));
+
+        // p5 sat down after the first hand was dealt, so is free to leave.
+        let seat = game.seat_of("p5").unwrap();
+        game.cash_out("p5").unwrap();
+        assert_eq!(game.seat_offer("w1"), Some(seat));
+        assert_eq!(game.seat_offer("w2"), None);
+
+        assert!(matches!(
+            game.buy_in("x".to_string(), "X".to_string(), 1000, None),
+            Err(ServerError::GameFull)
+        ));
+        assert!(matches!(
+            game.buy_in("w2".to_string(), "W2".to_string(), 1000, Some(seat)),
+            Err(ServerError::SeatTaken(s)) if s == seat
+        ));
+        assert!(matches!(
+            game.buy_in("w2".to_string(), "W2".to_string(), 1000, Some(MAX_PLAYERS)),
+            Err(ServerError::InvalidSeat(_))
+        ));
+
+        game.buy_in("w1".to_string(), "W1".to_string(), 1000, None)
+            .unwrap();
+        assert_eq!(game.seat_of("w1"), Some(seat));
+        assert_eq!(game.waitlisted_players().collect::<Vec<_>>(), vec!["w2"]);
+    }
 
-        let result = game.handle_action(&player_id, PlayerAction::Bet(200));
-        assert!(result.is_err());
-        let err = result.unwrap_err().to_string();
-        assert!(err.contains("Invalid") || err.contains("exceeds") || err.contains("maximum"));
+    #[test]
+    fn test_seat_offer_passes_on_when_it_runs_out() {
+        let mut game = create_full_table(0);
+        game.join_waitlist("w1").unwrap();
+        game.join_waitlist("w2").unwrap();
+        assert!(!game.claim_offer_timer());
+
+        let seat = game.seat_of("p5").unwrap();
+        game.cash_out("p5").unwrap();
+        assert!(game.claim_offer_timer());
+        assert!(!game.claim_offer_timer());
+
+        assert!(game.expire_seat_offers().is_some());
+        assert_eq!(game.seat_offer("w2"), Some(seat));
+        assert_eq!(game.waitlisted_players().collect::<Vec<_>>(), vec!["w2"]);
+
+        assert_eq!(game.expire_seat_offers(), None);
+        assert_eq!(game.waitlisted_players().count(), 0);
+        game.buy_in("x".to_string(), "X".to_string(), 1000, None)
+            .unwrap();
+        assert_eq!(game.seat_of("x"), Some(seat));
     }
 
     #[test]
-    fn test_all_in_handling() {
+    fn test_pause_holds_the_next_hand_and_new_blinds_wait_for_it() {
+        let (mut game, _rx) = create_run_out_game(TableConfig::new(5, 10), [1000, 1000]);
+        game.pause();
+        assert!(matches!(game.game_stage, GameStage::BettingRound(_)));
+        assert!(game.game_state_update().paused);
+
+        all_in_and_call(&mut game);
+        while game.run_out_next_street() {}
+        assert_eq!(game.game_stage, GameStage::Paused);
+        assert_eq!(game.hand_number, 1);
+        assert!(!game.is_hand_in_progress());
+
         let tx = tokio::sync::broadcast::channel(100).0;
         let mut game = PokerGame::new("test".to_string(), 5, 10, tx);
-        game.add_player("p1".to_string(), "Player1".to_string(), 50);
-        game.add_player("p2".to_string(), "Player2".to_string(), 1000);
+        game.pause();
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
+        assert_eq!(game.game_stage, GameStage::Paused);
+        assert!(game.set_next_blinds(20, 10).is_err());
+        game.set_next_blinds(10, 20).unwrap();
+        assert_eq!(game.big_blind, 10);
+
+        game.resume().unwrap();
+        assert!(matches!(game.resume(), Err(ServerError::NotPaused)));
+        assert!(matches!(game.game_stage, GameStage::BettingRound(_)));
+        assert_eq!(game.big_blind, 20);
+        assert_eq!(chips_at_table(&game), 2000);
+        assert_eq!(game.players.values().map(|p| p.current_bet).max(), Some(20));
+    }
 
-        let player_to_act = game.get_player_to_act().unwrap();
-        let player_id = player_to_act.id.clone();
+    #[test]
+    fn test_pause_freezes_the_turn_clock() {
+        let (mut game, _rx) = create_run_out_game(TableConfig::new(5, 10), [1000, 1000]);
+        let left = game.action_time_left().unwrap();
+        assert!(left <= Duration::from_secs(DEFAULT_ACTION_SECS));
+        assert!(game.claim_action_clock());
+
+        // Part way through the player's time, the host pauses.
+        game.action_deadline = Some(Instant::now() + Duration::from_secs(10));
+        game.pause();
+        let frozen = game.action_time_left().unwrap();
+        assert!(frozen <= Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(game.action_time_left(), Some(frozen));
+        // No one is on the clock while the table is paused.
+        assert_eq!(game.expire_action(), None);
+        assert!(!game.claim_action_clock());
+
+        game.resume().unwrap();
+        // The player picks up with the time they had left, not a fresh clock.
+        let resumed = game.action_time_left().unwrap();
+        assert!(resumed <= frozen && resumed > Duration::from_secs(9));
+        assert!(game.claim_action_clock());
+    }
 
-        let result = game.handle_action(&player_id, PlayerAction::AllIn);
-        assert!(result.is_ok(), "All-in should succeed: {:?}", result);
+    #[test]
+    fn test_player_out_of_time_checks_or_folds() {
+        let (mut game, _rx) = create_run_out_game(TableConfig::new(5, 10), [1000, 1000]);
+
+        // Facing the big blind, the small blind cannot check and so folds.
+        let small_blind = game.current_player_id.clone().unwrap();
+        game.action_deadline = Some(Instant::now());
+        assert!(game.expire_action().is_some());
+        assert!(game.players[&small_blind].is_folded);
+
+        // The big blind may check their option.
+        let big_blind = game.current_player_id.clone().unwrap();
+        game.action_deadline = Some(Instant::now());
+        game.expire_action();
+        assert!(!game.players[&big_blind].is_folded);
+        assert_eq!(game.current_street, Street::Flop);
+
+        let config = TableConfig {
+            action_secs: 0,
+            ..TableConfig::new(5, 10)
+        };
+        let (game, _rx) = create_run_out_game(config, [1000, 1000]);
+        assert_eq!(game.action_time_left(), None);
+    }
 
-        let p = game.players.get(&player_id).unwrap();
-        assert_eq!(p.chips, 0);
-        assert!(p.is_all_in);
-        assert!(p.has_acted);
+    /// Log sink shared with the test, so it can read what was written
+    #[derive(Clone, Default)]
+    struct SharedLog(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedLog {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_hand_events_carry_table_and_hand_fields() {
+        let log = SharedLog::default();
+        let writer = log.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_span_list(true)
+            .with_writer(move || writer.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let tx = tokio::sync::broadcast::channel(100).0;
+            let mut game = PokerGame::new("traced".to_string(), 5, 10, tx);
+            game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+                .unwrap();
+            game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+                .unwrap();
+            assert_eq!(game.hand_number(), 1);
+        });
+
+        let output = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+        let dealt = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .find(|event| event["fields"]["message"] == "Dealing hand")
+            .expect("dealing the hand should be logged");
+        let spans = dealt["spans"].as_array().unwrap();
+        assert_eq!(spans[0]["name"], "table");
+        assert_eq!(spans[0]["game_id"], "traced");
+        assert_eq!(spans[1]["name"], "hand");
+        assert_eq!(spans[1]["hand_number"], 1);
     }
 
     #[test]
@@ -0,0 +1,94 @@
+use poker_protocol::{ForcedBet, GameVariant, PlayerAction, Street};
+
+/// Something that happened during a hand, in the order it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandEvent {
+    /// An ante, blind, straddle or bring-in posted before the first voluntary action
+    ForcedBet(ForcedBet),
+    /// A player's betting action
+    Action {
+        player_id: String,
+        action: PlayerAction,
+    },
+    /// A player replaced cards in a draw game; the cards themselves stay private
+    Draw { player_id: String, discarded: usize },
+    /// Play moved on to a new street
+    Street(Street),
+}
+
+/// The record of a single hand as it was played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandHistory {
+    pub hand_number: i32,
+    pub variant: GameVariant,
+    events: Vec<HandEvent>,
+}
+
+impl HandHistory {
+    /// Starts an empty history for a new hand.
+    ///
+    /// # Arguments
+    /// * `hand_number` - The hand's number at the table
+    /// * `variant` - The game variant being dealt
+    pub fn new(hand_number: i32, variant: GameVariant) -> Self {
+        Self {
+            hand_number,
+            variant,
+            events: Vec::new(),
+        }
+    }
+
+    /// Appends an event to the history.
+    pub fn record(&mut self, event: HandEvent) {
+        self.events.push(event);
+    }
+
+    /// Returns every event recorded so far, oldest first.
+    #[allow(dead_code)]
+    pub fn events(&self) -> &[HandEvent] {
+        &self.events
+    }
+
+    /// Returns the forced bets posted this hand, in the order they were posted.
+    pub fn forced_bets(&self) -> Vec<ForcedBet> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                HandEvent::ForcedBet(bet) => Some(bet.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poker_protocol::ForcedBetKind;
+
+    #[test]
+    fn test_forced_bets_in_posting_order() {
+        let mut history = HandHistory::new(1, GameVariant::TexasHoldem);
+        history.record(HandEvent::ForcedBet(ForcedBet {
+            player_id: "p1".to_string(),
+            kind: ForcedBetKind::SmallBlind,
+            amount: 5,
+        }));
+        history.record(HandEvent::Action {
+            player_id: "p2".to_string(),
+            action: PlayerAction::Call,
+        });
+        history.record(HandEvent::ForcedBet(ForcedBet {
+            player_id: "p2".to_string(),
+            kind: ForcedBetKind::BigBlind,
+            amount: 10,
+        }));
+
+        let kinds: Vec<ForcedBetKind> = history.forced_bets().iter().map(|b| b.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![ForcedBetKind::SmallBlind, ForcedBetKind::BigBlind]
+        );
+        assert_eq!(history.events().len(), 3);
+    }
+}
@@ -0,0 +1,150 @@
+use poker_protocol::{Card, Rank};
+
+/// Highest card allowed in a qualifying low for eight-or-better split games.
+pub const EIGHT_OR_BETTER: u8 = 8;
+
+/// Number of cards in a low hand.
+const LOW_HAND_SIZE: usize = 5;
+
+/// An ace-to-five low hand.
+///
+/// Aces play low, and straights and flushes do not count against the hand.
+/// Ranks are stored highest first so that the derived ordering compares hands
+/// the way lowball does: the smaller value is the better low.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LowHand {
+    ranks: Vec<u8>,
+}
+
+impl LowHand {
+    /// Returns the low card values (ace = 1), highest first.
+    #[allow(dead_code)]
+    pub fn ranks(&self) -> &[u8] {
+        &self.ranks
+    }
+
+    /// Returns a human-readable description such as `8-6-4-2-A low`.
+    pub fn description(&self) -> String {
+        let cards: Vec<String> = self
+            .ranks
+            .iter()
+            .map(|&value| match value {
+                1 => "A".to_string(),
+                v => Rank::from_u8(v)
+                    .map(|r| r.to_string())
+                    .unwrap_or_else(|| "?".to_string()),
+            })
+            .collect();
+        format!("{} low", cards.join("-"))
+    }
+}
+
+/// Returns the value of a card in ace-to-five lowball, where the ace is 1.
+pub(crate) fn low_value(card: &Card) -> u8 {
+    match card.rank {
+        Rank::Ace => 1,
+        rank => rank as u8,
+    }
+}
+
+/// Finds the best qualifying ace-to-five low that can be made from `cards`.
+///
+/// # Arguments
+/// * `cards` - The cards available to the player
+/// * `qualifier` - The highest card value allowed in the low (e.g. 8)
+///
+/// # Returns
+/// The best five-card low with no paired ranks, or `None` if the cards do not
+/// contain five distinct ranks at or below the qualifier
+pub fn best_qualifying_low(cards: &[Card], qualifier: u8) -> Option<LowHand> {
+    let mut values: Vec<u8> = cards
+        .iter()
+        .map(low_value)
+        .filter(|&v| v <= qualifier)
+        .collect();
+    values.sort_unstable();
+    values.dedup();
+
+    if values.len() < LOW_HAND_SIZE {
+        return None;
+    }
+
+    let mut ranks: Vec<u8> = values.into_iter().take(LOW_HAND_SIZE).collect();
+    ranks.reverse();
+    Some(LowHand { ranks })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use poker_protocol::Suit;
+
+    fn cards(ranks: &[Rank]) -> Vec<Card> {
+        ranks
+            .iter()
+            .enumerate()
+            .map(|(i, &rank)| {
+                let suit = [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades][i % 4];
+                Card::new(suit, rank)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_wheel_is_best_low() {
+        let wheel = best_qualifying_low(
+            &cards(&[Rank::Ace, Rank::Two, Rank::Three, Rank::Four, Rank::Five]),
+            EIGHT_OR_BETTER,
+        )
+        .unwrap();
+        let six_low = best_qualifying_low(
+            &cards(&[Rank::Ace, Rank::Two, Rank::Three, Rank::Four, Rank::Six]),
+            EIGHT_OR_BETTER,
+        )
+        .unwrap();
+        assert!(wheel < six_low);
+        assert_eq!(wheel.description(), "5-4-3-2-A low");
+    }
+
+    #[test]
+    fn test_low_compares_from_the_top_card() {
+        let eight_six = best_qualifying_low(
+            &cards(&[Rank::Eight, Rank::Six, Rank::Four, Rank::Two, Rank::Ace]),
+            EIGHT_OR_BETTER,
+        )
+        .unwrap();
+        let eight_seven = best_qualifying_low(
+            &cards(&[Rank::Eight, Rank::Seven, Rank::Two, Rank::Three, Rank::Ace]),
+            EIGHT_OR_BETTER,
+        )
+        .unwrap();
+        assert!(eight_six < eight_seven);
+    }
+
+    #[test]
+    fn test_low_must_qualify() {
+        let nine_low = cards(&[Rank::Nine, Rank::Six, Rank::Four, Rank::Two, Rank::Ace]);
+        assert!(best_qualifying_low(&nine_low, EIGHT_OR_BETTER).is_none());
+
+        let paired = cards(&[Rank::Two, Rank::Two, Rank::Four, Rank::Five, Rank::Ace]);
+        assert!(best_qualifying_low(&paired, EIGHT_OR_BETTER).is_none());
+    }
+
+    #[test]
+    fn test_best_low_from_seven_cards() {
+        let low = best_qualifying_low(
+            &cards(&[
+                Rank::King,
+                Rank::Seven,
+                Rank::Five,
+                Rank::Five,
+                Rank::Three,
+                Rank::Two,
+                Rank::Ace,
+            ]),
+            EIGHT_OR_BETTER,
+        )
+        .unwrap();
+        assert_eq!(low.ranks(), &[7, 5, 3, 2, 1]);
+    }
+}
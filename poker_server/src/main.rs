@@ -1,31 +1,96 @@
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use clap::Parser;
 use futures::stream::StreamExt;
 use futures::SinkExt;
-use log::{debug, error, info, warn};
 use parking_lot::Mutex;
-use poker_protocol::{ClientMessage, HmacKey, NonceCache, ServerMessage, HMAC_SECRET_LEN};
+use poker_protocol::{
+    AnteStructure, BettingStructure, ClientMessage, CreateTableRequest, GameVariant, Hello,
+    HmacKey, JoinTableRequest, NonceCache, ProtocolError, ServerMessage, StraddleRule,
+};
 use rand::seq::SliceRandom;
 use tokio::net::TcpListener;
 use tokio::signal;
-use tokio_tungstenite::accept_async;
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::handshake::server::{
+    Callback, ErrorResponse, Request, Response,
+};
+use tokio_tungstenite::tungstenite::http::StatusCode;
 use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{accept_async, accept_hdr_async};
+use tracing::{debug, error, info, info_span, warn, Instrument, Span};
+use tracing_subscriber::EnvFilter;
 use uuid::Uuid;
 
+mod admin;
+mod betting;
+mod buy_in;
+mod config;
+mod evaluator;
 mod game;
+mod history;
+mod lowball;
+mod metrics;
+mod private_table;
+mod rake;
+mod rotation;
+mod seating;
 mod server;
-
-use crate::game::PokerGame;
+mod settlement;
+mod tls;
+mod tv_feed;
+
+use crate::admin::{handle_admin_connection, AuditLog};
+use crate::buy_in::BuyInRules;
+use crate::config::{
+    restart_only_server_changes, restart_only_table_changes, Cli, ServerLimits, Settings,
+};
+use crate::game::{TableConfig, DEFAULT_ACTION_SECS, DEFAULT_SEAT_OFFER_SECS};
+use crate::metrics::{Metrics, RateLimit, VerificationFailure};
+use crate::rake::{RakeCap, RakeStructure};
+use crate::rotation::{GameRotation, RotationGame, RotationSchedule};
 use crate::server::PokerServer;
+use crate::tls::TlsAcceptor;
+use crate::tv_feed::{TvFeed, DEFAULT_TV_DELAY_SECS};
 
 pub const SHUTDOWN_TIMEOUT_SECS: u64 = 5;
 /// Default server bind address
 pub const DEFAULT_SERVER_ADDR: &str = "127.0.0.1:8080";
 /// Environment variable for server bind address
 pub const ENV_SERVER_ADDR: &str = "POKER_SERVER_ADDR";
+/// Default bind address for the delayed observer (TV) feed
+pub const DEFAULT_TV_ADDR: &str = "127.0.0.1:8081";
+/// Environment variable for the observer feed bind address
+pub const ENV_TV_ADDR: &str = "POKER_TV_ADDR";
+/// Environment variable choosing the log format: `json` writes one JSON
+/// object per event carrying the fields of every enclosing span
+pub const ENV_LOG_FORMAT: &str = "POKER_LOG_FORMAT";
+/// Environment variable for the PEM certificate chain served over TLS
+pub const ENV_TLS_CERT: &str = "POKER_TLS_CERT";
+/// Environment variable for the PEM private key of the TLS certificate
+pub const ENV_TLS_KEY: &str = "POKER_TLS_KEY";
+/// Environment variable for the CA that signs admin client certificates
+pub const ENV_ADMIN_CLIENT_CA: &str = "POKER_ADMIN_CLIENT_CA";
+/// Default bind address for the Prometheus metrics endpoint
+pub const DEFAULT_METRICS_ADDR: &str = "127.0.0.1:9090";
+/// Environment variable for the metrics endpoint bind address
+pub const ENV_METRICS_ADDR: &str = "POKER_METRICS_ADDR";
+/// Default bind address for the admin endpoint
+pub const DEFAULT_ADMIN_ADDR: &str = "127.0.0.1:8082";
+/// Environment variable for the admin endpoint bind address
+pub const ENV_ADMIN_ADDR: &str = "POKER_ADMIN_ADDR";
+/// Environment variable for the token operators present to the admin
+/// endpoint; the endpoint is disabled while it is unset
+pub const ENV_ADMIN_TOKEN: &str = "POKER_ADMIN_TOKEN";
+/// Default file the admin audit trail is appended to
+pub const DEFAULT_ADMIN_AUDIT_LOG: &str = "admin_audit.log";
+/// Environment variable for the admin audit trail file
+pub const ENV_ADMIN_AUDIT_LOG: &str = "POKER_ADMIN_AUDIT_LOG";
 
 /// A thread-safe token bucket rate limiter implementation.
 /// 
@@ -201,14 +266,12 @@ fn validate_action_amount(amount: i64, max_allowed: i32) -> Result<i32, String>
     Ok(amount)
 }
 
-/// Maximum multiplier for bet relative to pot size (prevents oversized bets)
-pub const MAX_BET_MULTIPLIER: i32 = 10;
 /// Maximum allowed WebSocket message size in bytes (4KB)
 pub const MAX_MESSAGE_SIZE: usize = 4096;
 /// Maximum chips a player can have at any time
 pub const MAX_PLAYER_CHIPS: i32 = 1000000;
-/// Starting chips for new players
-pub const STARTING_CHIPS: i32 = 1000;
+/// Starting balance for new players, which they buy in from when they sit down
+pub const STARTING_CHIPS: i32 = 10000;
 /// Capacity for tokio mpsc channels used for message passing
 const CHANNEL_CAPACITY: usize = 100;
 /// Timeout for player inactivity in milliseconds (10 minutes)
@@ -222,8 +285,16 @@ pub const SESSION_TOKEN_EXPIRY_HOURS: u64 = 24;
 /// Maximum bet allowed per hand
 pub const MAX_BET_PER_HAND: i32 = 100000;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct ServerConfig {
+    /// Address players connect to
+    pub addr: String,
+    /// Address of the delayed observer feed
+    pub tv_addr: String,
+    /// Address of the Prometheus metrics endpoint
+    pub metrics_addr: String,
+    /// Address of the admin endpoint, when it is enabled
+    pub admin_addr: String,
     pub max_player_chips: i32,
     pub starting_chips: i32,
     pub small_blind: i32,
@@ -234,12 +305,35 @@ pub struct ServerConfig {
     pub max_connections_per_ip: usize,
     pub session_token_expiry_hours: u64,
     pub max_bet_per_hand: i32,
+    pub betting_structure: BettingStructure,
+    pub variant: GameVariant,
+    pub ante: AnteStructure,
+    pub straddle: StraddleRule,
+    pub max_runs: u32,
+    pub rake: RakeStructure,
+    pub buy_in: BuyInRules,
+    pub seat_offer_secs: u64,
+    pub action_secs: u64,
+    pub spectator_chat: bool,
+    pub rotation: Option<GameRotation>,
     pub enable_hmac_verification: bool,
+    /// Seconds the observer feed runs behind the tables
+    pub tv_delay_secs: u64,
+    /// PEM certificate chain; connections use TLS when set
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key for `tls_cert`
+    pub tls_key: Option<PathBuf>,
+    /// PEM CA certificates admin client certificates must be signed by
+    pub admin_client_ca: Option<PathBuf>,
 }
 
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
+            addr: DEFAULT_SERVER_ADDR.to_string(),
+            tv_addr: DEFAULT_TV_ADDR.to_string(),
+            metrics_addr: DEFAULT_METRICS_ADDR.to_string(),
+            admin_addr: DEFAULT_ADMIN_ADDR.to_string(),
             max_player_chips: MAX_PLAYER_CHIPS,
             starting_chips: STARTING_CHIPS,
             small_blind: 5,
@@ -250,14 +344,208 @@ impl Default for ServerConfig {
             max_connections_per_ip: MAX_CONNECTIONS_PER_IP,
             session_token_expiry_hours: SESSION_TOKEN_EXPIRY_HOURS,
             max_bet_per_hand: MAX_BET_PER_HAND,
+            betting_structure: BettingStructure::NoLimit,
+            variant: GameVariant::TexasHoldem,
+            ante: AnteStructure::None,
+            straddle: StraddleRule::None,
+            max_runs: 1,
+            rake: RakeStructure::default(),
+            buy_in: BuyInRules::default(),
+            seat_offer_secs: DEFAULT_SEAT_OFFER_SECS,
+            action_secs: DEFAULT_ACTION_SECS,
+            spectator_chat: false,
+            rotation: None,
             enable_hmac_verification: true,
+            tv_delay_secs: DEFAULT_TV_DELAY_SECS,
+            tls_cert: None,
+            tls_key: None,
+            admin_client_ca: None,
+        }
+    }
+}
+
+/// Parses a betting structure name as used by `POKER_BETTING_STRUCTURE`.
+///
+/// Fixed-limit tables bet one big blind on the early streets and two on the
+/// later streets, with the default raise cap.
+///
+/// # Arguments
+/// * `value` - One of `no-limit`, `pot-limit` or `fixed-limit`
+/// * `big_blind` - The table's big blind, used to size fixed-limit bets
+///
+/// # Returns
+/// The matching `BettingStructure`, or `None` if the name is not recognised
+pub fn parse_betting_structure(value: &str, big_blind: i32) -> Option<BettingStructure> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "no-limit" | "nl" => Some(BettingStructure::NoLimit),
+        "pot-limit" | "pl" => Some(BettingStructure::PotLimit),
+        "fixed-limit" | "limit" | "fl" => Some(BettingStructure::fixed_limit(big_blind)),
+        _ => None,
+    }
+}
+
+/// Returns the table a seating, waiting list or spectating message names in
+/// its optional `game_id`, leaving the server to pick one when it is absent.
+fn requested_table(value: &serde_json::Value) -> Option<String> {
+    value["game_id"].as_str().map(str::to_string)
+}
+
+/// Parses a game variant name as used by `POKER_GAME_VARIANT`.
+///
+/// # Arguments
+/// * `value` - One of `holdem`, `omaha`, `omaha-hi-lo`, `short-deck`, `stud`,
+///   `stud-hi-lo`, `razz`, `2-7-triple-draw` or `2-7-single-draw`
+///
+/// # Returns
+/// The matching `GameVariant`, or `None` if the name is not recognised
+pub fn parse_game_variant(value: &str) -> Option<GameVariant> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "holdem" | "texas-holdem" => Some(GameVariant::TexasHoldem),
+        "omaha" | "plo" => Some(GameVariant::Omaha),
+        "omaha-hi-lo" | "omaha8" | "plo8" => Some(GameVariant::OmahaHiLo),
+        "short-deck" | "6+" => Some(GameVariant::ShortDeck),
+        "stud" | "seven-card-stud" => Some(GameVariant::SevenCardStud),
+        "stud-hi-lo" | "stud8" => Some(GameVariant::SevenCardStudHiLo),
+        "razz" => Some(GameVariant::Razz),
+        "2-7-triple-draw" | "triple-draw" => Some(GameVariant::DeuceToSevenTripleDraw),
+        "2-7-single-draw" | "single-draw" => Some(GameVariant::DeuceToSevenSingleDraw),
+        _ => None,
+    }
+}
+
+/// Parses a straddle rule as used by `POKER_STRADDLE`.
+///
+/// # Arguments
+/// * `value` - One of `none`, `utg` or `mississippi`
+///
+/// # Returns
+/// The matching `StraddleRule`, or `None` if the name is not recognised
+pub fn parse_straddle_rule(value: &str) -> Option<StraddleRule> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "none" | "off" => Some(StraddleRule::None),
+        "utg" | "under-the-gun" => Some(StraddleRule::UnderTheGun),
+        "mississippi" | "button" => Some(StraddleRule::Mississippi),
+        _ => None,
+    }
+}
+
+/// Parses a rake percentage as used by `POKER_RAKE_PERCENT`.
+///
+/// # Arguments
+/// * `value` - A percentage such as `5`, `4.5` or `5%`
+///
+/// # Returns
+/// The rate in basis points, or `None` if the value is not between 0 and 100
+pub fn parse_rake_percent(value: &str) -> Option<u32> {
+    let percent: f64 = value.trim().trim_end_matches('%').parse().ok()?;
+    (0.0..=100.0)
+        .contains(&percent)
+        .then(|| (percent * 100.0).round() as u32)
+}
+
+/// Parses rake caps as used by `POKER_RAKE_CAP`.
+///
+/// # Arguments
+/// * `value` - A single cap such as `3`, or caps by player count such as
+///   `2:1,4:3` (a cap of 1 with two or three players and 3 from four players)
+///
+/// # Returns
+/// The caps, or `None` if any entry is malformed
+pub fn parse_rake_caps(value: &str) -> Option<Vec<RakeCap>> {
+    value
+        .split(',')
+        .map(|entry| {
+            let (min_players, cap) = match entry.split_once(':') {
+                Some((players, cap)) => (players.trim().parse().ok()?, cap),
+                None => (0, entry),
+            };
+            let cap = cap.trim().parse().ok().filter(|&cap: &i32| cap >= 0)?;
+            Some(RakeCap { min_players, cap })
+        })
+        .collect()
+}
+
+/// Parses a mixed-game rotation as used by `POKER_GAME_ROTATION`.
+///
+/// Each game in a custom list is played with its usual betting structure.
+///
+/// # Arguments
+/// * `value` - `horse`, `8-game`, or a comma-separated list of variant names
+/// * `big_blind` - The table's big blind, used to size limit bets and stud antes
+/// * `schedule` - When the table moves on to the next game
+///
+/// # Returns
+/// The rotation, or `None` if the list is empty or contains an unknown variant
+pub fn parse_game_rotation(
+    value: &str,
+    big_blind: i32,
+    schedule: RotationSchedule,
+) -> Option<GameRotation> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "horse" => Some(GameRotation::horse(big_blind, schedule)),
+        "8-game" | "eight-game" => Some(GameRotation::eight_game(big_blind, schedule)),
+        list => {
+            let games = list
+                .split(',')
+                .map(|name| parse_game_variant(name).map(|v| RotationGame::new(v, big_blind)))
+                .collect::<Option<Vec<_>>>()?;
+            GameRotation::new(games, schedule)
         }
     }
 }
 
 impl ServerConfig {
+    /// Builds the table configuration used for games created by this server.
+    pub fn table_config(&self) -> TableConfig {
+        TableConfig {
+            small_blind: self.small_blind,
+            big_blind: self.big_blind,
+            betting_structure: self.betting_structure,
+            max_bet_per_hand: self.max_bet_per_hand,
+            variant: self.variant,
+            ante: self.ante,
+            straddle: self.straddle,
+            max_runs: self.max_runs,
+            rake: self.rake.clone(),
+            buy_in: self.buy_in,
+            seat_offer_secs: self.seat_offer_secs,
+            action_secs: self.action_secs,
+            spectator_chat: self.spectator_chat,
+            rotation: self.rotation.clone(),
+        }
+    }
+
+    /// Returns the limits enforced on connections, which may change while
+    /// the server runs.
+    pub fn limits(&self) -> ServerLimits {
+        ServerLimits {
+            max_connections: self.max_connections,
+            max_connections_per_ip: self.max_connections_per_ip,
+            max_message_size: self.max_message_size,
+            inactivity_timeout_ms: self.inactivity_timeout_ms,
+            starting_chips: self.starting_chips,
+            session_token_expiry_hours: self.session_token_expiry_hours,
+        }
+    }
+
     pub fn from_env() -> Self {
+        let big_blind = std::env::var("POKER_BIG_BLIND")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10);
+        let variant = std::env::var("POKER_GAME_VARIANT")
+            .ok()
+            .and_then(|v| parse_game_variant(&v))
+            .unwrap_or_default();
+        let default_buy_in = BuyInRules::for_big_blind(big_blind);
         Self {
+            addr: std::env::var(ENV_SERVER_ADDR)
+                .unwrap_or_else(|_| DEFAULT_SERVER_ADDR.to_string()),
+            tv_addr: std::env::var(ENV_TV_ADDR).unwrap_or_else(|_| DEFAULT_TV_ADDR.to_string()),
+            metrics_addr: std::env::var(ENV_METRICS_ADDR)
+                .unwrap_or_else(|_| DEFAULT_METRICS_ADDR.to_string()),
+            admin_addr: std::env::var(ENV_ADMIN_ADDR)
+                .unwrap_or_else(|_| DEFAULT_ADMIN_ADDR.to_string()),
             max_player_chips: std::env::var("POKER_MAX_PLAYER_CHIPS")
                 .ok()
                 .and_then(|v| v.parse().ok())
@@ -270,10 +558,7 @@ impl ServerConfig {
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(5),
-            big_blind: std::env::var("POKER_BIG_BLIND")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(10),
+            big_blind,
             max_message_size: std::env::var("POKER_MAX_MESSAGE_SIZE")
                 .ok()
                 .and_then(|v| v.parse().ok())
@@ -298,10 +583,98 @@ impl ServerConfig {
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(MAX_BET_PER_HAND),
+            betting_structure: std::env::var("POKER_BETTING_STRUCTURE")
+                .ok()
+                .and_then(|v| parse_betting_structure(&v, big_blind))
+                .unwrap_or_else(|| variant.default_betting_structure(big_blind)),
+            variant,
+            ante: std::env::var("POKER_BUTTON_ANTE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&ante: &i32| ante > 0)
+                .map(AnteStructure::ButtonAnte)
+                .or_else(|| {
+                    std::env::var("POKER_BIG_BLIND_ANTE")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .filter(|&ante: &i32| ante > 0)
+                        .map(AnteStructure::BigBlindAnte)
+                })
+                .or_else(|| {
+                    std::env::var("POKER_ANTE")
+                        .ok()
+                        .and_then(|v| v.parse().ok())
+                        .filter(|&ante: &i32| ante > 0)
+                        .map(AnteStructure::PerPlayer)
+                })
+                .unwrap_or_else(|| TableConfig::for_variant(variant, 0, big_blind).ante),
+            straddle: std::env::var("POKER_STRADDLE")
+                .ok()
+                .and_then(|v| parse_straddle_rule(&v))
+                .unwrap_or_default(),
+            max_runs: std::env::var("POKER_MAX_RUNS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&runs: &u32| runs > 0)
+                .unwrap_or(1),
+            rake: RakeStructure {
+                rate_bps: std::env::var("POKER_RAKE_PERCENT")
+                    .ok()
+                    .and_then(|v| parse_rake_percent(&v))
+                    .unwrap_or(0),
+                caps: std::env::var("POKER_RAKE_CAP")
+                    .ok()
+                    .and_then(|v| parse_rake_caps(&v))
+                    .unwrap_or_default(),
+                no_flop_no_drop: std::env::var("POKER_NO_FLOP_NO_DROP")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(true),
+            },
+            buy_in: BuyInRules {
+                min: std::env::var("POKER_MIN_BUY_IN")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&min: &i32| min > 0)
+                    .unwrap_or(default_buy_in.min),
+                max: std::env::var("POKER_MAX_BUY_IN")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&max: &i32| max > 0)
+                    .unwrap_or(default_buy_in.max),
+            },
+            seat_offer_secs: std::env::var("POKER_SEAT_OFFER_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_SEAT_OFFER_SECS),
+            action_secs: std::env::var("POKER_ACTION_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_ACTION_SECS),
+            spectator_chat: std::env::var("POKER_SPECTATOR_CHAT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(false),
+            rotation: std::env::var("POKER_GAME_ROTATION").ok().and_then(|v| {
+                let schedule = std::env::var("POKER_ROTATION_HANDS")
+                    .ok()
+                    .and_then(|hands| hands.parse().ok())
+                    .filter(|&hands: &u32| hands > 0)
+                    .map(RotationSchedule::EveryHands)
+                    .unwrap_or(RotationSchedule::EachOrbit);
+                parse_game_rotation(&v, big_blind, schedule)
+            }),
             enable_hmac_verification: std::env::var("POKER_ENABLE_HMAC")
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(true),
+            tv_delay_secs: std::env::var("POKER_TV_DELAY_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_TV_DELAY_SECS),
+            tls_cert: std::env::var_os(ENV_TLS_CERT).map(PathBuf::from),
+            tls_key: std::env::var_os(ENV_TLS_KEY).map(PathBuf::from),
+            admin_client_ca: std::env::var_os(ENV_ADMIN_CLIENT_CA).map(PathBuf::from),
         }
     }
 }
@@ -379,66 +752,184 @@ async fn wait_for_shutdown_signal() {
     }
 }
 
+/// Sets up logging, filtered by `RUST_LOG` as before, as plain text or as
+/// JSON when `POKER_LOG_FORMAT=json`. Each connection, table and hand is a
+/// span, so every event carries the `player_id`, `game_id` and
+/// `hand_number` it happened under.
+fn init_tracing() {
+    let subscriber = tracing_subscriber::fmt().with_env_filter(EnvFilter::from_default_env());
+    let json =
+        std::env::var(ENV_LOG_FORMAT).is_ok_and(|format| format.eq_ignore_ascii_case("json"));
+    if json {
+        subscriber
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .init();
+    } else {
+        subscriber.init();
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
-
-    let config = ServerConfig::default();
+    init_tracing();
+
+    let cli = Cli::parse();
+    let settings = match Settings::load(&cli) {
+        Ok(settings) => settings,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let config = settings.server.clone();
     let server = Arc::new(Mutex::new(PokerServer::new()));
+    server.lock().set_limits(config.limits());
     let shutdown_state = ShutdownState::new();
 
-    let hmac_key = if config.enable_hmac_verification {
-        Arc::new(HmacKey::new().unwrap_or_else(|_| HmacKey::default()))
-    } else {
-        // Safe to unwrap: from_bytes with a zero-filled array of correct length always succeeds
-        // since it only validates the length, not the content of the key
-        Arc::new(HmacKey::from_bytes(&[0u8; HMAC_SECRET_LEN]).unwrap())
-    };
+    // Without a key, players' messages are accepted unsigned
+    let hmac_key = config
+        .enable_hmac_verification
+        .then(|| Arc::new(HmacKey::new().unwrap_or_else(|_| HmacKey::default())));
     let nonce_cache = Arc::new(NonceCache::new());
 
-    let addr = std::env::var(ENV_SERVER_ADDR).unwrap_or_else(|_| DEFAULT_SERVER_ADDR.to_string());
-    let listener = TcpListener::bind(&addr).await?;
-    info!("Poker server listening on: {}", addr);
-
-    let game = {
-        let mut server_guard = server.lock();
-        server_guard.create_game(
-            "main_table".to_string(),
-            config.small_blind,
-            config.big_blind,
-        )
+    let acceptors = TlsAcceptor::for_players(&config)
+        .and_then(|players| Ok((players, TlsAcceptor::for_admin(&config)?)));
+    let (tls, admin_tls) = match acceptors {
+        Ok(acceptors) => acceptors,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
     };
+    let scheme = if tls.is_some() { "wss" } else { "ws" };
 
-    let broadcast_task = {
-        let server = Arc::clone(&server);
-        let mut rx = game.lock().tx.subscribe();
-        tokio::spawn(async move {
-            while let Ok(msg) = rx.recv().await {
-                let s = server.lock();
-                s.broadcast_to_game("main_table", msg);
+    let listener = TcpListener::bind(&config.addr).await?;
+    info!("Poker server listening on: {}://{}", scheme, config.addr);
+
+    let tables = ConfiguredTables::new(Duration::from_secs(config.tv_delay_secs));
+    for (game_id, table_config) in &settings.tables {
+        tables.open(&server, game_id, table_config.clone());
+    }
+
+    let tv_listener = TcpListener::bind(&config.tv_addr).await?;
+    info!(
+        "Observer feed listening on: {} ({}s delay)",
+        config.tv_addr, config.tv_delay_secs
+    );
+    let tv_feeds = tables.clone();
+    let tv_task = tokio::spawn(async move {
+        loop {
+            match tv_listener.accept().await {
+                Ok((stream, addr)) => {
+                    let feeds = Arc::new(tv_feeds.feeds.lock().clone());
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_tv_connection(stream, feeds).await {
+                            warn!("Observer feed connection from {} failed: {}", addr, e);
+                        }
+                    });
+                }
+                Err(e) => error!("Failed to accept observer connection: {}", e),
             }
-        })
+        }
+    });
+
+    let metrics_listener = TcpListener::bind(&config.metrics_addr).await?;
+    info!(
+        "Metrics endpoint listening on: {}/metrics",
+        config.metrics_addr
+    );
+    let metrics_task = tokio::spawn(metrics::serve(metrics_listener, Arc::clone(&server)));
+
+    let admin_task = match std::env::var(ENV_ADMIN_TOKEN) {
+        Ok(token) if !token.is_empty() => {
+            let admin_addr = &config.admin_addr;
+            let audit_path = std::env::var(ENV_ADMIN_AUDIT_LOG)
+                .unwrap_or_else(|_| DEFAULT_ADMIN_AUDIT_LOG.to_string());
+            let admin_listener = TcpListener::bind(admin_addr).await?;
+            info!(
+                "Admin endpoint listening on: {} (audit trail in {})",
+                admin_addr, audit_path
+            );
+            let token = Arc::new(token);
+            let audit = Arc::new(Mutex::new(AuditLog::new(Some(audit_path.into()))));
+            let server = Arc::clone(&server);
+            Some(tokio::spawn(async move {
+                loop {
+                    match admin_listener.accept().await {
+                        Ok((stream, addr)) => {
+                            let server = Arc::clone(&server);
+                            let token = Arc::clone(&token);
+                            let audit = Arc::clone(&audit);
+                            let admin_tls = admin_tls.clone();
+                            tokio::spawn(
+                                async move {
+                                    let stream = match tls::accept(stream, admin_tls.as_ref()).await
+                                    {
+                                        Ok(stream) => stream,
+                                        Err(e) => {
+                                            warn!("Admin connection from {} refused: {}", addr, e);
+                                            return;
+                                        }
+                                    };
+                                    if let Err(e) =
+                                        handle_admin_connection(stream, addr, server, token, audit)
+                                            .await
+                                    {
+                                        warn!("Admin connection from {} failed: {}", addr, e);
+                                    }
+                                }
+                                .instrument(info_span!("admin", addr = %addr)),
+                            );
+                        }
+                        Err(e) => error!("Failed to accept admin connection: {}", e),
+                    }
+                }
+            }))
+        }
+        _ => {
+            info!(
+                "Admin endpoint disabled; set {} to enable it",
+                ENV_ADMIN_TOKEN
+            );
+            None
+        }
     };
 
-    let game_clone: Arc<Mutex<PokerGame>> = Arc::clone(&game);
+    let server_for_inactivity = Arc::clone(&server);
+    let tables_for_inactivity = tables.clone();
     let shutdown_flag = shutdown_state.should_shutdown.clone();
     let inactivity_task = tokio::spawn(async move {
         while !shutdown_flag.load(Ordering::Relaxed) {
             tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-            let active_count = {
-                let g = game_clone.lock();
-                g.players.values().filter(|p| !p.is_sitting_out).count()
-            };
-
-            if active_count < 2 {
-                let mut g = game_clone.lock();
-                if !g.players.is_empty() {
-                    g.game_stage = poker_protocol::GameStage::WaitingForPlayers;
+            for game_id in tables_for_inactivity.ids() {
+                let Some(game) = server_for_inactivity.lock().get_game(&game_id) else {
+                    continue;
+                };
+                let active_count = {
+                    let g = game.lock();
+                    g.players.values().filter(|p| !p.is_sitting_out).count()
+                };
+
+                if active_count < 2 {
+                    let mut g = game.lock();
+                    if !g.players.is_empty() && !g.is_paused() {
+                        g.game_stage = poker_protocol::GameStage::WaitingForPlayers;
+                    }
                 }
             }
         }
     });
 
+    #[cfg(unix)]
+    let reload_task = tokio::spawn(reload_on_hangup(
+        cli,
+        settings,
+        Arc::clone(&server),
+        tables.clone(),
+    ));
+
     let shutdown_signal = shutdown_state.should_shutdown.clone();
 
     let _signal_task = tokio::spawn(async move {
@@ -470,25 +961,31 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let shutdown_flag = shutdown_state.should_shutdown.clone();
         let hmac_key = hmac_key.clone();
         let nonce_cache = nonce_cache.clone();
+        let tls = tls.clone();
 
-        let handle = tokio::spawn(async move {
-            if shutdown_flag.load(Ordering::Relaxed) {
-                return;
-            }
+        let span = info_span!("connection", player_id = %player_id, ip = %addr.ip());
+        let handle = tokio::spawn(
+            async move {
+                if shutdown_flag.load(Ordering::Relaxed) {
+                    return;
+                }
 
-            if let Err(e) = handle_connection(
-                stream,
-                addr,
-                Arc::clone(&server),
-                player_id.clone(),
-                hmac_key,
-                nonce_cache,
-            )
-            .await
-            {
-                error!("Error handling connection: {}", e);
+                if let Err(e) = handle_connection(
+                    stream,
+                    addr,
+                    Arc::clone(&server),
+                    player_id.clone(),
+                    hmac_key,
+                    nonce_cache,
+                    tls,
+                )
+                .await
+                {
+                    error!("Error handling connection: {}", e);
+                }
             }
-        });
+            .instrument(span),
+        );
 
         active_connections.push(handle);
 
@@ -512,9 +1009,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let _ = tokio::time::timeout(Duration::from_secs(2), handle).await;
     }
 
-    info!("Shutting down broadcast task...");
-    drop(broadcast_task);
     inactivity_task.abort();
+    #[cfg(unix)]
+    reload_task.abort();
+    tables.close_feeds();
+    tv_task.abort();
+    metrics_task.abort();
+    if let Some(admin_task) = admin_task {
+        admin_task.abort();
+    }
 
     info!("Server shutdown complete");
     Ok(())
@@ -593,6 +1096,7 @@ struct MessageHandler {
     player_id: String,
     rate_limiter: Arc<RateLimiter>,
     chat_rate_limiter: Arc<ChatRateLimiter>,
+    metrics: Arc<Metrics>,
 }
 
 impl MessageHandler {
@@ -602,24 +1106,64 @@ impl MessageHandler {
         rate_limiter: Arc<RateLimiter>,
         chat_rate_limiter: Arc<ChatRateLimiter>,
     ) -> Self {
+        let metrics = server.lock().metrics();
         Self {
             server,
             player_id,
             rate_limiter,
             chat_rate_limiter,
+            metrics,
         }
     }
 
-    async fn handle_connect(&self) {
-        let mut server = self.server.lock();
-        if let Err(e) = server.handle_message(&self.player_id, ClientMessage::Connect) {
-            self.send_error(&e.to_string());
+    /// Answers the client's `Hello`, which must be its first message, with
+    /// what the connection will use.
+    ///
+    /// # Arguments
+    /// * `text` - The client's first message
+    /// * `signing` - Whether the server requires signed messages
+    ///
+    /// # Returns
+    /// `false` if the client sent something else or cannot talk to this
+    /// server, having been told why
+    fn handle_hello(&self, text: &str, signing: bool) -> bool {
+        let hello = serde_json::from_str::<serde_json::Value>(text)
+            .ok()
+            .filter(|value| value["type"] == "Hello")
+            .and_then(|value| serde_json::from_value::<Hello>(value).ok());
+        let agreed = match hello {
+            Some(hello) => Hello::new(signing).negotiate(&hello),
+            None => Err(ProtocolError::Incompatible(
+                "expected a Hello before any other message".to_string(),
+            )),
+        };
+        match agreed {
+            Ok(agreed) => {
+                debug!(
+                    "Player {} speaks protocol version {} with {:?}",
+                    self.player_id, agreed.version, agreed.features
+                );
+                self.send(ServerMessage::Hello(agreed));
+                true
+            }
+            Err(e) => {
+                warn!("Rejected player {}: {}", self.player_id, e);
+                self.metrics
+                    .record_verification_failure(VerificationFailure::Handshake);
+                self.send_error(&e.to_string());
+                false
+            }
         }
     }
 
+    async fn handle_connect(&self) {
+        self.dispatch(ClientMessage::Connect);
+    }
+
     async fn handle_action(&self, value: &serde_json::Value) {
         if !self.rate_limiter.allow() {
             warn!("Player {} action rate limited", self.player_id);
+            self.metrics.record_rate_limited(RateLimit::Action);
             return;
         }
 
@@ -627,6 +1171,12 @@ impl MessageHandler {
             if let Some(action_str) = action_value.as_str() {
                 if let Some(action) = poker_protocol::PlayerAction::parse_action(action_str) {
                     self.send_action(action);
+                } else if action_str == "Bet" || action_str == "Raise" {
+                    match value["amount"].as_i64() {
+                        Some(amount) if action_str == "Bet" => self.handle_bet(amount),
+                        Some(amount) => self.handle_raise(amount),
+                        None => self.send_error("An \"amount\" is required"),
+                    }
                 } else if let Some(action) =
                     poker_protocol::PlayerAction::from_value(&value["action"], None)
                 {
@@ -638,6 +1188,11 @@ impl MessageHandler {
                 self.handle_bet(amount_value);
             } else if let Some(amount_value) = value["action"]["Raise"].as_i64() {
                 self.handle_raise(amount_value);
+            } else if value["action"].get("Draw").is_some() {
+                match poker_protocol::PlayerAction::from_value(&value["action"], None) {
+                    Some(action) => self.send_action(action),
+                    None => self.send_error("Invalid draw"),
+                }
             }
         }
     }
@@ -667,23 +1222,40 @@ impl MessageHandler {
     }
 
     fn send_action(&self, action: poker_protocol::PlayerAction) {
-        let mut server = self.server.lock();
-        if let Err(e) = server.handle_message(&self.player_id, ClientMessage::Action(action)) {
+        self.dispatch(ClientMessage::Action(action));
+    }
+
+    /// Hands a message to the server, timing actions for the latency metric.
+    fn dispatch(&self, client_msg: ClientMessage) {
+        let started = Instant::now();
+        let is_action = matches!(client_msg, ClientMessage::Action(_));
+        // The lock must be released before replying, as `send_error` takes it again.
+        let result = self
+            .server
+            .lock()
+            .handle_message(&self.player_id, client_msg);
+        if is_action {
+            self.metrics.record_action_latency(started.elapsed());
+        }
+        if let Err(e) = result {
             self.send_error(&e.to_string());
         }
     }
 
     fn send_error(&self, error: &str) {
-        let error_msg = ServerMessage::Error(error.to_string());
-        match serde_json::to_string(&error_msg) {
+        self.send(ServerMessage::Error(error.to_string()));
+    }
+
+    fn send(&self, message: ServerMessage) {
+        match message.to_unified_json() {
             Ok(json) => {
                 let server = self.server.lock();
                 if let Err(e) = server.send_to_player(&self.player_id, json) {
-                    warn!("Failed to send error to {}: {}", self.player_id, e);
+                    warn!("Failed to send message to {}: {}", self.player_id, e);
                 }
             }
             Err(e) => {
-                error!("Failed to serialize error message: {}", e);
+                error!("Failed to serialize message: {}", e);
             }
         }
     }
@@ -691,37 +1263,115 @@ impl MessageHandler {
     async fn handle_chat(&self, value: &serde_json::Value) {
         if !self.chat_rate_limiter.allow() {
             warn!("Player {} chat rate limited", self.player_id);
+            self.metrics.record_rate_limited(RateLimit::Chat);
             self.send_error("Chat rate limit exceeded. Please wait before sending more messages.");
             return;
         }
         if let Some(chat_text) = value["text"].as_str() {
             let sanitized_text = sanitize_chat_message(chat_text);
-            let mut server = self.server.lock();
-            if let Err(e) =
-                server.handle_message(&self.player_id, ClientMessage::Chat(sanitized_text))
-            {
-                self.send_error(&e.to_string());
-            }
+            self.dispatch(ClientMessage::Chat(sanitized_text));
         }
     }
 
     async fn handle_sit_out(&self) {
-        let mut server = self.server.lock();
-        if let Err(e) = server.handle_message(&self.player_id, ClientMessage::SitOut) {
-            self.send_error(&e.to_string());
+        self.dispatch(ClientMessage::SitOut);
+    }
+
+    async fn handle_straddle(&self, value: &serde_json::Value) {
+        match value["enabled"].as_bool() {
+            Some(enabled) => {
+                self.handle_client_message(ClientMessage::Straddle(enabled))
+                    .await
+            }
+            None => self.send_error("Straddle requires an \"enabled\" flag"),
+        }
+    }
+
+    async fn handle_chips<F>(&self, value: &serde_json::Value, message_fn: F)
+    where
+        F: FnOnce(i32) -> ClientMessage,
+    {
+        match value["amount"].as_i64() {
+            Some(amount) => match validate_action_amount(amount, MAX_PLAYER_CHIPS) {
+                Ok(amount) => self.handle_client_message(message_fn(amount)).await,
+                Err(err_msg) => self.send_error(&err_msg),
+            },
+            None => self.send_error("An \"amount\" of chips is required"),
+        }
+    }
+
+    async fn handle_take_seat(&self, value: &serde_json::Value) {
+        match value["seat"].as_u64().and_then(|s| usize::try_from(s).ok()) {
+            Some(seat) => {
+                self.handle_client_message(ClientMessage::TakeSeat(seat, requested_table(value)))
+                    .await
+            }
+            None => self.send_error("TakeSeat requires a \"seat\" number"),
+        }
+    }
+
+    async fn handle_run_it(&self, value: &serde_json::Value) {
+        match value["times"].as_u64().and_then(|t| u32::try_from(t).ok()) {
+            Some(times) => {
+                self.handle_client_message(ClientMessage::RunIt(times))
+                    .await
+            }
+            None => self.send_error("RunIt requires a \"times\" count"),
         }
     }
 
     async fn handle_return(&self) {
-        let mut server = self.server.lock();
-        if let Err(e) = server.handle_message(&self.player_id, ClientMessage::Return) {
-            self.send_error(&e.to_string());
+        self.handle_client_message(ClientMessage::Return).await;
+    }
+
+    async fn handle_create_table(&self, value: &serde_json::Value) {
+        match serde_json::from_value::<CreateTableRequest>(value.clone()) {
+            Ok(request) => {
+                self.handle_client_message(ClientMessage::CreateTable(request))
+                    .await;
+                relay_new_tables(&self.server);
+            }
+            Err(_) => self.send_error("CreateTable requires \"small_blind\" and \"big_blind\""),
+        }
+    }
+
+    async fn handle_join_table(&self, value: &serde_json::Value) {
+        match serde_json::from_value::<JoinTableRequest>(value.clone()) {
+            Ok(request) => {
+                self.handle_client_message(ClientMessage::JoinTable(request))
+                    .await
+            }
+            Err(_) => self.send_error("JoinTable requires a \"game_id\""),
+        }
+    }
+
+    async fn handle_set_blinds(&self, value: &serde_json::Value) {
+        let blind = |key: &str| value[key].as_i64().and_then(|b| i32::try_from(b).ok());
+        match (blind("small_blind"), blind("big_blind")) {
+            (Some(small_blind), Some(big_blind)) => {
+                self.handle_client_message(ClientMessage::SetBlinds(small_blind, big_blind))
+                    .await
+            }
+            _ => self.send_error("SetBlinds requires \"small_blind\" and \"big_blind\""),
+        }
+    }
+
+    async fn handle_player_command<F>(&self, value: &serde_json::Value, message_fn: F)
+    where
+        F: FnOnce(String) -> ClientMessage,
+    {
+        match value["player_id"].as_str() {
+            Some(player_id) => {
+                self.handle_client_message(message_fn(player_id.to_string()))
+                    .await
+            }
+            None => self.send_error("A \"player_id\" is required"),
         }
     }
 
     async fn handle_ping(&self, timestamp: u64) {
         let pong_msg = ServerMessage::Pong(timestamp);
-        if let Ok(json) = serde_json::to_string(&pong_msg) {
+        if let Ok(json) = pong_msg.to_unified_json() {
             let server = self.server.lock();
             if let Err(e) = server.send_to_player(&self.player_id, json) {
                 warn!("Failed to send pong to {}: {}", self.player_id, e);
@@ -730,33 +1380,278 @@ impl MessageHandler {
     }
 
     async fn handle_client_message(&self, client_msg: ClientMessage) {
-        let mut server = self.server.lock();
-        if let Err(e) = server.handle_message(&self.player_id, client_msg) {
-            self.send_error(&e.to_string());
+        self.dispatch(client_msg);
+    }
+}
+
+/// Starts relaying the events of tables created since the last call to
+/// their players, until each table's channel closes.
+///
+/// # Arguments
+/// * `server` - The server the tables were created on
+fn relay_new_tables(server: &Arc<Mutex<PokerServer>>) {
+    let new_tables = server.lock().take_new_tables();
+    for (game_id, mut events) in new_tables {
+        let server = Arc::clone(server);
+        let span = info_span!("table", game_id = %game_id);
+        tokio::spawn(
+            async move {
+                loop {
+                    match events.recv().await {
+                        Ok(msg) => server.lock().relay_table_event(&game_id, msg),
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Table {} dropped {} events", game_id, skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            }
+            .instrument(span),
+        );
+    }
+}
+
+/// The tables opened from the configuration and their observer feeds.
+#[derive(Clone)]
+struct ConfiguredTables {
+    tv_delay: Duration,
+    /// Each table's observer feed, by game id
+    feeds: Arc<Mutex<HashMap<String, broadcast::Sender<String>>>>,
+    /// Tasks holding back each table's events for its observer feed
+    feed_tasks: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,
+}
+
+impl ConfiguredTables {
+    fn new(tv_delay: Duration) -> Self {
+        Self {
+            tv_delay,
+            feeds: Arc::new(Mutex::new(HashMap::new())),
+            feed_tasks: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Opens a table, relays its events to its players and starts its
+    /// observer feed.
+    ///
+    /// # Arguments
+    /// * `server` - The server to open the table on
+    /// * `game_id` - The table's id
+    /// * `config` - The table's settings
+    fn open(&self, server: &Arc<Mutex<PokerServer>>, game_id: &str, config: TableConfig) {
+        let game = server
+            .lock()
+            .create_game_with_config(game_id.to_string(), config);
+        relay_new_tables(server);
+
+        let feed = TvFeed::new(self.tv_delay);
+        self.feeds
+            .lock()
+            .insert(game_id.to_string(), feed.viewers());
+        let task = feed.spawn(game.lock().tx.subscribe());
+        self.feed_tasks.lock().push(task);
+    }
+
+    /// Returns the ids of the tables opened so far.
+    fn ids(&self) -> Vec<String> {
+        self.feeds.lock().keys().cloned().collect()
+    }
+
+    /// Stops every observer feed.
+    fn close_feeds(&self) {
+        for task in self.feed_tasks.lock().drain(..) {
+            task.abort();
+        }
+    }
+}
+
+/// Re-reads the configuration each time the server receives SIGHUP and
+/// applies it without dropping any connections. A configuration that fails
+/// validation is logged and the current one kept.
+///
+/// # Arguments
+/// * `cli` - The command-line flags, which still override the file
+/// * `current` - The settings the server started with
+/// * `server` - The running server
+/// * `tables` - The tables opened from the configuration
+#[cfg(unix)]
+async fn reload_on_hangup(
+    cli: Cli,
+    mut current: Settings,
+    server: Arc<Mutex<PokerServer>>,
+    tables: ConfiguredTables,
+) {
+    let mut hangups = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+        Ok(stream) => stream,
+        Err(e) => {
+            error!("Failed to install SIGHUP signal handler: {}", e);
+            return;
+        }
+    };
+    while hangups.recv().await.is_some() {
+        info!("Received SIGHUP signal, reloading configuration");
+        match Settings::load(&cli) {
+            Ok(settings) => {
+                apply_settings(&server, &tables, &current, &settings);
+                current = settings;
+            }
+            Err(e) => error!("Keeping the current configuration: {}", e),
         }
     }
 }
 
+/// Applies the changes between two sets of settings that are safe to make
+/// while the server runs: connection limits, new tables, and the table
+/// settings `PokerGame::reconfigure` handles. Anything else that changed is
+/// logged as needing a restart.
+///
+/// # Arguments
+/// * `server` - The running server
+/// * `tables` - The tables opened from the configuration
+/// * `old` - The settings the server is running with
+/// * `new` - The reloaded settings
+fn apply_settings(
+    server: &Arc<Mutex<PokerServer>>,
+    tables: &ConfiguredTables,
+    old: &Settings,
+    new: &Settings,
+) {
+    for setting in restart_only_server_changes(&old.server, &new.server) {
+        warn!("server.{} changed; restart the server to apply it", setting);
+    }
+    server.lock().set_limits(new.server.limits());
+
+    for (game_id, config) in &new.tables {
+        let Some((_, old_config)) = old.tables.iter().find(|(id, _)| id == game_id) else {
+            info!("Opening table {}", game_id);
+            tables.open(server, game_id, config.clone());
+            continue;
+        };
+        for setting in restart_only_table_changes(old_config, config) {
+            warn!(
+                "tables.{}.{} changed; restart the server to apply it",
+                game_id, setting
+            );
+        }
+        let game = server.lock().get_game(game_id);
+        if let Some(game) = game {
+            if let Err(e) = game.lock().reconfigure(old_config, config) {
+                warn!("Failed to reconfigure table {}: {}", game_id, e);
+            }
+        }
+    }
+    for (game_id, _) in &old.tables {
+        if !new.tables.iter().any(|(id, _)| id == game_id) {
+            warn!(
+                "Table {} is no longer configured; it stays open until the server restarts",
+                game_id
+            );
+        }
+    }
+    info!("Configuration reloaded");
+}
+
+/// Picks the table an observer wants to watch from the handshake's request
+/// path, turning the request away if there is no such table.
+struct TvFeedRequest<'a> {
+    feeds: &'a HashMap<String, broadcast::Sender<String>>,
+    feed: &'a mut Option<broadcast::Receiver<String>>,
+}
+
+impl Callback for TvFeedRequest<'_> {
+    fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+        let table = request.uri().path().trim_matches('/');
+        let table = if table.is_empty() {
+            "main_table"
+        } else {
+            table
+        };
+        match self.feeds.get(table) {
+            Some(viewers) => {
+                *self.feed = Some(viewers.subscribe());
+                Ok(response)
+            }
+            None => {
+                let mut error = ErrorResponse::new(Some(format!("No table {}", table)));
+                *error.status_mut() = StatusCode::NOT_FOUND;
+                Err(error)
+            }
+        }
+    }
+}
+
+/// Streams a table's delayed observer feed to a viewer, such as a streaming
+/// overlay. The table is picked by the request path (`/main_table`), and
+/// anything the viewer sends is ignored.
+///
+/// # Arguments
+/// * `stream` - The viewer's TCP connection
+/// * `feeds` - Each table's observer feed, by game id
+async fn handle_tv_connection(
+    stream: tokio::net::TcpStream,
+    feeds: Arc<HashMap<String, broadcast::Sender<String>>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut feed = None;
+    let request = TvFeedRequest {
+        feeds: &feeds,
+        feed: &mut feed,
+    };
+    let ws_stream = accept_hdr_async(stream, request).await?;
+    let Some(mut feed) = feed else {
+        return Ok(());
+    };
+
+    let (mut write, mut read) = ws_stream.split();
+    loop {
+        tokio::select! {
+            event = feed.recv() => match event {
+                Ok(json) => write.send(Message::Text(json.into())).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Observer fell behind and missed {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = read.next() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    Ok(())
+}
+
 async fn handle_connection(
     stream: tokio::net::TcpStream,
     addr: SocketAddr,
     server: Arc<Mutex<PokerServer>>,
     player_id: String,
-    hmac_key: Arc<HmacKey>,
+    hmac_key: Option<Arc<HmacKey>>,
     nonce_cache: Arc<NonceCache>,
+    tls: Option<TlsAcceptor>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let ip = addr.ip().to_string();
 
-    let can_accept = {
+    let (banned, can_accept) = {
         let s = server.lock();
-        s.can_accept_connection(&ip)
+        (s.is_banned(&ip), s.can_accept_connection(&ip))
     };
 
+    if banned {
+        warn!("Connection rejected from {}: address is banned", ip);
+        return Ok(());
+    }
     if !can_accept {
         warn!("Connection rejected from {}: too many connections", ip);
         return Ok(());
     }
 
+    let stream = match tls::accept(stream, tls.as_ref()).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            warn!("TLS handshake with {} failed: {}", ip, e);
+            return Ok(());
+        }
+    };
+
     {
         let mut s = server.lock();
         s.register_connection(&ip);
@@ -771,7 +1666,7 @@ async fn handle_connection(
     let chat_rate_limiter = Arc::new(ChatRateLimiter::new());
 
     let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(CHANNEL_CAPACITY);
-    let write_handle = tokio::spawn(async move {
+    let write_future = async move {
         let mut sink = write;
         while let Some(msg) = rx.recv().await {
             if let Err(e) = sink.send(Message::Text(msg.into())).await {
@@ -779,13 +1674,16 @@ async fn handle_connection(
                 break;
             }
         }
-    });
+    };
+    let write_handle = tokio::spawn(write_future.instrument(Span::current()));
 
     {
         let mut s = server.lock();
         let player_name = generate_player_name(&player_id);
-        s.register_player(player_id.clone(), player_name, STARTING_CHIPS);
+        let starting_chips = s.limits().starting_chips;
+        s.register_player(player_id.clone(), player_name, starting_chips);
         s.connect_player(&player_id, tx);
+        s.set_player_ip(&player_id, &ip);
     }
 
     let server_for_read = Arc::clone(&server);
@@ -797,6 +1695,7 @@ async fn handle_connection(
     let rate_limiter_for_handler = Arc::clone(&rate_limiter_clone);
     let hmac_key_clone = hmac_key.clone();
     let nonce_cache_clone = nonce_cache.clone();
+    let metrics = server.lock().metrics();
     let handler = MessageHandler::new(
         server,
         player_id.clone(),
@@ -804,22 +1703,24 @@ async fn handle_connection(
         chat_rate_limiter_clone,
     );
 
-    let read_task = tokio::spawn(async move {
+    let read_future = async move {
         let mut stream = read;
         let mut last_activity = Instant::now();
+        let mut greeted = false;
         let server_for_read = server_for_read;
         let player_id = player_id_clone;
-        let config = ServerConfig::default();
 
         while let Some(result) = stream.next().await {
+            let limits = server_for_read.lock().limits();
             match result {
                 Ok(Message::Text(text)) => {
                     last_activity = Instant::now();
 
                     if !rate_limiter_clone.allow() {
                         warn!("Player {} exceeded rate limit", player_id);
+                        metrics.record_rate_limited(RateLimit::Message);
                         let error_msg = ServerMessage::Error("Rate limit exceeded".to_string());
-                        if let Ok(json) = serde_json::to_string(&error_msg) {
+                        if let Ok(json) = error_msg.to_unified_json() {
                             let server = server_for_read.lock();
                             if let Err(e) = server.send_to_player(&player_id, json) {
                                 warn!("Failed to send rate limit error: {}", e);
@@ -828,10 +1729,10 @@ async fn handle_connection(
                         break;
                     }
 
-                    if text.len() > MAX_MESSAGE_SIZE {
+                    if text.len() > limits.max_message_size {
                         warn!("Message from {} too large: {} bytes", player_id, text.len());
                         let error_msg = ServerMessage::Error("Message too large".to_string());
-                        if let Ok(json) = serde_json::to_string(&error_msg) {
+                        if let Ok(json) = error_msg.to_unified_json() {
                             let server = server_for_read.lock();
                             if let Err(e) = server.send_to_player(&player_id, json) {
                                 warn!("Failed to send size error: {}", e);
@@ -841,12 +1742,17 @@ async fn handle_connection(
                     }
                     debug!("Received from {}: {}", player_id, text);
 
-                    let use_hmac = config.enable_hmac_verification;
-                    if use_hmac {
+                    if !greeted {
+                        // The hello is never signed, as it says whether signing is needed.
+                        if !handler.handle_hello(&text, hmac_key_clone.is_some()) {
+                            break;
+                        }
+                        greeted = true;
+                    } else if let Some(hmac_key) = &hmac_key_clone {
                         if let Ok(signed_msg) =
                             serde_json::from_str::<poker_protocol::SignedMessage>(&text)
                         {
-                            match signed_msg.verify(&hmac_key_clone, &nonce_cache_clone) {
+                            match signed_msg.verify(hmac_key, &nonce_cache_clone) {
                                 Ok(client_msg) => {
                                     handler.handle_client_message(client_msg).await;
                                 }
@@ -855,10 +1761,13 @@ async fn handle_connection(
                                         "HMAC verification failed for player {}: {}",
                                         player_id, e
                                     );
+                                    metrics.record_verification_failure(
+                                        VerificationFailure::Signature,
+                                    );
                                     let error_msg = ServerMessage::Error(
                                         "Invalid message signature".to_string(),
                                     );
-                                    if let Ok(json) = serde_json::to_string(&error_msg) {
+                                    if let Ok(json) = error_msg.to_unified_json() {
                                         let server = server_for_read.lock();
                                         let _ = server.send_to_player(&player_id, json);
                                     }
@@ -870,9 +1779,10 @@ async fn handle_connection(
                                 "Player {} sent unsigned message when HMAC is required",
                                 player_id
                             );
+                            metrics.record_verification_failure(VerificationFailure::Unsigned);
                             let error_msg =
                                 ServerMessage::Error("Message signing is required".to_string());
-                            if let Ok(json) = serde_json::to_string(&error_msg) {
+                            if let Ok(json) = error_msg.to_unified_json() {
                                 let server = server_for_read.lock();
                                 let _ = server.send_to_player(&player_id, json);
                             }
@@ -897,6 +1807,133 @@ async fn handle_connection(
                                     "Return" => {
                                         handler.handle_return().await;
                                     }
+                                    "Straddle" => {
+                                        handler.handle_straddle(&value).await;
+                                    }
+                                    "PostBlind" => {
+                                        handler
+                                            .handle_client_message(ClientMessage::PostBlind)
+                                            .await;
+                                    }
+                                    "RunIt" => {
+                                        handler.handle_run_it(&value).await;
+                                    }
+                                    "BuyIn" => {
+                                        handler
+                                            .handle_chips(&value, |amount| {
+                                                ClientMessage::BuyIn(
+                                                    amount,
+                                                    requested_table(&value),
+                                                )
+                                            })
+                                            .await;
+                                    }
+                                    "Rebuy" => {
+                                        handler.handle_chips(&value, ClientMessage::Rebuy).await;
+                                    }
+                                    "TopUp" => {
+                                        handler.handle_chips(&value, ClientMessage::TopUp).await;
+                                    }
+                                    "CashOut" => {
+                                        handler.handle_client_message(ClientMessage::CashOut).await;
+                                    }
+                                    "TakeSeat" => {
+                                        handler.handle_take_seat(&value).await;
+                                    }
+                                    "ListTables" => {
+                                        handler
+                                            .handle_client_message(ClientMessage::ListTables)
+                                            .await;
+                                    }
+                                    "CreateTable" => {
+                                        handler.handle_create_table(&value).await;
+                                    }
+                                    "JoinTable" => {
+                                        handler.handle_join_table(&value).await;
+                                    }
+                                    "ApproveSeat" => {
+                                        handler
+                                            .handle_player_command(
+                                                &value,
+                                                ClientMessage::ApproveSeat,
+                                            )
+                                            .await;
+                                    }
+                                    "RejectSeat" => {
+                                        handler
+                                            .handle_player_command(
+                                                &value,
+                                                ClientMessage::RejectSeat,
+                                            )
+                                            .await;
+                                    }
+                                    "PauseTable" => {
+                                        handler
+                                            .handle_client_message(ClientMessage::PauseTable)
+                                            .await;
+                                    }
+                                    "ResumeTable" => {
+                                        handler
+                                            .handle_client_message(ClientMessage::ResumeTable)
+                                            .await;
+                                    }
+                                    "SetBlinds" => {
+                                        handler.handle_set_blinds(&value).await;
+                                    }
+                                    "SitOutPlayer" => {
+                                        handler
+                                            .handle_player_command(
+                                                &value,
+                                                ClientMessage::SitOutPlayer,
+                                            )
+                                            .await;
+                                    }
+                                    "KickPlayer" => {
+                                        handler
+                                            .handle_player_command(
+                                                &value,
+                                                ClientMessage::KickPlayer,
+                                            )
+                                            .await;
+                                    }
+                                    "EndSession" => {
+                                        handler
+                                            .handle_client_message(ClientMessage::EndSession)
+                                            .await;
+                                    }
+                                    "JoinWaitlist" => {
+                                        handler
+                                            .handle_client_message(ClientMessage::JoinWaitlist(
+                                                requested_table(&value),
+                                            ))
+                                            .await;
+                                    }
+                                    "LeaveWaitlist" => {
+                                        handler
+                                            .handle_client_message(ClientMessage::LeaveWaitlist(
+                                                requested_table(&value),
+                                            ))
+                                            .await;
+                                    }
+                                    "AcceptSeat" => {
+                                        handler
+                                            .handle_client_message(ClientMessage::AcceptSeat(
+                                                requested_table(&value),
+                                            ))
+                                            .await;
+                                    }
+                                    "Spectate" => {
+                                        handler
+                                            .handle_client_message(ClientMessage::Spectate(
+                                                requested_table(&value),
+                                            ))
+                                            .await;
+                                    }
+                                    "StopSpectating" => {
+                                        handler
+                                            .handle_client_message(ClientMessage::StopSpectating)
+                                            .await;
+                                    }
                                     "Ping" => {
                                         if let Some(ts) = value["timestamp"].as_u64() {
                                             handler.handle_ping(ts).await;
@@ -922,15 +1959,24 @@ async fn handle_connection(
                 }
                 _ => {}
             }
-            if last_activity.elapsed() > Duration::from_millis(INACTIVITY_TIMEOUT_MS) {
+            if last_activity.elapsed() > Duration::from_millis(limits.inactivity_timeout_ms) {
                 warn!("Player {} timed out due to inactivity", player_id);
                 break;
             }
         }
-    });
-
-    let read_result = read_task.await;
-    drop(write_handle);
+    };
+    let read_task = tokio::spawn(read_future.instrument(Span::current()));
+
+    let mut read_task = read_task;
+    let mut write_handle = write_handle;
+    let read_result = tokio::select! {
+        result = &mut read_task => result,
+        _ = &mut write_handle => {
+            // The server dropped this player's sender, so they were removed.
+            read_task.abort();
+            Ok(())
+        }
+    };
 
     {
         let mut s = server_for_cleanup.lock();
@@ -944,6 +1990,7 @@ async fn handle_connection(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::PokerGame;
 
     #[test]
     fn test_rate_limiter_allow() {
@@ -1051,8 +2098,10 @@ mod tests {
             poker_protocol::GameStage::WaitingForPlayers
         );
 
-        game.add_player("p1".to_string(), "Player1".to_string(), 1000);
-        game.add_player("p2".to_string(), "Player2".to_string(), 1000);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
+        game.add_player("p2".to_string(), "Player2".to_string(), 1000)
+            .unwrap();
 
         assert_eq!(game.players.len(), 2);
         assert!(game.get_players().contains_key("p1"));
@@ -1064,7 +2113,8 @@ mod tests {
         let tx = tokio::sync::broadcast::channel(100).0;
         let mut game = PokerGame::new("test_sitout".to_string(), 5, 10, tx);
 
-        game.add_player("p1".to_string(), "Player1".to_string(), 1000);
+        game.add_player("p1".to_string(), "Player1".to_string(), 1000)
+            .unwrap();
 
         assert!(!game.get_players().get("p1").unwrap().is_sitting_out);
 
@@ -1086,6 +2136,93 @@ mod tests {
         assert!(players.values().all(|p| p.current_bet == 0));
     }
 
+    #[test]
+    fn test_parse_betting_structure() {
+        assert_eq!(
+            parse_betting_structure("no-limit", 10),
+            Some(BettingStructure::NoLimit)
+        );
+        assert_eq!(
+            parse_betting_structure("Pot-Limit", 10),
+            Some(BettingStructure::PotLimit)
+        );
+        assert_eq!(
+            parse_betting_structure("fixed-limit", 10),
+            Some(BettingStructure::FixedLimit {
+                small_bet: 10,
+                big_bet: 20,
+                raise_cap: poker_protocol::DEFAULT_FIXED_LIMIT_RAISE_CAP,
+            })
+        );
+        assert_eq!(parse_betting_structure("spread-limit", 10), None);
+    }
+
+    #[test]
+    fn test_parse_game_variant() {
+        assert_eq!(parse_game_variant("holdem"), Some(GameVariant::TexasHoldem));
+        assert_eq!(parse_game_variant("Omaha"), Some(GameVariant::Omaha));
+        assert_eq!(parse_game_variant("plo8"), Some(GameVariant::OmahaHiLo));
+        assert_eq!(parse_game_variant("6+"), Some(GameVariant::ShortDeck));
+        assert_eq!(
+            parse_game_variant("Seven-Card-Stud"),
+            Some(GameVariant::SevenCardStud)
+        );
+        assert_eq!(parse_game_variant("razz"), Some(GameVariant::Razz));
+        assert_eq!(
+            parse_game_variant("2-7-triple-draw"),
+            Some(GameVariant::DeuceToSevenTripleDraw)
+        );
+        assert_eq!(parse_game_variant("badugi"), None);
+    }
+
+    #[test]
+    fn test_parse_straddle_rule() {
+        assert_eq!(parse_straddle_rule("UTG"), Some(StraddleRule::UnderTheGun));
+        assert_eq!(
+            parse_straddle_rule("mississippi"),
+            Some(StraddleRule::Mississippi)
+        );
+        assert_eq!(parse_straddle_rule("none"), Some(StraddleRule::None));
+        assert_eq!(parse_straddle_rule("double"), None);
+    }
+
+    #[test]
+    fn test_parse_rake() {
+        assert_eq!(parse_rake_percent("5"), Some(500));
+        assert_eq!(parse_rake_percent("4.5%"), Some(450));
+        assert_eq!(parse_rake_percent("120"), None);
+
+        let caps = parse_rake_caps("2:1, 4:3").unwrap();
+        assert_eq!(
+            caps,
+            vec![
+                RakeCap {
+                    min_players: 2,
+                    cap: 1
+                },
+                RakeCap {
+                    min_players: 4,
+                    cap: 3
+                },
+            ]
+        );
+        assert_eq!(parse_rake_caps("3").unwrap()[0].min_players, 0);
+        assert_eq!(parse_rake_caps("2:x"), None);
+    }
+
+    #[test]
+    fn test_parse_game_rotation() {
+        let horse = parse_game_rotation("HORSE", 10, RotationSchedule::EachOrbit).unwrap();
+        assert_eq!(horse.games().len(), 5);
+
+        let custom =
+            parse_game_rotation("holdem, razz", 10, RotationSchedule::EveryHands(6)).unwrap();
+        let variants: Vec<GameVariant> = custom.games().iter().map(|g| g.variant).collect();
+        assert_eq!(variants, vec![GameVariant::TexasHoldem, GameVariant::Razz]);
+
+        assert!(parse_game_rotation("holdem,badugi", 10, RotationSchedule::EachOrbit).is_none());
+    }
+
     #[tokio::test]
     async fn test_integration_protocol_serialization() {
         let _action_msg = r#"{"Action":"Fold"}"#;
@@ -1112,4 +2249,73 @@ mod tests {
         assert_eq!(player.hole_cards.len(), 2);
         assert_eq!(player.hole_cards[0].to_string(), "A♥");
     }
+
+    #[tokio::test]
+    async fn test_reload_applies_limits_and_opens_new_tables() {
+        let load = |contents: &str| {
+            let file = toml::from_str(contents).unwrap();
+            Settings::resolve(ServerConfig::default(), &file, &Default::default()).unwrap()
+        };
+        let old = load("[[tables]]\nid = \"a\"\nbig_blind = 10\n");
+        let new = load(
+            "[server]\nmax_connections = 2\naddr = \"127.0.0.1:9999\"\n\n\
+             [[tables]]\nid = \"a\"\nbig_blind = 20\n\n[[tables]]\nid = \"b\"\n",
+        );
+        let server = Arc::new(Mutex::new(PokerServer::new()));
+        let tables = ConfiguredTables::new(Duration::from_secs(0));
+        for (game_id, config) in &old.tables {
+            tables.open(&server, game_id, config.clone());
+        }
+        server.lock().register_connection("10.0.0.1");
+        server.lock().register_connection("10.0.0.2");
+
+        apply_settings(&server, &tables, &old, &new);
+
+        assert_eq!(server.lock().limits().max_connections, 2);
+        assert!(!server.lock().can_accept_connection("10.0.0.3"));
+        assert!(server.lock().get_game("b").is_some());
+        let mut ids = tables.ids();
+        ids.sort();
+        assert_eq!(ids, ["a", "b"]);
+        tables.close_feeds();
+    }
+
+    #[tokio::test]
+    async fn test_hello_must_open_the_connection_and_agree_on_a_version() {
+        async fn reply(rx: &mut tokio::sync::mpsc::Receiver<String>) -> serde_json::Value {
+            serde_json::from_str(&rx.recv().await.unwrap()).unwrap()
+        }
+
+        let server = Arc::new(Mutex::new(PokerServer::new()));
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(CHANNEL_CAPACITY);
+        server.lock().create_game("main_table".to_string(), 5, 10);
+        server
+            .lock()
+            .register_player("p1".to_string(), "Player1".to_string(), 1000);
+        server.lock().connect_player("p1", tx);
+        let handler = MessageHandler::new(
+            Arc::clone(&server),
+            "p1".to_string(),
+            Arc::new(RateLimiter::new()),
+            Arc::new(ChatRateLimiter::new()),
+        );
+
+        assert!(!handler.handle_hello(r#"{"type": "Connect"}"#, false));
+        assert_eq!(reply(&mut rx).await["type"], "Error");
+
+        let hello = Hello::new(false).to_unified_json().unwrap();
+        assert!(!handler.handle_hello(&hello, true));
+        assert_eq!(
+            reply(&mut rx).await["message"],
+            "Incompatible client: this server requires signed messages"
+        );
+
+        assert!(handler.handle_hello(&hello, false));
+        let agreed = reply(&mut rx).await;
+        assert_eq!(agreed["type"], "Hello");
+        assert_eq!(agreed["version"], poker_protocol::PROTOCOL_VERSION);
+
+        handler.handle_connect().await;
+        assert_eq!(reply(&mut rx).await["type"], "Connected");
+    }
 }
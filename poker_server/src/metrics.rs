@@ -0,0 +1,410 @@
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use tracing::{debug, error, warn};
+
+use crate::server::PokerServer;
+
+/// Upper bounds, in seconds, of the action latency histogram's buckets
+const ACTION_LATENCY_BUCKETS: [f64; 8] = [0.0005, 0.001, 0.0025, 0.005, 0.01, 0.05, 0.1, 0.5];
+/// Largest scrape request read before it is turned away
+const MAX_REQUEST_BYTES: usize = 8192;
+/// Seconds a scraper has to send its request
+const REQUEST_TIMEOUT_SECS: u64 = 5;
+
+/// Which rate limit turned a message away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimit {
+    /// Any message, which also closes the connection
+    Message,
+    Action,
+    Chat,
+}
+
+impl RateLimit {
+    const ALL: [RateLimit; 3] = [RateLimit::Message, RateLimit::Action, RateLimit::Chat];
+
+    fn label(self) -> &'static str {
+        match self {
+            RateLimit::Message => "message",
+            RateLimit::Action => "action",
+            RateLimit::Chat => "chat",
+        }
+    }
+}
+
+/// Why a message failed verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationFailure {
+    /// The HMAC signature, nonce or timestamp did not check out
+    Signature,
+    /// The message was not signed while signing is required
+    Unsigned,
+    /// The client's `Hello` was missing or incompatible
+    Handshake,
+}
+
+impl VerificationFailure {
+    const ALL: [VerificationFailure; 3] = [
+        VerificationFailure::Signature,
+        VerificationFailure::Unsigned,
+        VerificationFailure::Handshake,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            VerificationFailure::Signature => "signature",
+            VerificationFailure::Unsigned => "unsigned",
+            VerificationFailure::Handshake => "handshake",
+        }
+    }
+}
+
+/// Counters of events across the server, scraped in the Prometheus text format.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    broadcast_timeouts: AtomicU64,
+    rate_limited: [AtomicU64; 3],
+    verification_failures: [AtomicU64; 3],
+    /// Actions that took at most each bucket's bound, not cumulative
+    action_latency_buckets: [AtomicU64; ACTION_LATENCY_BUCKETS.len()],
+    action_latency_count: AtomicU64,
+    action_latency_sum_micros: AtomicU64,
+}
+
+impl Metrics {
+    /// Counts a broadcast that timed out waiting for a player's channel.
+    pub fn record_broadcast_timeout(&self) {
+        self.broadcast_timeouts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a message turned away by a rate limit.
+    pub fn record_rate_limited(&self, limit: RateLimit) {
+        self.rate_limited[limit as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a message that failed verification.
+    pub fn record_verification_failure(&self, failure: VerificationFailure) {
+        self.verification_failures[failure as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records how long the server took to handle a player's action,
+    /// including waiting for the server lock.
+    pub fn record_action_latency(&self, latency: Duration) {
+        let seconds = latency.as_secs_f64();
+        if let Some(bucket) = ACTION_LATENCY_BUCKETS
+            .iter()
+            .position(|&bound| seconds <= bound)
+        {
+            self.action_latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+        self.action_latency_count.fetch_add(1, Ordering::Relaxed);
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.action_latency_sum_micros
+            .fetch_add(micros, Ordering::Relaxed);
+    }
+
+    /// Renders every metric in the Prometheus text exposition format.
+    ///
+    /// # Arguments
+    /// * `gauges` - The server's current state, read when scraped
+    pub fn render(&self, gauges: &ServerGauges) -> String {
+        let mut out = String::new();
+        for (name, help, value) in [
+            (
+                "poker_connections",
+                "Open player connections.",
+                gauges.connections,
+            ),
+            (
+                "poker_connection_ips",
+                "Distinct addresses with open player connections.",
+                gauges.connection_ips,
+            ),
+            (
+                "poker_send_backlog_messages",
+                "Messages queued for players across all connections.",
+                gauges.send_backlog,
+            ),
+            (
+                "poker_send_backlog_max_messages",
+                "Messages queued for the most backed-up connection.",
+                gauges.max_send_backlog,
+            ),
+        ] {
+            header(&mut out, name, "gauge", help);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+
+        header(
+            &mut out,
+            "poker_hands_total",
+            "counter",
+            "Hands dealt at each table; rate() gives hands per minute.",
+        );
+        for table in &gauges.tables {
+            let _ = writeln!(
+                out,
+                "poker_hands_total{{table=\"{}\"}} {}",
+                escape_label(&table.game_id),
+                table.hands
+            );
+        }
+        header(
+            &mut out,
+            "poker_table_players",
+            "gauge",
+            "Players seated at each table.",
+        );
+        for table in &gauges.tables {
+            let _ = writeln!(
+                out,
+                "poker_table_players{{table=\"{}\"}} {}",
+                escape_label(&table.game_id),
+                table.players
+            );
+        }
+        header(
+            &mut out,
+            "poker_table_event_backlog",
+            "gauge",
+            "Events each table has queued that have not reached every relay.",
+        );
+        for table in &gauges.tables {
+            let _ = writeln!(
+                out,
+                "poker_table_event_backlog{{table=\"{}\"}} {}",
+                escape_label(&table.game_id),
+                table.event_backlog
+            );
+        }
+
+        header(
+            &mut out,
+            "poker_broadcast_timeouts_total",
+            "counter",
+            "Broadcasts that timed out waiting for a player's channel.",
+        );
+        let _ = writeln!(
+            out,
+            "poker_broadcast_timeouts_total {}",
+            self.broadcast_timeouts.load(Ordering::Relaxed)
+        );
+        header(
+            &mut out,
+            "poker_rate_limited_total",
+            "counter",
+            "Messages turned away by a rate limit.",
+        );
+        for limit in RateLimit::ALL {
+            let _ = writeln!(
+                out,
+                "poker_rate_limited_total{{limit=\"{}\"}} {}",
+                limit.label(),
+                self.rate_limited[limit as usize].load(Ordering::Relaxed)
+            );
+        }
+        header(
+            &mut out,
+            "poker_verification_failures_total",
+            "counter",
+            "Messages that failed verification.",
+        );
+        for failure in VerificationFailure::ALL {
+            let _ = writeln!(
+                out,
+                "poker_verification_failures_total{{reason=\"{}\"}} {}",
+                failure.label(),
+                self.verification_failures[failure as usize].load(Ordering::Relaxed)
+            );
+        }
+
+        header(
+            &mut out,
+            "poker_action_latency_seconds",
+            "histogram",
+            "Time taken to handle a player's action.",
+        );
+        let mut cumulative = 0;
+        for (bound, count) in ACTION_LATENCY_BUCKETS
+            .iter()
+            .zip(&self.action_latency_buckets)
+        {
+            cumulative += count.load(Ordering::Relaxed);
+            let _ = writeln!(
+                out,
+                "poker_action_latency_seconds_bucket{{le=\"{}\"}} {}",
+                bound, cumulative
+            );
+        }
+        let count = self.action_latency_count.load(Ordering::Relaxed);
+        let _ = writeln!(
+            out,
+            "poker_action_latency_seconds_bucket{{le=\"+Inf\"}} {}",
+            count
+        );
+        let _ = writeln!(
+            out,
+            "poker_action_latency_seconds_sum {}",
+            self.action_latency_sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0
+        );
+        let _ = writeln!(out, "poker_action_latency_seconds_count {}", count);
+        out
+    }
+}
+
+/// The state of a table when metrics are scraped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableGauges {
+    pub game_id: String,
+    /// Hands dealt at the table so far
+    pub hands: u64,
+    pub players: usize,
+    /// Events queued on the table's channel that not every relay has taken
+    pub event_backlog: usize,
+}
+
+/// The state of the server when metrics are scraped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerGauges {
+    pub connections: usize,
+    pub connection_ips: usize,
+    /// Messages queued for players across all connections
+    pub send_backlog: usize,
+    /// Messages queued for the most backed-up connection
+    pub max_send_backlog: usize,
+    pub tables: Vec<TableGauges>,
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escapes a label value for the text exposition format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serves `GET /metrics` to scrapers until the listener fails.
+///
+/// # Arguments
+/// * `listener` - The socket scrapers connect to
+/// * `server` - The poker server whose metrics are served
+pub async fn serve(listener: TcpListener, server: Arc<Mutex<PokerServer>>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, addr)) => {
+                let server = Arc::clone(&server);
+                tokio::spawn(async move {
+                    if let Err(e) = handle_scrape(stream, server).await {
+                        warn!("Metrics request from {} failed: {}", addr, e);
+                    }
+                });
+            }
+            Err(e) => error!("Failed to accept metrics connection: {}", e),
+        }
+    }
+}
+
+/// Answers one HTTP request for metrics and closes the connection.
+async fn handle_scrape(
+    mut stream: TcpStream,
+    server: Arc<Mutex<PokerServer>>,
+) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let read = match timeout(
+            Duration::from_secs(REQUEST_TIMEOUT_SECS),
+            stream.read(&mut buf),
+        )
+        .await
+        {
+            Ok(read) => read?,
+            Err(_) => return Ok(()),
+        };
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+
+    let request_line = String::from_utf8_lossy(&request);
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let (metrics, gauges) = {
+                let server = server.lock();
+                (server.metrics(), server.gauges())
+            };
+            ("200 OK", metrics.render(&gauges))
+        }
+        (Some(method), Some(path)) => {
+            debug!("Metrics endpoint got {} {}", method, path);
+            ("404 Not Found", "Not found\n".to_string())
+        }
+        _ => ("400 Bad Request", "Bad request\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counts_events_and_table_state() {
+        let metrics = Metrics::default();
+        metrics.record_rate_limited(RateLimit::Chat);
+        metrics.record_verification_failure(VerificationFailure::Unsigned);
+        metrics.record_broadcast_timeout();
+        metrics.record_action_latency(Duration::from_micros(800));
+        metrics.record_action_latency(Duration::from_secs(2));
+
+        let text = metrics.render(&ServerGauges {
+            connections: 3,
+            connection_ips: 2,
+            send_backlog: 5,
+            max_send_backlog: 4,
+            tables: vec![TableGauges {
+                game_id: "main_table".to_string(),
+                hands: 42,
+                players: 6,
+                event_backlog: 1,
+            }],
+        });
+        for line in [
+            "poker_connections 3",
+            "poker_hands_total{table=\"main_table\"} 42",
+            "poker_rate_limited_total{limit=\"chat\"} 1",
+            "poker_rate_limited_total{limit=\"action\"} 0",
+            "poker_verification_failures_total{reason=\"unsigned\"} 1",
+            "poker_broadcast_timeouts_total 1",
+            "poker_action_latency_seconds_bucket{le=\"0.0005\"} 0",
+            "poker_action_latency_seconds_bucket{le=\"0.001\"} 1",
+            "poker_action_latency_seconds_bucket{le=\"0.5\"} 1",
+            "poker_action_latency_seconds_bucket{le=\"+Inf\"} 2",
+            "poker_action_latency_seconds_count 2",
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing {}", line);
+        }
+        assert_eq!(escape_label("a\"b"), "a\\\"b");
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::{HashMap, HashSet};
+
+use poker_protocol::{ServerError, ServerResult};
+use rand::Rng;
+
+/// Length of a generated invite code
+const INVITE_CODE_LEN: usize = 8;
+/// Characters invite codes are made from, leaving out ones easily misread
+const INVITE_CODE_CHARS: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+/// Most invite codes issued for one table
+pub const MAX_INVITE_CODES: u32 = 50;
+
+/// What a player must show to join a private table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TableKey {
+    Password(String),
+    /// Invite codes not yet used; each admits one player
+    InviteCodes(HashSet<String>),
+}
+
+/// A table that is hidden from the lobby, joined with a password or a
+/// one-time invite code and the host's approval.
+#[derive(Debug, Clone)]
+pub struct PrivateTable {
+    /// The player who created the table and approves seat requests
+    pub host: String,
+    key: TableKey,
+    /// Players waiting for the host's approval, with the seat they asked for
+    requests: HashMap<String, Option<usize>>,
+}
+
+impl PrivateTable {
+    /// Creates a table joined with a password.
+    ///
+    /// # Arguments
+    /// * `host` - The player creating the table
+    /// * `password` - The password players must give
+    pub fn with_password(host: &str, password: String) -> ServerResult<Self> {
+        if password.is_empty() {
+            return Err(ServerError::InvalidTableSettings(
+                "password cannot be empty".to_string(),
+            ));
+        }
+        Ok(Self::new(host, TableKey::Password(password)))
+    }
+
+    /// Creates a table joined with one-time invite codes.
+    ///
+    /// # Arguments
+    /// * `host` - The player creating the table
+    /// * `count` - How many codes to issue
+    ///
+    /// # Returns
+    /// The table and its invite codes
+    pub fn with_invite_codes(host: &str, count: u32) -> ServerResult<(Self, Vec<String>)> {
+        if count == 0 || count > MAX_INVITE_CODES {
+            return Err(ServerError::InvalidTableSettings(format!(
+                "between 1 and {} invite codes can be issued",
+                MAX_INVITE_CODES
+            )));
+        }
+        let mut rng = rand::thread_rng();
+        let mut codes = HashSet::new();
+        while codes.len() < count as usize {
+            let code: String = (0..INVITE_CODE_LEN)
+                .map(|_| INVITE_CODE_CHARS[rng.gen_range(0..INVITE_CODE_CHARS.len())] as char)
+                .collect();
+            codes.insert(code);
+        }
+        let issued = codes.iter().cloned().collect();
+        Ok((Self::new(host, TableKey::InviteCodes(codes)), issued))
+    }
+
+    fn new(host: &str, key: TableKey) -> Self {
+        Self {
+            host: host.to_string(),
+            key,
+            requests: HashMap::new(),
+        }
+    }
+
+    /// Checks a joining player's credential, using up an invite code.
+    ///
+    /// # Arguments
+    /// * `credential` - The password or invite code the player gave
+    pub fn admit(&mut self, credential: Option<&str>) -> ServerResult<()> {
+        let credential = credential.ok_or(ServerError::InvalidTableCredential)?;
+        let admitted = match &mut self.key {
+            TableKey::Password(password) => password == credential,
+            TableKey::InviteCodes(codes) => codes.remove(credential),
+        };
+        if admitted {
+            Ok(())
+        } else {
+            Err(ServerError::InvalidTableCredential)
+        }
+    }
+
+    /// Records an admitted player's request for a seat, awaiting approval.
+    pub fn request_seat(&mut self, player_id: &str, seat: Option<usize>) {
+        self.requests.insert(player_id.to_string(), seat);
+    }
+
+    /// Takes a player's seat request for the host to approve or reject.
+    ///
+    /// # Returns
+    /// The seat the player asked for, or `NoSeatRequest` if they did not ask
+    pub fn take_request(&mut self, player_id: &str) -> ServerResult<Option<usize>> {
+        self.requests
+            .remove(player_id)
+            .ok_or_else(|| ServerError::NoSeatRequest(player_id.to_string()))
+    }
+
+    /// Returns whether a player is waiting for the host's approval.
+    pub fn has_request(&self, player_id: &str) -> bool {
+        self.requests.contains_key(player_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invite_codes_admit_once() {
+        let (mut table, codes) = PrivateTable::with_invite_codes("host", 3).unwrap();
+        assert_eq!(codes.len(), 3);
+        assert!(codes.iter().all(|code| code.len() == INVITE_CODE_LEN));
+
+        table.admit(Some(&codes[0])).unwrap();
+        assert!(matches!(
+            table.admit(Some(&codes[0])),
+            Err(ServerError::InvalidTableCredential)
+        ));
+        assert!(matches!(
+            table.admit(None),
+            Err(ServerError::InvalidTableCredential)
+        ));
+        assert!(PrivateTable::with_invite_codes("host", 0).is_err());
+    }
+
+    #[test]
+    fn test_password_admits_every_time() {
+        let mut table = PrivateTable::with_password("host", "s3cret".to_string()).unwrap();
+        table.admit(Some("s3cret")).unwrap();
+        table.admit(Some("s3cret")).unwrap();
+        assert!(table.admit(Some("guess")).is_err());
+
+        table.request_seat("p1", Some(4));
+        assert!(table.has_request("p1"));
+        assert_eq!(table.take_request("p1").unwrap(), Some(4));
+        assert!(matches!(
+            table.take_request("p1"),
+            Err(ServerError::NoSeatRequest(_))
+        ));
+    }
+}
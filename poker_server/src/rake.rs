@@ -0,0 +1,193 @@
+/// Basis points in a whole pot (100%).
+const FULL_POT_BPS: i64 = 10_000;
+
+/// A limit on the rake taken from one hand, applying once enough players are
+/// dealt in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RakeCap {
+    /// Fewest players dealt in for this cap to apply
+    pub min_players: usize,
+    /// Most chips raked from a single hand
+    pub cap: i32,
+}
+
+/// How the house rakes pots at a cash table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RakeStructure {
+    /// Share of each pot raked, in basis points (500 is 5%); 0 disables the rake
+    pub rate_bps: u32,
+    /// Caps by player count; the cap with the highest `min_players` not above
+    /// the number of players dealt in applies, and the rake is uncapped if none does
+    pub caps: Vec<RakeCap>,
+    /// Take no rake from hands that end before the flop (before the second
+    /// street in stud and draw games)
+    pub no_flop_no_drop: bool,
+}
+
+impl RakeStructure {
+    /// Returns the cap on a hand's rake for the number of players dealt in.
+    pub fn cap_for(&self, players: usize) -> Option<i32> {
+        self.caps
+            .iter()
+            .filter(|cap| cap.min_players <= players)
+            .max_by_key(|cap| cap.min_players)
+            .map(|cap| cap.cap)
+    }
+
+    /// Calculates the rake taken from each of a hand's pots.
+    ///
+    /// The rake is a share of the chips in every pot together, rounded down
+    /// and capped, then split between the pots in proportion to their size.
+    /// Chips left over from rounding the split come from the earliest pots,
+    /// so the per-pot rake always adds up to the hand's rake exactly.
+    ///
+    /// # Arguments
+    /// * `pots` - Chips in each pot that may be raked, main pot first
+    /// * `players` - Players dealt in to the hand
+    /// * `flop_seen` - Whether the hand got past its first street
+    ///
+    /// # Returns
+    /// The chips raked from each pot, in the same order as `pots`
+    pub fn rake_pots(&self, pots: &[i32], players: usize, flop_seen: bool) -> Vec<i32> {
+        let mut rake = vec![0; pots.len()];
+        if self.rate_bps == 0 || (self.no_flop_no_drop && !flop_seen) {
+            return rake;
+        }
+
+        let total: i64 = pots.iter().map(|&amount| i64::from(amount.max(0))).sum();
+        if total == 0 {
+            return rake;
+        }
+        let mut hand_rake = total * i64::from(self.rate_bps) / FULL_POT_BPS;
+        if let Some(cap) = self.cap_for(players) {
+            hand_rake = hand_rake.min(i64::from(cap.max(0)));
+        }
+
+        let mut remaining = hand_rake;
+        for (taken, &amount) in rake.iter_mut().zip(pots) {
+            let share = hand_rake * i64::from(amount.max(0)) / total;
+            *taken = share as i32;
+            remaining -= share;
+        }
+        for (taken, &amount) in rake.iter_mut().zip(pots) {
+            if remaining == 0 {
+                break;
+            }
+            if *taken < amount {
+                *taken += 1;
+                remaining -= 1;
+            }
+        }
+        rake
+    }
+}
+
+/// The rake taken from one hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RakeEntry {
+    pub hand_number: i32,
+    /// Chips raked from each pot, main pot first
+    pub pots: Vec<i32>,
+}
+
+impl RakeEntry {
+    /// Returns the total chips raked from the hand.
+    pub fn amount(&self) -> i32 {
+        self.pots.iter().sum()
+    }
+}
+
+/// Running record of the rake taken at a table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RakeLedger {
+    entries: Vec<RakeEntry>,
+}
+
+impl RakeLedger {
+    /// Records the rake taken from a hand. Hands that were not raked are not recorded.
+    ///
+    /// # Arguments
+    /// * `hand_number` - The hand the rake was taken from
+    /// * `pots` - Chips raked from each pot, main pot first
+    pub fn record(&mut self, hand_number: i32, pots: Vec<i32>) {
+        let entry = RakeEntry { hand_number, pots };
+        if entry.amount() > 0 {
+            self.entries.push(entry);
+        }
+    }
+
+    /// Returns every raked hand, oldest first.
+    pub fn entries(&self) -> &[RakeEntry] {
+        &self.entries
+    }
+
+    /// Returns the total rake taken at the table.
+    pub fn total(&self) -> i64 {
+        self.entries.iter().map(|e| i64::from(e.amount())).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn five_percent(caps: Vec<RakeCap>) -> RakeStructure {
+        RakeStructure {
+            rate_bps: 500,
+            caps,
+            no_flop_no_drop: true,
+        }
+    }
+
+    #[test]
+    fn test_rake_rounds_down_to_the_chip() {
+        let rake = five_percent(Vec::new());
+        assert_eq!(rake.rake_pots(&[119], 2, true), vec![5]);
+        assert_eq!(rake.rake_pots(&[19], 2, true), vec![0]);
+    }
+
+    #[test]
+    fn test_cap_depends_on_player_count() {
+        let rake = five_percent(vec![
+            RakeCap {
+                min_players: 2,
+                cap: 1,
+            },
+            RakeCap {
+                min_players: 4,
+                cap: 3,
+            },
+        ]);
+        assert_eq!(rake.cap_for(1), None);
+        assert_eq!(rake.rake_pots(&[1000], 3, true), vec![1]);
+        assert_eq!(rake.rake_pots(&[1000], 6, true), vec![3]);
+    }
+
+    #[test]
+    fn test_side_pot_rake_adds_up_exactly() {
+        let rake = five_percent(Vec::new());
+        // 5% of 430 is 21: 10, 5 and 5 by proportion, with the chip left
+        // over from rounding taken from the main pot.
+        let pots = rake.rake_pots(&[210, 110, 110], 4, true);
+        assert_eq!(pots, vec![11, 5, 5]);
+
+        let capped = five_percent(vec![RakeCap {
+            min_players: 2,
+            cap: 7,
+        }]);
+        assert_eq!(capped.rake_pots(&[300, 100], 3, true), vec![6, 1]);
+    }
+
+    #[test]
+    fn test_no_flop_no_drop_and_ledger() {
+        let rake = five_percent(Vec::new());
+        assert_eq!(rake.rake_pots(&[400], 2, false), vec![0]);
+
+        let mut ledger = RakeLedger::default();
+        ledger.record(1, rake.rake_pots(&[400], 2, false));
+        ledger.record(2, rake.rake_pots(&[400, 100], 3, true));
+        assert_eq!(ledger.entries().len(), 1);
+        assert_eq!(ledger.entries()[0].hand_number, 2);
+        assert_eq!(ledger.total(), 25);
+    }
+}
@@ -0,0 +1,196 @@
+use poker_protocol::{AnteStructure, BettingStructure, GameVariant};
+
+use crate::game::TableConfig;
+
+/// One game in a mixed-game rotation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationGame {
+    /// Variant dealt while this game is current
+    pub variant: GameVariant,
+    /// Betting structure the game is played with
+    pub betting_structure: BettingStructure,
+    /// Antes posted while this game is current
+    pub ante: AnteStructure,
+}
+
+impl RotationGame {
+    /// Creates a rotation game using the variant's usual betting structure and antes.
+    ///
+    /// # Arguments
+    /// * `variant` - The game variant to deal
+    /// * `big_blind` - The table's big blind, used to size limit bets and stud antes
+    pub fn new(variant: GameVariant, big_blind: i32) -> Self {
+        let config = TableConfig::for_variant(variant, 0, big_blind);
+        Self {
+            variant,
+            betting_structure: config.betting_structure,
+            ante: config.ante,
+        }
+    }
+
+    /// Returns this game played with a different betting structure.
+    pub fn with_structure(mut self, betting_structure: BettingStructure) -> Self {
+        self.betting_structure = betting_structure;
+        self
+    }
+}
+
+/// When a mixed-game table moves on to the next game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationSchedule {
+    /// After a fixed number of hands
+    EveryHands(u32),
+    /// After one hand per player dealt in, so every player has the button once
+    EachOrbit,
+}
+
+/// A list of games a table cycles through between hands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRotation {
+    games: Vec<RotationGame>,
+    schedule: RotationSchedule,
+    current: usize,
+}
+
+impl GameRotation {
+    /// Creates a rotation starting with the first game in the list.
+    ///
+    /// # Returns
+    /// `None` if `games` is empty
+    pub fn new(games: Vec<RotationGame>, schedule: RotationSchedule) -> Option<Self> {
+        if games.is_empty() {
+            return None;
+        }
+        Some(Self {
+            games,
+            schedule,
+            current: 0,
+        })
+    }
+
+    /// Creates a HORSE rotation: limit Hold'em, Omaha Hi/Lo, Razz, Stud and Stud Hi/Lo.
+    pub fn horse(big_blind: i32, schedule: RotationSchedule) -> Self {
+        let limit = BettingStructure::fixed_limit(big_blind);
+        let games = vec![
+            RotationGame::new(GameVariant::TexasHoldem, big_blind).with_structure(limit),
+            RotationGame::new(GameVariant::OmahaHiLo, big_blind).with_structure(limit),
+            RotationGame::new(GameVariant::Razz, big_blind),
+            RotationGame::new(GameVariant::SevenCardStud, big_blind),
+            RotationGame::new(GameVariant::SevenCardStudHiLo, big_blind),
+        ];
+        Self {
+            games,
+            schedule,
+            current: 0,
+        }
+    }
+
+    /// Creates an 8-game rotation: 2-7 Triple Draw, the five HORSE games,
+    /// No-Limit Hold'em and Pot-Limit Omaha.
+    pub fn eight_game(big_blind: i32, schedule: RotationSchedule) -> Self {
+        let mut games = vec![RotationGame::new(
+            GameVariant::DeuceToSevenTripleDraw,
+            big_blind,
+        )];
+        games.extend(Self::horse(big_blind, schedule).games);
+        games.push(RotationGame::new(GameVariant::TexasHoldem, big_blind));
+        games.push(RotationGame::new(GameVariant::Omaha, big_blind));
+        Self {
+            games,
+            schedule,
+            current: 0,
+        }
+    }
+
+    /// Returns the game currently being dealt.
+    pub fn current(&self) -> &RotationGame {
+        &self.games[self.current]
+    }
+
+    /// Returns every game in the rotation, in order.
+    pub fn games(&self) -> &[RotationGame] {
+        &self.games
+    }
+
+    /// Returns true once the current game has been played long enough.
+    ///
+    /// # Arguments
+    /// * `hands_played` - Hands dealt since the current game started
+    /// * `players` - Players currently dealt in at the table
+    pub fn is_due(&self, hands_played: u32, players: usize) -> bool {
+        let hands_per_game = match self.schedule {
+            RotationSchedule::EveryHands(hands) => hands,
+            RotationSchedule::EachOrbit => players as u32,
+        };
+        hands_played >= hands_per_game.max(1)
+    }
+
+    /// Moves on to the next game, wrapping back to the first.
+    pub fn advance(&mut self) -> &RotationGame {
+        self.current = (self.current + 1) % self.games.len();
+        self.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_horse_order_and_structures() {
+        let rotation = GameRotation::horse(10, RotationSchedule::EachOrbit);
+        let variants: Vec<GameVariant> = rotation.games().iter().map(|g| g.variant).collect();
+        assert_eq!(
+            variants,
+            vec![
+                GameVariant::TexasHoldem,
+                GameVariant::OmahaHiLo,
+                GameVariant::Razz,
+                GameVariant::SevenCardStud,
+                GameVariant::SevenCardStudHiLo,
+            ]
+        );
+        assert!(rotation
+            .games()
+            .iter()
+            .all(|g| g.betting_structure == BettingStructure::fixed_limit(10)));
+        assert_eq!(rotation.games()[3].ante, AnteStructure::PerPlayer(2));
+    }
+
+    #[test]
+    fn test_rotation_schedules() {
+        let rotation = GameRotation::horse(10, RotationSchedule::EveryHands(8));
+        assert!(!rotation.is_due(7, 6));
+        assert!(rotation.is_due(8, 6));
+
+        let rotation = GameRotation::horse(10, RotationSchedule::EachOrbit);
+        assert!(!rotation.is_due(5, 6));
+        assert!(rotation.is_due(6, 6));
+    }
+
+    #[test]
+    fn test_rotation_wraps_around() {
+        let games = vec![
+            RotationGame::new(GameVariant::TexasHoldem, 10),
+            RotationGame::new(GameVariant::Razz, 10),
+        ];
+        let mut rotation = GameRotation::new(games, RotationSchedule::EveryHands(1)).unwrap();
+        assert_eq!(rotation.advance().variant, GameVariant::Razz);
+        assert_eq!(rotation.advance().variant, GameVariant::TexasHoldem);
+        assert!(GameRotation::new(Vec::new(), RotationSchedule::EachOrbit).is_none());
+    }
+
+    #[test]
+    fn test_eight_game_has_eight_games() {
+        let rotation = GameRotation::eight_game(10, RotationSchedule::EachOrbit);
+        assert_eq!(rotation.games().len(), 8);
+        assert_eq!(
+            rotation.current().variant,
+            GameVariant::DeuceToSevenTripleDraw
+        );
+        assert_eq!(
+            rotation.games()[7].betting_structure,
+            BettingStructure::PotLimit
+        );
+    }
+}
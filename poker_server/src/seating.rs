@@ -0,0 +1,298 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// A table's seats, each either empty or holding a player id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seats {
+    seats: Vec<Option<String>>,
+}
+
+impl Seats {
+    /// Creates a table with `count` empty seats.
+    pub fn new(count: usize) -> Self {
+        Self {
+            seats: vec![None; count],
+        }
+    }
+
+    /// Returns the number of seats at the table, occupied or not.
+    pub fn count(&self) -> usize {
+        self.seats.len()
+    }
+
+    /// Returns the seat a player is sitting in.
+    pub fn seat_of(&self, player_id: &str) -> Option<usize> {
+        self.seats
+            .iter()
+            .position(|seat| seat.as_deref() == Some(player_id))
+    }
+
+    /// Returns the player sitting in a seat, if any.
+    pub fn occupant(&self, seat: usize) -> Option<&str> {
+        self.seats.get(seat).and_then(|s| s.as_deref())
+    }
+
+    /// Seats a player in the lowest-numbered empty seat.
+    ///
+    /// # Returns
+    /// The seat taken, or `None` if the table is full
+    #[allow(dead_code)]
+    pub fn sit(&mut self, player_id: String) -> Option<usize> {
+        let seat = self.empty_seats().next()?;
+        self.sit_at(seat, player_id).then_some(seat)
+    }
+
+    /// Seats a player in a particular seat.
+    ///
+    /// # Returns
+    /// `false` if the seat does not exist or is taken
+    pub fn sit_at(&mut self, seat: usize, player_id: String) -> bool {
+        match self.seats.get_mut(seat) {
+            Some(slot @ None) => {
+                *slot = Some(player_id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the empty seats, lowest-numbered first.
+    pub fn empty_seats(&self) -> impl Iterator<Item = usize> + '_ {
+        self.seats
+            .iter()
+            .enumerate()
+            .filter(|(_, seat)| seat.is_none())
+            .map(|(seat, _)| seat)
+    }
+
+    /// Empties the seat a player is sitting in.
+    ///
+    /// # Returns
+    /// The seat given up, or `None` if the player was not seated
+    pub fn stand(&mut self, player_id: &str) -> Option<usize> {
+        let seat = self.seat_of(player_id)?;
+        self.seats[seat] = None;
+        Some(seat)
+    }
+
+    /// Returns the occupied seats clockwise, starting with the seat after `seat`
+    /// and ending with `seat` itself.
+    pub fn clockwise_from(&self, seat: usize) -> Vec<(usize, &str)> {
+        let count = self.seats.len();
+        (1..=count)
+            .map(|offset| (seat + offset) % count.max(1))
+            .filter_map(|s| self.occupant(s).map(|id| (s, id)))
+            .collect()
+    }
+}
+
+/// A seat held for a player on the waiting list until they take it or the
+/// offer runs out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeatOffer {
+    pub player_id: String,
+    pub seat: usize,
+    pub expires_at: Instant,
+}
+
+/// Players waiting for a seat at a full table, in the order they joined,
+/// and the seats held for those who have been offered one.
+#[derive(Debug, Clone, Default)]
+pub struct Waitlist {
+    queue: VecDeque<String>,
+    offers: Vec<SeatOffer>,
+}
+
+impl Waitlist {
+    /// Adds a player to the back of the list.
+    ///
+    /// # Returns
+    /// The player's place in the list, starting from 1
+    pub fn join(&mut self, player_id: &str) -> usize {
+        if let Some(place) = self.place_of(player_id) {
+            return place;
+        }
+        self.queue.push_back(player_id.to_string());
+        self.queue.len()
+    }
+
+    /// Removes a player from the list, releasing any seat held for them.
+    ///
+    /// # Returns
+    /// `true` if the player was on the list
+    pub fn leave(&mut self, player_id: &str) -> bool {
+        self.offers.retain(|offer| offer.player_id != player_id);
+        let before = self.queue.len();
+        self.queue.retain(|id| id != player_id);
+        self.queue.len() != before
+    }
+
+    /// Returns a player's place in the list, starting from 1.
+    pub fn place_of(&self, player_id: &str) -> Option<usize> {
+        self.queue
+            .iter()
+            .position(|id| id == player_id)
+            .map(|i| i + 1)
+    }
+
+    /// Returns the players on the list, first in line first.
+    pub fn players(&self) -> impl Iterator<Item = &str> {
+        self.queue.iter().map(String::as_str)
+    }
+
+    /// Returns the seat held for a player, if they have been offered one.
+    pub fn offer_for(&self, player_id: &str) -> Option<&SeatOffer> {
+        self.offers
+            .iter()
+            .find(|offer| offer.player_id == player_id)
+    }
+
+    /// Returns true if a seat is being held for a waiting player.
+    pub fn is_held(&self, seat: usize) -> bool {
+        self.offers.iter().any(|offer| offer.seat == seat)
+    }
+
+    /// Holds a seat for the first player in line who has not been offered one.
+    ///
+    /// # Arguments
+    /// * `seat` - The seat that has opened
+    /// * `timeout` - How long the player has to take the seat
+    ///
+    /// # Returns
+    /// The offer made, or `None` if nobody is waiting
+    pub fn offer(&mut self, seat: usize, timeout: Duration) -> Option<SeatOffer> {
+        let player_id = self
+            .queue
+            .iter()
+            .find(|id| self.offer_for(id).is_none())?
+            .clone();
+        let offer = SeatOffer {
+            player_id,
+            seat,
+            expires_at: Instant::now() + timeout,
+        };
+        self.offers.push(offer.clone());
+        Some(offer)
+    }
+
+    /// Withdraws offers that have run out, taking those players off the list.
+    ///
+    /// # Returns
+    /// The withdrawn offers, whose seats are free again
+    pub fn expire(&mut self, now: Instant) -> Vec<SeatOffer> {
+        let (expired, live): (Vec<_>, Vec<_>) = self
+            .offers
+            .drain(..)
+            .partition(|offer| offer.expires_at <= now);
+        self.offers = live;
+        self.queue
+            .retain(|id| !expired.iter().any(|offer| &offer.player_id == id));
+        expired
+    }
+
+    /// Returns when the next outstanding offer runs out.
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.offers.iter().map(|offer| offer.expires_at).min()
+    }
+}
+
+/// Blinds a seated player owes before they are dealt in again.
+///
+/// A player who owes blinds sits out until the big blind reaches them, unless
+/// they choose to post: a live big blind if they are new or missed the big
+/// blind, plus a dead small blind if they missed the small blind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlindDebt {
+    /// The player has not yet been dealt in at this table
+    pub new_player: bool,
+    /// The big blind passed the player while they were sitting out
+    pub missed_big: bool,
+    /// The small blind passed the player while they were sitting out
+    pub missed_small: bool,
+    /// The player chose to post what they owe rather than wait for the big blind
+    pub will_post: bool,
+}
+
+impl BlindDebt {
+    /// Returns the debt of a player who has just sat down.
+    pub fn new_player() -> Self {
+        Self {
+            new_player: true,
+            ..Self::default()
+        }
+    }
+
+    /// Returns true if the player must post or wait for the big blind.
+    pub fn owes(&self) -> bool {
+        self.new_player || self.missed_big || self.missed_small
+    }
+
+    /// Returns true if the player owes a live big blind when posting.
+    pub fn owes_big_blind(&self) -> bool {
+        self.new_player || self.missed_big
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seats_fill_lowest_first_and_wrap_clockwise() {
+        let mut seats = Seats::new(4);
+        assert_eq!(seats.sit("a".to_string()), Some(0));
+        assert_eq!(seats.sit("b".to_string()), Some(1));
+        assert_eq!(seats.sit("c".to_string()), Some(2));
+        assert_eq!(seats.seat_of("c"), Some(2));
+        assert_eq!(seats.occupant(3), None);
+
+        let order: Vec<&str> = seats.clockwise_from(1).iter().map(|(_, id)| *id).collect();
+        assert_eq!(order, vec!["c", "a", "b"]);
+
+        seats.sit("d".to_string());
+        assert_eq!(seats.sit("e".to_string()), None);
+
+        assert_eq!(seats.stand("b"), Some(1));
+        assert_eq!(seats.stand("b"), None);
+        assert!(!seats.sit_at(0, "e".to_string()));
+        assert!(!seats.sit_at(4, "e".to_string()));
+        assert_eq!(seats.empty_seats().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(seats.sit("e".to_string()), Some(1));
+    }
+
+    #[test]
+    fn test_waitlist_offers_seats_in_order() {
+        let mut list = Waitlist::default();
+        assert_eq!(list.join("a"), 1);
+        assert_eq!(list.join("b"), 2);
+        assert_eq!(list.join("a"), 1);
+
+        let offer = list.offer(3, Duration::from_secs(30)).unwrap();
+        assert_eq!(offer.player_id, "a");
+        assert!(list.is_held(3));
+        assert_eq!(list.offer(5, Duration::ZERO).unwrap().player_id, "b");
+        assert!(list.offer(6, Duration::ZERO).is_none());
+
+        let expired = list.expire(Instant::now());
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].seat, 5);
+        assert_eq!(list.place_of("b"), None);
+        assert_eq!(list.next_expiry(), Some(offer.expires_at));
+
+        assert!(list.leave("a"));
+        assert!(!list.is_held(3));
+        assert!(!list.leave("a"));
+    }
+
+    #[test]
+    fn test_blind_debt() {
+        assert!(BlindDebt::new_player().owes_big_blind());
+        let missed_small = BlindDebt {
+            missed_small: true,
+            ..BlindDebt::default()
+        };
+        assert!(missed_small.owes());
+        assert!(!missed_small.owes_big_blind());
+        assert!(!BlindDebt::default().owes());
+    }
+}
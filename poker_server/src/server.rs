@@ -1,20 +1,24 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
-use log::{debug, error, warn};
 use parking_lot::Mutex;
 use poker_protocol::{
-    ChatMessage, ClientMessage, PlayerUpdate, ServerError, ServerMessage, ServerResult,
+    ChatMessage, ClientMessage, CreateTableRequest, JoinTableRequest, PlayerUpdate,
+    SeatRequestUpdate, ServerError, ServerMessage, ServerResult, TableCreatedUpdate, TableSummary,
 };
 use tokio::sync::broadcast;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::Semaphore;
-use tokio::time::{timeout, Duration};
-#[cfg(test)]
+use tokio::time::{sleep, timeout, Duration};
+use tracing::{debug, error, info_span, warn, Span};
 use uuid::Uuid;
 
-use crate::game::PokerGame;
+use crate::admin::{ConnectionsInfo, PlayerInfo, TableInfo};
+use crate::config::ServerLimits;
+use crate::game::{PokerGame, TableConfig};
+use crate::metrics::{Metrics, ServerGauges, TableGauges};
+use crate::private_table::PrivateTable;
 
 /// Timeout in milliseconds for sending broadcast messages to players
 const BROADCAST_SEND_TIMEOUT_MS: u64 = 5000;
@@ -22,6 +26,10 @@ const BROADCAST_SEND_TIMEOUT_MS: u64 = 5000;
 const MAX_BROADCAST_TASKS: usize = 50;
 /// Maximum concurrent send tasks to prevent resource exhaustion
 const MAX_SEND_TASKS: usize = 100;
+/// Pause in milliseconds between streets when an all-in hand is run out
+const RUN_OUT_PAUSE_MS: u64 = 1500;
+/// Events a table buffers for its players before the oldest are dropped
+const TABLE_EVENT_CAPACITY: usize = 100;
 
 /// Type alias for player identifiers.
 pub type PlayerId = String;
@@ -29,10 +37,13 @@ pub type PlayerId = String;
 #[derive(Debug, Clone)]
 pub struct ServerPlayer {
     pub name: String,
+    /// Chips the player holds away from the table, used for buy-ins
     pub chips: i32,
     pub connected: bool,
     pub ws_sender: Option<Sender<String>>,
     pub seated: bool,
+    /// Address the player connected from
+    pub ip: Option<String>,
     #[cfg(test)]
     pub session_token: String,
     pub session_created_at: DateTime<Utc>,
@@ -46,6 +57,7 @@ impl ServerPlayer {
             connected: false,
             ws_sender: None,
             seated: false,
+            ip: None,
             #[cfg(test)]
             session_token: Uuid::new_v4().to_string(),
             session_created_at: Utc::now(),
@@ -65,12 +77,23 @@ pub struct PokerServer {
     players: HashMap<PlayerId, ServerPlayer>,
     games: HashMap<String, Arc<Mutex<PokerGame>>>,
     player_sessions: HashMap<PlayerId, String>,
-    tx: broadcast::Sender<ServerMessage>,
+    /// Connections watching each table without a seat, by game id
+    spectators: HashMap<String, HashSet<PlayerId>>,
+    /// Hosts, credentials and seat requests of private tables, by game id
+    private_tables: HashMap<String, PrivateTable>,
+    /// Event streams of tables created since they were last taken for relaying
+    new_tables: Vec<(String, broadcast::Receiver<ServerMessage>)>,
+    /// Players removed during a hand, by the table that cashes them out when it ends
+    removed_in_hand: HashMap<PlayerId, String>,
     connection_count: usize,
     ip_connections: HashMap<String, usize>,
-    session_expiry_hours: u64,
+    /// Addresses an administrator has banned from connecting
+    banned_ips: HashSet<String>,
+    /// Connection limits, replaced when the configuration is reloaded
+    limits: ServerLimits,
     broadcast_semaphore: Arc<Semaphore>,
     send_semaphore: Arc<Semaphore>,
+    metrics: Arc<Metrics>,
 }
 
 impl PokerServer {
@@ -80,19 +103,38 @@ impl PokerServer {
             players: HashMap::new(),
             games: HashMap::new(),
             player_sessions: HashMap::new(),
-            tx: broadcast::channel(100).0,
+            spectators: HashMap::new(),
+            private_tables: HashMap::new(),
+            new_tables: Vec::new(),
+            removed_in_hand: HashMap::new(),
             connection_count: 0,
             ip_connections: HashMap::new(),
-            session_expiry_hours: 24,
+            banned_ips: HashSet::new(),
+            limits: ServerLimits::default(),
             broadcast_semaphore: Arc::new(Semaphore::new(MAX_BROADCAST_TASKS)),
             send_semaphore: Arc::new(Semaphore::new(MAX_SEND_TASKS)),
+            metrics: Arc::new(Metrics::default()),
         }
     }
 
     /// Sets the session token expiry duration in hours.
     #[cfg(test)]
     pub fn set_session_expiry_hours(&mut self, hours: u64) {
-        self.session_expiry_hours = hours;
+        self.limits.session_token_expiry_hours = hours;
+    }
+
+    /// Returns the limits enforced on connections.
+    pub fn limits(&self) -> ServerLimits {
+        self.limits
+    }
+
+    /// Replaces the connection limits. Connections already open are kept
+    /// even if there are now more than the limits allow.
+    ///
+    /// # Arguments
+    /// * `limits` - The new limits
+    pub fn set_limits(&mut self, limits: ServerLimits) {
+        self.limits = limits;
     }
 
     /// Checks if a new connection can be accepted from the given IP.
@@ -103,11 +145,11 @@ impl PokerServer {
     /// # Returns
     /// `true` if the connection can be accepted, `false` otherwise
     pub fn can_accept_connection(&self, ip: &str) -> bool {
-        self.connection_count < crate::MAX_CONNECTIONS
+        self.connection_count < self.limits.max_connections
             && self
                 .ip_connections
                 .get(ip)
-                .map(|c| *c < crate::MAX_CONNECTIONS_PER_IP)
+                .map(|c| *c < self.limits.max_connections_per_ip)
                 .unwrap_or(true)
     }
 
@@ -134,6 +176,213 @@ impl PokerServer {
         }
     }
 
+    /// Returns the counters of events across the server.
+    pub fn metrics(&self) -> Arc<Metrics> {
+        Arc::clone(&self.metrics)
+    }
+
+    /// Reads connection, backlog and table state for a metrics scrape.
+    pub fn gauges(&self) -> ServerGauges {
+        let backlogs: Vec<usize> = self
+            .players
+            .values()
+            .filter_map(|player| player.ws_sender.as_ref())
+            .map(|sender| sender.max_capacity() - sender.capacity())
+            .collect();
+        let mut tables: Vec<TableGauges> = self
+            .games
+            .iter()
+            .map(|(game_id, game)| {
+                let game = game.lock();
+                TableGauges {
+                    game_id: game_id.clone(),
+                    hands: u64::try_from(game.hand_number()).unwrap_or(0),
+                    players: game.player_count(),
+                    event_backlog: game.tx.len(),
+                }
+            })
+            .collect();
+        tables.sort_by(|a, b| a.game_id.cmp(&b.game_id));
+        ServerGauges {
+            connections: self.connection_count,
+            connection_ips: self.ip_connections.len(),
+            send_backlog: backlogs.iter().sum(),
+            max_send_backlog: backlogs.into_iter().max().unwrap_or(0),
+            tables,
+        }
+    }
+
+    /// Returns whether an administrator has banned an address.
+    pub fn is_banned(&self, ip: &str) -> bool {
+        self.banned_ips.contains(ip)
+    }
+
+    /// Bans the address a player connected from and removes every player
+    /// connected from it.
+    ///
+    /// # Returns
+    /// The banned address
+    pub fn ban_player(&mut self, player_id: &str) -> ServerResult<String> {
+        let ip = self
+            .players
+            .get(player_id)
+            .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?
+            .ip
+            .clone()
+            .ok_or_else(|| ServerError::PlayerNotConnected(player_id.to_string()))?;
+        let from_ip: Vec<PlayerId> = self
+            .players
+            .iter()
+            .filter(|(_, player)| player.connected && player.ip.as_deref() == Some(&ip))
+            .map(|(id, _)| id.clone())
+            .collect();
+        self.banned_ips.insert(ip.clone());
+        for id in from_ip {
+            self.remove_player(&id)?;
+        }
+        Ok(ip)
+    }
+
+    /// Lifts a ban on an address.
+    ///
+    /// # Returns
+    /// `true` if the address was banned
+    pub fn unban(&mut self, ip: &str) -> bool {
+        self.banned_ips.remove(ip)
+    }
+
+    /// Records the address a player connected from.
+    pub fn set_player_ip(&mut self, player_id: &str, ip: &str) {
+        if let Some(player) = self.players.get_mut(player_id) {
+            player.ip = Some(ip.to_string());
+        }
+    }
+
+    /// Removes a player from the server, cashing them out of their table and
+    /// closing their connection. A player in a hand is folded and keeps their
+    /// seat until the hand ends, when their stack is returned to their balance.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player to remove
+    pub fn remove_player(&mut self, player_id: &str) -> ServerResult<()> {
+        if !self.players.contains_key(player_id) {
+            return Err(ServerError::PlayerNotFound(player_id.to_string()));
+        }
+        let table = self
+            .player_sessions
+            .get(player_id)
+            .and_then(|game_id| Some((game_id.clone(), Arc::clone(self.games.get(game_id)?))));
+        if let Some((game_id, game)) = table {
+            let cashed_out = game.lock().cash_out(player_id);
+            match cashed_out {
+                Ok(chips) => {
+                    watch_seat_offers(&game);
+                    self.return_stack(player_id, chips);
+                }
+                Err(ServerError::CashOutInHand) => {
+                    self.removed_in_hand.insert(player_id.to_string(), game_id);
+                    let left = game.lock().leave_after_hand(player_id);
+                    if let Err(e) = left {
+                        warn!("Removed player {} keeps their seat: {}", player_id, e);
+                    }
+                    watch_table(&game);
+                }
+                Err(e) => warn!("Removed player {} keeps their seat: {}", player_id, e),
+            }
+        }
+        self.send_message(
+            player_id,
+            ServerMessage::Error(ServerError::RemovedByAdmin.to_string()),
+        );
+        // Dropping the player's sender closes their connection.
+        self.disconnect_player(player_id);
+        Ok(())
+    }
+
+    /// Adds chips to, or takes chips from, a player's balance away from the
+    /// table.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player whose balance changes
+    /// * `amount` - Chips to add; negative to take chips away
+    ///
+    /// # Returns
+    /// The player's new balance
+    pub fn adjust_chips(&mut self, player_id: &str, amount: i32) -> ServerResult<i32> {
+        let player = self
+            .players
+            .get_mut(player_id)
+            .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+        let balance = player.chips.saturating_add(amount);
+        if balance < 0 {
+            return Err(ServerError::InsufficientBalance(player.chips));
+        }
+        if balance > crate::MAX_PLAYER_CHIPS {
+            return Err(ServerError::AmountExceedsMax(crate::MAX_PLAYER_CHIPS));
+        }
+        player.chips = balance;
+        Ok(balance)
+    }
+
+    /// Returns open connections in total and by address, with banned addresses.
+    pub fn connections_info(&self) -> ConnectionsInfo {
+        let mut banned: Vec<String> = self.banned_ips.iter().cloned().collect();
+        banned.sort();
+        ConnectionsInfo {
+            total: self.connection_count,
+            by_ip: self.ip_connections.clone().into_iter().collect(),
+            banned,
+        }
+    }
+
+    /// Returns every player the server knows of, with where they are sitting.
+    pub fn players_info(&self) -> Vec<PlayerInfo> {
+        let mut players: Vec<PlayerInfo> = self
+            .players
+            .iter()
+            .map(|(player_id, player)| PlayerInfo {
+                player_id: player_id.clone(),
+                name: player.name.clone(),
+                balance: player.chips,
+                connected: player.connected,
+                ip: player.ip.clone(),
+                table: self.player_sessions.get(player_id).cloned(),
+                spectating: self.spectated_game(player_id).map(str::to_string),
+            })
+            .collect();
+        players.sort_by(|a, b| a.player_id.cmp(&b.player_id));
+        players
+    }
+
+    /// Returns every table, public and private.
+    pub fn tables_info(&self) -> Vec<TableInfo> {
+        let mut tables: Vec<TableInfo> = self
+            .games
+            .iter()
+            .map(|(game_id, game)| {
+                let game = game.lock();
+                TableInfo {
+                    game_id: game_id.clone(),
+                    players: game.player_count(),
+                    small_blind: game.small_blind,
+                    big_blind: game.big_blind,
+                    variant: game.variant(),
+                    paused: game.is_paused(),
+                    hand_in_progress: game.is_hand_in_progress(),
+                    host: self
+                        .private_tables
+                        .get(game_id)
+                        .map(|table| table.host.clone()),
+                    spectators: self.spectator_count(game_id),
+                    rake: game.rake_ledger().total(),
+                    raked_hands: game.rake_ledger().entries().len(),
+                }
+            })
+            .collect();
+        tables.sort_by(|a, b| a.game_id.cmp(&b.game_id));
+        tables
+    }
+
     /// Creates a new poker game table.
     ///
     /// # Arguments
@@ -143,22 +392,356 @@ impl PokerServer {
     ///
     /// # Returns
     /// A new `Arc<Mutex<PokerGame>>` for the created game
+    #[allow(dead_code)]
     pub fn create_game(
         &mut self,
         game_id: String,
         small_blind: i32,
         big_blind: i32,
     ) -> Arc<Mutex<PokerGame>> {
-        let game = Arc::new(Mutex::new(PokerGame::new(
+        self.create_game_with_config(game_id, TableConfig::new(small_blind, big_blind))
+    }
+
+    /// Creates a new poker game table from a table configuration.
+    ///
+    /// # Arguments
+    /// * `game_id` - Unique identifier for this game table
+    /// * `config` - Blinds, betting structure and limits for the table
+    ///
+    /// # Returns
+    /// A new `Arc<Mutex<PokerGame>>` for the created game
+    pub fn create_game_with_config(
+        &mut self,
+        game_id: String,
+        config: TableConfig,
+    ) -> Arc<Mutex<PokerGame>> {
+        let (tx, events) = broadcast::channel(TABLE_EVENT_CAPACITY);
+        let game = Arc::new(Mutex::new(PokerGame::with_config(
             game_id.clone(),
-            small_blind,
-            big_blind,
-            self.tx.clone(),
+            config,
+            tx,
         )));
         self.games.insert(game_id.clone(), game.clone());
+        self.new_tables.push((game_id, events));
         game
     }
 
+    /// Takes the event streams of tables created since the last call, so each
+    /// can be relayed to its players with `broadcast_to_game`.
+    pub fn take_new_tables(&mut self) -> Vec<(String, broadcast::Receiver<ServerMessage>)> {
+        std::mem::take(&mut self.new_tables)
+    }
+
+    /// Creates a private table hosted by a player.
+    ///
+    /// # Arguments
+    /// * `host_id` - The player creating the table, who approves seat requests
+    /// * `request` - Blinds and the password or number of invite codes
+    ///
+    /// # Returns
+    /// The new table's game id
+    pub fn create_private_table(
+        &mut self,
+        host_id: &str,
+        request: CreateTableRequest,
+    ) -> ServerResult<String> {
+        if !self.players.contains_key(host_id) {
+            return Err(ServerError::PlayerNotFound(host_id.to_string()));
+        }
+        if let Ok((game_id, _)) = self.hosted_game(host_id) {
+            return Err(ServerError::InvalidTableSettings(format!(
+                "you already host {}",
+                game_id
+            )));
+        }
+        if request.small_blind <= 0 || request.big_blind < request.small_blind {
+            return Err(ServerError::InvalidTableSettings(
+                "blinds must be positive, with the big blind at least the small blind".to_string(),
+            ));
+        }
+        let (table, invite_codes) = match (request.password, request.invite_codes) {
+            (Some(password), 0) => (PrivateTable::with_password(host_id, password)?, Vec::new()),
+            (None, count) => PrivateTable::with_invite_codes(host_id, count)?,
+            (Some(_), _) => {
+                return Err(ServerError::InvalidTableSettings(
+                    "use either a password or invite codes, not both".to_string(),
+                ))
+            }
+        };
+
+        let game_id = format!("private-{}", Uuid::new_v4());
+        self.create_game_with_config(
+            game_id.clone(),
+            TableConfig::new(request.small_blind, request.big_blind),
+        );
+        self.private_tables.insert(game_id.clone(), table);
+
+        self.send_message(
+            host_id,
+            ServerMessage::TableCreated(TableCreatedUpdate {
+                game_id: game_id.clone(),
+                invite_codes,
+            }),
+        );
+        Ok(game_id)
+    }
+
+    /// Returns the tables shown in the lobby; private tables are left out.
+    pub fn list_tables(&self) -> Vec<TableSummary> {
+        let mut tables: Vec<TableSummary> = self
+            .games
+            .iter()
+            .filter(|(game_id, _)| !self.private_tables.contains_key(*game_id))
+            .map(|(game_id, game)| {
+                let game = game.lock();
+                TableSummary {
+                    game_id: game_id.clone(),
+                    players: game.player_count(),
+                    small_blind: game.small_blind,
+                    big_blind: game.big_blind,
+                    variant: game.variant(),
+                }
+            })
+            .collect();
+        tables.sort_by(|a, b| a.game_id.cmp(&b.game_id));
+        tables
+    }
+
+    /// Sits a player down at a table, or for a private table checks their
+    /// password or invite code and passes the request to the host.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player asking to sit down
+    /// * `request` - The table, credential and seat asked for
+    pub fn join_table(&mut self, player_id: &str, request: JoinTableRequest) -> ServerResult<()> {
+        let JoinTableRequest {
+            game_id,
+            credential,
+            seat,
+        } = request;
+        if !self.games.contains_key(&game_id) {
+            return Err(ServerError::GameNotFound(game_id));
+        }
+        if self.player_sessions.contains_key(player_id) {
+            return Err(ServerError::AlreadySeated);
+        }
+        let player_name = self
+            .players
+            .get(player_id)
+            .map(|p| p.name.clone())
+            .ok_or_else(|| ServerError::PlayerNotFound(player_id.to_string()))?;
+
+        let host = match self.private_tables.get_mut(&game_id) {
+            Some(table) if table.host != player_id => {
+                table.admit(credential.as_deref())?;
+                table.request_seat(player_id, seat);
+                table.host.clone()
+            }
+            _ => return self.seat_player(player_id, &game_id, seat),
+        };
+
+        let update = ServerMessage::SeatRequested(SeatRequestUpdate {
+            game_id,
+            player_id: player_id.to_string(),
+            player_name,
+        });
+        self.send_message(&host, update.clone());
+        self.send_message(player_id, update);
+        Ok(())
+    }
+
+    /// Settles a seat request at the host's private table.
+    ///
+    /// # Arguments
+    /// * `host_id` - The host answering the request
+    /// * `player_id` - The player who asked to sit down
+    /// * `approve` - Seat the player if `true`, turn them away if `false`
+    fn answer_seat_request(
+        &mut self,
+        host_id: &str,
+        player_id: &str,
+        approve: bool,
+    ) -> ServerResult<()> {
+        if !self.private_tables.values().any(|t| t.host == host_id) {
+            return Err(ServerError::NotTableHost);
+        }
+        let (game_id, table) = self
+            .private_tables
+            .iter_mut()
+            .find(|(_, table)| table.host == host_id && table.has_request(player_id))
+            .ok_or_else(|| ServerError::NoSeatRequest(player_id.to_string()))?;
+        let seat = table.take_request(player_id)?;
+        let game_id = game_id.clone();
+
+        if approve {
+            self.seat_player(player_id, &game_id, seat)
+        } else {
+            self.send_message(
+                player_id,
+                ServerMessage::Error(ServerError::SeatRequestRejected.to_string()),
+            );
+            Ok(())
+        }
+    }
+
+    /// Returns the private table a player hosts, for host-only controls.
+    fn hosted_game(&self, host_id: &str) -> ServerResult<(String, Arc<Mutex<PokerGame>>)> {
+        self.private_tables
+            .iter()
+            .find(|(_, table)| table.host == host_id)
+            .and_then(|(game_id, _)| {
+                self.games
+                    .get(game_id)
+                    .map(|game| (game_id.clone(), Arc::clone(game)))
+            })
+            .ok_or(ServerError::NotTableHost)
+    }
+
+    /// Removes a player from the host's table between hands. A player in a
+    /// hand is sat out instead, so they can be removed once it ends.
+    ///
+    /// # Arguments
+    /// * `host_id` - The host removing the player
+    /// * `target` - The player to remove
+    fn kick_player(&mut self, host_id: &str, target: &str) -> ServerResult<()> {
+        let (_, game) = self.hosted_game(host_id)?;
+        let chips = {
+            let mut poker_game = game.lock();
+            if !poker_game.players.contains_key(target) {
+                return Err(ServerError::PlayerNotFound(target.to_string()));
+            }
+            poker_game.sit_out(target);
+            poker_game.cash_out(target).map_err(|e| match e {
+                ServerError::CashOutInHand => ServerError::KickInHand(target.to_string()),
+                e => e,
+            })?
+        };
+        watch_seat_offers(&game);
+        self.return_stack(target, chips);
+        self.send_message(
+            target,
+            ServerMessage::Error(ServerError::RemovedByHost.to_string()),
+        );
+        Ok(())
+    }
+
+    /// Cashes out everyone at the host's table, sends everyone who played
+    /// the settlement and closes the table.
+    ///
+    /// # Arguments
+    /// * `host_id` - The host ending the session
+    fn end_session(&mut self, host_id: &str) -> ServerResult<()> {
+        let (game_id, game) = self.hosted_game(host_id)?;
+        let (stacks, followers, settlement) = {
+            let mut poker_game = game.lock();
+            if poker_game.is_hand_in_progress() {
+                return Err(ServerError::HandInProgress);
+            }
+            let seated: Vec<String> = poker_game.players.keys().cloned().collect();
+            let stacks = seated
+                .into_iter()
+                .map(|player_id| {
+                    let chips = poker_game.cash_out(&player_id)?;
+                    Ok((player_id, chips))
+                })
+                .collect::<ServerResult<Vec<(String, i32)>>>()?;
+            let followers: HashSet<String> = stacks
+                .iter()
+                .map(|(player_id, _)| player_id.clone())
+                .chain(poker_game.waitlisted_players().map(str::to_string))
+                .chain(self.spectators.get(&game_id).into_iter().flatten().cloned())
+                .chain(std::iter::once(host_id.to_string()))
+                .collect();
+            (stacks, followers, poker_game.settlement())
+        };
+
+        for (player_id, chips) in stacks {
+            self.return_stack(&player_id, chips);
+        }
+        let players: HashSet<&str> = settlement
+            .results
+            .iter()
+            .map(|result| result.player_id.as_str())
+            .chain(std::iter::once(host_id))
+            .collect();
+        for player_id in players {
+            self.send_message(player_id, ServerMessage::Settlement(settlement.clone()));
+        }
+        for player_id in &followers {
+            self.send_message(player_id, ServerMessage::SessionEnded(game_id.clone()));
+        }
+        self.games.remove(&game_id);
+        self.private_tables.remove(&game_id);
+        self.spectators.remove(&game_id);
+        Ok(())
+    }
+
+    /// Returns a stack to the balance of a player who has left their table.
+    fn return_stack(&mut self, player_id: &str, chips: i32) {
+        if let Some(player) = self.players.get_mut(player_id) {
+            player.chips = player.chips.saturating_add(chips);
+            player.seated = false;
+        }
+        self.player_sessions.remove(player_id);
+    }
+
+    /// Serializes a message and sends it to a player, logging any failure.
+    fn send_message(&self, player_id: &str, message: ServerMessage) {
+        match message.to_unified_json() {
+            Ok(json) => {
+                if let Err(e) = self.send_to_player(player_id, json) {
+                    warn!("Failed to send message to {}: {}", player_id, e);
+                }
+            }
+            Err(e) => error!("Failed to serialize message for {}: {}", player_id, e),
+        }
+    }
+
+    /// Returns the table a player's chat goes to: where they sit, or else
+    /// the table they are watching.
+    fn chat_table(&self, player_id: &str) -> &str {
+        self.player_sessions
+            .get(player_id)
+            .map(String::as_str)
+            .or_else(|| self.spectated_game(player_id))
+            .unwrap_or("main_table")
+    }
+
+    /// Returns the table a seating, waiting list or spectating message acts
+    /// on: the one it names, or else the table the player sits at, watches or
+    /// waits for, or else the main table. A private table can only be named
+    /// by its host or a player already there; others join it with `JoinTable`.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player who sent the message
+    /// * `game_id` - The table the message names, if any
+    fn message_table(&self, player_id: &str, game_id: Option<String>) -> ServerResult<String> {
+        let own = self
+            .player_sessions
+            .get(player_id)
+            .cloned()
+            .or_else(|| self.spectated_game(player_id).map(str::to_string))
+            .or_else(|| {
+                self.games
+                    .iter()
+                    .find(|(_, game)| game.lock().waitlisted_players().any(|p| p == player_id))
+                    .map(|(game_id, _)| game_id.clone())
+            });
+        match game_id {
+            Some(game_id) => {
+                let outsider = self
+                    .private_tables
+                    .get(&game_id)
+                    .is_some_and(|table| table.host != player_id);
+                if outsider && own.as_deref() != Some(game_id.as_str()) {
+                    return Err(ServerError::InvalidTableCredential);
+                }
+                Ok(game_id)
+            }
+            None => Ok(own.unwrap_or_else(|| "main_table".to_string())),
+        }
+    }
+
     /// Gets a reference to a game by its ID.
     ///
     /// # Arguments
@@ -188,7 +771,7 @@ impl PokerServer {
     /// # Arguments
     /// * `player_id` - Unique player identifier
     /// * `name` - Player's display name
-    /// * `chips` - Starting chip amount
+    /// * `chips` - Starting balance, bought in with when the player sits down
     pub fn register_player(&mut self, player_id: PlayerId, name: String, chips: i32) {
         if self.players.contains_key(&player_id) {
             debug!("Player {} already registered, skipping", player_id);
@@ -234,6 +817,15 @@ impl PokerServer {
         }
 
         self.player_sessions.remove(player_id);
+        self.stop_spectating(player_id);
+        for table in self.private_tables.values_mut() {
+            let _ = table.take_request(player_id);
+        }
+        for game in self.games.values() {
+            if game.lock().leave_waitlist(player_id).is_ok() {
+                watch_seat_offers(game);
+            }
+        }
     }
 
     fn broadcast_to_game_by_player(&self, exclude_player_id: &str, message: &str) {
@@ -291,6 +883,7 @@ impl PokerServer {
         for (player_id, sender) in players {
             let msg = Arc::clone(&msg_arc);
             let sem = Arc::clone(&semaphore);
+            let metrics = Arc::clone(&self.metrics);
             tokio::spawn(async move {
                 let permit = match sem.acquire().await {
                     Ok(permit) => permit,
@@ -300,6 +893,7 @@ impl PokerServer {
                     }
                 };
                 if let Err(e) = timeout(timeout_duration, sender.send((*msg).clone())).await {
+                    metrics.record_broadcast_timeout();
                     error!("Timeout sending to player {}: {}", player_id, e);
                 }
                 drop(permit);
@@ -307,7 +901,50 @@ impl PokerServer {
         }
     }
 
-    pub fn seat_player(&mut self, player_id: &str, game_id: &str) -> ServerResult<()> {
+    /// Seats a player with the largest buy-in their balance allows.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player to seat
+    /// * `game_id` - The table to seat them at
+    /// * `seat` - The seat to sit in, or `None` for any free seat
+    pub fn seat_player(
+        &mut self,
+        player_id: &str,
+        game_id: &str,
+        seat: Option<usize>,
+    ) -> ServerResult<()> {
+        let balance = self
+            .players
+            .get(player_id)
+            .ok_or(ServerError::PlayerNotFound(player_id.to_string()))?
+            .chips;
+        let game = self
+            .games
+            .get(game_id)
+            .ok_or(ServerError::GameNotFound(game_id.to_string()))?;
+
+        if balance <= 0 {
+            return Err(ServerError::NoChips);
+        }
+
+        let (_, max) = game.lock().buy_in_range();
+        self.buy_in(player_id, game_id, balance.min(max), seat)
+    }
+
+    /// Seats a player with chips taken from their balance.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player to seat
+    /// * `game_id` - The table to seat them at
+    /// * `amount` - Chips to sit down with, within the table's buy-in limits
+    /// * `seat` - The seat to sit in, or `None` for any free seat
+    pub fn buy_in(
+        &mut self,
+        player_id: &str,
+        game_id: &str,
+        amount: i32,
+        seat: Option<usize>,
+    ) -> ServerResult<()> {
         let player = self
             .players
             .get_mut(player_id)
@@ -318,27 +955,30 @@ impl PokerServer {
             .get(game_id)
             .ok_or(ServerError::GameNotFound(game_id.to_string()))?;
 
-        if player.chips <= 0 {
-            return Err(ServerError::NoChips);
-        }
-
         if self.player_sessions.contains_key(player_id) {
-            return Ok(());
+            return Err(ServerError::AlreadySeated);
+        }
+        if amount > player.chips {
+            return Err(ServerError::InsufficientBalance(player.chips));
         }
-
-        player.seated = true;
-        self.player_sessions
-            .insert(player_id.to_string(), game_id.to_string());
 
         game.lock()
-            .add_player(player_id.to_string(), player.name.clone(), player.chips)
+            .buy_in(player_id.to_string(), player.name.clone(), amount, seat)
             .map_err(|e| {
                 warn!("Failed to add player {} to game: {}", player_id, e);
                 e
             })?;
+        watch_table(game);
+
+        player.chips -= amount;
+        player.seated = true;
+        self.player_sessions
+            .insert(player_id.to_string(), game_id.to_string());
+        self.stop_spectating(player_id);
 
         let connected_msg = ServerMessage::Connected(player_id.to_string());
-        let json = serde_json::to_string(&connected_msg)
+        let json = connected_msg
+            .to_unified_json()
             .map_err(|e| ServerError::GameState(e.to_string()))?;
         if let Err(e) = self.send_to_player(player_id, json) {
             warn!("Failed to send connected message to {}: {}", player_id, e);
@@ -349,6 +989,100 @@ impl PokerServer {
         Ok(())
     }
 
+    /// Adds chips from a player's balance to their stack at their table.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player adding chips
+    /// * `amount` - Chips to add
+    /// * `add` - Adds the chips at the table, as a rebuy or a top-up
+    fn add_chips(
+        &mut self,
+        player_id: &str,
+        amount: i32,
+        add: impl FnOnce(&mut PokerGame, &str, i32) -> ServerResult<()>,
+    ) -> ServerResult<()> {
+        let session = self
+            .player_sessions
+            .get(player_id)
+            .ok_or(ServerError::PlayerNotInGame)?
+            .clone();
+        let game = self
+            .games
+            .get(&session)
+            .ok_or_else(|| ServerError::GameNotFound(session.clone()))?;
+        let player = self
+            .players
+            .get_mut(player_id)
+            .ok_or(ServerError::PlayerNotFound(player_id.to_string()))?;
+
+        if amount > player.chips {
+            return Err(ServerError::InsufficientBalance(player.chips));
+        }
+        add(&mut game.lock(), player_id, amount)?;
+        watch_table(game);
+        player.chips -= amount;
+        Ok(())
+    }
+
+    /// Starts a player watching a table without a seat.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player who wants to watch
+    /// * `game_id` - The table to watch
+    pub fn spectate(&mut self, player_id: &str, game_id: &str) -> ServerResult<()> {
+        let game = self
+            .games
+            .get(game_id)
+            .ok_or(ServerError::GameNotFound(game_id.to_string()))?;
+        if self.player_sessions.contains_key(player_id) {
+            return Err(ServerError::AlreadySeated);
+        }
+        let state = ServerMessage::GameStateUpdate(game.lock().game_state_update());
+
+        self.stop_spectating(player_id);
+        self.spectators
+            .entry(game_id.to_string())
+            .or_default()
+            .insert(player_id.to_string());
+
+        // Spectators learn their id here, since they never sit down.
+        for message in [ServerMessage::Connected(player_id.to_string()), state] {
+            let json = message
+                .to_unified_json()
+                .map_err(|e| ServerError::GameState(e.to_string()))?;
+            if let Err(e) = self.send_to_player(player_id, json) {
+                warn!(
+                    "Failed to send table state to spectator {}: {}",
+                    player_id, e
+                );
+            }
+        }
+        self.send_game_state_to_player(player_id, game_id)
+    }
+
+    /// Stops a player watching whichever table they are spectating.
+    ///
+    /// # Returns
+    /// `true` if the player was spectating a table
+    fn stop_spectating(&mut self, player_id: &str) -> bool {
+        self.spectators
+            .values_mut()
+            .any(|watchers| watchers.remove(player_id))
+    }
+
+    /// Returns the table a player is watching, if they are spectating.
+    fn spectated_game(&self, player_id: &str) -> Option<&str> {
+        self.spectators
+            .iter()
+            .find(|(_, watchers)| watchers.contains(player_id))
+            .map(|(game_id, _)| game_id.as_str())
+    }
+
+    /// Returns the number of connections watching a table without a seat.
+    pub fn spectator_count(&self, game_id: &str) -> usize {
+        self.spectators.get(game_id).map_or(0, HashSet::len)
+    }
+
     fn send_game_state_to_player(&self, player_id: &str, game_id: &str) -> ServerResult<()> {
         let game = self
             .games
@@ -369,12 +1103,18 @@ impl PokerServer {
                 is_folded: p.is_folded,
                 is_sitting_out: p.is_sitting_out,
                 hole_cards: p.hole_cards.iter().map(|c| c.to_string()).collect(),
+                up_cards: p.up_cards.iter().map(|c| c.to_string()).collect(),
+                seat: poker_game.seat_of(&p.id).unwrap_or_default(),
+                waiting_for_big_blind: poker_game.is_waiting_for_big_blind(&p.id),
             })
             .collect();
 
         drop(poker_game);
 
-        let game_state = ServerMessage::PlayerUpdates(players);
+        let mut game_state = ServerMessage::PlayerUpdates(players);
+        if self.player_sessions.get(player_id).map(String::as_str) != Some(game_id) {
+            game_state = game_state.redacted();
+        }
         let json = match game_state.to_unified_json() {
             Ok(json) => json,
             Err(e) => {
@@ -398,12 +1138,38 @@ impl PokerServer {
     /// # Returns
     /// Result indicating success or error
     pub fn handle_message(&mut self, player_id: &str, message: ClientMessage) -> ServerResult<()> {
+        let span = self.message_span(player_id, &message);
+        let _entered = span.enter();
+        self.process_message(player_id, message)
+    }
+
+    /// Returns a span for handling a player's message, within the span of
+    /// the table they sit at or watch so it carries the table and hand.
+    fn message_span(&self, player_id: &str, message: &ClientMessage) -> Span {
+        let table = self
+            .player_sessions
+            .get(player_id)
+            .map(String::as_str)
+            .or_else(|| self.spectated_game(player_id))
+            .and_then(|game_id| self.games.get(game_id));
+        match table {
+            Some(game) => {
+                let parent = game.lock().span().clone();
+                info_span!(parent: &parent, "message", player_id = %player_id, message = %message)
+            }
+            None => info_span!("message", player_id = %player_id, message = %message),
+        }
+    }
+
+    fn process_message(&mut self, player_id: &str, message: ClientMessage) -> ServerResult<()> {
         match message {
+            // The connection answers the first `Hello` before anything reaches the server.
+            ClientMessage::Hello(_) => return Err(ServerError::UnexpectedHello),
             ClientMessage::Connect => {
                 if self.player_sessions.contains_key(player_id) {
                     return Ok(());
                 }
-                self.seat_player(player_id, "main_table")?;
+                self.seat_player(player_id, "main_table", None)?;
             }
             ClientMessage::Reconnect(existing_player_id) => {
                 if existing_player_id != player_id {
@@ -415,7 +1181,7 @@ impl PokerServer {
                 }
 
                 if let Some(player) = self.players.get_mut(&existing_player_id) {
-                    if player.is_session_expired(self.session_expiry_hours) {
+                    if player.is_session_expired(self.limits.session_token_expiry_hours) {
                         warn!("Session expired for player {}", existing_player_id);
                         return Err(ServerError::SessionExpired);
                     }
@@ -435,13 +1201,23 @@ impl PokerServer {
                     .clone();
 
                 if let Some(game) = self.games.get(&session) {
-                    let mut poker_game = game.lock();
-                    poker_game.handle_action(player_id, action)?;
+                    game.lock().handle_action(player_id, action)?;
+                    watch_table(game);
                 } else {
                     return Err(ServerError::GameNotFound(session));
                 }
             }
             ClientMessage::Chat(text) => {
+                if !self.player_sessions.contains_key(player_id) {
+                    if let Some(game) = self
+                        .spectated_game(player_id)
+                        .and_then(|game_id| self.games.get(game_id))
+                    {
+                        if !game.lock().allows_spectator_chat() {
+                            return Err(ServerError::SpectatorChatDisabled);
+                        }
+                    }
+                }
                 let chat_msg = ChatMessage {
                     player_id: player_id.to_string(),
                     player_name: self
@@ -452,8 +1228,13 @@ impl PokerServer {
                     text,
                     timestamp: chrono::Utc::now().timestamp_millis().max(0) as u64,
                 };
-                if let Err(e) = self.tx.send(ServerMessage::Chat(chat_msg)) {
-                    error!("Failed to send chat message to broadcast channel: {}", e);
+                match self.games.get(self.chat_table(player_id)) {
+                    Some(game) => {
+                        if let Err(e) = game.lock().tx.send(ServerMessage::Chat(chat_msg)) {
+                            error!("Failed to send chat message to broadcast channel: {}", e);
+                        }
+                    }
+                    None => debug!("No table for chat from {}", player_id),
                 }
             }
             ClientMessage::SitOut => {
@@ -484,11 +1265,196 @@ impl PokerServer {
                 let mut poker_game = game.lock();
                 poker_game.return_to_game(player_id);
             }
+            ClientMessage::Straddle(enabled) => {
+                let session = self
+                    .player_sessions
+                    .get(player_id)
+                    .ok_or(ServerError::PlayerNotInGame)?
+                    .clone();
+
+                let game = self
+                    .games
+                    .get(&session)
+                    .ok_or_else(|| ServerError::GameNotFound(session.clone()))?;
+                let mut poker_game = game.lock();
+                poker_game.set_straddle(player_id, enabled)?;
+            }
+            ClientMessage::PostBlind => {
+                let session = self
+                    .player_sessions
+                    .get(player_id)
+                    .ok_or(ServerError::PlayerNotInGame)?
+                    .clone();
+
+                let game = self
+                    .games
+                    .get(&session)
+                    .ok_or_else(|| ServerError::GameNotFound(session.clone()))?;
+                let mut poker_game = game.lock();
+                poker_game.post_blind(player_id)?;
+            }
+            ClientMessage::RunIt(times) => {
+                let session = self
+                    .player_sessions
+                    .get(player_id)
+                    .ok_or(ServerError::PlayerNotInGame)?
+                    .clone();
+
+                let game = self
+                    .games
+                    .get(&session)
+                    .ok_or_else(|| ServerError::GameNotFound(session.clone()))?;
+                let mut poker_game = game.lock();
+                poker_game.set_run_it(player_id, times)?;
+            }
+            ClientMessage::BuyIn(amount, game_id) => {
+                let game_id = self.message_table(player_id, game_id)?;
+                self.buy_in(player_id, &game_id, amount, None)?;
+            }
+            ClientMessage::Rebuy(amount) => {
+                self.add_chips(player_id, amount, PokerGame::rebuy)?;
+            }
+            ClientMessage::TopUp(amount) => {
+                self.add_chips(player_id, amount, PokerGame::top_up)?;
+            }
+            ClientMessage::CashOut => {
+                let session = self
+                    .player_sessions
+                    .get(player_id)
+                    .ok_or(ServerError::PlayerNotInGame)?
+                    .clone();
+
+                let game = self
+                    .games
+                    .get(&session)
+                    .ok_or_else(|| ServerError::GameNotFound(session.clone()))?;
+                let chips = game.lock().cash_out(player_id)?;
+                watch_seat_offers(game);
+                self.return_stack(player_id, chips);
+            }
+            ClientMessage::TakeSeat(seat, game_id) => {
+                let game_id = self.message_table(player_id, game_id)?;
+                self.seat_player(player_id, &game_id, Some(seat))?;
+            }
+            ClientMessage::JoinWaitlist(game_id) => {
+                if self.player_sessions.contains_key(player_id) {
+                    return Err(ServerError::AlreadySeated);
+                }
+                let game_id = self.message_table(player_id, game_id)?;
+                let game = self
+                    .games
+                    .get(&game_id)
+                    .ok_or(ServerError::GameNotFound(game_id))?;
+                game.lock().join_waitlist(player_id)?;
+                watch_seat_offers(game);
+
+                // Players waiting for a seat learn their id here rather than on sitting down.
+                let connected_msg = ServerMessage::Connected(player_id.to_string());
+                let json = connected_msg
+                    .to_unified_json()
+                    .map_err(|e| ServerError::GameState(e.to_string()))?;
+                if let Err(e) = self.send_to_player(player_id, json) {
+                    warn!("Failed to send connected message to {}: {}", player_id, e);
+                }
+            }
+            ClientMessage::LeaveWaitlist(game_id) => {
+                let game_id = self.message_table(player_id, game_id)?;
+                let game = self
+                    .games
+                    .get(&game_id)
+                    .ok_or(ServerError::GameNotFound(game_id))?;
+                game.lock().leave_waitlist(player_id)?;
+                watch_seat_offers(game);
+            }
+            ClientMessage::AcceptSeat(game_id) => {
+                let game_id = self.message_table(player_id, game_id)?;
+                let game = self
+                    .games
+                    .get(&game_id)
+                    .ok_or_else(|| ServerError::GameNotFound(game_id.clone()))?;
+                if game.lock().seat_offer(player_id).is_none() {
+                    return Err(ServerError::NoSeatOffer);
+                }
+                self.seat_player(player_id, &game_id, None)?;
+            }
+            ClientMessage::Spectate(game_id) => {
+                let game_id = self.message_table(player_id, game_id)?;
+                self.spectate(player_id, &game_id)?;
+            }
+            ClientMessage::StopSpectating => {
+                if !self.stop_spectating(player_id) {
+                    return Err(ServerError::NotSpectating);
+                }
+            }
+            ClientMessage::ListTables => {
+                self.send_message(player_id, ServerMessage::TableList(self.list_tables()));
+            }
+            ClientMessage::CreateTable(request) => {
+                self.create_private_table(player_id, request)?;
+            }
+            ClientMessage::JoinTable(request) => {
+                self.join_table(player_id, request)?;
+            }
+            ClientMessage::ApproveSeat(requester) => {
+                self.answer_seat_request(player_id, &requester, true)?;
+            }
+            ClientMessage::RejectSeat(requester) => {
+                self.answer_seat_request(player_id, &requester, false)?;
+            }
+            ClientMessage::PauseTable => {
+                let (_, game) = self.hosted_game(player_id)?;
+                game.lock().pause();
+            }
+            ClientMessage::ResumeTable => {
+                let (_, game) = self.hosted_game(player_id)?;
+                game.lock().resume()?;
+                watch_table(&game);
+            }
+            ClientMessage::SetBlinds(small_blind, big_blind) => {
+                let (_, game) = self.hosted_game(player_id)?;
+                game.lock().set_next_blinds(small_blind, big_blind)?;
+            }
+            ClientMessage::SitOutPlayer(target) => {
+                let (_, game) = self.hosted_game(player_id)?;
+                let mut poker_game = game.lock();
+                if !poker_game.players.contains_key(&target) {
+                    return Err(ServerError::PlayerNotFound(target));
+                }
+                poker_game.sit_out(&target);
+            }
+            ClientMessage::KickPlayer(target) => {
+                self.kick_player(player_id, &target)?;
+            }
+            ClientMessage::EndSession => {
+                self.end_session(player_id)?;
+            }
         }
 
         Ok(())
     }
 
+    /// Passes an event from a table on to its players, first returning the
+    /// stack of a player removed during a hand to their balance once the
+    /// table cashes them out.
+    ///
+    /// # Arguments
+    /// * `game_id` - The table the event came from
+    /// * `message` - The event
+    pub fn relay_table_event(&mut self, game_id: &str, message: ServerMessage) {
+        if let ServerMessage::CashedOut(update) = &message {
+            if self
+                .removed_in_hand
+                .get(&update.player_id)
+                .map(String::as_str)
+                == Some(game_id)
+            {
+                self.removed_in_hand.remove(&update.player_id);
+                self.return_stack(&update.player_id, update.chips);
+            }
+        }
+        self.broadcast_to_game(game_id, message);
+    }
+
     /// Broadcasts a message to all connected players in a game.
     ///
     /// # Arguments
@@ -498,15 +1464,8 @@ impl PokerServer {
         let json = match message.to_unified_json() {
             Ok(json) => json,
             Err(e) => {
-                error!("Failed to serialize message to unified format: {}", e);
-                match serde_json::to_string(&message) {
-                    Ok(fallback) => fallback,
-                    Err(e2) => {
-                        error!("Failed to serialize message to fallback format: {}", e2);
-                        error!("Message was: {:?}", message);
-                        return;
-                    }
-                }
+                error!("Failed to serialize message {:?}: {}", message, e);
+                return;
             }
         };
 
@@ -517,37 +1476,53 @@ impl PokerServer {
 
         let pg = game.lock();
 
-        let players: Vec<(String, tokio::sync::mpsc::Sender<String>)> = {
-            pg.get_players()
-                .keys()
-                .filter(|player_id| {
-                    self.players
-                        .get(player_id.as_str())
-                        .map(|p| p.connected)
-                        .unwrap_or(false)
-                })
-                .filter_map(|player_id| {
-                    self.players
-                        .get(player_id.as_str())
-                        .and_then(|p| p.ws_sender.as_ref())
-                        .map(|sender| (player_id.clone(), sender.clone()))
-                })
-                .collect()
-        };
+        // Players on the waiting list follow the table so they see seats being
+        // offered; they and spectators only see what an onlooker could.
+        let seated = self.connected_senders(pg.get_players().keys().map(String::as_str));
+        let watcher_ids: HashSet<&str> = pg
+            .waitlisted_players()
+            .chain(
+                self.spectators
+                    .get(game_id)
+                    .into_iter()
+                    .flatten()
+                    .map(String::as_str),
+            )
+            .collect();
+        let watchers = self.connected_senders(watcher_ids.into_iter());
+        drop(pg);
 
-        if players.is_empty() {
+        if seated.is_empty() && watchers.is_empty() {
             debug!("No connected players to broadcast to in game {}", game_id);
             return;
         }
 
-        let timeout_duration = Duration::from_millis(BROADCAST_SEND_TIMEOUT_MS);
         let msg_arc = Arc::new(json);
+        let mut recipients: Vec<_> = seated
+            .into_iter()
+            .map(|(player_id, sender)| (player_id, sender, Arc::clone(&msg_arc)))
+            .collect();
+        if !watchers.is_empty() {
+            match message.redacted().to_unified_json() {
+                Ok(redacted) => {
+                    let redacted = Arc::new(redacted);
+                    recipients.extend(
+                        watchers
+                            .into_iter()
+                            .map(|(player_id, sender)| (player_id, sender, Arc::clone(&redacted))),
+                    );
+                }
+                Err(e) => error!("Failed to serialize redacted message: {}", e),
+            }
+        }
+
+        let timeout_duration = Duration::from_millis(BROADCAST_SEND_TIMEOUT_MS);
         let semaphore = Arc::clone(&self.broadcast_semaphore);
 
-        for (player_id, sender) in players {
-            let msg = Arc::clone(&msg_arc);
+        for (player_id, sender, msg) in recipients {
             let sender = sender.clone();
             let sem = Arc::clone(&semaphore);
+            let metrics = Arc::clone(&self.metrics);
             tokio::spawn(async move {
                 let permit = match sem.acquire().await {
                     Ok(permit) => permit,
@@ -557,6 +1532,7 @@ impl PokerServer {
                     }
                 };
                 if let Err(e) = timeout(timeout_duration, sender.send((*msg).clone())).await {
+                    metrics.record_broadcast_timeout();
                     error!("Timeout sending to player {}: {}", player_id, e);
                 }
                 drop(permit);
@@ -564,6 +1540,22 @@ impl PokerServer {
         }
     }
 
+    /// Returns the senders of the given players who are still connected.
+    fn connected_senders<'a>(
+        &self,
+        player_ids: impl Iterator<Item = &'a str>,
+    ) -> Vec<(String, Sender<String>)> {
+        player_ids
+            .filter_map(|player_id| {
+                self.players
+                    .get(player_id)
+                    .filter(|p| p.connected)
+                    .and_then(|p| p.ws_sender.as_ref())
+                    .map(|sender| (player_id.to_string(), sender.clone()))
+            })
+            .collect()
+    }
+
     /// Sends a message to a specific player.
     ///
     /// # Arguments
@@ -610,11 +1602,108 @@ impl PokerServer {
     pub fn verify_session(&self, player_id: &str, token: &str) -> bool {
         self.players
             .get(player_id)
-            .map(|p| p.session_token == token && !p.is_session_expired(self.session_expiry_hours))
+            .map(|p| {
+                p.session_token == token
+                    && !p.is_session_expired(self.limits.session_token_expiry_hours)
+            })
             .unwrap_or(false)
     }
 }
 
+/// Starts the tasks a table needs after anything that may start, close or
+/// resume a hand: dealing out a hand whose action has closed, since forced
+/// bets alone can close one, and timing the player to act.
+///
+/// # Arguments
+/// * `game` - The game to watch
+pub(crate) fn watch_table(game: &Arc<Mutex<PokerGame>>) {
+    drive_run_out(game);
+    watch_action_clock(game);
+}
+
+/// Starts a task that deals the remaining streets of a hand whose action has
+/// closed, pausing between each so players can follow the run-out, unless
+/// one is already running.
+///
+/// # Arguments
+/// * `game` - The game whose hand is being run out
+fn drive_run_out(game: &Arc<Mutex<PokerGame>>) {
+    if !game.lock().claim_run_out() {
+        return;
+    }
+    let game = Arc::clone(game);
+    tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_millis(RUN_OUT_PAUSE_MS)).await;
+            let more = {
+                let mut poker_game = game.lock();
+                let span = poker_game.span().clone();
+                let _entered = span.enter();
+                poker_game.run_out_next_street()
+            };
+            if !more {
+                break;
+            }
+        }
+        // The next hand, or a draw, may be waiting on a player.
+        watch_action_clock(&game);
+    });
+}
+
+/// Starts a task that acts for each player who runs out of time on their
+/// turn, unless one is already running.
+///
+/// # Arguments
+/// * `game` - The game whose turn clock to watch
+fn watch_action_clock(game: &Arc<Mutex<PokerGame>>) {
+    if !game.lock().claim_action_clock() {
+        return;
+    }
+    let game = Arc::clone(game);
+    tokio::spawn(async move {
+        loop {
+            let wait = {
+                let mut poker_game = game.lock();
+                let span = poker_game.span().clone();
+                let _entered = span.enter();
+                poker_game.expire_action()
+            };
+            // Acting for a player may close the action.
+            drive_run_out(&game);
+            match wait {
+                Some(wait) => sleep(wait).await,
+                None => break,
+            }
+        }
+    });
+}
+
+/// Starts a task that withdraws seat offers as they run out, passing each
+/// seat to the next player on the waiting list, unless one is already running.
+///
+/// # Arguments
+/// * `game` - The game whose seat offers to watch
+fn watch_seat_offers(game: &Arc<Mutex<PokerGame>>) {
+    if !game.lock().claim_offer_timer() {
+        return;
+    }
+    let game = Arc::clone(game);
+    tokio::spawn(async move {
+        loop {
+            let wait = {
+                let mut poker_game = game.lock();
+                let span = poker_game.span().clone();
+                let _entered = span.enter();
+                poker_game.expire_seat_offers()
+            };
+            match wait {
+                Some(wait) => sleep(wait).await,
+                None => break,
+            }
+        }
+    });
+}
+
 impl Default for PokerServer {
     fn default() -> Self {
         Self::new()
@@ -808,6 +1897,438 @@ mod tests {
         assert!(player.map(|p| p.is_sitting_out).unwrap_or(false));
     }
 
+    #[tokio::test]
+    async fn test_handle_buy_in_top_up_and_cash_out() {
+        let mut server = PokerServer::new();
+        server.register_player("player1".to_string(), "TestPlayer".to_string(), 5000);
+        server.create_game("main_table".to_string(), 5, 10);
+
+        assert!(matches!(
+            server.handle_message("player1", ClientMessage::BuyIn(6000, None)),
+            Err(ServerError::InsufficientBalance(5000))
+        ));
+        assert!(matches!(
+            server.handle_message("player1", ClientMessage::BuyIn(100, None)),
+            Err(ServerError::BuyInOutOfRange(200, 1000))
+        ));
+        server
+            .handle_message("player1", ClientMessage::BuyIn(800, None))
+            .unwrap();
+        assert_eq!(server.players["player1"].chips, 4200);
+        assert!(matches!(
+            server.handle_message("player1", ClientMessage::BuyIn(800, None)),
+            Err(ServerError::AlreadySeated)
+        ));
+
+        assert!(matches!(
+            server.handle_message("player1", ClientMessage::TopUp(300)),
+            Err(ServerError::TopUpExceedsMax(200))
+        ));
+        server
+            .handle_message("player1", ClientMessage::TopUp(200))
+            .unwrap();
+        assert_eq!(server.players["player1"].chips, 4000);
+
+        server
+            .handle_message("player1", ClientMessage::CashOut)
+            .unwrap();
+        assert_eq!(server.players["player1"].chips, 5000);
+        assert!(!server.is_player_in_game("player1"));
+
+        // Coming back sits down with the largest buy-in the balance allows.
+        server
+            .handle_message("player1", ClientMessage::Connect)
+            .unwrap();
+        let game = server.games.get("main_table").unwrap();
+        assert_eq!(game.lock().players["player1"].chips, 1000);
+    }
+
+    #[tokio::test]
+    async fn test_handle_seat_choice_and_waitlist() {
+        let mut server = PokerServer::new();
+        server.register_player("player1".to_string(), "TestPlayer".to_string(), 1000);
+        server.register_player("player2".to_string(), "OtherPlayer".to_string(), 1000);
+        server.create_game("main_table".to_string(), 5, 10);
+
+        server
+            .handle_message("player1", ClientMessage::TakeSeat(3, None))
+            .unwrap();
+        assert!(matches!(
+            server.handle_message("player2", ClientMessage::TakeSeat(3, None)),
+            Err(ServerError::SeatTaken(3))
+        ));
+        assert!(matches!(
+            server.handle_message("player2", ClientMessage::TakeSeat(99, None)),
+            Err(ServerError::InvalidSeat(99))
+        ));
+        assert!(matches!(
+            server.handle_message("player2", ClientMessage::AcceptSeat(None)),
+            Err(ServerError::NoSeatOffer)
+        ));
+
+        // With seats free, joining the list offers one straight away.
+        server
+            .handle_message("player2", ClientMessage::JoinWaitlist(None))
+            .unwrap();
+        server
+            .handle_message("player2", ClientMessage::AcceptSeat(None))
+            .unwrap();
+        assert!(server.is_player_in_game("player2"));
+        assert!(matches!(
+            server.handle_message("player2", ClientMessage::LeaveWaitlist(None)),
+            Err(ServerError::NotOnWaitlist)
+        ));
+        assert!(matches!(
+            server.handle_message("player1", ClientMessage::JoinWaitlist(None)),
+            Err(ServerError::AlreadySeated)
+        ));
+
+        let game = server.games.get("main_table").unwrap();
+        assert_eq!(game.lock().seat_of("player1"), Some(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_hand_closed_by_blinds_on_sitting_down_reaches_showdown() {
+        let mut server = PokerServer::new();
+        server.register_player("player1".to_string(), "TestPlayer".to_string(), 1000);
+        server.register_player("player2".to_string(), "OtherPlayer".to_string(), 5);
+        let mut config = TableConfig::new(5, 10);
+        config.buy_in.min = 5;
+        let game = server.create_game_with_config("main_table".to_string(), config);
+
+        // Both blinds put the players all in, so the hand closes as it starts.
+        server
+            .handle_message("player1", ClientMessage::BuyIn(5, None))
+            .unwrap();
+        server
+            .handle_message("player2", ClientMessage::TakeSeat(4, None))
+            .unwrap();
+        assert!(game.lock().is_running_out());
+
+        for _ in 0..10 {
+            sleep(Duration::from_millis(RUN_OUT_PAUSE_MS)).await;
+            let poker_game = game.lock();
+            if poker_game.hand_number() > 1 || !poker_game.is_hand_in_progress() {
+                break;
+            }
+        }
+        let poker_game = game.lock();
+        assert!(poker_game.hand_number() > 1 || !poker_game.is_hand_in_progress());
+    }
+
+    #[tokio::test]
+    async fn test_player_removed_mid_hand_is_paid_out_when_it_ends() {
+        let mut server = PokerServer::new();
+        server.register_player("player1".to_string(), "TestPlayer".to_string(), 1000);
+        server.register_player("player2".to_string(), "OtherPlayer".to_string(), 1000);
+        let game = server.create_game("main_table".to_string(), 5, 10);
+        let (_, mut events) = server.take_new_tables().pop().unwrap();
+        for player_id in ["player1", "player2"] {
+            server
+                .handle_message(player_id, ClientMessage::BuyIn(500, None))
+                .unwrap();
+        }
+        assert!(game.lock().is_hand_in_progress());
+
+        server.remove_player("player1").unwrap();
+        assert!(game.lock().players["player1"].is_folded);
+        assert_eq!(server.players["player1"].chips, 500);
+
+        // The hand plays out without them, and then they are cashed out.
+        for _ in 0..4 {
+            server
+                .handle_message("player2", ClientMessage::Action(PlayerAction::Check))
+                .unwrap();
+            while let Ok(event) = events.try_recv() {
+                server.relay_table_event("main_table", event);
+            }
+        }
+        assert!(!game.lock().players.contains_key("player1"));
+        assert!(server.removed_in_hand.is_empty());
+
+        // Folding heads-up loses only the blind they posted.
+        let balance = server.players["player1"].chips;
+        assert!(balance == 990 || balance == 995, "balance {}", balance);
+        assert_eq!(balance + game.lock().players["player2"].chips, 1500);
+    }
+
+    #[tokio::test]
+    async fn test_seating_messages_act_on_the_players_table() {
+        let mut server = PokerServer::new();
+        for id in ["host", "waiter", "watcher", "outsider"] {
+            server.register_player(id.to_string(), id.to_string(), 1000);
+        }
+        server.create_game("main_table".to_string(), 5, 10);
+        server.create_game("second_table".to_string(), 5, 10);
+        let private = server
+            .create_private_table(
+                "host",
+                CreateTableRequest {
+                    small_blind: 1,
+                    big_blind: 2,
+                    password: Some("s3cret".to_string()),
+                    invite_codes: 0,
+                },
+            )
+            .unwrap();
+
+        // A player on a table's waiting list takes the seat it offers them.
+        server
+            .handle_message(
+                "waiter",
+                ClientMessage::JoinWaitlist(Some("second_table".to_string())),
+            )
+            .unwrap();
+        server
+            .handle_message("waiter", ClientMessage::AcceptSeat(None))
+            .unwrap();
+        assert_eq!(
+            server.player_sessions.get("waiter").map(String::as_str),
+            Some("second_table")
+        );
+
+        // A spectator buys in at the table they are watching.
+        server
+            .handle_message(
+                "watcher",
+                ClientMessage::Spectate(Some("second_table".to_string())),
+            )
+            .unwrap();
+        server
+            .handle_message("watcher", ClientMessage::BuyIn(500, None))
+            .unwrap();
+        assert_eq!(
+            server.player_sessions.get("watcher").map(String::as_str),
+            Some("second_table")
+        );
+
+        // Naming a private table needs its host; others must use JoinTable.
+        assert!(matches!(
+            server.handle_message("outsider", ClientMessage::BuyIn(500, Some(private.clone()))),
+            Err(ServerError::InvalidTableCredential)
+        ));
+        assert!(matches!(
+            server.handle_message("outsider", ClientMessage::Spectate(Some(private.clone()))),
+            Err(ServerError::InvalidTableCredential)
+        ));
+        server
+            .handle_message("host", ClientMessage::TakeSeat(2, Some(private.clone())))
+            .unwrap();
+        let game = server.get_game(&private).unwrap();
+        assert_eq!(game.lock().seat_of("host"), Some(2));
+        assert!(!server.is_player_in_game("outsider"));
+    }
+
+    #[tokio::test]
+    async fn test_spectators_watch_with_cards_hidden() {
+        let mut server = PokerServer::new();
+        server.register_player("player1".to_string(), "TestPlayer".to_string(), 1000);
+        server.register_player("watcher".to_string(), "Watcher".to_string(), 1000);
+        server.create_game("main_table".to_string(), 5, 10);
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+        server.connect_player("watcher", tx);
+
+        server
+            .handle_message("player1", ClientMessage::Connect)
+            .unwrap();
+        assert!(matches!(
+            server.handle_message("player1", ClientMessage::Spectate(None)),
+            Err(ServerError::AlreadySeated)
+        ));
+        server
+            .handle_message("watcher", ClientMessage::Spectate(None))
+            .unwrap();
+        assert_eq!(server.spectator_count("main_table"), 1);
+        assert_eq!(
+            server.get_game("main_table").unwrap().lock().player_count(),
+            1
+        );
+        assert!(matches!(
+            server.handle_message("watcher", ClientMessage::Chat("hi".to_string())),
+            Err(ServerError::SpectatorChatDisabled)
+        ));
+
+        while timeout(Duration::from_millis(100), rx.recv()).await.is_ok() {}
+        server.broadcast_to_game(
+            "main_table",
+            ServerMessage::PlayerUpdates(vec![PlayerUpdate {
+                player_id: "player1".to_string(),
+                player_name: "TestPlayer".to_string(),
+                chips: 1000,
+                current_bet: 0,
+                has_acted: false,
+                is_all_in: false,
+                is_folded: false,
+                is_sitting_out: false,
+                hole_cards: vec!["A♠".to_string(), "A♥".to_string()],
+                up_cards: vec![],
+                seat: 0,
+                waiting_for_big_blind: false,
+            }]),
+        );
+        let json = timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(json.contains(poker_protocol::HIDDEN_CARD));
+        assert!(!json.contains("A♠"));
+
+        server
+            .handle_message("watcher", ClientMessage::StopSpectating)
+            .unwrap();
+        assert!(matches!(
+            server.handle_message("watcher", ClientMessage::StopSpectating),
+            Err(ServerError::NotSpectating)
+        ));
+        assert_eq!(server.spectator_count("main_table"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_private_table_needs_password_and_host_approval() {
+        let mut server = PokerServer::new();
+        for id in ["host", "guest", "other"] {
+            server.register_player(id.to_string(), id.to_string(), 1000);
+        }
+        server.create_game("main_table".to_string(), 5, 10);
+
+        let game_id = server
+            .create_private_table(
+                "host",
+                CreateTableRequest {
+                    small_blind: 1,
+                    big_blind: 2,
+                    password: Some("s3cret".to_string()),
+                    invite_codes: 0,
+                },
+            )
+            .unwrap();
+        let listed: Vec<String> = server
+            .list_tables()
+            .into_iter()
+            .map(|table| table.game_id)
+            .collect();
+        assert_eq!(listed, vec!["main_table"]);
+
+        let join = |credential: &str| {
+            ClientMessage::JoinTable(JoinTableRequest {
+                game_id: game_id.clone(),
+                credential: Some(credential.to_string()),
+                seat: None,
+            })
+        };
+        assert!(matches!(
+            server.handle_message("guest", join("guess")),
+            Err(ServerError::InvalidTableCredential)
+        ));
+        server.handle_message("guest", join("s3cret")).unwrap();
+        server.handle_message("other", join("s3cret")).unwrap();
+        assert!(!server.is_player_in_game("guest"));
+        assert!(matches!(
+            server.handle_message("other", ClientMessage::ApproveSeat("guest".to_string())),
+            Err(ServerError::NotTableHost)
+        ));
+
+        server
+            .handle_message("host", ClientMessage::ApproveSeat("guest".to_string()))
+            .unwrap();
+        server
+            .handle_message("host", ClientMessage::RejectSeat("other".to_string()))
+            .unwrap();
+        assert!(matches!(
+            server.handle_message("host", ClientMessage::ApproveSeat("other".to_string())),
+            Err(ServerError::NoSeatRequest(_))
+        ));
+
+        // The host sits at their own table without a password.
+        server
+            .handle_message(
+                "host",
+                ClientMessage::JoinTable(JoinTableRequest {
+                    game_id: game_id.clone(),
+                    credential: None,
+                    seat: None,
+                }),
+            )
+            .unwrap();
+        let game = server.get_game(&game_id).unwrap();
+        assert!(game.lock().players.contains_key("guest"));
+        assert!(game.lock().players.contains_key("host"));
+        assert!(!server.is_player_in_game("other"));
+    }
+
+    #[tokio::test]
+    async fn test_host_controls_need_the_host() {
+        let mut server = PokerServer::new();
+        for id in ["host", "guest"] {
+            server.register_player(id.to_string(), id.to_string(), 1000);
+        }
+        let game_id = server
+            .create_private_table(
+                "host",
+                CreateTableRequest {
+                    small_blind: 1,
+                    big_blind: 2,
+                    password: Some("s3cret".to_string()),
+                    invite_codes: 0,
+                },
+            )
+            .unwrap();
+        server
+            .handle_message(
+                "guest",
+                ClientMessage::JoinTable(JoinTableRequest {
+                    game_id: game_id.clone(),
+                    credential: Some("s3cret".to_string()),
+                    seat: None,
+                }),
+            )
+            .unwrap();
+        server
+            .handle_message("host", ClientMessage::ApproveSeat("guest".to_string()))
+            .unwrap();
+        assert!(matches!(
+            server.handle_message("guest", ClientMessage::PauseTable),
+            Err(ServerError::NotTableHost)
+        ));
+        assert!(matches!(
+            server.handle_message("guest", ClientMessage::KickPlayer("host".to_string())),
+            Err(ServerError::NotTableHost)
+        ));
+
+        server
+            .handle_message("host", ClientMessage::PauseTable)
+            .unwrap();
+        server
+            .handle_message("host", ClientMessage::SetBlinds(2, 4))
+            .unwrap();
+        server
+            .handle_message("host", ClientMessage::KickPlayer("guest".to_string()))
+            .unwrap();
+        assert!(!server.is_player_in_game("guest"));
+        assert_eq!(server.players["guest"].chips, 1000);
+
+        server
+            .handle_message(
+                "host",
+                ClientMessage::JoinTable(JoinTableRequest {
+                    game_id: game_id.clone(),
+                    credential: None,
+                    seat: None,
+                }),
+            )
+            .unwrap();
+        server
+            .handle_message("host", ClientMessage::EndSession)
+            .unwrap();
+        assert!(server.get_game(&game_id).is_none());
+        assert!(!server.is_player_in_game("host"));
+        assert_eq!(server.players["host"].chips, 1000);
+        assert!(matches!(
+            server.handle_message("host", ClientMessage::ResumeTable),
+            Err(ServerError::NotTableHost)
+        ));
+    }
+
     #[tokio::test]
     async fn test_handle_return() {
         let mut server = PokerServer::new();
@@ -828,4 +2349,68 @@ mod tests {
         let player = poker_game.players.get("player1");
         assert!(!player.map(|p| p.is_sitting_out).unwrap_or(true));
     }
+
+    #[tokio::test]
+    async fn test_ending_a_session_settles_up() {
+        let mut server = PokerServer::new();
+        for id in ["host", "guest"] {
+            server.register_player(id.to_string(), id.to_string(), 1000);
+        }
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+        server.connect_player("guest", tx);
+        let game_id = server
+            .create_private_table(
+                "host",
+                CreateTableRequest {
+                    small_blind: 1,
+                    big_blind: 2,
+                    password: Some("s3cret".to_string()),
+                    invite_codes: 0,
+                },
+            )
+            .unwrap();
+        server
+            .handle_message("host", ClientMessage::PauseTable)
+            .unwrap();
+        for (player_id, credential) in [("host", None), ("guest", Some("s3cret"))] {
+            server
+                .handle_message(
+                    player_id,
+                    ClientMessage::JoinTable(JoinTableRequest {
+                        game_id: game_id.clone(),
+                        credential: credential.map(str::to_string),
+                        seat: None,
+                    }),
+                )
+                .unwrap();
+        }
+        server
+            .handle_message("host", ClientMessage::ApproveSeat("guest".to_string()))
+            .unwrap();
+        {
+            let game = server.get_game(&game_id).unwrap();
+            let mut game = game.lock();
+            game.players.get_mut("guest").unwrap().chips -= 80;
+            game.players.get_mut("host").unwrap().chips += 80;
+        }
+
+        while timeout(Duration::from_millis(100), rx.recv()).await.is_ok() {}
+        server
+            .handle_message("host", ClientMessage::EndSession)
+            .unwrap();
+        assert_eq!(server.players["host"].chips, 1080);
+        assert_eq!(server.players["guest"].chips, 920);
+
+        let mut settlement = None;
+        while let Ok(Some(json)) = timeout(Duration::from_millis(100), rx.recv()).await {
+            if json.contains("\"Settlement\"") {
+                settlement = Some(json);
+            }
+        }
+        let settlement: serde_json::Value = serde_json::from_str(&settlement.unwrap()).unwrap();
+        assert_eq!(
+            settlement["transfers"],
+            serde_json::json!([{"from": "guest", "to": "host", "amount": 80}])
+        );
+    }
 }
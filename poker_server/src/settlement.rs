@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use poker_protocol::{PlayerResult, SettlementReport, Transfer};
+
+/// Chips a player has moved on and off one table.
+#[derive(Debug, Clone, Default)]
+struct Account {
+    name: String,
+    bought_in: i32,
+    cashed_out: i32,
+}
+
+/// Every chip a table's players brought and took away, for settling up
+/// once the session is over.
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    /// Accounts by player id, kept in order so reports are stable
+    accounts: BTreeMap<String, Account>,
+}
+
+impl Ledger {
+    /// Records chips a player brought to the table: a buy-in, rebuy or top-up.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player adding chips
+    /// * `name` - The player's display name
+    /// * `amount` - Chips added
+    pub fn record_buy_in(&mut self, player_id: &str, name: &str, amount: i32) {
+        let account = self.accounts.entry(player_id.to_string()).or_default();
+        account.name = name.to_string();
+        account.bought_in = account.bought_in.saturating_add(amount);
+    }
+
+    /// Records chips a player took from the table when cashing out.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player leaving
+    /// * `amount` - Chips they left with
+    pub fn record_cash_out(&mut self, player_id: &str, amount: i32) {
+        let account = self.accounts.entry(player_id.to_string()).or_default();
+        account.cashed_out = account.cashed_out.saturating_add(amount);
+    }
+
+    /// Works out each player's result and the payments that settle the session.
+    ///
+    /// # Arguments
+    /// * `game_id` - The table being settled
+    pub fn settle(&self, game_id: &str) -> SettlementReport {
+        let results: Vec<PlayerResult> = self
+            .accounts
+            .iter()
+            .map(|(player_id, account)| PlayerResult {
+                player_id: player_id.clone(),
+                player_name: account.name.clone(),
+                bought_in: account.bought_in,
+                cashed_out: account.cashed_out,
+                net: account.cashed_out.saturating_sub(account.bought_in),
+            })
+            .collect();
+        let nets: Vec<(String, i32)> = results
+            .iter()
+            .map(|result| (result.player_id.clone(), result.net))
+            .collect();
+        let rake = -nets.iter().map(|(_, net)| net).sum::<i32>();
+
+        SettlementReport {
+            game_id: game_id.to_string(),
+            results,
+            transfers: minimal_transfers(&nets),
+            rake: rake.max(0),
+        }
+    }
+}
+
+/// Pairs losers with winners so everyone is paid with as few transfers as
+/// practical.
+///
+/// Losers and winners with the same amount are paired first, since one
+/// payment settles both; the rest are settled largest loser to largest
+/// winner, which needs at most one transfer fewer than there are players.
+/// Losses beyond what the winners won went to rake and are not transferred.
+///
+/// # Arguments
+/// * `nets` - Each player's id and winnings, negative for a loss
+pub fn minimal_transfers(nets: &[(String, i32)]) -> Vec<Transfer> {
+    let mut debtors: Vec<(String, i32)> = nets
+        .iter()
+        .filter(|(_, net)| *net < 0)
+        .map(|(player_id, net)| (player_id.clone(), -net))
+        .collect();
+    let mut creditors: Vec<(String, i32)> =
+        nets.iter().filter(|(_, net)| *net > 0).cloned().collect();
+    let mut transfers = Vec::new();
+
+    for (debtor, owed) in debtors.iter_mut() {
+        if let Some((creditor, due)) = creditors.iter_mut().find(|(_, due)| due == owed) {
+            transfers.push(Transfer {
+                from: debtor.clone(),
+                to: creditor.clone(),
+                amount: *owed,
+            });
+            *owed = 0;
+            *due = 0;
+        }
+    }
+
+    loop {
+        debtors.retain(|(_, owed)| *owed > 0);
+        creditors.retain(|(_, due)| *due > 0);
+        let largest = |entries: &[(String, i32)]| {
+            (0..entries.len()).max_by_key(|&i| (entries[i].1, std::cmp::Reverse(i)))
+        };
+        let (Some(d), Some(c)) = (largest(&debtors), largest(&creditors)) else {
+            break;
+        };
+        let amount = debtors[d].1.min(creditors[c].1);
+        transfers.push(Transfer {
+            from: debtors[d].0.clone(),
+            to: creditors[c].0.clone(),
+            amount,
+        });
+        debtors[d].1 -= amount;
+        creditors[c].1 -= amount;
+    }
+    transfers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nets(entries: &[(&str, i32)]) -> Vec<(String, i32)> {
+        entries
+            .iter()
+            .map(|(player_id, net)| (player_id.to_string(), *net))
+            .collect()
+    }
+
+    #[test]
+    fn test_transfers_pay_every_winner_in_few_payments() {
+        let transfers =
+            minimal_transfers(&nets(&[("a", -300), ("b", -500), ("c", 300), ("d", 500)]));
+        assert_eq!(transfers.len(), 2);
+        assert!(transfers
+            .iter()
+            .all(|t| (t.from == "a" && t.to == "c") || (t.from == "b" && t.to == "d")));
+
+        let transfers =
+            minimal_transfers(&nets(&[("a", -700), ("b", -100), ("c", 450), ("d", 350)]));
+        assert!(transfers.len() <= 3);
+        for (player_id, net) in [("a", -700), ("b", -100), ("c", 450), ("d", 350)] {
+            let received: i32 = transfers
+                .iter()
+                .filter(|t| t.to == player_id)
+                .map(|t| t.amount)
+                .sum();
+            let paid: i32 = transfers
+                .iter()
+                .filter(|t| t.from == player_id)
+                .map(|t| t.amount)
+                .sum();
+            assert_eq!(received - paid, net);
+        }
+    }
+
+    #[test]
+    fn test_ledger_counts_rebuys_and_rake() {
+        let mut ledger = Ledger::default();
+        ledger.record_buy_in("a", "Alice", 1000);
+        ledger.record_buy_in("a", "Alice", 500);
+        ledger.record_buy_in("b", "Bob", 1000);
+        ledger.record_cash_out("a", 0);
+        ledger.record_cash_out("b", 2480);
+
+        let report = ledger.settle("private-1");
+        assert_eq!(report.results[0].bought_in, 1500);
+        assert_eq!(report.results[0].net, -1500);
+        assert_eq!(report.results[1].net, 1480);
+        assert_eq!(report.rake, 20);
+        assert_eq!(
+            report.transfers,
+            vec![Transfer {
+                from: "a".to_string(),
+                to: "b".to_string(),
+                amount: 1480,
+            }]
+        );
+    }
+}
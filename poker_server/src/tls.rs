@@ -0,0 +1,278 @@
+use std::io;
+#[cfg(feature = "tls")]
+use std::path::Path;
+#[cfg(feature = "tls")]
+use std::sync::Arc;
+#[cfg(feature = "tls")]
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::{RootCertStore, ServerConfig as RustlsConfig};
+
+use crate::config::ConfigError;
+use crate::ServerConfig;
+
+/// Seconds a client has to complete the TLS handshake
+#[cfg(feature = "tls")]
+const HANDSHAKE_TIMEOUT_SECS: u64 = 10;
+
+/// The byte stream of an accepted connection, encrypted or not.
+pub trait Transport: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Transport for T {}
+
+/// Terminates TLS on accepted connections.
+#[derive(Clone)]
+pub struct TlsAcceptor {
+    #[cfg(feature = "tls")]
+    inner: tokio_rustls::TlsAcceptor,
+    #[cfg(not(feature = "tls"))]
+    never: std::convert::Infallible,
+}
+
+impl TlsAcceptor {
+    /// Builds the acceptor for player connections.
+    ///
+    /// # Returns
+    /// `None` if no certificate is configured, so players connect without TLS
+    pub fn for_players(config: &ServerConfig) -> Result<Option<Self>, ConfigError> {
+        match (&config.tls_cert, &config.tls_key) {
+            (Some(cert), Some(key)) => Self::load(cert, key, None).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Builds the acceptor for the admin endpoint, which also asks for a
+    /// client certificate when `admin_client_ca` is set.
+    ///
+    /// # Returns
+    /// `None` if no certificate is configured
+    pub fn for_admin(config: &ServerConfig) -> Result<Option<Self>, ConfigError> {
+        match (&config.tls_cert, &config.tls_key) {
+            (Some(cert), Some(key)) => {
+                Self::load(cert, key, config.admin_client_ca.as_deref()).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Loads a certificate chain and its private key from PEM files.
+    ///
+    /// # Arguments
+    /// * `cert` - The certificate chain, leaf first
+    /// * `key` - The certificate's private key
+    /// * `client_ca` - CA certificates clients must present a certificate
+    ///   signed by, or `None` to not ask for one
+    #[cfg(feature = "tls")]
+    pub fn load(cert: &Path, key: &Path, client_ca: Option<&Path>) -> Result<Self, ConfigError> {
+        let chain = read_certificates(cert)?;
+        let key = PrivateKeyDer::from_pem_file(key)
+            .map_err(|e| ConfigError::Tls(format!("{}: {}", key.display(), e)))?;
+
+        let builder = RustlsConfig::builder();
+        let builder = match client_ca {
+            Some(ca) => {
+                let mut roots = RootCertStore::empty();
+                for certificate in read_certificates(ca)? {
+                    roots
+                        .add(certificate)
+                        .map_err(|e| ConfigError::Tls(format!("{}: {}", ca.display(), e)))?;
+                }
+                let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+                    .build()
+                    .map_err(|e| ConfigError::Tls(format!("{}: {}", ca.display(), e)))?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+        let config = builder
+            .with_single_cert(chain, key)
+            .map_err(|e| ConfigError::Tls(format!("{}: {}", cert.display(), e)))?;
+        Ok(Self {
+            inner: tokio_rustls::TlsAcceptor::from(Arc::new(config)),
+        })
+    }
+
+    #[cfg(not(feature = "tls"))]
+    pub fn load(
+        _cert: &std::path::Path,
+        _key: &std::path::Path,
+        _client_ca: Option<&std::path::Path>,
+    ) -> Result<Self, ConfigError> {
+        Err(ConfigError::Tls(
+            "the server was built without the `tls` feature".to_string(),
+        ))
+    }
+
+    /// Completes the TLS handshake on an accepted connection.
+    ///
+    /// # Arguments
+    /// * `stream` - The accepted connection
+    #[cfg(feature = "tls")]
+    pub async fn accept(&self, stream: TcpStream) -> io::Result<Box<dyn Transport>> {
+        let handshake = tokio::time::timeout(
+            Duration::from_secs(HANDSHAKE_TIMEOUT_SECS),
+            self.inner.accept(stream),
+        );
+        match handshake.await {
+            Ok(stream) => Ok(Box::new(stream?)),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "TLS handshake timed out",
+            )),
+        }
+    }
+
+    #[cfg(not(feature = "tls"))]
+    pub async fn accept(&self, _stream: TcpStream) -> io::Result<Box<dyn Transport>> {
+        match self.never {}
+    }
+}
+
+/// Accepts a connection, completing the TLS handshake if TLS is enabled.
+///
+/// # Arguments
+/// * `stream` - The accepted connection
+/// * `tls` - The acceptor to use, or `None` for a plain connection
+pub async fn accept(
+    stream: TcpStream,
+    tls: Option<&TlsAcceptor>,
+) -> io::Result<Box<dyn Transport>> {
+    match tls {
+        Some(tls) => tls.accept(stream).await,
+        None => Ok(Box::new(stream)),
+    }
+}
+
+#[cfg(feature = "tls")]
+fn read_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, ConfigError> {
+    let invalid =
+        |e: &dyn std::fmt::Display| ConfigError::Tls(format!("{}: {}", path.display(), e));
+    let certificates = CertificateDer::pem_file_iter(path)
+        .map_err(|e| invalid(&e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| invalid(&e))?;
+    if certificates.is_empty() {
+        return Err(invalid(&"no certificates found"));
+    }
+    Ok(certificates)
+}
+
+#[cfg(all(test, feature = "tls"))]
+mod tests {
+    use super::*;
+    use rcgen::{
+        BasicConstraints, CertificateParams, CertifiedKey, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+    };
+    use std::path::PathBuf;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio_rustls::rustls::pki_types::ServerName;
+    use tokio_rustls::rustls::ClientConfig;
+    use tokio_rustls::TlsConnector;
+
+    /// Writes PEM contents to a file that is removed when dropped.
+    struct PemFile(PathBuf);
+
+    impl PemFile {
+        fn new(contents: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("poker-tls-{}.pem", uuid::Uuid::new_v4()));
+            std::fs::write(&path, contents).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for PemFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// Accepts one connection with `acceptor` and echoes a line back, with
+    /// `client` connecting to it.
+    async fn echo(acceptor: TlsAcceptor, client: ClientConfig) -> io::Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            let mut stream = accept(stream, Some(&acceptor)).await?;
+            let mut buf = [0u8; 5];
+            stream.read_exact(&mut buf).await?;
+            stream.write_all(&buf).await?;
+            stream.flush().await
+        });
+
+        let connector = TlsConnector::from(Arc::new(client));
+        let stream = TcpStream::connect(addr).await?;
+        let name = ServerName::try_from("localhost").unwrap();
+        let mut stream = connector.connect(name, stream).await?;
+        stream.write_all(b"hello").await?;
+        let mut buf = [0u8; 5];
+        stream.read_exact(&mut buf).await?;
+        server.await.unwrap()?;
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+
+    fn trusting(cert: &rcgen::Certificate) -> RootCertStore {
+        let mut roots = RootCertStore::empty();
+        roots.add(cert.der().clone()).unwrap();
+        roots
+    }
+
+    #[tokio::test]
+    async fn test_players_connect_over_tls_with_a_self_signed_certificate() {
+        let CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_file = PemFile::new(&cert.pem());
+        let key_file = PemFile::new(&key_pair.serialize_pem());
+        let acceptor = TlsAcceptor::load(&cert_file.0, &key_file.0, None).unwrap();
+
+        let client = ClientConfig::builder()
+            .with_root_certificates(trusting(&cert))
+            .with_no_client_auth();
+        assert_eq!(echo(acceptor, client).await.unwrap(), "hello");
+
+        let missing = TlsAcceptor::load(&cert_file.0, Path::new("/nonexistent.pem"), None);
+        assert!(matches!(missing, Err(ConfigError::Tls(_))));
+    }
+
+    #[tokio::test]
+    async fn test_admin_endpoint_can_require_a_client_certificate() {
+        let CertifiedKey { cert, key_pair } =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_file = PemFile::new(&cert.pem());
+        let key_file = PemFile::new(&key_pair.serialize_pem());
+
+        let mut ca_params = CertificateParams::new(Vec::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca_key = KeyPair::generate().unwrap();
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+        let ca_file = PemFile::new(&ca.pem());
+
+        let mut admin_params = CertificateParams::new(vec!["admin".to_string()]).unwrap();
+        admin_params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+        let admin_key = KeyPair::generate().unwrap();
+        let admin = admin_params.signed_by(&admin_key, &ca, &ca_key).unwrap();
+
+        let acceptor = TlsAcceptor::load(&cert_file.0, &key_file.0, Some(&ca_file.0)).unwrap();
+        let anonymous = ClientConfig::builder()
+            .with_root_certificates(trusting(&cert))
+            .with_no_client_auth();
+        assert!(echo(acceptor.clone(), anonymous).await.is_err());
+
+        let key = PrivateKeyDer::try_from(admin_key.serialize_der()).unwrap();
+        let authenticated = ClientConfig::builder()
+            .with_root_certificates(trusting(&cert))
+            .with_client_auth_cert(vec![admin.der().clone()], key)
+            .unwrap();
+        assert_eq!(echo(acceptor, authenticated).await.unwrap(), "hello");
+    }
+}
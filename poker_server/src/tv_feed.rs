@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+
+use poker_protocol::ServerMessage;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep_until, Duration, Instant};
+use tracing::{error, warn};
+
+/// Seconds the observer feed runs behind the table by default
+pub const DEFAULT_TV_DELAY_SECS: u64 = 300;
+/// Delayed events buffered for viewers that fall behind
+const TV_VIEWER_CAPACITY: usize = 1024;
+
+/// A delayed copy of everything that happens at a table, hole cards
+/// included, for streaming games to an audience.
+///
+/// Events are held back by the delay before being passed to viewers, so
+/// nothing seen on the feed can help anyone still playing the hand.
+pub struct TvFeed {
+    delay: Duration,
+    /// Serialized events waiting to be shown, with the time each is due
+    buffer: VecDeque<(Instant, String)>,
+    viewers: broadcast::Sender<String>,
+}
+
+impl TvFeed {
+    /// Creates an empty feed.
+    ///
+    /// # Arguments
+    /// * `delay` - How far the feed runs behind the table
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            buffer: VecDeque::new(),
+            viewers: broadcast::channel(TV_VIEWER_CAPACITY).0,
+        }
+    }
+
+    /// Returns the channel delayed events are sent on; subscribe to watch.
+    pub fn viewers(&self) -> broadcast::Sender<String> {
+        self.viewers.clone()
+    }
+
+    /// Buffers an event from the table to be shown once the delay has passed.
+    pub fn record(&mut self, message: &ServerMessage) {
+        match message.to_unified_json() {
+            Ok(json) => self.buffer.push_back((Instant::now() + self.delay, json)),
+            Err(e) => error!("Failed to serialize event for the TV feed: {}", e),
+        }
+    }
+
+    /// Sends every event that has waited out the delay to viewers.
+    ///
+    /// # Returns
+    /// When the next buffered event is due, if there is one
+    pub fn release_due(&mut self, now: Instant) -> Option<Instant> {
+        while let Some((due, _)) = self.buffer.front() {
+            if *due > now {
+                return Some(*due);
+            }
+            if let Some((_, json)) = self.buffer.pop_front() {
+                // No viewers is not an error; the event is simply not shown.
+                let _ = self.viewers.send(json);
+            }
+        }
+        None
+    }
+
+    /// Starts a task that records a table's events and releases them to
+    /// viewers as they come due, until the table's channel closes.
+    ///
+    /// # Arguments
+    /// * `events` - A subscription to the table's broadcast channel
+    pub fn spawn(mut self, mut events: broadcast::Receiver<ServerMessage>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let next_due = self.release_due(Instant::now());
+                tokio::select! {
+                    event = events.recv() => match event {
+                        Ok(message) => self.record(&message),
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("TV feed missed {} table events", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = sleep_until(next_due.unwrap_or_else(Instant::now)),
+                        if next_due.is_some() => {}
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_events_are_shown_after_the_delay() {
+        let (table_tx, table_rx) = broadcast::channel(16);
+        let feed = TvFeed::new(Duration::from_secs(300));
+        let mut viewer = feed.viewers().subscribe();
+        let _task = feed.spawn(table_rx);
+
+        table_tx
+            .send(ServerMessage::Connected("p1".to_string()))
+            .unwrap();
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        table_tx
+            .send(ServerMessage::Connected("p2".to_string()))
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_secs(239)).await;
+        assert!(viewer.try_recv().is_err());
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(viewer.try_recv().unwrap().contains("p1"));
+        assert!(viewer.try_recv().is_err());
+
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert!(viewer.try_recv().unwrap().contains("p2"));
+    }
+}